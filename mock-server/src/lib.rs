@@ -0,0 +1,725 @@
+//! # Local mock server for the DSH resource management api
+//!
+//! The mock server serves the resource management api of the platform `mock` from in-memory
+//! fixtures, including a fake access token endpoint. It is used by the integration tests
+//! of the `dsh` tool and can be started from the command line to try the tool offline.
+//!
+//! Since the endpoints of a platform are always https urls, the mock server acts as an
+//! https proxy for the domain of the mock platform, using a certificate that is signed by
+//! a certificate authority that is generated when the server starts. All changes to
+//! the home directory of the `dsh` tool are made in the directory that is passed to
+//! [`MockServer::start`], so the settings of the user are never touched.
+
+#[allow(dead_code)]
+#[path = "../../src/openapi.rs"]
+mod openapi;
+
+use crate::openapi::{escape_pointer_token, request_body_schema, resolve, validate};
+use chrono::DateTime;
+use dsh_api::dsh_api_client::DshApiClient;
+use hyper::server::conn::Http;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use lazy_static::lazy_static;
+use log::{debug, info};
+use rcgen::{BasicConstraints, CertificateParams, DnType, IsCa, KeyPair};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::convert::Infallible;
+use std::fs;
+use std::net::{SocketAddr, TcpListener};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::oneshot;
+use tokio_native_tls::native_tls::Identity;
+use tokio_native_tls::TlsAcceptor;
+
+pub const MOCK_PLATFORM: &str = "mock";
+pub const MOCK_TENANT: &str = "mock";
+pub const MOCK_PASSWORD: &str = "mock";
+
+const MOCK_PLATFORM_ALIAS: &str = "mock-server";
+const MOCK_PUBLIC_DOMAIN: &str = "mock.dsh.localhost";
+const MOCK_REALM: &str = "mock";
+
+// Hosts of the mock platform that are served via the proxy
+const MOCK_HOSTS: [&str; 3] = ["api.mock.dsh.localhost", "auth.mock.dsh.localhost", "console.mock.dsh.localhost"];
+
+const MOCK_SERVER_CERTIFICATE_FILE: &str = "mock-server-certificate.pem";
+const USER_PLATFORMS_FILE: &str = "platforms.json";
+const REST_API_PATH_PREFIX: &str = "/resources/v0";
+const TENANT_PLACEHOLDER: &str = "{tenant}";
+
+const APPLICATION_CONFIGURATION_TEMPLATE: &str = "/allocation/{tenant}/application/{appid}/configuration";
+const APP_CATALOG_APP_CONFIGURATION_TEMPLATE: &str = "/appcatalog/{tenant}/appcatalogapp/{appcatalogappid}/configuration";
+
+lazy_static! {
+  static ref OPENAPI_SPEC: Option<Value> = serde_json::from_str(DshApiClient::openapi_spec()).ok();
+}
+
+/// Built-in fixtures, as a map from resource paths to values
+pub const DEFAULT_FIXTURES: &str = include_str!("../fixtures.json");
+
+/// # Running mock server
+///
+/// The server runs in a background thread until it is stopped or dropped.
+pub struct MockServer {
+  address: SocketAddr,
+  home: PathBuf,
+  shutdown: Option<oneshot::Sender<()>>,
+  thread: Option<JoinHandle<()>>,
+}
+
+impl MockServer {
+  /// # Starts a mock server
+  ///
+  /// # Parameters
+  /// * `home` - home directory of the `dsh` tool that will use the mock server,
+  ///   the user platforms file and the certificate of the mock server will be written here
+  /// * `port` - local port of the server, use `0` to select a free port
+  /// * `fixtures` - initial resources in json format, when `None` the default fixtures will be used
+  pub fn start(home: &Path, port: u16, fixtures: Option<&str>) -> Result<Self, String> {
+    let resources = serde_json::from_str::<BTreeMap<String, Value>>(fixtures.unwrap_or(DEFAULT_FIXTURES)).map_err(|error| format!("invalid fixtures ({})", error))?;
+    let (ca_certificate_pem, tls_acceptor) = tls_acceptor()?;
+    fs::create_dir_all(home).map_err(|error| format!("could not create directory '{}' ({})", home.to_string_lossy(), error))?;
+    let certificate_file = home.join(MOCK_SERVER_CERTIFICATE_FILE);
+    fs::write(&certificate_file, ca_certificate_pem).map_err(|error| format!("could not write certificate '{}' ({})", certificate_file.to_string_lossy(), error))?;
+    write_mock_platform(home)?;
+    let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], port))).map_err(|error| format!("could not start mock server on port {} ({})", port, error))?;
+    listener.set_nonblocking(true).map_err(|error| error.to_string())?;
+    let address = listener.local_addr().map_err(|error| error.to_string())?;
+    let runtime = tokio::runtime::Builder::new_current_thread()
+      .enable_all()
+      .build()
+      .map_err(|error| error.to_string())?;
+    let (shutdown, shutdown_receiver) = oneshot::channel::<()>();
+    let mock_server = Arc::new(MockServerState { resources: Mutex::new(resources), tls_acceptor });
+    let thread = std::thread::spawn(move || {
+      runtime.block_on(async move {
+        let server = match Server::from_tcp(listener) {
+          Ok(builder) => builder.serve(make_service_fn(move |_| {
+            let mock_server = mock_server.clone();
+            async move { Ok::<_, Infallible>(service_fn(move |request| handle_connection_request(request, mock_server.clone()))) }
+          })),
+          Err(error) => {
+            info!("mock server failed ({})", error);
+            return;
+          }
+        };
+        if let Err(error) = server
+          .with_graceful_shutdown(async {
+            let _ = shutdown_receiver.await;
+          })
+          .await
+        {
+          info!("mock server failed ({})", error);
+        }
+      })
+    });
+    Ok(Self { address, home: home.to_path_buf(), shutdown: Some(shutdown), thread: Some(thread) })
+  }
+
+  /// # Returns the address of the mock server
+  pub fn address(&self) -> SocketAddr {
+    self.address
+  }
+
+  /// # Returns the environment variables that configure the `dsh` tool to use the mock server
+  pub fn environment_variables(&self) -> Vec<(&'static str, String)> {
+    vec![
+      ("DSH_CLI_HOME", self.home.to_string_lossy().to_string()),
+      ("DSH_CLI_PLATFORM", MOCK_PLATFORM.to_string()),
+      ("DSH_CLI_TENANT", MOCK_TENANT.to_string()),
+      ("DSH_CLI_PASSWORD", MOCK_PASSWORD.to_string()),
+      ("HTTPS_PROXY", format!("http://{}", self.address)),
+      ("SSL_CERT_FILE", self.home.join(MOCK_SERVER_CERTIFICATE_FILE).to_string_lossy().to_string()),
+    ]
+  }
+
+  /// # Stops the mock server
+  pub fn stop(&mut self) {
+    if let Some(shutdown) = self.shutdown.take() {
+      let _ = shutdown.send(());
+    }
+    if let Some(thread) = self.thread.take() {
+      let _ = thread.join();
+    }
+  }
+}
+
+impl Drop for MockServer {
+  fn drop(&mut self) {
+    self.stop();
+  }
+}
+
+struct MockServerState {
+  resources: Mutex<BTreeMap<String, Value>>,
+  tls_acceptor: TlsAcceptor,
+}
+
+// Generates a certificate authority and a tls acceptor with a certificate for the mock hosts, signed by this authority
+fn tls_acceptor() -> Result<(String, TlsAcceptor), String> {
+  let ca_key_pair = KeyPair::generate().map_err(|error| format!("could not generate key ({})", error))?;
+  let mut ca_parameters = CertificateParams::default();
+  ca_parameters.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+  ca_parameters.distinguished_name.push(DnType::CommonName, "dsh mock server");
+  let ca_certificate = ca_parameters
+    .self_signed(&ca_key_pair)
+    .map_err(|error| format!("could not generate certificate ({})", error))?;
+  let key_pair = KeyPair::generate().map_err(|error| format!("could not generate key ({})", error))?;
+  let certificate = CertificateParams::new(MOCK_HOSTS.iter().map(|host| host.to_string()).collect::<Vec<_>>())
+    .and_then(|parameters| parameters.signed_by(&key_pair, &ca_certificate, &ca_key_pair))
+    .map_err(|error| format!("could not generate certificate ({})", error))?;
+  let identity = Identity::from_pkcs8(certificate.pem().as_bytes(), key_pair.serialize_pem().as_bytes()).map_err(|error| error.to_string())?;
+  let tls_acceptor = TlsAcceptor::from(tokio_native_tls::native_tls::TlsAcceptor::new(identity).map_err(|error| error.to_string())?);
+  Ok((ca_certificate.pem(), tls_acceptor))
+}
+
+// Handles a request on a connection to the mock server,
+// where a CONNECT request will start a tls tunnel to one of the hosts of the mock platform
+async fn handle_connection_request(request: Request<Body>, mock_server: Arc<MockServerState>) -> Result<Response<Body>, Infallible> {
+  if request.method() != Method::CONNECT {
+    return Ok(text_response(
+      StatusCode::METHOD_NOT_ALLOWED,
+      "only https tunnels to the mock platform are supported".to_string(),
+    ));
+  }
+  let host = request.uri().host().unwrap_or_default().to_string();
+  if !MOCK_HOSTS.contains(&host.as_str()) {
+    return Ok(text_response(StatusCode::BAD_GATEWAY, format!("unknown host '{}'", host)));
+  }
+  debug!("tunnel to {}", request.uri());
+  tokio::spawn(async move {
+    match hyper::upgrade::on(request).await {
+      Ok(upgraded) => match mock_server.tls_acceptor.accept(upgraded).await {
+        Ok(tls_stream) => {
+          let service = service_fn(move |request| handle_request(request, mock_server.clone()));
+          if let Err(error) = Http::new().http1_only(true).serve_connection(tls_stream, service).await {
+            debug!("tunnel connection closed ({})", error);
+          }
+        }
+        Err(error) => info!("tls handshake failed ({})", error),
+      },
+      Err(error) => info!("upgrade failed ({})", error),
+    }
+  });
+  Ok(Response::new(Body::empty()))
+}
+
+async fn handle_request(request: Request<Body>, mock_server: Arc<MockServerState>) -> Result<Response<Body>, Infallible> {
+  let method = request.method().clone();
+  let path = request.uri().path().to_string();
+  let authorized = request
+    .headers()
+    .get(hyper::header::AUTHORIZATION)
+    .and_then(|authorization| authorization.to_str().ok())
+    .is_some_and(|authorization| authorization.starts_with("Bearer "));
+  let body = hyper::body::to_bytes(request.into_body()).await.unwrap_or_default();
+  let (status, content) = if method == Method::POST && path.ends_with("/protocol/openid-connect/token") {
+    if form_value(&body, "client_secret").as_deref() == Some(MOCK_PASSWORD) {
+      (StatusCode::OK, Some(("application/json", access_token().to_string())))
+    } else {
+      (
+        StatusCode::UNAUTHORIZED,
+        Some((
+          "application/json",
+          json!({ "error": "unauthorized_client", "error_description": "Invalid client credentials" }).to_string(),
+        )),
+      )
+    }
+  } else {
+    match path.strip_prefix(REST_API_PATH_PREFIX) {
+      Some(resource_path) if authorized => {
+        let mut resources = mock_server.resources.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        handle_resource_request(&method, resource_path, &body, &mut resources)
+      }
+      Some(_) => (StatusCode::UNAUTHORIZED, Some(("text/plain", "missing access token".to_string()))),
+      None => (StatusCode::NOT_FOUND, Some(("text/plain", format!("unknown path '{}'", path)))),
+    }
+  };
+  info!("{} {} {}", method, path, status.as_u16());
+  let mut response = Response::builder().status(status);
+  let body = match content {
+    Some((content_type, content)) => {
+      response = response.header(hyper::header::CONTENT_TYPE, content_type);
+      Body::from(content)
+    }
+    None => Body::empty(),
+  };
+  Ok(response.body(body).unwrap_or_default())
+}
+
+fn text_response(status: StatusCode, message: String) -> Response<Body> {
+  let mut response = Response::new(Body::from(message));
+  *response.status_mut() = status;
+  response
+}
+
+// Returns the value of a field from an url encoded form, not decoding any escaped characters
+fn form_value(body: &[u8], field: &str) -> Option<String> {
+  String::from_utf8_lossy(body)
+    .split('&')
+    .find_map(|pair| pair.split_once('=').filter(|(key, _)| *key == field).map(|(_, value)| value.to_string()))
+}
+
+fn access_token() -> Value {
+  json!({
+    "access_token": "mock-access-token",
+    "expires_in": 300,
+    "refresh_expires_in": 0,
+    "token_type": "Bearer",
+    "not-before-policy": 0,
+    "scope": "mock"
+  })
+}
+
+type MockResponse = (StatusCode, Option<(&'static str, String)>);
+
+/// # Handles a request for the mock rest api
+///
+/// The resources are stored in a map from normalized paths to values,
+/// where the tenant in the path is replaced by `{tenant}`.
+/// Values that are not stored explicitly are derived from the stored values:
+/// * the actual value of a resource is the same as its configuration,
+/// * the status of an existing resource is always provisioned,
+/// * lists of identifiers and maps of configurations are derived from the stored paths.
+///
+/// Created or updated resources are validated against the open api specification.
+fn handle_resource_request(method: &Method, path: &str, body: &[u8], resources: &mut BTreeMap<String, Value>) -> MockResponse {
+  let Some(template) = match_path_template(path) else {
+    return (StatusCode::NOT_FOUND, Some(("text/plain", format!("unknown path '{}'", path))));
+  };
+  let Some(operation) = operation(method.as_str(), template) else {
+    return (
+      StatusCode::METHOD_NOT_ALLOWED,
+      Some(("text/plain", format!("method {} not allowed for '{}'", method, template))),
+    );
+  };
+  let Some((success_status, success_content)) = success_response(operation) else {
+    return (StatusCode::NOT_IMPLEMENTED, None);
+  };
+  let success_status = StatusCode::from_u16(success_status).unwrap_or(StatusCode::OK);
+  let path = normalize_path(path, template);
+  match *method {
+    Method::GET => match success_content.and_then(|(content_type, schema)| get_resource(&path, schema, resources).map(|value| (content_type, value))) {
+      Some(("application/json", value)) => (success_status, Some(("application/json", value.to_string()))),
+      Some((_, Value::String(value))) => (success_status, Some(("text/plain", value))),
+      Some((_, value)) => (success_status, Some(("text/plain", value.to_string()))),
+      None => (StatusCode::NOT_FOUND, Some(("text/plain", format!("resource '{}' does not exist", path)))),
+    },
+    Method::DELETE => {
+      let resource = path.strip_suffix("/configuration").unwrap_or(&path).to_string();
+      let resource_prefix = format!("{}/", resource);
+      let removed_paths = resources
+        .keys()
+        .filter(|stored_path| **stored_path == resource || stored_path.starts_with(&resource_prefix))
+        .cloned()
+        .collect::<Vec<_>>();
+      if removed_paths.is_empty() {
+        return (StatusCode::NOT_FOUND, Some(("text/plain", format!("resource '{}' does not exist", path))));
+      }
+      for removed_path in removed_paths {
+        resources.remove(&removed_path);
+      }
+      if template == APPLICATION_CONFIGURATION_TEMPLATE {
+        reconcile_tasks(&path, 0, resources);
+      }
+      if template == APP_CATALOG_APP_CONFIGURATION_TEMPLATE {
+        allocate_app(&path, None, resources);
+      }
+      (success_status, None)
+    }
+    Method::PATCH | Method::POST | Method::PUT => {
+      let value = if body.is_empty() {
+        Value::Object(Map::new())
+      } else {
+        match serde_json::from_slice::<Value>(body) {
+          Ok(value) => value,
+          Err(_) => Value::String(String::from_utf8_lossy(body).to_string()),
+        }
+      };
+      if let Some(schema) = request_body_schema(&method.as_str().to_lowercase(), template, None) {
+        let errors = validate(schema, &value);
+        if !errors.is_empty() {
+          return (
+            StatusCode::BAD_REQUEST,
+            Some(("text/plain", errors.iter().map(|error| error.to_string()).collect::<Vec<_>>().join("\n"))),
+          );
+        }
+      }
+      if *method == Method::POST {
+        if let Some(name) = value.get("name").and_then(|name| name.as_str()) {
+          // Create a new resource in a collection, e.g. a secret
+          let resource = format!("{}/{}", path, name);
+          if resources.contains_key(&resource) || resources.contains_key(&format!("{}/configuration", resource)) {
+            return (StatusCode::CONFLICT, Some(("text/plain", format!("resource '{}' already exists", resource))));
+          }
+          match value.get("value") {
+            Some(secret_value) => {
+              resources.insert(resource.clone(), secret_value.clone());
+              resources.insert(format!("{}/configuration", resource), Value::Object(Map::new()));
+            }
+            None => {
+              resources.insert(format!("{}/configuration", resource), value);
+            }
+          }
+          return (success_status, None);
+        }
+      }
+      if template == APPLICATION_CONFIGURATION_TEMPLATE {
+        if let Some(instances) = value.get("instances").and_then(|instances| instances.as_u64()) {
+          reconcile_tasks(&path, instances as usize, resources);
+        }
+      }
+      if template == APP_CATALOG_APP_CONFIGURATION_TEMPLATE {
+        allocate_app(&path, Some(&value), resources);
+      }
+      resources.insert(path, value);
+      (success_status, None)
+    }
+    _ => (StatusCode::METHOD_NOT_ALLOWED, None),
+  }
+}
+
+// Returns the stored or derived value of a resource
+fn get_resource(path: &str, schema: &'static Value, resources: &BTreeMap<String, Value>) -> Option<Value> {
+  if let Some(value) = resources.get(path) {
+    return Some(value.clone());
+  }
+  let (parent, last_segment) = path.rsplit_once('/')?;
+  let actual = |resource: &str| {
+    resources
+      .get(&format!("{}/actual", resource))
+      .or_else(|| resources.get(&format!("{}/configuration", resource)))
+  };
+  let schema = resolve(schema);
+  if last_segment == "actual" {
+    return actual(parent).cloned();
+  }
+  if last_segment == "status" {
+    return actual(parent).map(|_| allocation_status());
+  }
+  if schema.get("type").and_then(|schema_type| schema_type.as_str()) == Some("array") {
+    // List of identifiers, derived from the stored paths
+    let prefix = format!("{}/", path);
+    let ids = resources
+      .keys()
+      .filter_map(|stored_path| stored_path.strip_prefix(&prefix))
+      .filter_map(|child_path| child_path.split('/').next())
+      .collect::<BTreeSet<_>>();
+    return Some(Value::from(ids.into_iter().collect::<Vec<_>>()));
+  }
+  if schema.get("additionalProperties").is_some() && (last_segment == "configuration" || last_segment == "actual") {
+    // Map of configurations, derived from the stored paths
+    let prefix = format!("{}/", parent);
+    let map = resources
+      .iter()
+      .filter_map(|(stored_path, value)| {
+        stored_path
+          .strip_prefix(&prefix)
+          .and_then(|child_path| child_path.strip_suffix("/configuration"))
+          .filter(|id| !id.contains('/'))
+          .map(|id| (id.to_string(), value.clone()))
+      })
+      .collect::<Map<_, _>>();
+    return Some(Value::Object(map));
+  }
+  let properties = schema.get("properties").and_then(|properties| properties.as_object())?;
+  if properties.contains_key("status") {
+    // Combined status of a resource
+    let mut status = Map::new();
+    if let Some(configured) = resources.get(&format!("{}/configuration", path)) {
+      status.insert("configuration".to_string(), configured.clone());
+    }
+    if let Some(actual) = actual(path) {
+      status.insert("actual".to_string(), actual.clone());
+      status.insert("status".to_string(), allocation_status());
+      return Some(Value::Object(status));
+    }
+  }
+  None
+}
+
+// Simulates the deployment of an app from the app catalog, by keeping the allocated app in line with its configuration.
+// The resources of the manifest are not rendered, so the allocated app will not have any resources.
+fn allocate_app(app_configuration_path: &str, app_configuration: Option<&Value>, resources: &mut BTreeMap<String, Value>) {
+  let Some(app_id) = app_configuration_path.split('/').nth(4) else {
+    return;
+  };
+  let allocated_app_path = format!("/allocation/{}/appcatalogapp/{}/configuration", TENANT_PLACEHOLDER, app_id);
+  match app_configuration {
+    Some(app_configuration) => {
+      let allocated_app = json!({
+        "configuration": app_configuration["configuration"].to_string(),
+        "manifestUrn": app_configuration["manifestUrn"],
+        "name": app_configuration["name"],
+        "resources": resources.get(&allocated_app_path).map(|allocated_app| allocated_app["resources"].clone()).unwrap_or(json!({}))
+      });
+      resources.insert(allocated_app_path, allocated_app);
+    }
+    None => {
+      resources.remove(&allocated_app_path);
+    }
+  }
+}
+
+// Simulates the tasks of a service, by killing or starting tasks until the requested number of instances is running
+fn reconcile_tasks(application_configuration_path: &str, instances: usize, resources: &mut BTreeMap<String, Value>) {
+  let Some(application_id) = application_configuration_path.split('/').nth(4) else {
+    return;
+  };
+  let tasks_prefix = format!("/allocation/{}/task/{}/", TENANT_PLACEHOLDER, application_id);
+  let timestamp = utc_timestamp(SystemTime::now());
+  let last_task_number = resources
+    .keys()
+    .filter_map(|path| path.strip_prefix(&tasks_prefix))
+    .filter_map(|task| task.split('/').next()?.rsplit('-').next().and_then(|number| u64::from_str_radix(number, 16).ok()))
+    .max()
+    .unwrap_or_default();
+  let mut running_tasks = resources
+    .iter_mut()
+    .filter(|(path, task)| path.starts_with(&tasks_prefix) && task.get("state") == Some(&Value::from("RUNNING")))
+    .map(|(_, task)| task)
+    .collect::<Vec<_>>();
+  let running_task_count = running_tasks.len();
+  for task in running_tasks.iter_mut().skip(instances) {
+    task["state"] = Value::from("KILLED");
+    task["stoppedAt"] = Value::from(timestamp.clone());
+  }
+  for index in running_task_count..instances {
+    resources.insert(
+      format!(
+        "{}{}.00000000-0000-0000-0000-{:012x}/actual",
+        tasks_prefix,
+        application_id,
+        last_task_number + (index - running_task_count) as u64 + 1
+      ),
+      json!({ "healthy": true, "host": "10.0.0.1", "stagedAt": timestamp, "startedAt": timestamp, "state": "RUNNING" }),
+    );
+  }
+}
+
+fn allocation_status() -> Value {
+  json!({ "notifications": [], "provisioned": true })
+}
+
+// Replaces the tenant in the path by a placeholder, such that all tenants share the same resources
+fn normalize_path(path: &str, template: &str) -> String {
+  path
+    .trim_matches('/')
+    .split('/')
+    .zip(template.trim_matches('/').split('/'))
+    .enumerate()
+    .map(|(index, (path_segment, template_segment))| if index == 1 && template_segment.starts_with('{') { TENANT_PLACEHOLDER } else { path_segment })
+    .fold(String::new(), |normalized_path, segment| format!("{}/{}", normalized_path, segment))
+}
+
+/// # Returns the path template that matches a path
+///
+/// When more than one template matches, the template with the most literal segments is returned.
+/// For example, the path `/allocation/my-tenant/bucket/fromthirdparty` matches the template
+/// `/allocation/{tenant}/bucket/fromthirdparty` and not `/allocation/{tenant}/bucket/{id}`.
+fn match_path_template(path: &str) -> Option<&'static str> {
+  let path_segments = path.trim_matches('/').split('/').collect::<Vec<_>>();
+  OPENAPI_SPEC
+    .as_ref()?
+    .get("paths")?
+    .as_object()?
+    .keys()
+    .filter_map(|template| {
+      let template_segments = template.trim_matches('/').split('/').collect::<Vec<_>>();
+      if template_segments.len() != path_segments.len() {
+        return None;
+      }
+      let mut literal_segments = 0;
+      for (template_segment, path_segment) in template_segments.iter().zip(path_segments.iter()) {
+        if template_segment.starts_with('{') {
+          if path_segment.is_empty() {
+            return None;
+          }
+        } else if template_segment == path_segment {
+          literal_segments += 1;
+        } else {
+          return None;
+        }
+      }
+      Some((literal_segments, template.as_str()))
+    })
+    .max_by_key(|(literal_segments, _)| *literal_segments)
+    .map(|(_, template)| template)
+}
+
+/// # Returns the operation for a method and path template
+fn operation(method: &str, path_template: &str) -> Option<&'static Value> {
+  OPENAPI_SPEC
+    .as_ref()?
+    .pointer(&format!("/paths/{}/{}", escape_pointer_token(path_template), method.to_lowercase()))
+}
+
+/// # Returns the first successful response of an operation
+///
+/// Returns the status code, and the content type and schema of the response body when the response has a body.
+fn success_response(operation: &'static Value) -> Option<(u16, Option<(&'static str, &'static Value)>)> {
+  let (status, response) = operation.get("responses")?.as_object()?.iter().find(|(status, _)| status.starts_with('2'))?;
+  let content = response
+    .get("content")
+    .and_then(|content| content.as_object())
+    .and_then(|content| content.iter().next())
+    .and_then(|(content_type, media_type)| media_type.get("schema").map(|schema| (content_type.as_str(), schema)));
+  Some((status.parse().ok()?, content))
+}
+
+// Writes the user defined platforms file with only the mock platform,
+// in the format of the platforms file of the dsh tool
+fn write_mock_platform(home: &Path) -> Result<(), String> {
+  let mock_platforms = json!([{
+    "name": MOCK_PLATFORM,
+    "description": "Local mock server",
+    "alias": MOCK_PLATFORM_ALIAS,
+    "is-production": false,
+    "cloud-provider": "aws",
+    "access-token-endpoint": format!("https://auth.{}/auth/realms/{}/protocol/openid-connect/token", MOCK_PUBLIC_DOMAIN, MOCK_REALM),
+    "realm": MOCK_REALM,
+    "public-domain": MOCK_PUBLIC_DOMAIN
+  }]);
+  let platforms_file = home.join(USER_PLATFORMS_FILE);
+  fs::write(&platforms_file, serde_json::to_string_pretty(&mock_platforms).unwrap_or_default())
+    .map_err(|error| format!("could not write platforms file '{}' ({})", platforms_file.to_string_lossy(), error))
+}
+
+// Formats a system time as a utc timestamp in rfc 3339 format, like the dsh tool does
+fn utc_timestamp(time: SystemTime) -> String {
+  time
+    .duration_since(UNIX_EPOCH)
+    .ok()
+    .and_then(|duration| DateTime::from_timestamp_millis(duration.as_millis() as i64))
+    .map(|timestamp| format!("{:?}", timestamp))
+    .unwrap_or_default()
+}
+
+#[test]
+fn test_default_fixtures() {
+  let resources = serde_json::from_str::<BTreeMap<String, Value>>(DEFAULT_FIXTURES).unwrap();
+  for (path, value) in &resources {
+    let template = match_path_template(path).unwrap_or_else(|| panic!("unknown fixture path '{}'", path));
+    let (_, content) = success_response(operation("get", template).unwrap()).unwrap();
+    let (_, schema) = content.unwrap();
+    assert!(validate(schema, value).is_empty(), "invalid fixture '{}' {:?}", path, validate(schema, value));
+    // Validate the values that are derived from the fixture
+    let resource = path.strip_suffix("/configuration").or_else(|| path.strip_suffix("/actual")).unwrap_or(path);
+    for derived_path in [resource.to_string(), format!("{}/actual", resource), format!("{}/status", resource)] {
+      if let Some(template) = match_path_template(&derived_path) {
+        let (_, content) = success_response(operation("get", template).unwrap()).unwrap();
+        let (_, schema) = content.unwrap();
+        let derived_value = get_resource(&derived_path, schema, &resources).unwrap_or_else(|| panic!("no value for '{}'", derived_path));
+        assert!(
+          validate(schema, &derived_value).is_empty(),
+          "invalid derived value '{}' {:?}",
+          derived_path,
+          validate(schema, &derived_value)
+        );
+      }
+    }
+  }
+}
+
+#[test]
+fn test_handle_resource_request() {
+  let mut resources = serde_json::from_str::<BTreeMap<String, Value>>(DEFAULT_FIXTURES).unwrap();
+  let mut request = |method: Method, path: &str, body: &str| handle_resource_request(&method, path, body.as_bytes(), &mut resources);
+  let (status, content) = request(Method::GET, "/allocation/my-tenant/volume", "");
+  assert_eq!((status, content.unwrap().1), (StatusCode::OK, "[\"greeter-data\"]".to_string()));
+  assert_eq!(
+    request(Method::GET, "/allocation/my-tenant/volume/other/configuration", "").0,
+    StatusCode::NOT_FOUND
+  );
+  assert_eq!(
+    request(Method::PUT, "/allocation/my-tenant/volume/other/configuration", "{\"sizeGiB\":\"big\"}").0,
+    StatusCode::BAD_REQUEST
+  );
+  assert_eq!(
+    request(Method::PUT, "/allocation/my-tenant/volume/other/configuration", "{\"sizeGiB\":2}").0,
+    StatusCode::ACCEPTED
+  );
+  let (status, content) = request(Method::GET, "/allocation/my-tenant/volume/other", "");
+  assert_eq!(status, StatusCode::OK);
+  assert_eq!(
+    serde_json::from_str::<Value>(&content.unwrap().1).unwrap(),
+    json!({ "actual": { "sizeGiB": 2 }, "configuration": { "sizeGiB": 2 }, "status": { "notifications": [], "provisioned": true } })
+  );
+  assert_eq!(
+    request(Method::DELETE, "/allocation/my-tenant/volume/other/configuration", "").0,
+    StatusCode::ACCEPTED
+  );
+  assert_eq!(request(Method::GET, "/allocation/my-tenant/volume/other/status", "").0, StatusCode::NOT_FOUND);
+  assert_eq!(
+    request(Method::POST, "/allocation/my-tenant/secret", "{\"name\":\"new-secret\",\"value\":\"s3cr3t\"}").0,
+    StatusCode::CREATED
+  );
+  assert_eq!(
+    request(Method::POST, "/allocation/my-tenant/secret", "{\"name\":\"new-secret\",\"value\":\"s3cr3t\"}").0,
+    StatusCode::CONFLICT
+  );
+  assert_eq!(
+    request(Method::GET, "/allocation/my-tenant/secret/new-secret", "").1,
+    Some(("text/plain", "s3cr3t".to_string()))
+  );
+  let app_configuration = "{\"configuration\":{},\"manifestUrn\":\"appcatalog/manifest/kpn/mock-echo/1.1.0\",\"name\":\"other\",\"stopped\":false}";
+  assert_eq!(
+    request(Method::PUT, "/appcatalog/my-tenant/appcatalogapp/other/configuration", app_configuration).0,
+    StatusCode::ACCEPTED
+  );
+  let app_ids = |content: Option<(&str, String)>| {
+    serde_json::from_str::<Map<String, Value>>(&content.unwrap().1)
+      .unwrap()
+      .keys()
+      .cloned()
+      .collect::<Vec<_>>()
+  };
+  assert_eq!(
+    app_ids(request(Method::GET, "/allocation/my-tenant/appcatalogapp/configuration", "").1),
+    vec!["echo", "other"]
+  );
+  assert_eq!(
+    request(Method::DELETE, "/appcatalog/my-tenant/appcatalogapp/other/configuration", "").0,
+    StatusCode::ACCEPTED
+  );
+  assert_eq!(
+    app_ids(request(Method::GET, "/allocation/my-tenant/appcatalogapp/configuration", "").1),
+    vec!["echo"]
+  );
+  let (status, content) = request(Method::GET, "/allocation/my-tenant/application/configuration", "");
+  assert_eq!(status, StatusCode::OK);
+  let applications = serde_json::from_str::<Value>(&content.unwrap().1).unwrap();
+  assert_eq!(applications.as_object().unwrap().keys().collect::<Vec<_>>(), vec!["consumer", "greeter"]);
+  assert_eq!(request(Method::PATCH, "/allocation/my-tenant/volume", "").0, StatusCode::METHOD_NOT_ALLOWED);
+  let service = "{\"cpus\":0.1,\"image\":\"image\",\"instances\":INSTANCES,\"mem\":256,\"user\":\"1054:1054\"}";
+  let task_states = |resources: &BTreeMap<String, Value>| {
+    resources
+      .iter()
+      .filter(|(path, _)| path.starts_with("/allocation/{tenant}/task/consumer/"))
+      .map(|(_, task)| task["state"].as_str().unwrap().to_string())
+      .collect::<Vec<_>>()
+  };
+  handle_resource_request(
+    &Method::PUT,
+    "/allocation/my-tenant/application/consumer/configuration",
+    service.replace("INSTANCES", "0").as_bytes(),
+    &mut resources,
+  );
+  assert_eq!(task_states(&resources), vec!["KILLED", "KILLED"]);
+  handle_resource_request(
+    &Method::PUT,
+    "/allocation/my-tenant/application/consumer/configuration",
+    service.replace("INSTANCES", "1").as_bytes(),
+    &mut resources,
+  );
+  assert_eq!(task_states(&resources), vec!["KILLED", "KILLED", "RUNNING"]);
+  assert_eq!(
+    normalize_path("/allocation/my-tenant/volume/v", "/allocation/{tenant}/volume/{id}"),
+    "/allocation/{tenant}/volume/v"
+  );
+}
@@ -0,0 +1,76 @@
+use clap::builder::ValueParser;
+use clap::{value_parser, Arg, ArgAction, Command};
+use dsh_mock_server::MockServer;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::mpsc;
+
+const FIXTURES_ARGUMENT: &str = "fixtures-argument";
+const PORT_ARGUMENT: &str = "port-argument";
+
+const DEFAULT_PORT: u16 = 8443;
+
+fn main() -> ExitCode {
+  match run() {
+    Ok(()) => ExitCode::SUCCESS,
+    Err(error) => {
+      eprintln!("{}", error);
+      ExitCode::FAILURE
+    }
+  }
+}
+
+// Runs the mock server with a temporary home directory until it is interrupted
+fn run() -> Result<(), String> {
+  let matches = Command::new("dsh-mock-server")
+    .about("Start local mock server for the DSH resource management api")
+    .long_about(
+      "Start a local mock server that serves the DSH resource management api from in-memory fixtures, \
+      including a fake access token endpoint. Changes made via the api are kept in memory \
+      until the server is stopped. The mock server uses a temporary home directory for the dsh tool, \
+      which is removed when the server is stopped. The instructions to configure the dsh tool \
+      to use the mock server will be shown on start.",
+    )
+    .args(vec![
+      Arg::new(FIXTURES_ARGUMENT)
+        .long("fixtures")
+        .action(ArgAction::Set)
+        .value_parser(ValueParser::path_buf())
+        .value_name("FILE")
+        .help(
+          "Json file with the initial resources, as a map from resource paths to values, \
+          where the tenant in the paths is replaced by '{tenant}'",
+        ),
+      Arg::new(PORT_ARGUMENT)
+        .long("port")
+        .action(ArgAction::Set)
+        .value_parser(value_parser!(u16))
+        .value_name("PORT")
+        .help(format!("Port of the mock server [default: {}]", DEFAULT_PORT)),
+    ])
+    .get_matches();
+  let fixtures = match matches.get_one::<PathBuf>(FIXTURES_ARGUMENT) {
+    Some(fixtures_file) => Some(fs::read_to_string(fixtures_file).map_err(|error| format!("could not read fixtures file '{}' ({})", fixtures_file.to_string_lossy(), error))?),
+    None => None,
+  };
+  let home = tempfile::Builder::new()
+    .prefix("dsh-mock-server-")
+    .tempdir()
+    .map_err(|error| format!("could not create home directory ({})", error))?;
+  let port = matches.get_one::<u16>(PORT_ARGUMENT).cloned().unwrap_or(DEFAULT_PORT);
+  let mut mock_server = MockServer::start(home.path(), port, fixtures.as_deref())?;
+  println!("mock server listening on {}", mock_server.address());
+  println!("use the mock server from another terminal with");
+  for (name, value) in mock_server.environment_variables() {
+    println!("  export {}={}", name, value);
+  }
+  let (interrupted, interrupt_receiver) = mpsc::channel::<()>();
+  ctrlc::set_handler(move || {
+    let _ = interrupted.send(());
+  })
+  .map_err(|error| error.to_string())?;
+  let _ = interrupt_receiver.recv();
+  mock_server.stop();
+  home.close().map_err(|error| format!("could not remove home directory ({})", error))
+}
@@ -1,3 +1,4 @@
+use crate::completion::resource_id_completer;
 use clap::builder::PossibleValue;
 use clap::{builder, Arg, ArgAction};
 use dsh_api::platform::DshPlatform;
@@ -29,6 +30,7 @@ pub(crate) fn app_id_argument() -> Arg {
     .value_name("APP")
     .help("App identifier")
     .long_help("Identifies an app from the app catalog.")
+    .add(resource_id_completer(APP_ID_ARGUMENT))
 }
 
 pub(crate) fn bucket_id_argument() -> Arg {
@@ -38,6 +40,7 @@ pub(crate) fn bucket_id_argument() -> Arg {
     .value_name("BUCKET")
     .help("Bucket identifier")
     .long_help("Identifies an S3 bucket on the DSH.")
+    .add(resource_id_completer(BUCKET_ID_ARGUMENT))
 }
 
 pub(crate) fn certificate_id_argument() -> Arg {
@@ -47,6 +50,7 @@ pub(crate) fn certificate_id_argument() -> Arg {
     .value_name("CERT")
     .help("Certificate identifier")
     .long_help("Identifies a certificate on the DSH.")
+    .add(resource_id_completer(CERTIFICATE_ID_ARGUMENT))
 }
 
 #[cfg(feature = "manage")]
@@ -76,6 +80,7 @@ pub(crate) fn manifest_id_argument() -> Arg {
     .value_name("MANIFEST")
     .help("Manifest identifier")
     .long_help("Identifies a manifest from the app catalog.")
+    .add(resource_id_completer(MANIFEST_ID_ARGUMENT))
 }
 
 pub(crate) fn platform_name_argument() -> Arg {
@@ -114,6 +119,7 @@ pub(crate) fn proxy_id_argument() -> Arg {
     .value_name("PROXY")
     .help("Proxy identifier")
     .long_help("Identifies a proxy configured on the DSH.")
+    .add(resource_id_completer(PROXY_ID_ARGUMENT))
 }
 
 pub(crate) fn secret_id_argument() -> Arg {
@@ -123,6 +129,7 @@ pub(crate) fn secret_id_argument() -> Arg {
     .value_name("SECRET")
     .help("Secret identifier")
     .long_help("Identifies a secret configured on the DSH.")
+    .add(resource_id_completer(SECRET_ID_ARGUMENT))
 }
 
 pub(crate) fn service_id_argument() -> Arg {
@@ -132,6 +139,7 @@ pub(crate) fn service_id_argument() -> Arg {
     .value_name("SERVICE")
     .help("Service identifier")
     .long_help("Identifies a service deployed on the DSH.")
+    .add(resource_id_completer(SERVICE_ID_ARGUMENT))
 }
 
 pub(crate) fn tenant_name_argument() -> Arg {
@@ -150,6 +158,7 @@ pub(crate) fn topic_id_argument() -> Arg {
     .value_name("TOPIC")
     .help("Topic identifier")
     .long_help("Identifies a topic deployed on the DSH.")
+    .add(resource_id_completer(TOPIC_ID_ARGUMENT))
 }
 
 pub(crate) fn vendor_name_argument() -> Arg {
@@ -177,6 +186,7 @@ pub(crate) fn vhost_id_argument() -> Arg {
     .value_name("VHOST")
     .help("Vhost identifier")
     .long_help("Identifies a vhost configured on the DSH.")
+    .add(resource_id_completer(VHOST_ID_ARGUMENT))
 }
 
 pub(crate) fn volume_id_argument() -> Arg {
@@ -186,4 +196,5 @@ pub(crate) fn volume_id_argument() -> Arg {
     .value_name("VOLUME")
     .help("Volume identifier")
     .long_help("Identifies a volume configured on the DSH.")
+    .add(resource_id_completer(VOLUME_ID_ARGUMENT))
 }
@@ -0,0 +1,157 @@
+use crate::dsh_directory;
+use crate::log_writer::utc_timestamp;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+const AUDIT_LOG_FILENAME: &str = "audit.log";
+
+lazy_static! {
+  static ref ABSOLUTE_SINCE_REGEX: Regex = Regex::new(r"^\d{4}-\d{2}-\d{2}(T\d{2}(:\d{2}(:\d{2})?)?)?$").unwrap();
+  static ref RELATIVE_SINCE_REGEX: Regex = Regex::new(r"^(\d+)([smhdw])$").unwrap();
+}
+
+/// # Record in the audit log
+///
+/// An audit record is appended to the audit log in the `dsh` tool's home directory
+/// for each mutation of a resource on the platform that was not executed in dry-run mode.
+/// The audit log contains one json object per line.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub(crate) struct AuditRecord {
+  pub(crate) timestamp: String,
+  pub(crate) user: String,
+  pub(crate) platform: String,
+  pub(crate) tenant: String,
+  pub(crate) command: String,
+  pub(crate) resource: String,
+  pub(crate) outcome: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub(crate) before: Option<Value>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub(crate) after: Option<Value>,
+}
+
+impl AuditRecord {
+  pub(crate) fn new(platform: &str, tenant: &str, resource: String, outcome: String, before: Option<Value>, after: Option<Value>) -> Self {
+    Self {
+      timestamp: utc_timestamp(SystemTime::now()),
+      user: env::var("USER").or_else(|_| env::var("USERNAME")).unwrap_or_default(),
+      platform: platform.to_string(),
+      tenant: tenant.to_string(),
+      command: command_line(),
+      resource,
+      outcome,
+      before,
+      after,
+    }
+  }
+}
+
+/// # Appends a record to the audit log
+pub(crate) fn append_audit_record(audit_record: &AuditRecord) -> Result<(), String> {
+  let audit_log_file = audit_log_file()?;
+  let line = serde_json::to_string(audit_record).map_err(|error| error.to_string())?;
+  let mut file = OpenOptions::new()
+    .create(true)
+    .append(true)
+    .open(&audit_log_file)
+    .map_err(|error| format!("could not open audit log '{}' ({})", audit_log_file.to_string_lossy(), error))?;
+  writeln!(file, "{}", line).map_err(|error| format!("could not write audit log '{}' ({})", audit_log_file.to_string_lossy(), error))
+}
+
+/// # Reads all records from the audit log
+///
+/// The records will be returned in the order in which they were appended.
+/// When the audit log does not exist, an empty list will be returned.
+pub(crate) fn read_audit_records() -> Result<Vec<AuditRecord>, String> {
+  let audit_log_file = audit_log_file()?;
+  let file = match File::open(&audit_log_file) {
+    Ok(file) => file,
+    Err(error) if error.kind() == ErrorKind::NotFound => return Ok(vec![]),
+    Err(error) => return Err(format!("could not open audit log '{}' ({})", audit_log_file.to_string_lossy(), error)),
+  };
+  let mut audit_records = vec![];
+  for (index, line) in BufReader::new(file).lines().enumerate() {
+    let line = line.map_err(|error| format!("could not read audit log '{}' ({})", audit_log_file.to_string_lossy(), error))?;
+    if !line.trim().is_empty() {
+      audit_records.push(serde_json::from_str::<AuditRecord>(&line).map_err(|error| {
+        format!(
+          "invalid record in audit log '{}' at line {} ({})",
+          audit_log_file.to_string_lossy(),
+          index + 1,
+          error
+        )
+      })?);
+    }
+  }
+  Ok(audit_records)
+}
+
+/// # Converts a `--since` value to a timestamp
+///
+/// The value can either be an absolute utc date or date/time, like `2025-03-01` or `2025-03-01T12:00`,
+/// or a relative period, like `30m`, `12h`, `7d` or `2w`.
+/// The returned timestamp can be compared lexicographically with the audit record timestamps.
+pub(crate) fn since_timestamp(since: &str) -> Result<String, String> {
+  if ABSOLUTE_SINCE_REGEX.is_match(since) {
+    Ok(since.to_string())
+  } else if let Some(captures) = RELATIVE_SINCE_REGEX.captures(since) {
+    let amount = captures[1].parse::<u64>().map_err(|_| format!("invalid period '{}'", since))?;
+    let seconds = match &captures[2] {
+      "s" => amount,
+      "m" => amount * 60,
+      "h" => amount * 3600,
+      "d" => amount * 86400,
+      _ => amount * 604800,
+    };
+    SystemTime::now()
+      .checked_sub(Duration::from_secs(seconds))
+      .map(utc_timestamp)
+      .ok_or_else(|| format!("invalid period '{}'", since))
+  } else {
+    Err(format!(
+      "invalid since value '{}', expected a date (like 2025-03-01), a date/time (like 2025-03-01T12:00) or a period (like 12h or 7d)",
+      since
+    ))
+  }
+}
+
+fn audit_log_file() -> Result<PathBuf, String> {
+  match dsh_directory()? {
+    Some(dsh_directory) => Ok(dsh_directory.join(AUDIT_LOG_FILENAME)),
+    None => Err("audit log is not available since the dsh tool's home directory is not set".to_string()),
+  }
+}
+
+fn command_line() -> String {
+  env::args()
+    .enumerate()
+    .map(|(index, argument)| {
+      if index == 0 {
+        "dsh".to_string()
+      } else if argument.is_empty() || argument.contains(char::is_whitespace) {
+        format!("'{}'", argument)
+      } else {
+        argument
+      }
+    })
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+#[test]
+fn test_since_timestamp() {
+  assert_eq!(since_timestamp("2025-03-01").unwrap(), "2025-03-01");
+  assert_eq!(since_timestamp("2025-03-01T12:00").unwrap(), "2025-03-01T12:00");
+  assert!(since_timestamp("7d").unwrap() < utc_timestamp(SystemTime::now()));
+  assert!(since_timestamp("12h").unwrap() > since_timestamp("1d").unwrap());
+  for invalid_since in ["", "yesterday", "7y", "2025-3-1"] {
+    assert!(since_timestamp(invalid_since).is_err(), "since '{}'", invalid_since);
+  }
+}
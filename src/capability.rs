@@ -5,12 +5,15 @@ use async_trait::async_trait;
 use clap::{Arg, ArgMatches, Command};
 use dsh_api::dsh_api_client::DshApiClient;
 
+pub(crate) const ADD_COMMAND: &str = "add";
 pub(crate) const COPY_COMMAND: &str = "copy";
 pub(crate) const CREATE_COMMAND: &str = "create";
 pub(crate) const CREATE_COMMAND_ALIAS: &str = "c";
 pub(crate) const DEFAULT_COMMAND: &str = "default";
 pub(crate) const DEFAULT_COMMAND_ALIAS: &str = "d";
 pub(crate) const DELETE_COMMAND: &str = "delete";
+pub(crate) const DEPLOY_COMMAND: &str = "deploy";
+pub(crate) const DIFF_COMMAND: &str = "diff";
 pub(crate) const DUPLICATE_COMMAND: &str = "duplicate";
 pub(crate) const EDIT_COMMAND: &str = "edit";
 pub(crate) const EXPORT_COMMAND: &str = "export";
@@ -24,9 +27,15 @@ pub(crate) const LIST_COMMAND: &str = "list";
 pub(crate) const LIST_COMMAND_ALIAS: &str = "l";
 pub(crate) const OPEN_COMMAND: &str = "open";
 pub(crate) const OPEN_COMMAND_ALIAS: &str = "o";
+pub(crate) const OUTDATED_COMMAND: &str = "outdated";
+#[cfg(feature = "manage")]
+pub(crate) const PROVISION_COMMAND: &str = "provision";
+pub(crate) const REFRESH_COMMAND: &str = "refresh";
+pub(crate) const REMOVE_COMMAND: &str = "remove";
 pub(crate) const RESTART_COMMAND: &str = "restart";
 #[cfg(feature = "manage")]
 pub(crate) const REVOKE_COMMAND: &str = "revoke";
+pub(crate) const ROLLBACK_COMMAND: &str = "rollback";
 pub(crate) const SET_COMMAND: &str = "set";
 pub(crate) const SHOW_COMMAND: &str = "show";
 pub(crate) const SHOW_COMMAND_ALIAS: &str = "s";
@@ -34,6 +43,8 @@ pub(crate) const START_COMMAND: &str = "start";
 pub(crate) const STOP_COMMAND: &str = "stop";
 pub(crate) const UNSET_COMMAND: &str = "unset";
 pub(crate) const UPDATE_COMMAND: &str = "update";
+pub(crate) const USAGE_COMMAND: &str = "usage";
+pub(crate) const VALIDATE_COMMAND: &str = "validate";
 
 #[async_trait]
 pub trait Capability {
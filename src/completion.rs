@@ -0,0 +1,340 @@
+use crate::arguments::{
+  APP_ID_ARGUMENT, BUCKET_ID_ARGUMENT, CERTIFICATE_ID_ARGUMENT, MANIFEST_ID_ARGUMENT, PROXY_ID_ARGUMENT, SECRET_ID_ARGUMENT, SERVICE_ID_ARGUMENT, TOPIC_ID_ARGUMENT,
+  VHOST_ID_ARGUMENT, VOLUME_ID_ARGUMENT,
+};
+use crate::resource_ids::fetch_resource_ids;
+use crate::settings::get_settings;
+use crate::targets::all_targets;
+use crate::{dsh_directory, get_target_password_non_interactive, get_target_platform_implicit, get_target_tenant_implicit};
+use clap_complete::engine::{ArgValueCompleter, CompletionCandidate};
+use dsh_api::dsh_api_client::DshApiClient;
+use dsh_api::dsh_api_client_factory::DshApiClientFactory;
+use dsh_api::dsh_api_tenant::DshApiTenant;
+use dsh_api::platform::DshPlatform;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::runtime::Handle;
+use tokio::task::block_in_place;
+
+const COMPLETION_CACHE_SUBDIRECTORY: &str = "completion";
+
+/// Number of seconds that the cached resource identifiers are considered up to date
+const COMPLETION_CACHE_TTL: u64 = 300;
+
+/// Resource types and the arguments whose values will be completed from the completion cache
+pub(crate) const CACHED_RESOURCE_ARGUMENTS: [(&str, &str); 10] = [
+  ("app", APP_ID_ARGUMENT),
+  ("bucket", BUCKET_ID_ARGUMENT),
+  ("certificate", CERTIFICATE_ID_ARGUMENT),
+  ("manifest", MANIFEST_ID_ARGUMENT),
+  ("proxy", PROXY_ID_ARGUMENT),
+  ("secret", SECRET_ID_ARGUMENT),
+  ("service", SERVICE_ID_ARGUMENT),
+  ("topic", TOPIC_ID_ARGUMENT),
+  ("vhost", VHOST_ID_ARGUMENT),
+  ("volume", VOLUME_ID_ARGUMENT),
+];
+
+/// # Cached resource identifiers
+///
+/// The completion cache is stored in the `dsh` tool's home directory,
+/// in the file `completion/<platform>/<tenant>.json`.
+/// It contains the identifiers of the resources per argument,
+/// together with the moment (in seconds since the epoch) at which they were fetched.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+struct CachedResourceIds {
+  timestamp: u64,
+  ids: Vec<String>,
+}
+
+/// # Creates the completer for a resource identifier argument
+///
+/// When completing, the target platform and tenant will be determined from the
+/// command line that is being completed, or else from the environment variables and settings.
+/// The resource identifiers are taken from the completion cache when they are up to date,
+/// else they will be fetched from the platform, if the password is available
+/// without user interaction.
+pub(crate) fn resource_id_completer(argument_id: &'static str) -> ArgValueCompleter {
+  ArgValueCompleter::new(move |current: &OsStr| {
+    let current = current.to_string_lossy();
+    match completion_target() {
+      Some((platform, tenant, password_file)) => completion_resource_ids(&platform, &tenant, password_file, argument_id)
+        .into_iter()
+        .filter(|resource_id| resource_id.starts_with(current.as_ref()))
+        .map(CompletionCandidate::new)
+        .collect(),
+      None => vec![],
+    }
+  })
+}
+
+/// # Creates the completer for the `--platform` argument
+///
+/// The candidates are the platforms of the stored targets.
+/// When there are no stored targets, all platforms will be candidates.
+pub(crate) fn target_platform_completer() -> ArgValueCompleter {
+  ArgValueCompleter::new(|current: &OsStr| {
+    let current = current.to_string_lossy();
+    let mut platforms = all_targets().unwrap_or_default().into_iter().map(|target| target.platform).collect::<Vec<_>>();
+    if platforms.is_empty() {
+      platforms = DshPlatform::all().to_vec();
+    }
+    platforms.dedup();
+    platforms
+      .into_iter()
+      .filter(|platform| platform.name().starts_with(current.as_ref()))
+      .map(|platform| CompletionCandidate::new(platform.name()).help(Some(platform.description().to_string().into())))
+      .collect()
+  })
+}
+
+/// # Creates the completer for the `--tenant` argument
+///
+/// The candidates are the tenants of the stored targets.
+/// When the platform is known, only the tenants on that platform will be candidates.
+pub(crate) fn target_tenant_completer() -> ArgValueCompleter {
+  ArgValueCompleter::new(|current: &OsStr| {
+    let current = current.to_string_lossy();
+    let arguments = env::args().collect::<Vec<_>>();
+    let platform = argument_value(&arguments, "--platform", Some('p')).and_then(|platform| DshPlatform::try_from(platform.as_str()).ok());
+    target_tenants(platform.as_ref())
+      .into_iter()
+      .filter(|tenant| tenant.starts_with(current.as_ref()))
+      .map(CompletionCandidate::new)
+      .collect()
+  })
+}
+
+/// # Returns the tenants of the stored targets
+///
+/// When `platform` is provided, only the tenants on that platform will be returned.
+pub(crate) fn target_tenants(platform: Option<&DshPlatform>) -> Vec<String> {
+  let mut tenants = all_targets()
+    .unwrap_or_default()
+    .into_iter()
+    .filter(|target| platform.is_none_or(|platform| &target.platform == platform))
+    .map(|target| target.tenant)
+    .collect::<Vec<_>>();
+  tenants.sort();
+  tenants.dedup();
+  tenants
+}
+
+/// # Returns the resource identifiers for an argument, using the completion cache
+///
+/// When the completion cache contains up to date identifiers for the argument,
+/// these will be returned. Else the identifiers will be fetched using `client`
+/// and stored in the completion cache.
+/// Errors are silently ignored, in which case an empty list will be returned.
+pub(crate) fn cached_resource_ids(platform: &DshPlatform, tenant: &str, argument_id: &str, client: &DshApiClient) -> Vec<String> {
+  let mut cache = read_completion_cache(platform, tenant);
+  if let Some(resource_ids) = up_to_date_resource_ids(&cache, argument_id, now()) {
+    return resource_ids;
+  }
+  match Handle::try_current() {
+    Ok(handle) => match block_in_place(|| handle.block_on(fetch_resource_ids(argument_id, client))) {
+      Some(Ok(resource_ids)) => {
+        cache.insert(argument_id.to_string(), CachedResourceIds { timestamp: now(), ids: resource_ids.clone() });
+        let _ = write_completion_cache(platform, tenant, &cache);
+        resource_ids
+      }
+      _ => vec![],
+    },
+    Err(_) => vec![],
+  }
+}
+
+/// # Rebuilds the completion cache for the target of `client`
+///
+/// Returns the number of identifiers that were cached per resource type.
+pub(crate) async fn refresh_completion_cache(client: &DshApiClient) -> Result<Vec<(&'static str, usize)>, String> {
+  let mut cache = BTreeMap::new();
+  let mut counts = vec![];
+  for (resource_type, argument_id) in CACHED_RESOURCE_ARGUMENTS {
+    if let Some(resource_ids) = fetch_resource_ids(argument_id, client).await {
+      let resource_ids = resource_ids.map_err(|error| error.to_string())?;
+      counts.push((resource_type, resource_ids.len()));
+      cache.insert(argument_id.to_string(), CachedResourceIds { timestamp: now(), ids: resource_ids });
+    }
+  }
+  write_completion_cache(client.platform(), client.tenant_name(), &cache)?;
+  Ok(counts)
+}
+
+/// # Invalidates the cached resource identifiers after a resource was changed
+///
+/// The resource has the form `<kind>/<id>`, as in the audit log. The cached identifiers
+/// for the resource type will be removed, such that they will be fetched again
+/// when they are completed next. Errors are silently ignored.
+pub(crate) fn invalidate_cached_resource_ids(platform: &DshPlatform, tenant: &str, resource: &str) {
+  let resource_type = resource.split('/').next().unwrap_or_default();
+  let mut cache = read_completion_cache(platform, tenant);
+  let number_of_cached_arguments = cache.len();
+  cache.retain(|argument_id, _| {
+    !CACHED_RESOURCE_ARGUMENTS
+      .iter()
+      .any(|(cached_resource_type, cached_argument_id)| *cached_resource_type == resource_type && cached_argument_id == argument_id)
+  });
+  if cache.len() != number_of_cached_arguments {
+    let _ = write_completion_cache(platform, tenant, &cache);
+  }
+}
+
+/// Returns the resource identifiers for the completion target, without user interaction
+fn completion_resource_ids(platform: &DshPlatform, tenant: &str, password_file: Option<PathBuf>, argument_id: &str) -> Vec<String> {
+  let cache = read_completion_cache(platform, tenant);
+  if let Some(resource_ids) = up_to_date_resource_ids(&cache, argument_id, now()) {
+    return resource_ids;
+  }
+  let dsh_api_tenant = DshApiTenant::new(tenant.to_string(), platform.clone());
+  let password = match get_target_password_non_interactive(password_file.as_ref(), &dsh_api_tenant) {
+    Ok(Some(password)) => password,
+    _ => return vec![],
+  };
+  let client = match Handle::try_current() {
+    Ok(handle) => block_in_place(|| {
+      handle.block_on(async {
+        match DshApiClientFactory::create(dsh_api_tenant, password) {
+          Ok(factory) => factory.client().await.ok(),
+          Err(_) => None,
+        }
+      })
+    }),
+    Err(_) => None,
+  };
+  match client {
+    Some(client) => cached_resource_ids(platform, tenant, argument_id, &client),
+    None => vec![],
+  }
+}
+
+/// # Determines the target of the command line that is being completed
+///
+/// The platform and tenant are taken from the `--platform` and `--tenant` arguments,
+/// or else from the environment variables and the settings file.
+fn completion_target() -> Option<(DshPlatform, String, Option<PathBuf>)> {
+  let arguments = env::args().collect::<Vec<_>>();
+  let settings = get_settings(None).map(|(settings, _)| settings).unwrap_or_default();
+  let platform = match argument_value(&arguments, "--platform", Some('p')) {
+    Some(platform) => DshPlatform::try_from(platform.as_str()).ok()?,
+    None => get_target_platform_implicit(&settings).ok()??,
+  };
+  let tenant = match argument_value(&arguments, "--tenant", Some('t')) {
+    Some(tenant) => tenant,
+    None => get_target_tenant_implicit(&settings)?,
+  };
+  let password_file = argument_value(&arguments, "--password-file", None).map(PathBuf::from);
+  debug!("completion target '{}@{}'", tenant, platform);
+  Some((platform, tenant, password_file))
+}
+
+/// # Returns the last value of an option in a raw command line
+///
+/// Supports the forms `--long value`, `--long=value`, `-s value` and `-svalue`.
+fn argument_value(arguments: &[String], long: &str, short: Option<char>) -> Option<String> {
+  let short = short.map(|short| format!("-{}", short)).unwrap_or_else(|| long.to_string());
+  let long_with_equals = format!("{}=", long);
+  let mut value = None;
+  let mut arguments = arguments.iter();
+  while let Some(argument) = arguments.next() {
+    if argument == long || argument == &short {
+      if let Some(next) = arguments.next() {
+        value = Some(next.to_string());
+      }
+    } else if let Some(long_value) = argument.strip_prefix(long_with_equals.as_str()) {
+      value = Some(long_value.to_string());
+    } else if let Some(short_value) = argument
+      .strip_prefix(short.as_str())
+      .filter(|short_value| !short_value.is_empty() && !argument.starts_with("--"))
+    {
+      value = Some(short_value.to_string());
+    }
+  }
+  value
+}
+
+fn up_to_date_resource_ids(cache: &BTreeMap<String, CachedResourceIds>, argument_id: &str, now: u64) -> Option<Vec<String>> {
+  cache
+    .get(argument_id)
+    .filter(|cached_resource_ids| now.saturating_sub(cached_resource_ids.timestamp) < COMPLETION_CACHE_TTL)
+    .map(|cached_resource_ids| cached_resource_ids.ids.clone())
+}
+
+fn read_completion_cache(platform: &DshPlatform, tenant: &str) -> BTreeMap<String, CachedResourceIds> {
+  completion_cache_file(platform, tenant)
+    .ok()
+    .and_then(|completion_cache_file| fs::read_to_string(completion_cache_file).ok())
+    .and_then(|completion_cache| serde_json::from_str(&completion_cache).ok())
+    .unwrap_or_default()
+}
+
+fn write_completion_cache(platform: &DshPlatform, tenant: &str, cache: &BTreeMap<String, CachedResourceIds>) -> Result<(), String> {
+  let completion_cache_file = completion_cache_file(platform, tenant)?;
+  let completion_cache = serde_json::to_string(cache).map_err(|error| error.to_string())?;
+  fs::write(&completion_cache_file, completion_cache).map_err(|error| format!("could not write completion cache '{}' ({})", completion_cache_file.to_string_lossy(), error))
+}
+
+fn completion_cache_file(platform: &DshPlatform, tenant: &str) -> Result<PathBuf, String> {
+  match dsh_directory()? {
+    Some(dsh_directory) => {
+      let completion_cache_directory = dsh_directory.join(COMPLETION_CACHE_SUBDIRECTORY).join(platform.name());
+      fs::create_dir_all(&completion_cache_directory).map_err(|error| {
+        format!(
+          "could not create completion cache directory '{}' ({})",
+          completion_cache_directory.to_string_lossy(),
+          error
+        )
+      })?;
+      Ok(completion_cache_directory.join(format!("{}.json", tenant)))
+    }
+    None => Err("completion cache is not available since the dsh tool's home directory is not set".to_string()),
+  }
+}
+
+fn now() -> u64 {
+  SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or_default()
+}
+
+#[test]
+fn test_argument_value() {
+  let arguments = |arguments: &str| arguments.split_whitespace().map(|argument| argument.to_string()).collect::<Vec<_>>();
+  assert_eq!(argument_value(&arguments("dsh service show"), "--platform", Some('p')), None);
+  assert_eq!(
+    argument_value(&arguments("dsh --platform np-aws-lz-dsh service show"), "--platform", Some('p')),
+    Some("np-aws-lz-dsh".to_string())
+  );
+  assert_eq!(
+    argument_value(&arguments("dsh --platform=nplz service show"), "--platform", Some('p')),
+    Some("nplz".to_string())
+  );
+  assert_eq!(
+    argument_value(&arguments("dsh -t my-tenant service show"), "--tenant", Some('t')),
+    Some("my-tenant".to_string())
+  );
+  assert_eq!(
+    argument_value(&arguments("dsh -tmy-tenant -t other service show"), "--tenant", Some('t')),
+    Some("other".to_string())
+  );
+  assert_eq!(argument_value(&arguments("dsh --tenant"), "--tenant", Some('t')), None);
+}
+
+#[test]
+fn test_up_to_date_resource_ids() {
+  let mut cache = BTreeMap::new();
+  cache.insert(
+    SERVICE_ID_ARGUMENT.to_string(),
+    CachedResourceIds { timestamp: 1000, ids: vec!["service-1".to_string()] },
+  );
+  assert_eq!(
+    up_to_date_resource_ids(&cache, SERVICE_ID_ARGUMENT, 1000 + COMPLETION_CACHE_TTL - 1),
+    Some(vec!["service-1".to_string()])
+  );
+  assert_eq!(up_to_date_resource_ids(&cache, SERVICE_ID_ARGUMENT, 1000 + COMPLETION_CACHE_TTL), None);
+  assert_eq!(up_to_date_resource_ids(&cache, SECRET_ID_ARGUMENT, 1000), None);
+}
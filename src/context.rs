@@ -1,19 +1,28 @@
+use crate::audit::{append_audit_record, AuditRecord};
+use crate::completion::invalidate_cached_resource_ids;
 use crate::environment_variables::{
   ENV_VAR_CSV_QUOTE, ENV_VAR_CSV_SEPARATOR, ENV_VAR_DRY_RUN, ENV_VAR_ERROR_COLOR, ENV_VAR_ERROR_STYLE, ENV_VAR_LABEL_COLOR, ENV_VAR_LABEL_STYLE, ENV_VAR_MATCHING_COLOR,
   ENV_VAR_MATCHING_STYLE, ENV_VAR_NO_COLOR, ENV_VAR_NO_ESCAPE, ENV_VAR_NO_HEADERS, ENV_VAR_OUTPUT_FORMAT, ENV_VAR_QUIET, ENV_VAR_SHOW_EXECUTION_TIME, ENV_VAR_STDERR_COLOR,
   ENV_VAR_STDERR_STYLE, ENV_VAR_STDOUT_COLOR, ENV_VAR_STDOUT_STYLE, ENV_VAR_SUPPRESS_EXIT_STATUS, ENV_VAR_TERMINAL_WIDTH, ENV_VAR_VERBOSITY, ENV_VAR_WARNING_COLOR,
   ENV_VAR_WARNING_STYLE,
 };
+use crate::error::DshCliError;
+use crate::fan_out::OutputCollector;
+use crate::formatters::list_options::{Condition, ListOptions};
+use crate::formatters::query::{print_query_result, Query};
+use crate::formatters::template::Template;
 use crate::formatters::OutputFormat;
 use crate::global_arguments::{
-  DRY_RUN_ARGUMENT, FORCE_ARGUMENT, NO_ESCAPE_ARGUMENT, NO_HEADERS_ARGUMENT, OUTPUT_FORMAT_ARGUMENT, QUIET_ARGUMENT, SHOW_EXECUTION_TIME_ARGUMENT, SUPPRESS_EXIT_STATUS_ARGUMENT,
-  TERMINAL_WIDTH_ARGUMENT, VERBOSITY_ARGUMENT,
+  COLUMNS_ARGUMENT, DESCENDING_ARGUMENT, DRY_RUN_ARGUMENT, FORCE_ARGUMENT, NO_ESCAPE_ARGUMENT, NO_HEADERS_ARGUMENT, OUTPUT_FORMAT_ARGUMENT, QUERY_ARGUMENT, QUIET_ARGUMENT,
+  SHOW_EXECUTION_TIME_ARGUMENT, SORT_BY_ARGUMENT, SUPPRESS_EXIT_STATUS_ARGUMENT, TEMPLATE_ARGUMENT, TEMPLATE_FILE_ARGUMENT, TERMINAL_WIDTH_ARGUMENT, VERBOSITY_ARGUMENT,
+  WHERE_ARGUMENT,
 };
 use crate::settings::Settings;
 use crate::style::{style_from, DshColor, DshStyle};
 use crate::verbosity::Verbosity;
 use clap::builder::styling::Style;
 use clap::ArgMatches;
+use dsh_api::dsh_api_client::DshApiClient;
 use dsh_api::dsh_api_tenant::DshApiTenant;
 use dsh_api::query_processor::Part;
 use dsh_api::query_processor::Part::{Matching, NonMatching};
@@ -22,20 +31,25 @@ use rpassword::prompt_password;
 use serde::Serialize;
 use std::fmt::Display;
 use std::io::{stderr, stdin, stdout, IsTerminal, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Instant;
 use terminal_size::{terminal_size, Height, Width};
 use OutputFormat::Csv;
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub(crate) struct Context {
+  collector: Option<Arc<OutputCollector>>,
   csv_quote: Option<char>,
   csv_separator: String,
   dry_run: bool,
   error_style: Style,
   force: bool,
   label_style: Style,
+  list_options: ListOptions,
   matching_style: Style,
   output_format_specification: Option<OutputFormat>,
+  query: Option<Query>,
   quiet: bool,
   settings: Settings,
   show_execution_time: bool,
@@ -48,6 +62,7 @@ pub(crate) struct Context {
   stdout_no_escape: bool,
   stdout_style: Style,
   suppress_exit_status: bool,
+  template: Option<Template>,
   terminal_width: Option<usize>,
   verbosity: Verbosity,
   warning_style: Style,
@@ -95,23 +110,29 @@ impl Context {
     let quiet = Self::get_quiet(matches, &settings);
     let force = Self::get_force(matches, &settings);
     let suppress_exit_status = Self::get_suppress_exit_status(matches, &settings);
-    let output_format_specification = Self::get_output_format_specification(matches, &settings)?;
+    let template = Self::get_template(matches)?;
+    let output_format_specification = Self::get_output_format_specification(matches, &settings, template.is_some())?;
     let show_execution_time = Self::get_show_execution_time(matches, &settings);
     let verbosity = Self::get_verbosity(matches, &settings)?;
     let show_headers = !Self::get_no_headers(matches, &settings);
     let terminal_width = Self::get_terminal_width(matches, &settings)?;
+    let list_options = Self::get_list_options(matches)?;
+    let query = Self::get_query(matches)?;
     if dry_run && verbosity >= Verbosity::Medium {
       eprintln!("dry-run mode enabled");
     }
     Ok(Context {
+      collector: None,
       csv_quote,
       csv_separator,
       dry_run,
       error_style,
       force,
       label_style,
+      list_options,
       matching_style,
       output_format_specification,
+      query,
       quiet,
       settings,
       show_execution_time,
@@ -124,12 +145,25 @@ impl Context {
       stdout_no_escape,
       stdout_style,
       suppress_exit_status,
+      template,
       terminal_width,
       verbosity,
       warning_style,
     })
   }
 
+  /// # Returns a copy of this context that collects the output
+  ///
+  /// The returned context collects the output to stdout in `collector`, instead of printing it.
+  /// Since the output is not printed directly, the user will not be prompted for confirmations.
+  pub(crate) fn with_collector(&self, collector: Arc<OutputCollector>) -> Context {
+    Context { collector: Some(collector), show_execution_time: false, stdin_is_terminal: false, ..self.clone() }
+  }
+
+  pub(crate) fn collector(&self) -> Option<&OutputCollector> {
+    self.collector.as_deref()
+  }
+
   pub(crate) fn csv_quote(&self) -> &Option<char> {
     &self.csv_quote
   }
@@ -142,6 +176,21 @@ impl Context {
     self.dry_run
   }
 
+  pub(crate) fn list_options(&self) -> &ListOptions {
+    &self.list_options
+  }
+
+  pub(crate) fn query(&self) -> Option<&Query> {
+    self.query.as_ref()
+  }
+
+  pub(crate) fn template(&self) -> Result<&Template, String> {
+    self
+      .template
+      .as_ref()
+      .ok_or_else(|| "output format template requires the --template or --template-file option".to_string())
+  }
+
   pub(crate) fn settings(&self) -> &Settings {
     &self.settings
   }
@@ -348,12 +397,15 @@ impl Context {
 
   /// Gets output format specification
   ///
+  /// 1. If a template was provided, use `OutputFormat::Template`
   /// 1. Try flag `--output-format`
   /// 1. Try environment variable `DSH_CLI_OUTPUT_FORMAT`
   /// 1. Try settings file
   /// 1. Else default to `None`
-  fn get_output_format_specification(matches: &ArgMatches, settings: &Settings) -> Result<Option<OutputFormat>, String> {
+  fn get_output_format_specification(matches: &ArgMatches, settings: &Settings, has_template: bool) -> Result<Option<OutputFormat>, String> {
     match matches.get_one::<OutputFormat>(OUTPUT_FORMAT_ARGUMENT) {
+      Some(OutputFormat::Template) | None if has_template => Ok(Some(OutputFormat::Template)),
+      Some(output_format_argument) if has_template => Err(format!("output format {} cannot be combined with a template", output_format_argument)),
       Some(output_format_argument) => Ok(Some(output_format_argument.to_owned())),
       None => match std::env::var(ENV_VAR_OUTPUT_FORMAT) {
         Ok(output_format_env_var) => OutputFormat::try_from(output_format_env_var.as_str())
@@ -401,6 +453,47 @@ impl Context {
     matches.get_flag(SHOW_EXECUTION_TIME_ARGUMENT) || std::env::var(ENV_VAR_SHOW_EXECUTION_TIME).is_ok() || settings.show_execution_time.unwrap_or(false)
   }
 
+  /// Gets list options context value
+  ///
+  /// The list options are only set via the `--columns`, `--sort-by`, `--desc`
+  /// and `--where` arguments.
+  fn get_list_options(matches: &ArgMatches) -> Result<ListOptions, String> {
+    let columns = matches.get_many::<String>(COLUMNS_ARGUMENT).map(|columns| columns.cloned().collect::<Vec<_>>());
+    let sort_by = matches.get_one::<String>(SORT_BY_ARGUMENT).cloned();
+    let descending = matches.get_flag(DESCENDING_ARGUMENT);
+    let conditions = matches
+      .get_many::<String>(WHERE_ARGUMENT)
+      .map(|conditions| conditions.map(|condition| Condition::try_from(condition.as_str())).collect::<Result<Vec<_>, _>>())
+      .transpose()?
+      .unwrap_or_default();
+    Ok(ListOptions::new(columns, sort_by, descending, conditions))
+  }
+
+  /// Gets query context value
+  ///
+  /// The query is only set via the `--query` argument.
+  fn get_query(matches: &ArgMatches) -> Result<Option<Query>, String> {
+    matches.get_one::<String>(QUERY_ARGUMENT).map(|query| Query::try_from(query.as_str())).transpose()
+  }
+
+  /// Gets template context value
+  ///
+  /// 1. Try argument `--template`
+  /// 1. Try argument `--template-file`
+  /// 1. Default to `None`
+  fn get_template(matches: &ArgMatches) -> Result<Option<Template>, String> {
+    match matches.get_one::<String>(TEMPLATE_ARGUMENT) {
+      Some(template) => Template::try_from(template.as_str()).map(Some),
+      None => match matches.get_one::<PathBuf>(TEMPLATE_FILE_ARGUMENT) {
+        Some(template_file) => match std::fs::read_to_string(template_file) {
+          Ok(template) => Template::try_from(template.trim_end_matches(['\n', '\r'])).map(Some),
+          Err(error) => Err(format!("could not read template file '{}' ({})", template_file.display(), error)),
+        },
+        None => Ok(None),
+      },
+    }
+  }
+
   /// Gets terminal width context value
   ///
   /// 1. Try flag `--terminal-width`
@@ -487,10 +580,20 @@ impl Context {
   /// If `quiet` is `true`, nothing will be printed.
   /// This standard output device can either be a tty, a pipe or an output file,
   /// depending on how the `dsh` tool was run from a shell or script.
-  pub(crate) fn print_serializable<T: Serialize>(&self, output: T, default_output_format: Option<OutputFormat>) {
+  /// If a query was provided, only the result of the query will be printed.
+  ///
+  /// ## Returns
+  /// * `Ok(())` - when the output was printed
+  /// * `Err(message)` - when the query could not be applied or the output could not be printed
+  pub(crate) fn print_serializable<T: Serialize>(&self, output: T, default_output_format: Option<OutputFormat>) -> Result<(), String> {
     if !self.quiet {
+      if let Some(query) = &self.query {
+        let result = query.apply(output)?;
+        return print_query_result(&result, self.output_format(default_output_format), self);
+      }
       match self.output_format(default_output_format) {
         Csv => self.print_warning("csv output is not supported here, use --output-format json|toml|yaml"),
+        OutputFormat::Html => self.print_warning("html output is not supported here, use --output-format json|toml|yaml"),
         OutputFormat::Json => match serde_json::to_string_pretty(&output) {
           Ok(json) => self.println(json),
           Err(_) => self.print_error("serializing to json failed"),
@@ -499,9 +602,12 @@ impl Context {
           Ok(json) => self.println(json),
           Err(_) => self.print_error("serializing to json failed"),
         },
+        OutputFormat::Markdown => self.print_warning("markdown output is not supported here, use --output-format json|toml|yaml"),
+        OutputFormat::Ndjson => self.print_ndjson(output)?,
         OutputFormat::Plain => self.print_warning("plain output is not supported here, use --output-format json|toml|yaml"),
         OutputFormat::Quiet => (),
         OutputFormat::Table | OutputFormat::TableNoBorder => self.print_warning("table output is not supported here, use --output-format json|toml|yaml"),
+        OutputFormat::Template => self.print_warning("template output is not supported here, use --output-format json|toml|yaml"),
         OutputFormat::Toml => match toml::to_string_pretty(&output) {
           Ok(toml) => self.println(toml),
           Err(_) => self.print_error("serializing to toml failed"),
@@ -516,6 +622,27 @@ impl Context {
         },
       }
     }
+    Ok(())
+  }
+
+  /// # Prints a serializable value as newline delimited json
+  ///
+  /// If the value is an array, each element will be printed as compact json on a separate line,
+  /// else the value itself will be printed as compact json.
+  pub(crate) fn print_ndjson<T: Serialize>(&self, output: T) -> Result<(), String> {
+    match serde_json::to_value(output) {
+      Ok(serde_json::Value::Array(elements)) => {
+        for element in elements {
+          self.print(element.to_string());
+        }
+        Ok(())
+      }
+      Ok(value) => {
+        self.print(value.to_string());
+        Ok(())
+      }
+      Err(error) => Err(format!("could not convert value to ndjson ({})", error)),
+    }
   }
 
   /// # Prints the next progress bar character to stderr
@@ -560,6 +687,47 @@ impl Context {
     }
   }
 
+  /// # Prints the outcome of a mutation to stderr and records it in the audit log
+  ///
+  /// This method prints the outcome like `print_outcome` does, and appends a record to
+  /// the audit log. It must only be called after a resource on the platform has been
+  /// changed, hence never in dry-run mode. Failing to write the audit log
+  /// will not fail the command, but will result in a warning.
+  pub(crate) fn print_audited_outcome<T: Display>(&self, client: &DshApiClient, resource: impl Display, outcome: T) {
+    self.print_audited_change::<T, ()>(client, resource, outcome, None, None)
+  }
+
+  /// # Prints the outcome of a mutation to stderr and records it and the change in the audit log
+  ///
+  /// Like `print_audited_outcome`, but the configuration before and/or after the change
+  /// will also be recorded in the audit log.
+  pub(crate) fn print_audited_change<T: Display, C: Serialize>(&self, client: &DshApiClient, resource: impl Display, outcome: T, before: Option<&C>, after: Option<&C>) {
+    self.audit_change(client, resource, &outcome, before, after);
+    self.print_outcome(outcome);
+  }
+
+  /// # Records a mutation in the audit log
+  ///
+  /// Like `print_audited_change`, but without printing the outcome.
+  /// This method can be used when a command applies multiple changes,
+  /// but prints only one outcome.
+  /// The cached resource identifiers used for completion will be invalidated
+  /// for the type of the changed resource.
+  pub(crate) fn audit_change<T: Display, C: Serialize>(&self, client: &DshApiClient, resource: impl Display, outcome: T, before: Option<&C>, after: Option<&C>) {
+    let audit_record = AuditRecord::new(
+      client.platform().name(),
+      client.tenant_name(),
+      resource.to_string(),
+      outcome.to_string(),
+      before.and_then(|before| serde_json::to_value(before).ok()),
+      after.and_then(|after| serde_json::to_value(after).ok()),
+    );
+    if let Err(error) = append_audit_record(&audit_record) {
+      self.print_warning(error);
+    }
+    invalidate_cached_resource_ids(client.platform(), client.tenant_name(), &audit_record.resource);
+  }
+
   /// # Prints a warning to stderr
   ///
   /// This method is used to print a warning to the standard error device.
@@ -590,6 +758,37 @@ impl Context {
     }
   }
 
+  /// # Prints a status to stderr
+  ///
+  /// This method is used to print a status that is part of the output of a command,
+  /// like the http status of a raw api request, to the standard error device.
+  /// Unlike `print_outcome`, the status will be printed regardless of the verbosity.
+  /// If `quiet` is `true`, nothing will be printed.
+  pub(crate) fn print_status<T: Display>(&self, status: T) {
+    if !self.quiet {
+      self.eprintln(status);
+    }
+  }
+
+  /// # Prints a `DshCliError` to stderr
+  ///
+  /// When the output format json, json-compact or ndjson was specified,
+  /// the error will be printed as a structured json object containing the error code,
+  /// the exit code, the message and the details of the api error.
+  /// Else only the error message will be printed, like the `print_error` method.
+  /// If `quiet` is `true`, nothing will be printed.
+  pub(crate) fn print_cli_error(&self, error: &DshCliError) {
+    if !self.quiet {
+      match self.output_format_specification {
+        Some(OutputFormat::Json) | Some(OutputFormat::JsonCompact) | Some(OutputFormat::Ndjson) => match serde_json::to_string(&error.report()) {
+          Ok(json) => eprintln!("{}", json),
+          Err(_) => self.eprintln_error(error),
+        },
+        _ => self.eprintln_error(error),
+      }
+    }
+  }
+
   /// # Prints an explanation to stderr
   ///
   /// This method is used to print an explanation about the function that is
@@ -607,6 +806,20 @@ impl Context {
     }
   }
 
+  /// # Prints the collected output for a target
+  ///
+  /// This method prints a header with the target to stderr,
+  /// followed by the collected output lines to stdout.
+  /// If `quiet` is `true`, nothing will be printed.
+  pub(crate) fn print_collected_output<T: Display>(&self, target: T, lines: &[String]) {
+    if !self.quiet {
+      self.eprintln(format!("target {}", target));
+      for line in lines {
+        self.println(line)
+      }
+    }
+  }
+
   /// # Prints the target platform and tenant to stderr
   ///
   /// This method is used to print the target platform and tenant to stderr,
@@ -710,12 +923,34 @@ impl Context {
   /// the `label_style` will be applied to the provided string, and it will be
   /// post-fixed with an escape sequence to set the `stdout_style`.
   pub(crate) fn apply_label_style_for_stdout<T: Display>(&self, text: T, default_output_format: Option<OutputFormat>) -> String {
+    self.apply_style_for_stdout(text, &self.label_style, default_output_format)
+  }
+
+  /// Applies warning styling for stdout
+  ///
+  /// If the output format is `Table` or `TableNoBorder` and `stdout_no_escape` is not set,
+  /// the `warning_style` will be applied to the provided string, and it will be
+  /// post-fixed with an escape sequence to set the `stdout_style`.
+  pub(crate) fn apply_warning_style_for_stdout<T: Display>(&self, text: T, default_output_format: Option<OutputFormat>) -> String {
+    self.apply_style_for_stdout(text, &self.warning_style, default_output_format)
+  }
+
+  /// Applies error styling for stdout
+  ///
+  /// If the output format is `Table` or `TableNoBorder` and `stdout_no_escape` is not set,
+  /// the `error_style` will be applied to the provided string, and it will be
+  /// post-fixed with an escape sequence to set the `stdout_style`.
+  pub(crate) fn apply_error_style_for_stdout<T: Display>(&self, text: T, default_output_format: Option<OutputFormat>) -> String {
+    self.apply_style_for_stdout(text, &self.error_style, default_output_format)
+  }
+
+  fn apply_style_for_stdout<T: Display>(&self, text: T, style: &Style, default_output_format: Option<OutputFormat>) -> String {
     match self.output_format(default_output_format) {
       OutputFormat::Table | OutputFormat::TableNoBorder => {
         if self.stdout_no_escape {
           text.to_string()
         } else {
-          format!("{}{}{:#}{}", self.label_style, text, self.label_style, self.stdout_style)
+          format!("{}{}{:#}{}", style, text, style, self.stdout_style)
         }
       }
       _ => text.to_string(),
@@ -803,7 +1038,9 @@ impl Context {
   /// If `stdout_no_escape` is not set, the `stdout_style` will be applied to the provided string,
   /// and it will be post-fixed with an escape sequence to reset the `stdout_style`.
   fn println<T: Display>(&self, text: T) {
-    if self.stdout_no_escape {
+    if let Some(collector) = &self.collector {
+      collector.collect_line(text.to_string())
+    } else if self.stdout_no_escape {
       println!("{}", text)
     } else {
       println!("{}{}{:#}", self.stdout_style, text, self.stdout_style)
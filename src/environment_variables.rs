@@ -186,6 +186,7 @@ const ENVIRONMENT_VARIABLES: [(&str, &str, &str); 33] = [
      When this environment variable is set, the normal list of default platforms \
      will not be included. If you need these too, make sure that you also \
      include the default platforms in your platforms file. \
+     When this environment variable is set, user defined platforms will also not be included. \
      See the bottom of this page for more information.",
   ),
   (
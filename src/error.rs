@@ -0,0 +1,195 @@
+use dsh_api::DshApiError;
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use std::process::ExitCode;
+
+/// Exit code for errors that do not fall in one of the other categories
+pub(crate) const EXIT_CODE_GENERAL: u8 = 1;
+/// Exit code for invalid arguments, parameters or input files
+pub(crate) const EXIT_CODE_VALIDATION: u8 = 2;
+/// Exit code when a resource could not be found
+pub(crate) const EXIT_CODE_NOT_FOUND: u8 = 3;
+/// Exit code when the user or tenant is not authorized
+pub(crate) const EXIT_CODE_UNAUTHORIZED: u8 = 4;
+/// Exit code when a resource already exists or is in a conflicting state
+pub(crate) const EXIT_CODE_CONFLICT: u8 = 5;
+/// Exit code when the platform could not be reached
+pub(crate) const EXIT_CODE_NETWORK: u8 = 6;
+/// Exit code when the user cancelled the operation
+pub(crate) const EXIT_CODE_CANCELLED: u8 = 7;
+
+/// Prefix of the message of `DshApiError::Unexpected` for responses with an unexpected status
+pub(crate) const UNEXPECTED_RESPONSE_PREFIX: &str = "unexpected response ";
+
+/// # Errors of the `dsh` tool
+///
+/// Each error variant has its own exit code and error code,
+/// such that scripts can branch on the kind of error without parsing the error message.
+#[derive(Debug)]
+pub(crate) enum DshCliError {
+  /// Error reported by the dsh api, categorized by the kind of api error
+  Api(DshApiError),
+  /// The user cancelled the operation
+  Cancelled(String),
+  /// A resource already exists or is in a conflicting state
+  Conflict(String),
+  /// Errors that do not fall in one of the other categories
+  General(String),
+  /// A resource could not be found
+  NotFound(String),
+  /// Invalid arguments, parameters or input files
+  Validation(String),
+}
+
+/// # Structured representation of an error
+///
+/// This representation will be printed to stderr when the output format is json.
+#[derive(Debug, Serialize)]
+pub(crate) struct ErrorReport {
+  code: &'static str,
+  #[serde(rename = "exit-code")]
+  exit_code: u8,
+  message: String,
+  #[serde(rename = "api-error", skip_serializing_if = "Option::is_none")]
+  api_error: Option<ApiErrorReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ApiErrorReport {
+  kind: &'static str,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  details: Option<String>,
+}
+
+impl DshCliError {
+  /// # Returns the error code
+  pub(crate) fn code(&self) -> &'static str {
+    match self.exit_code() {
+      EXIT_CODE_VALIDATION => "validation",
+      EXIT_CODE_NOT_FOUND => "not-found",
+      EXIT_CODE_UNAUTHORIZED => "unauthorized",
+      EXIT_CODE_CONFLICT => "conflict",
+      EXIT_CODE_NETWORK => "network",
+      EXIT_CODE_CANCELLED => "cancelled",
+      _ => "general",
+    }
+  }
+
+  /// # Returns the exit code
+  pub(crate) fn exit_code(&self) -> u8 {
+    match self {
+      DshCliError::Api(api_error) => match api_error {
+        DshApiError::BadRequest(_) | DshApiError::Parameter(_) | DshApiError::Unprocessable(_) => EXIT_CODE_VALIDATION,
+        DshApiError::Configuration(_) => EXIT_CODE_GENERAL,
+        DshApiError::NotAuthorized(_) => EXIT_CODE_UNAUTHORIZED,
+        DshApiError::NotFound(_) => EXIT_CODE_NOT_FOUND,
+        DshApiError::Unexpected(message, _) => {
+          if message.starts_with("communication error") || message.starts_with("could not fetch token") {
+            EXIT_CODE_NETWORK
+          } else if response_status(message) == Some(StatusCode::CONFLICT) {
+            EXIT_CODE_CONFLICT
+          } else {
+            EXIT_CODE_GENERAL
+          }
+        }
+      },
+      DshCliError::Cancelled(_) => EXIT_CODE_CANCELLED,
+      DshCliError::Conflict(_) => EXIT_CODE_CONFLICT,
+      DshCliError::General(_) => EXIT_CODE_GENERAL,
+      DshCliError::NotFound(_) => EXIT_CODE_NOT_FOUND,
+      DshCliError::Validation(_) => EXIT_CODE_VALIDATION,
+    }
+  }
+
+  /// # Returns the structured representation of the error
+  pub(crate) fn report(&self) -> ErrorReport {
+    let api_error = match self {
+      DshCliError::Api(api_error) => Some(match api_error {
+        DshApiError::BadRequest(message) => ApiErrorReport { kind: "bad-request", details: Some(message.to_string()).filter(|message| !message.is_empty()) },
+        DshApiError::Configuration(message) => ApiErrorReport { kind: "configuration", details: Some(message.to_string()) },
+        DshApiError::NotAuthorized(cause) => ApiErrorReport { kind: "not-authorized", details: cause.clone() },
+        DshApiError::NotFound(cause) => ApiErrorReport { kind: "not-found", details: cause.clone() },
+        DshApiError::Parameter(message) => ApiErrorReport { kind: "parameter", details: Some(message.to_string()) },
+        DshApiError::Unexpected(_, cause) => ApiErrorReport { kind: "unexpected", details: cause.clone() },
+        DshApiError::Unprocessable(message) => ApiErrorReport { kind: "unprocessable", details: message.clone() },
+      }),
+      _ => None,
+    };
+    ErrorReport { code: self.code(), exit_code: self.exit_code(), message: self.to_string(), api_error }
+  }
+}
+
+impl Display for DshCliError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      DshCliError::Api(api_error) => write!(f, "{}", api_error),
+      DshCliError::Cancelled(message) => write!(f, "{}", message),
+      DshCliError::Conflict(message) => write!(f, "{}", message),
+      DshCliError::General(message) => write!(f, "{}", message),
+      DshCliError::NotFound(message) => write!(f, "{}", message),
+      DshCliError::Validation(message) => write!(f, "{}", message),
+    }
+  }
+}
+
+impl From<DshApiError> for DshCliError {
+  fn from(api_error: DshApiError) -> Self {
+    DshCliError::Api(api_error)
+  }
+}
+
+impl From<String> for DshCliError {
+  fn from(message: String) -> Self {
+    DshCliError::General(message)
+  }
+}
+
+impl From<&str> for DshCliError {
+  fn from(message: &str) -> Self {
+    DshCliError::General(message.to_string())
+  }
+}
+
+/// # Returns the http status code of an unexpected response
+///
+/// The `dsh_api` crate does not expose the status code of responses with an unexpected status,
+/// but reports them as `DshApiError::Unexpected` with the message `unexpected response <status>`.
+/// The generic api operations use the same message.
+fn response_status(message: &str) -> Option<StatusCode> {
+  message
+    .strip_prefix(UNEXPECTED_RESPONSE_PREFIX)
+    .and_then(|status| status.split_whitespace().next())
+    .and_then(|status_code| StatusCode::from_bytes(status_code.as_bytes()).ok())
+}
+
+impl From<DshCliError> for ExitCode {
+  fn from(error: DshCliError) -> Self {
+    ExitCode::from(error.exit_code())
+  }
+}
+
+#[test]
+fn test_exit_codes() {
+  assert_eq!(DshCliError::from(DshApiError::NotFound(None)).exit_code(), EXIT_CODE_NOT_FOUND);
+  assert_eq!(DshCliError::from(DshApiError::NotAuthorized(None)).exit_code(), EXIT_CODE_UNAUTHORIZED);
+  assert_eq!(DshCliError::from(DshApiError::Unprocessable(None)).exit_code(), EXIT_CODE_VALIDATION);
+  assert_eq!(
+    DshCliError::from(DshApiError::Unexpected("communication error (reqwest error: timeout)".to_string(), None)).exit_code(),
+    EXIT_CODE_NETWORK
+  );
+  assert_eq!(
+    DshCliError::from(DshApiError::Unexpected("unexpected response 409 Conflict".to_string(), None)).exit_code(),
+    EXIT_CODE_CONFLICT
+  );
+  assert_eq!(
+    DshCliError::from(DshApiError::Unexpected("unexpected response 500 Internal Server Error (409)".to_string(), None)).exit_code(),
+    EXIT_CODE_GENERAL
+  );
+  assert_eq!(DshCliError::from("some error").exit_code(), EXIT_CODE_GENERAL);
+  assert_eq!(DshCliError::Cancelled("cancelled".to_string()).code(), "cancelled");
+  assert_eq!(
+    serde_json::to_string(&DshCliError::from(DshApiError::NotFound(Some("service".to_string()))).report()).unwrap(),
+    r#"{"code":"not-found","exit-code":3,"message":"not found (service)","api-error":{"kind":"not-found","details":"service"}}"#
+  );
+}
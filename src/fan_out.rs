@@ -0,0 +1,338 @@
+use crate::context::Context;
+use crate::error::DshCliError;
+use crate::formatters::formatter::SubjectFormatter;
+use crate::formatters::list_formatter::ListFormatter;
+use crate::global_arguments::{TARGET_PLATFORM_ARGUMENT, TARGET_TENANT_ARGUMENT};
+use crate::subject::SubjectRegistry;
+use crate::targets::{all_targets, Target};
+use crate::{create_client_for_stored_target, DshCliResult};
+use clap::{builder, Arg, ArgAction, ArgMatches};
+use futures::future::join_all;
+use log::debug;
+use regex::Regex;
+use serde::{Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::sync::{Arc, Mutex};
+
+pub(crate) const ALL_TARGETS_ARGUMENT: &str = "all-targets-argument";
+pub(crate) const TARGETS_ARGUMENT: &str = "targets-argument";
+
+const TARGET_COLUMN: &str = "target";
+
+pub(crate) fn all_targets_argument() -> Arg {
+  Arg::new(ALL_TARGETS_ARGUMENT)
+    .long("all-targets")
+    .action(ArgAction::SetTrue)
+    .conflicts_with_all([TARGETS_ARGUMENT, TARGET_PLATFORM_ARGUMENT, TARGET_TENANT_ARGUMENT])
+    .help("Run command for all stored targets")
+    .long_help(
+      "When this flag is provided, the command will be run concurrently for all stored targets, \
+      see the 'target' subject. See the --targets option for how the output is combined.",
+    )
+    .hide_short_help(true)
+    .global(true)
+}
+
+pub(crate) fn targets_argument() -> Arg {
+  Arg::new(TARGETS_ARGUMENT)
+    .long("targets")
+    .action(ArgAction::Set)
+    .value_parser(builder::NonEmptyStringValueParser::new())
+    .value_delimiter(',')
+    .value_name("TARGETS")
+    .conflicts_with_all([TARGET_PLATFORM_ARGUMENT, TARGET_TENANT_ARGUMENT])
+    .help("Run command for multiple stored targets")
+    .long_help(
+      "Comma separated list of the stored targets for which the command will be run concurrently. \
+      A target is selected by its tenant name (e.g. 'greenbox-dev') or by tenant and platform \
+      (e.g. 'greenbox-dev@np-aws-lz-dsh'), where the wildcards '*' and '?' can be used \
+      (e.g. 'greenbox-*@prod-*'). The passwords of the targets are read from the keyring. \
+      The list output of all targets is combined into one list, with an extra 'target' column, \
+      where the command fails when the columns of the list output differ between targets. \
+      Other output is printed per target. When the command fails for a target, \
+      the error is reported and the command continues for the other targets. \
+      Since confirmations can not be asked for multiple targets at the same time, \
+      commands that make changes require the --force flag.",
+    )
+    .hide_short_help(true)
+    .global(true)
+}
+
+/// # Returns the stored targets selected by the `--targets` or `--all-targets` arguments
+///
+/// ## Returns
+/// * `Ok(Some(targets))` - when one of the arguments was provided
+/// * `Ok(None)` - when none of the arguments was provided
+/// * `Err(message)` - when the stored targets could not be read or no target was selected
+pub(crate) fn selected_targets(matches: &ArgMatches) -> Result<Option<Vec<Target>>, String> {
+  if matches.get_flag(ALL_TARGETS_ARGUMENT) {
+    let targets = all_targets()?;
+    if targets.is_empty() {
+      return Err("there are no stored targets".to_string());
+    }
+    return Ok(Some(targets));
+  }
+  match matches.get_many::<String>(TARGETS_ARGUMENT) {
+    Some(patterns) => {
+      let patterns = patterns.map(|pattern| TargetPattern::try_from(pattern.as_str())).collect::<Result<Vec<_>, _>>()?;
+      let targets = all_targets()?
+        .into_iter()
+        .filter(|target| patterns.iter().any(|pattern| pattern.matches(target)))
+        .collect::<Vec<_>>();
+      if targets.is_empty() {
+        return Err("no stored targets match the --targets option".to_string());
+      }
+      Ok(Some(targets))
+    }
+    None => Ok(None),
+  }
+}
+
+/// # Executes a command for multiple targets
+///
+/// The command is executed concurrently for all targets, each with its own client
+/// and with a context that collects the output instead of printing it.
+/// When all targets are done, the collected list output is merged and printed
+/// with an extra target column, followed by the other output per target.
+/// Failures are reported per target and do not abort the execution for the other targets.
+pub(crate) async fn execute_for_targets(
+  targets: &[Target],
+  subject_registry: &SubjectRegistry<'_>,
+  subject_command_name: &str,
+  sub_matches: &ArgMatches,
+  context: &Context,
+) -> DshCliResult {
+  context.print_explanation(format!("execute command for {} targets", targets.len()));
+  let start_instant = context.now();
+  let results = join_all(targets.iter().map(|target| async move {
+    let collector = Arc::new(OutputCollector::default());
+    let target_context = context.with_collector(collector.clone());
+    let result = match create_client_for_stored_target(target).await {
+      Ok(client) => subject_registry.execute(subject_command_name, sub_matches, Some(&client), &target_context).await,
+      Err(error) => Err(DshCliError::from(error)),
+    };
+    debug!("command for target '{}' finished", target);
+    (target, collector.take(), result)
+  }))
+  .await;
+  context.print_execution_time(start_instant);
+
+  if let Some((header, rows)) = merge_list_output(results.iter().map(|(target, collected_output, _)| (*target, collected_output)))? {
+    let labels = [vec![TARGET_COLUMN.to_string()], header].concat();
+    let mut formatter: ListFormatter<String, TargetRow> = ListFormatter::new(&labels, None, context);
+    formatter.push_values(&rows);
+    formatter.print(None)?;
+  }
+  for (target, collected_output, _) in &results {
+    if !collected_output.lines.is_empty() {
+      context.print_collected_output(target, &collected_output.lines);
+    }
+  }
+  let mut number_of_failures = 0;
+  for (target, _, result) in &results {
+    if let Err(error) = result {
+      context.print_error(format!("target {}: {}", target, error));
+      number_of_failures += 1;
+    }
+  }
+  if number_of_failures > 0 {
+    Err(DshCliError::General(format!(
+      "command failed for {} of {} targets",
+      number_of_failures,
+      targets.len()
+    )))
+  } else {
+    Ok(())
+  }
+}
+
+// Header and rows of the merged list output
+type MergedList = (Vec<String>, Vec<TargetRow>);
+
+/// # Merges the collected list output of multiple targets
+///
+/// ## Returns
+/// * `Ok(Some((header, rows)))` - when list output was collected for at least one target
+/// * `Ok(None)` - when no list output was collected
+/// * `Err(message)` - when the headers of the collected list output differ between targets
+fn merge_list_output<'a>(collected_outputs: impl Iterator<Item = (&'a Target, &'a CollectedOutput)>) -> Result<Option<MergedList>, String> {
+  let mut merged: Option<(&Target, &Vec<String>)> = None;
+  let mut rows: Vec<TargetRow> = vec![];
+  for (target, collected_output) in collected_outputs {
+    if let Some(target_header) = &collected_output.header {
+      let (first_target, header) = merged.get_or_insert((target, target_header));
+      if target_header != *header {
+        return Err(format!(
+          "list output of target {} has columns {}, which differ from the columns {} of target {}",
+          target,
+          target_header.join(", "),
+          header.join(", "),
+          first_target
+        ));
+      }
+      for (row, value) in collected_output.rows.iter().zip(&collected_output.values) {
+        rows.push(TargetRow::new(target, header, row, value));
+      }
+    }
+  }
+  Ok(merged.map(|(_, header)| (header.clone(), rows)))
+}
+
+/// # Collects the output of a command
+///
+/// When the context of a command has a collector, list output will be collected as rows
+/// and other output to stdout will be collected as lines, instead of being printed.
+#[derive(Debug, Default)]
+pub(crate) struct OutputCollector {
+  output: Mutex<CollectedOutput>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct CollectedOutput {
+  header: Option<Vec<String>>,
+  rows: Vec<Vec<String>>,
+  values: Vec<Value>,
+  lines: Vec<String>,
+}
+
+impl OutputCollector {
+  /// # Collects the header, the rows and the values of list output
+  ///
+  /// The rows are collected unfiltered, since the list options will be applied to the merged list.
+  /// The serialized values are collected next to the rows, such that serialized output
+  /// of the merged list keeps the original types of the values.
+  pub(crate) fn collect_list(&self, header: Vec<String>, rows: Vec<Vec<String>>, values: Vec<Value>) {
+    let mut output = self.output.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    output.header = Some(header);
+    output.rows.extend(rows);
+    output.values.extend(values);
+  }
+
+  /// # Collects a line of output to stdout
+  pub(crate) fn collect_line(&self, line: String) {
+    self.output.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).lines.push(line);
+  }
+
+  fn take(&self) -> CollectedOutput {
+    std::mem::take(&mut *self.output.lock().unwrap_or_else(|poisoned| poisoned.into_inner()))
+  }
+}
+
+// Row of the merged list output, with the target as the first column
+//
+// The columns are used for the formatted output, the serialized value of the row
+// is used for the serialized output.
+#[derive(Debug)]
+struct TargetRow {
+  columns: Vec<(String, String)>,
+  value: Map<String, Value>,
+}
+
+impl TargetRow {
+  fn new(target: &Target, header: &[String], row: &[String], value: &Value) -> Self {
+    let mut columns = vec![(TARGET_COLUMN.to_string(), target.to_string())];
+    columns.extend(header.iter().cloned().zip(row.iter().cloned()));
+    let mut fields = Map::from_iter([(TARGET_COLUMN.to_string(), Value::String(target.to_string()))]);
+    match value {
+      Value::Object(value_fields) => fields.extend(value_fields.clone()),
+      value => {
+        fields.insert(header.first().cloned().unwrap_or("value".to_string()), value.clone());
+      }
+    }
+    Self { columns, value: fields }
+  }
+}
+
+impl SubjectFormatter<String> for TargetRow {
+  fn value(&self, label: &String, _target_id: &str) -> String {
+    self
+      .columns
+      .iter()
+      .find(|(column, _)| column == label)
+      .map(|(_, value)| value.clone())
+      .unwrap_or_default()
+  }
+}
+
+impl Serialize for TargetRow {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    self.value.serialize(serializer)
+  }
+}
+
+/// # Pattern that selects stored targets
+///
+/// A pattern without `@` only matches the tenant name, else the pattern matches
+/// the tenant name and the platform name or alias.
+#[derive(Debug)]
+struct TargetPattern {
+  tenant: Regex,
+  platform: Option<Regex>,
+}
+
+impl TargetPattern {
+  fn matches(&self, target: &Target) -> bool {
+    self.tenant.is_match(&target.tenant)
+      && self
+        .platform
+        .as_ref()
+        .is_none_or(|platform| platform.is_match(target.platform.name()) || platform.is_match(target.platform.alias()))
+  }
+}
+
+impl TryFrom<&str> for TargetPattern {
+  type Error = String;
+
+  fn try_from(pattern: &str) -> Result<Self, Self::Error> {
+    match pattern.split_once('@') {
+      Some((tenant, platform)) => Ok(Self { tenant: glob_to_regex(tenant)?, platform: Some(glob_to_regex(platform)?) }),
+      None => Ok(Self { tenant: glob_to_regex(pattern)?, platform: None }),
+    }
+  }
+}
+
+fn glob_to_regex(glob: &str) -> Result<Regex, String> {
+  let regex = regex::escape(glob).replace(r"\*", ".*").replace(r"\?", ".");
+  Regex::new(&format!("^{}$", regex)).map_err(|error| format!("invalid target pattern '{}' ({})", glob, error))
+}
+
+#[test]
+fn test_target_pattern() {
+  use dsh_api::platform::DshPlatform;
+  let target = Target::new(DshPlatform::try_from("np-aws-lz-dsh").unwrap(), "greenbox-dev".to_string(), None).unwrap();
+  let matches = |pattern: &str| TargetPattern::try_from(pattern).unwrap().matches(&target);
+  assert!(matches("greenbox-dev"));
+  assert!(matches("greenbox-*"));
+  assert!(matches("greenbox-de?"));
+  assert!(matches("*@np-aws-lz-dsh"));
+  assert!(matches(&format!("greenbox-dev@{}", target.platform.alias())));
+  assert!(!matches("greenbox"));
+  assert!(!matches("greenbox-dev@prod-*"));
+  assert!(!matches("green.ox-dev"));
+}
+
+#[test]
+fn test_merge_list_output() {
+  use dsh_api::platform::DshPlatform;
+  let target = |tenant: &str| Target::new(DshPlatform::try_from("np-aws-lz-dsh").unwrap(), tenant.to_string(), None).unwrap();
+  let (first, second) = (target("first"), target("second"));
+  let collected_output = |header: &[&str], instances: u64| CollectedOutput {
+    header: Some(header.iter().map(|column| column.to_string()).collect()),
+    rows: vec![vec!["id".to_string(), instances.to_string()]],
+    values: vec![serde_json::json!({ "id": "id", "instances": instances })],
+    lines: vec![],
+  };
+  let (first_output, second_output) = (collected_output(&["id", "instances"], 1), collected_output(&["id", "instances"], 2));
+  let (header, rows) = merge_list_output([(&first, &first_output), (&second, &second_output)].into_iter())
+    .unwrap()
+    .unwrap();
+  assert_eq!(header, vec!["id", "instances"]);
+  assert_eq!(rows[1].value(&"instances".to_string(), ""), "2");
+  assert_eq!(
+    serde_json::to_value(&rows[1]).unwrap(),
+    serde_json::json!({ "target": second.to_string(), "id": "id", "instances": 2 })
+  );
+  let other_output = collected_output(&["id", "cpus"], 1);
+  assert!(merge_list_output([(&first, &first_output), (&second, &other_output)].into_iter()).is_err());
+  assert!(merge_list_output(std::iter::empty()).unwrap().is_none());
+}
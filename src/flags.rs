@@ -2,6 +2,8 @@ use clap::{Arg, ArgAction};
 
 #[derive(Debug)]
 pub(crate) enum FlagType {
+  #[cfg(feature = "manage")]
+  Access,
   _Actual,
   AllocationStatus,
   Configuration,
@@ -18,6 +20,8 @@ pub(crate) enum FlagType {
 impl FlagType {
   pub(crate) fn id(&self) -> &'static str {
     match &self {
+      #[cfg(feature = "manage")]
+      Self::Access => "access-flag",
       Self::_Actual => "actual-flag",
       Self::AllocationStatus => "status-flag",
       Self::Configuration => "configuration-flag",
@@ -34,6 +38,8 @@ impl FlagType {
 
   pub(crate) fn option(&self) -> &'static str {
     match &self {
+      #[cfg(feature = "manage")]
+      Self::Access => "access",
       Self::_Actual => "actual",
       Self::AllocationStatus => "status",
       Self::Configuration => "configuration",
@@ -51,6 +57,8 @@ impl FlagType {
 
 pub(crate) fn create_flag(flag_type: &FlagType, subject: &str, long_help: Option<&str>) -> Arg {
   match flag_type {
+    #[cfg(feature = "manage")]
+    FlagType::Access => create_clap_flag(FlagType::Access, format!("Include the {}'s access rights", subject), long_help),
     FlagType::_Actual => create_clap_flag(FlagType::_Actual, format!("Use the 'actual' {} configuration", subject), long_help),
     FlagType::AllocationStatus => create_clap_flag(FlagType::AllocationStatus, format!("Include the {}'s allocation status", subject), long_help),
     FlagType::Configuration => create_clap_flag(FlagType::Configuration, format!("Include the {}'s initial configuration", subject), long_help),
@@ -1,5 +1,5 @@
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::hash::Hash;
 
 /// # Defines behavior of labels
@@ -120,13 +120,13 @@ where
   }
 }
 
-/// # Defines how a `HashMap` can be formatted
+/// # Defines how a `BTreeMap` can be formatted
 ///
-/// The key of the `HashMap` must implement `Label`.
+/// The key of the `BTreeMap` must implement `Label`.
 /// This implementation does not allow the specification of a target id.
-impl<L> SubjectFormatter<L> for HashMap<L, String>
+impl<L> SubjectFormatter<L> for BTreeMap<L, String>
 where
-  L: Label,
+  L: Label + Ord,
 {
   fn value(&self, label: &L, target_id: &str) -> String {
     if label.is_target_label() {
@@ -1,4 +1,5 @@
 use crate::context::Context;
+use crate::formatters::markup::{html_table, markdown_table};
 use crate::formatters::OutputFormat;
 use std::collections::HashMap;
 use tabled::settings::peaker::PriorityMax;
@@ -37,12 +38,25 @@ impl<'a> IdsFormatter<'a> {
   }
 
   pub fn print(&self, default_output_format: Option<OutputFormat>) -> Result<(), String> {
+    if let Some(collector) = self.context.collector() {
+      collector.collect_list(
+        vec![self.label.to_string()],
+        self.rows().into_iter().map(|row| row.into_iter().map(str::to_string).collect()).collect(),
+        self.ids.iter().map(|id| serde_json::Value::String(id.to_string())).collect(),
+      );
+      return Ok(());
+    }
     match self.context.output_format(default_output_format) {
       OutputFormat::Csv => {
         self.context.print(self.ids.join(","));
         Ok(())
       }
 
+      OutputFormat::Html => {
+        self.context.print(html_table(self.header().as_deref(), &self.rows()));
+        Ok(())
+      }
+
       OutputFormat::Json => match serde_json::to_string_pretty(&self.ids) {
         Ok(json) => {
           self.context.print(json);
@@ -59,6 +73,13 @@ impl<'a> IdsFormatter<'a> {
         Err(error) => Err(format!("could not convert target ids to json compact ({})", error)),
       },
 
+      OutputFormat::Markdown => {
+        self.context.print(markdown_table(self.header().as_deref(), &self.rows()));
+        Ok(())
+      }
+
+      OutputFormat::Ndjson => self.context.print_ndjson(&self.ids),
+
       OutputFormat::Plain => {
         self.context.print(self.ids.join("\n"));
         Ok(())
@@ -98,6 +119,14 @@ impl<'a> IdsFormatter<'a> {
         Ok(())
       }
 
+      OutputFormat::Template => {
+        let template = self.context.template()?;
+        for target_id in &self.ids {
+          self.context.print(template.render_target_id(self.label, target_id)?);
+        }
+        Ok(())
+      }
+
       OutputFormat::Toml => match toml::to_string_pretty(&HashMap::from([(&self.label, &self.ids)])) {
         Ok(toml) => {
           self.context.print(toml);
@@ -123,4 +152,16 @@ impl<'a> IdsFormatter<'a> {
       },
     }
   }
+
+  fn header(&self) -> Option<Vec<&str>> {
+    if self.context.show_headers() {
+      Some(vec![self.label])
+    } else {
+      None
+    }
+  }
+
+  fn rows(&self) -> Vec<Vec<&str>> {
+    self.ids.iter().map(|id| vec![*id]).collect::<Vec<_>>()
+  }
 }
@@ -1,8 +1,12 @@
 use crate::context::Context;
 use crate::formatters::formatter::{Label, SubjectFormatter};
+use crate::formatters::list_options::SelectedColumns;
+use crate::formatters::markup::{html_table, markdown_table};
+use crate::formatters::query::print_query_result;
 use crate::formatters::OutputFormat;
 use itertools::Itertools;
 use serde::Serialize;
+use serde_json::{Map, Value};
 use std::marker::PhantomData;
 use tabled::settings::peaker::PriorityMax;
 use tabled::settings::{Padding, Width};
@@ -75,6 +79,30 @@ where
     self.values.is_empty()
   }
 
+  /// # Returns whether values can be streamed
+  ///
+  /// Values can be streamed when the output format is ndjson and the values
+  /// do not need to be sorted or queried as a whole.
+  /// In that case executors can print each value via the `print_value` method
+  /// as soon as it is available, instead of pushing it and calling `print`.
+  pub fn is_streaming(&self, default_output_format: Option<OutputFormat>) -> bool {
+    self.context.collector().is_none()
+      && self.context.output_format(default_output_format) == OutputFormat::Ndjson
+      && self.context.query().is_none()
+      && !self.context.list_options().has_sort_by()
+  }
+
+  /// # Prints a single value as a line of ndjson
+  ///
+  /// The column selection and filter conditions from the context will be applied.
+  pub fn print_value(&self, target_id: &str, value: &V) -> Result<(), String> {
+    if self.context.list_options().is_selected(self.labels, target_id, value)? {
+      let labels = self.context.list_options().select_labels(self.labels)?;
+      self.print_ndjson_row(&labels, target_id, value)?;
+    }
+    Ok(())
+  }
+
   fn label_to_header(&self, label: &L) -> String {
     if label.is_target_label() {
       self.add_csv_quote(self.target_label.unwrap_or(label.as_str_for_list()))
@@ -90,22 +118,50 @@ where
     }
   }
 
+  /// # Prints the values
+  ///
+  /// The list options from the context (column selection, sorting and filtering)
+  /// will be applied before the values are printed in the requested output format.
+  /// If a query was provided, it will be applied to the selected values
+  /// and only the result of the query will be printed.
+  /// If the context has a collector, all values will be collected instead.
   pub fn print(&self, default_output_format: Option<OutputFormat>) -> Result<(), String> {
+    if let Some(collector) = self.context.collector() {
+      let header = self.labels.iter().map(|label| self.label_to_key(label).to_string()).collect::<Vec<_>>();
+      let rows = self
+        .values
+        .iter()
+        .map(|(target_id, value)| self.labels.iter().map(|label| value.value(label, target_id)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+      let values = self
+        .values
+        .iter()
+        .map(|(_, value)| serde_json::to_value(value))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| format!("could not convert values to json ({})", error))?;
+      collector.collect_list(header, rows, values);
+      return Ok(());
+    }
+    let list_options = self.context.list_options();
+    let labels = list_options.select_labels(self.labels)?;
+    let values = list_options.select_values(self.labels, &self.values)?;
+    if let Some(query) = self.context.query() {
+      let result = match self.simplified_values(&labels, &values) {
+        Some(simplified_values) => query.apply(simplified_values)?,
+        None => query.apply(&values)?,
+      };
+      return print_query_result(&result, self.context.output_format(default_output_format), self.context);
+    }
     match self.context.output_format(default_output_format) {
       OutputFormat::Csv => {
         if self.context.show_headers() {
-          self.context.print(
-            self
-              .labels
-              .iter()
-              .map(|label| self.label_to_header(label))
-              .join(self.context.csv_separator().as_str()),
-          );
+          self
+            .context
+            .print(labels.iter().map(|label| self.label_to_header(label)).join(self.context.csv_separator().as_str()));
         }
-        for (target_id, value) in &self.values {
+        for (target_id, value) in &values {
           self.context.print(
-            self
-              .labels
+            labels
               .iter()
               .map(|label| self.add_csv_quote(value.value(label, target_id).as_str()))
               .join(self.context.csv_separator().as_str()),
@@ -114,7 +170,13 @@ where
         Ok(())
       }
 
-      OutputFormat::Json => match self.simplified_values() {
+      OutputFormat::Html => {
+        let (header, rows) = self.markup_header_and_rows(&labels, &values);
+        self.context.print(html_table(header.as_deref(), &rows));
+        Ok(())
+      }
+
+      OutputFormat::Json => match self.simplified_values(&labels, &values) {
         Some(simplified_values) => match serde_json::to_string_pretty(&simplified_values) {
           Ok(json) => {
             self.context.print(json);
@@ -122,7 +184,7 @@ where
           }
           Err(error) => Err(format!("could not convert simplified values to json ({})", error)),
         },
-        None => match serde_json::to_string_pretty(&self.values) {
+        None => match serde_json::to_string_pretty(&values) {
           Ok(json) => {
             self.context.print(json);
             Ok(())
@@ -131,7 +193,7 @@ where
         },
       },
 
-      OutputFormat::JsonCompact => match self.simplified_values() {
+      OutputFormat::JsonCompact => match self.simplified_values(&labels, &values) {
         Some(simplified_values) => match serde_json::to_string(&simplified_values) {
           Ok(json) => {
             self.context.print(json);
@@ -139,7 +201,7 @@ where
           }
           Err(error) => Err(format!("could not convert simplified values to json compact ({})", error)),
         },
-        None => match serde_json::to_string(&self.values) {
+        None => match serde_json::to_string(&values) {
           Ok(json) => {
             self.context.print(json);
             Ok(())
@@ -148,12 +210,25 @@ where
         },
       },
 
+      OutputFormat::Markdown => {
+        let (header, rows) = self.markup_header_and_rows(&labels, &values);
+        self.context.print(markdown_table(header.as_deref(), &rows));
+        Ok(())
+      }
+
+      OutputFormat::Ndjson => {
+        for (target_id, value) in &values {
+          self.print_ndjson_row(&labels, target_id, *value)?;
+        }
+        Ok(())
+      }
+
       OutputFormat::Plain => {
         if self.context.show_headers() {
-          self.context.print(self.labels.iter().map(|label| label.as_str()).join(","));
+          self.context.print(labels.iter().map(|label| label.as_str()).join(","));
         }
-        for (target_id, value) in &self.values {
-          self.context.print(self.labels.iter().map(|label| value.value(label, target_id)).join(","));
+        for (target_id, value) in &values {
+          self.context.print(labels.iter().map(|label| value.value(label, target_id)).join(","));
         }
         Ok(())
       }
@@ -163,14 +238,13 @@ where
       OutputFormat::Table => {
         let mut tabled_builder = TabledBuilder::default();
         tabled_builder.push_record(
-          self
-            .labels
+          labels
             .iter()
             .map(|label| if label.is_target_label() { self.target_label.unwrap_or(label.as_str_for_list()) } else { label.as_str_for_list() }),
         );
         let mut last_target_id: Option<String> = None;
-        for (target_id, value) in &self.values {
-          let record = self.labels.iter().map(|label| {
+        for (target_id, value) in &values {
+          let record = labels.iter().map(|label| {
             if label.is_target_label() {
               let target_id_value = value.value(label, target_id);
               if last_target_id.clone().is_some_and(|last| last == target_id_value) {
@@ -190,7 +264,7 @@ where
           table.with(Width::wrap(terminal_width).keep_words(true).priority(PriorityMax::new(true)));
         }
         table.with(Padding::new(1, 1, 0, 0));
-        if self.values.is_empty() {
+        if values.is_empty() {
           table.with(Style::modern());
         } else {
           table.with(Style::sharp());
@@ -202,13 +276,12 @@ where
       OutputFormat::TableNoBorder => {
         let mut tabled_builder = TabledBuilder::default();
         tabled_builder.push_record(
-          self
-            .labels
+          labels
             .iter()
             .map(|label| if label.is_target_label() { self.target_label.unwrap_or(label.as_str_for_list()) } else { label.as_str_for_list() }),
         );
-        for (target_id, value) in &self.values {
-          tabled_builder.push_record(self.labels.iter().map(|label| value.value(label, target_id)));
+        for (target_id, value) in &values {
+          tabled_builder.push_record(labels.iter().map(|label| value.value(label, target_id)));
         }
         let mut table = tabled_builder.build();
         if let Some(terminal_width) = self.context.terminal_width() {
@@ -220,7 +293,15 @@ where
         Ok(())
       }
 
-      OutputFormat::Toml => match self.simplified_values() {
+      OutputFormat::Template => {
+        let template = self.context.template()?;
+        for (target_id, value) in &values {
+          self.context.print(template.render(self.labels, self.target_label, target_id, *value)?);
+        }
+        Ok(())
+      }
+
+      OutputFormat::Toml => match self.simplified_values(&labels, &values) {
         Some(simplified_values) => match toml::to_string_pretty(&simplified_values) {
           Ok(json) => {
             self.context.print(json);
@@ -228,7 +309,7 @@ where
           }
           Err(error) => Err(format!("could not convert simplified values to toml ({})", error)),
         },
-        None => match toml::to_string_pretty(&self.values) {
+        None => match toml::to_string_pretty(&values) {
           Ok(json) => {
             self.context.print(json);
             Ok(())
@@ -237,7 +318,7 @@ where
         },
       },
 
-      OutputFormat::TomlCompact => match self.simplified_values() {
+      OutputFormat::TomlCompact => match self.simplified_values(&labels, &values) {
         Some(simplified_values) => match toml::to_string(&simplified_values) {
           Ok(json) => {
             self.context.print(json);
@@ -245,7 +326,7 @@ where
           }
           Err(error) => Err(format!("could not convert simplified values to toml compact ({})", error)),
         },
-        None => match toml::to_string(&self.values) {
+        None => match toml::to_string(&values) {
           Ok(json) => {
             self.context.print(json);
             Ok(())
@@ -254,7 +335,7 @@ where
         },
       },
 
-      OutputFormat::Yaml => match self.simplified_values() {
+      OutputFormat::Yaml => match self.simplified_values(&labels, &values) {
         Some(simplified_values) => match serde_yaml::to_string(&simplified_values) {
           Ok(json) => {
             self.context.print(json);
@@ -262,7 +343,7 @@ where
           }
           Err(error) => Err(format!("could not convert simplified values to yaml ({})", error)),
         },
-        None => match serde_yaml::to_string(&self.values) {
+        None => match serde_yaml::to_string(&values) {
           Ok(json) => {
             self.context.print(json);
             Ok(())
@@ -277,11 +358,94 @@ where
     self.labels.iter().any(|label| label.is_target_label())
   }
 
-  fn simplified_values(&self) -> Option<Vec<&'a V>> {
-    if self.has_target_label() {
+  /// # Returns the values to serialize
+  ///
+  /// When columns were selected, only the selected columns will be serialized.
+  /// Else, when the labels do not contain a target label, the values will be serialized
+  /// without their target ids.
+  fn simplified_values(&self, labels: &[&'a L], values: &[(String, &'a V)]) -> Option<SimplifiedValues<'a, V>> {
+    if self.context.list_options().has_columns() {
+      Some(SimplifiedValues::Columns(
+        values
+          .iter()
+          .map(|(target_id, value)| {
+            SelectedColumns(
+              labels
+                .iter()
+                .map(|label| (self.label_to_key(label), value.value(label, target_id)))
+                .collect::<Vec<_>>(),
+            )
+          })
+          .collect::<Vec<_>>(),
+      ))
+    } else if self.has_target_label() {
       None
     } else {
-      Some(self.values.iter().map(|(_, value)| *value).collect::<Vec<_>>())
+      Some(SimplifiedValues::Values(values.iter().map(|(_, value)| *value).collect::<Vec<_>>()))
+    }
+  }
+
+  fn print_ndjson_row(&self, labels: &[&L], target_id: &str, value: &V) -> Result<(), String> {
+    let row = if self.context.list_options().has_columns() {
+      serde_json::to_string(&SelectedColumns(
+        labels
+          .iter()
+          .map(|label| (self.label_to_key(label), value.value(label, target_id)))
+          .collect::<Vec<_>>(),
+      ))
+    } else if let Some(target_label) = self.labels.iter().find(|label| label.is_target_label()) {
+      // The target id is added to the fields of the value, such that each row is a json object
+      serde_json::to_value(value).and_then(|value| {
+        let fields = match value {
+          Value::Object(fields) => fields,
+          value => {
+            let key = self
+              .labels
+              .iter()
+              .find(|label| !label.is_target_label())
+              .map(|label| self.label_to_key(label))
+              .unwrap_or("value");
+            Map::from_iter([(key.to_string(), value)])
+          }
+        };
+        let target_id = Value::String(target_id.to_string());
+        let mut columns = vec![(self.label_to_key(target_label), &target_id)];
+        columns.extend(fields.iter().map(|(field, value)| (field.as_str(), value)));
+        serde_json::to_string(&SelectedColumns(columns))
+      })
+    } else {
+      serde_json::to_string(value)
+    };
+    match row {
+      Ok(row) => {
+        self.context.print(row);
+        Ok(())
+      }
+      Err(error) => Err(format!("could not convert value to ndjson ({})", error)),
     }
   }
+
+  fn markup_header_and_rows(&self, labels: &[&'a L], values: &[(String, &'a V)]) -> (Option<Vec<&'a str>>, Vec<Vec<String>>) {
+    let header = if self.context.show_headers() { Some(labels.iter().map(|label| self.label_to_key(label)).collect::<Vec<_>>()) } else { None };
+    let rows = values
+      .iter()
+      .map(|(target_id, value)| labels.iter().map(|label| value.value(label, target_id)).collect::<Vec<_>>())
+      .collect::<Vec<_>>();
+    (header, rows)
+  }
+
+  fn label_to_key(&self, label: &'a L) -> &'a str {
+    if label.is_target_label() {
+      self.target_label.unwrap_or(label.as_str_for_list())
+    } else {
+      label.as_str_for_list()
+    }
+  }
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum SimplifiedValues<'a, V: Serialize> {
+  Columns(Vec<SelectedColumns<'a>>),
+  Values(Vec<&'a V>),
 }
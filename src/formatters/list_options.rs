@@ -0,0 +1,237 @@
+use crate::formatters::formatter::{Label, SubjectFormatter};
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+use std::cmp::Ordering;
+
+/// # Column selection, sorting and filtering for list output
+///
+/// These options are set via the global `--columns`, `--sort-by`, `--desc` and `--where`
+/// arguments and are applied by the `ListFormatter`, independent of the output format.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ListOptions {
+  columns: Option<Vec<String>>,
+  sort_by: Option<String>,
+  descending: bool,
+  conditions: Vec<Condition>,
+}
+
+impl ListOptions {
+  pub(crate) fn new(columns: Option<Vec<String>>, sort_by: Option<String>, descending: bool, conditions: Vec<Condition>) -> Self {
+    Self { columns, sort_by, descending, conditions }
+  }
+
+  /// # Returns whether columns were selected
+  pub(crate) fn has_columns(&self) -> bool {
+    self.columns.is_some()
+  }
+
+  /// # Returns whether a sort column was selected
+  pub(crate) fn has_sort_by(&self) -> bool {
+    self.sort_by.is_some()
+  }
+
+  /// # Returns whether a value satisfies all conditions
+  pub(crate) fn is_selected<L: Label, V: SubjectFormatter<L>>(&self, labels: &[L], target_id: &str, value: &V) -> Result<bool, String> {
+    for condition in &self.conditions {
+      if !condition.is_satisfied_by(&value.value(find_label(&condition.column, labels)?, target_id)) {
+        return Ok(false);
+      }
+    }
+    Ok(true)
+  }
+
+  /// # Selects the labels to print
+  ///
+  /// If columns were selected, the labels will be returned in the order of the selection.
+  /// Else all labels will be returned in their original order.
+  pub(crate) fn select_labels<'a, L: Label>(&self, labels: &'a [L]) -> Result<Vec<&'a L>, String> {
+    match self.columns {
+      Some(ref columns) => columns.iter().map(|column| find_label(column, labels)).collect(),
+      None => Ok(labels.iter().collect()),
+    }
+  }
+
+  /// # Filters and sorts the values
+  ///
+  /// Values that do not satisfy all conditions will be removed.
+  /// If a sort column was selected, the values will be sorted on that column.
+  /// Columns that contain only numerical values will be sorted numerically.
+  pub(crate) fn select_values<'a, L: Label, V: SubjectFormatter<L>>(&self, labels: &[L], values: &[(String, &'a V)]) -> Result<Vec<(String, &'a V)>, String> {
+    let conditions = self
+      .conditions
+      .iter()
+      .map(|condition| find_label(&condition.column, labels).map(|label| (label, condition)))
+      .collect::<Result<Vec<_>, _>>()?;
+    let mut selected_values = values
+      .iter()
+      .filter(|(target_id, value)| {
+        conditions
+          .iter()
+          .all(|(label, condition)| condition.is_satisfied_by(&value.value(label, target_id)))
+      })
+      .map(|(target_id, value)| (target_id.clone(), *value))
+      .collect::<Vec<_>>();
+    if let Some(ref sort_by) = self.sort_by {
+      let label = find_label(sort_by, labels)?;
+      selected_values.sort_by(|(target_id_a, value_a), (target_id_b, value_b)| {
+        let ordering = compare_values(&value_a.value(label, target_id_a), &value_b.value(label, target_id_b));
+        if self.descending {
+          ordering.reverse()
+        } else {
+          ordering
+        }
+      });
+    }
+    Ok(selected_values)
+  }
+}
+
+/// # Condition on the value of a column
+///
+/// A condition has the form `<column><operator><value>`, e.g. `#>0` or `image~kafka`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Condition {
+  column: String,
+  operator: Operator,
+  value: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Operator {
+  Contains,
+  Equal,
+  GreaterThan,
+  GreaterThanOrEqual,
+  LessThan,
+  LessThanOrEqual,
+  NotContains,
+  NotEqual,
+}
+
+// Operators that consist of two characters must precede their one character prefixes
+const OPERATORS: [(&str, Operator); 8] = [
+  (">=", Operator::GreaterThanOrEqual),
+  ("<=", Operator::LessThanOrEqual),
+  ("!=", Operator::NotEqual),
+  ("!~", Operator::NotContains),
+  ("=", Operator::Equal),
+  ("~", Operator::Contains),
+  (">", Operator::GreaterThan),
+  ("<", Operator::LessThan),
+];
+
+impl Condition {
+  fn is_satisfied_by(&self, value: &str) -> bool {
+    match self.operator {
+      Operator::Contains => value.contains(&self.value),
+      Operator::Equal => compare_values(value, &self.value) == Ordering::Equal,
+      Operator::GreaterThan => compare_values(value, &self.value) == Ordering::Greater,
+      Operator::GreaterThanOrEqual => compare_values(value, &self.value) != Ordering::Less,
+      Operator::LessThan => compare_values(value, &self.value) == Ordering::Less,
+      Operator::LessThanOrEqual => compare_values(value, &self.value) != Ordering::Greater,
+      Operator::NotContains => !value.contains(&self.value),
+      Operator::NotEqual => compare_values(value, &self.value) != Ordering::Equal,
+    }
+  }
+}
+
+impl TryFrom<&str> for Condition {
+  type Error = String;
+
+  fn try_from(representation: &str) -> Result<Self, Self::Error> {
+    let operator_position = representation.find(['=', '~', '>', '<', '!']);
+    match operator_position {
+      Some(position) if position > 0 => {
+        let (column, remainder) = representation.split_at(position);
+        match OPERATORS.iter().find(|(symbol, _)| remainder.starts_with(symbol)) {
+          Some((symbol, operator)) => Ok(Condition { column: column.trim().to_string(), operator: operator.clone(), value: remainder[symbol.len()..].to_string() }),
+          None => Err(format!("invalid operator in condition '{}'", representation)),
+        }
+      }
+      _ => Err(format!(
+        "invalid condition '{}', expected <column><operator><value>, where operator is one of =, !=, ~, !~, >, >=, < or <=",
+        representation
+      )),
+    }
+  }
+}
+
+/// # Selected columns of one value
+///
+/// Serializes to a map from column names to values, in the order of the selected columns.
+pub(crate) struct SelectedColumns<'a, T = String>(pub(crate) Vec<(&'a str, T)>);
+
+impl<T: Serialize> Serialize for SelectedColumns<'_, T> {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut map = serializer.serialize_map(Some(self.0.len()))?;
+    for (column, value) in &self.0 {
+      map.serialize_entry(column, value)?;
+    }
+    map.end()
+  }
+}
+
+pub(crate) fn find_label<'a, L: Label>(column: &str, labels: &'a [L]) -> Result<&'a L, String> {
+  let normalized_column = normalize(column);
+  labels
+    .iter()
+    .find(|label| normalize(label.as_str_for_list()) == normalized_column || normalize(label.as_str()) == normalized_column)
+    .ok_or_else(|| {
+      format!(
+        "unknown column '{}', available columns are {}",
+        column,
+        labels
+          .iter()
+          .map(|label| format!("'{}'", normalize(label.as_str_for_list())))
+          .collect::<Vec<_>>()
+          .join(", ")
+      )
+    })
+}
+
+pub(crate) fn normalize(column: &str) -> String {
+  column.trim().to_lowercase().replace([' ', '_'], "-")
+}
+
+/// # Compares two values
+///
+/// When both values are numerical they will be compared numerically,
+/// else they will be compared as strings.
+fn compare_values(value_a: &str, value_b: &str) -> Ordering {
+  match (value_a.parse::<f64>(), value_b.parse::<f64>()) {
+    (Ok(number_a), Ok(number_b)) => number_a.partial_cmp(&number_b).unwrap_or(Ordering::Equal),
+    _ => value_a.cmp(value_b),
+  }
+}
+
+#[test]
+fn test_conditions() {
+  let valid_conditions = vec![
+    ("#>0", "#", Operator::GreaterThan, "0"),
+    ("image~kafka", "image", Operator::Contains, "kafka"),
+    ("image!~kafka", "image", Operator::NotContains, "kafka"),
+    ("cpu>=0.5", "cpu", Operator::GreaterThanOrEqual, "0.5"),
+    ("mem<=1024", "mem", Operator::LessThanOrEqual, "1024"),
+    ("user!=1000:1000", "user", Operator::NotEqual, "1000:1000"),
+    ("tenant=", "tenant", Operator::Equal, ""),
+    ("env=A=B", "env", Operator::Equal, "A=B"),
+  ];
+  for (representation, column, operator, value) in valid_conditions {
+    assert_eq!(
+      Condition::try_from(representation).unwrap(),
+      Condition { column: column.to_string(), operator, value: value.to_string() }
+    );
+  }
+  for invalid_condition in ["", "image", "=kafka", "image!kafka"] {
+    assert!(Condition::try_from(invalid_condition).is_err());
+  }
+}
+
+#[test]
+fn test_satisfied_conditions() {
+  assert!(Condition::try_from("#>2").unwrap().is_satisfied_by("10"));
+  assert!(!Condition::try_from("#>2").unwrap().is_satisfied_by("1"));
+  assert!(Condition::try_from("cpu=0.50").unwrap().is_satisfied_by("0.5"));
+  assert!(Condition::try_from("image~kafka").unwrap().is_satisfied_by("registry/kafka-proxy:1.0"));
+  assert!(Condition::try_from("image!~kafka").unwrap().is_satisfied_by("registry/whoami:1.0"));
+}
@@ -0,0 +1,85 @@
+/// # Creates a GitHub flavored markdown table
+///
+/// Since markdown tables always require a header row, a header row with empty cells
+/// will be generated when `header` is `None`.
+/// Pipe characters in the cells will be escaped and new lines will be replaced by `<br>`.
+pub(crate) fn markdown_table<H: AsRef<str>, C: AsRef<str>>(header: Option<&[H]>, rows: &[Vec<C>]) -> String {
+  let number_of_columns = header
+    .map(|header| header.len())
+    .or_else(|| rows.iter().map(|row| row.len()).max())
+    .unwrap_or_default();
+  let header_row = match header {
+    Some(header) => markdown_row(header),
+    None => markdown_row(&vec![""; number_of_columns]),
+  };
+  let mut lines = vec![header_row, markdown_row(&vec!["---"; number_of_columns])];
+  for row in rows {
+    lines.push(markdown_row(row));
+  }
+  lines.join("\n")
+}
+
+fn markdown_row<C: AsRef<str>>(cells: &[C]) -> String {
+  format!(
+    "|{}|",
+    cells
+      .iter()
+      .map(|cell| format!(" {} ", cell.as_ref().replace('|', "\\|").replace('\n', "<br>")))
+      .collect::<Vec<_>>()
+      .join("|")
+  )
+}
+
+/// # Creates a standalone html table
+///
+/// The header will only be included when `header` is not `None`.
+/// Html special characters in the cells will be escaped and new lines will be replaced by `<br>`.
+pub(crate) fn html_table<H: AsRef<str>, C: AsRef<str>>(header: Option<&[H]>, rows: &[Vec<C>]) -> String {
+  let mut lines = vec!["<table>".to_string()];
+  if let Some(header) = header {
+    lines.push("  <thead>".to_string());
+    lines.push(html_row(header, "th"));
+    lines.push("  </thead>".to_string());
+  }
+  lines.push("  <tbody>".to_string());
+  for row in rows {
+    lines.push(html_row(row, "td"));
+  }
+  lines.push("  </tbody>".to_string());
+  lines.push("</table>".to_string());
+  lines.join("\n")
+}
+
+fn html_row<C: AsRef<str>>(cells: &[C], element: &str) -> String {
+  format!(
+    "    <tr>{}</tr>",
+    cells
+      .iter()
+      .map(|cell| format!("<{}>{}</{}>", element, escape_html(cell.as_ref()).replace('\n', "<br>"), element))
+      .collect::<Vec<_>>()
+      .join("")
+  )
+}
+
+fn escape_html(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+    .replace('\'', "&#39;")
+}
+
+#[test]
+fn test_markup_tables() {
+  let rows = vec![vec!["a|b", "1"], vec!["<c>", "2\n3"]];
+  assert_eq!(
+    markdown_table(Some(&["name", "value"]), &rows),
+    "| name | value |\n| --- | --- |\n| a\\|b | 1 |\n| <c> | 2<br>3 |"
+  );
+  assert_eq!(markdown_table::<&str, &str>(None, &rows[..1]), "|  |  |\n| --- | --- |\n| a\\|b | 1 |");
+  assert_eq!(
+    html_table(Some(&["name", "value"]), &rows),
+    "<table>\n  <thead>\n    <tr><th>name</th><th>value</th></tr>\n  </thead>\n  <tbody>\n    <tr><td>a|b</td><td>1</td></tr>\n    <tr><td>&lt;c&gt;</td><td>2<br>3</td></tr>\n  </tbody>\n</table>"
+  );
+}
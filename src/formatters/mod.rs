@@ -5,6 +5,10 @@ use std::fmt::{Display, Formatter};
 pub(crate) mod formatter;
 pub(crate) mod ids_formatter;
 pub(crate) mod list_formatter;
+pub(crate) mod list_options;
+pub(crate) mod markup;
+pub(crate) mod query;
+pub(crate) mod template;
 pub(crate) mod unit_formatter;
 
 #[derive(clap::ValueEnum, Eq, Clone, Debug, Deserialize, Hash, PartialEq, Serialize)]
@@ -12,12 +16,21 @@ pub enum OutputFormat {
   /// Output will be formatted as comma separated values
   #[serde(rename = "csv")]
   Csv,
+  /// Output will be formatted as an html table
+  #[serde(rename = "html")]
+  Html,
   /// Output will be in json format
   #[serde(rename = "json")]
   Json,
   /// Output will be in compact json format
   #[serde(rename = "json-compact")]
   JsonCompact,
+  /// Output will be formatted as a markdown table
+  #[serde(rename = "markdown")]
+  Markdown,
+  /// Output will be formatted as newline delimited json, with one compact json value per line
+  #[serde(rename = "ndjson")]
+  Ndjson,
   /// Output will be formatted as plain text
   #[serde(rename = "plain")]
   Plain,
@@ -30,6 +43,9 @@ pub enum OutputFormat {
   /// Output will be formatted as a table without borders
   #[serde(rename = "table-no-border")]
   TableNoBorder,
+  /// Output will be formatted using the template from the --template or --template-file option
+  #[serde(rename = "template")]
+  Template,
   /// Output will be in toml format
   #[serde(rename = "toml")]
   Toml,
@@ -45,12 +61,16 @@ impl Display for OutputFormat {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
     match self {
       OutputFormat::Csv => write!(f, "csv"),
+      OutputFormat::Html => write!(f, "html"),
       OutputFormat::Json => write!(f, "json"),
       OutputFormat::JsonCompact => write!(f, "json-compact"),
+      OutputFormat::Markdown => write!(f, "markdown"),
+      OutputFormat::Ndjson => write!(f, "ndjson"),
       OutputFormat::Plain => write!(f, "plain"),
       OutputFormat::Quiet => write!(f, "quiet"),
       OutputFormat::Table => write!(f, "table"),
       OutputFormat::TableNoBorder => write!(f, "table-no-border"),
+      OutputFormat::Template => write!(f, "template"),
       OutputFormat::Toml => write!(f, "toml"),
       OutputFormat::TomlCompact => write!(f, "toml-compact"),
       OutputFormat::Yaml => write!(f, "yaml"),
@@ -64,12 +84,16 @@ impl TryFrom<&str> for OutputFormat {
   fn try_from(value: &str) -> Result<Self, Self::Error> {
     match value {
       "csv" => Ok(Self::Csv),
+      "html" => Ok(Self::Html),
       "json" => Ok(Self::Json),
       "json-compact" => Ok(Self::JsonCompact),
+      "markdown" => Ok(Self::Markdown),
+      "ndjson" => Ok(Self::Ndjson),
       "plain" => Ok(Self::Plain),
       "quiet" => Ok(Self::Quiet),
       "table" => Ok(Self::Table),
       "table-no-border" => Ok(Self::TableNoBorder),
+      "template" => Ok(Self::Template),
       "toml" => Ok(Self::Toml),
       "toml-compact" => Ok(Self::TomlCompact),
       "yaml" => Ok(Self::Yaml),
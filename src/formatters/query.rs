@@ -0,0 +1,461 @@
+use crate::context::Context;
+use crate::formatters::markup::{html_table, markdown_table};
+use crate::formatters::OutputFormat;
+use itertools::Itertools;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tabled::settings::peaker::PriorityMax;
+use tabled::settings::{Padding, Style, Width};
+use tabled::{builder::Builder as TabledBuilder, Table};
+
+/// # Query on structured output
+///
+/// A query selects a part of the serializable output of a command, before it is formatted.
+/// Queries support a subset of the JMESPath expression language:
+///
+/// * `@` - selects the current value,
+/// * `name` or `"quoted-name"` - selects the value of a field of an object,
+/// * `a.b.c` - selects nested fields,
+/// * `[2]`, `[-1]` - selects an element of an array, negative indices count from the end,
+/// * `[*]` - projects the remainder of the query on all elements of an array,
+/// * `*` - projects the remainder of the query on all values of an object,
+/// * `[]` - flattens nested arrays and projects the remainder of the query on the elements,
+/// * `[?field=='value']` - filters the elements of an array and projects the remainder
+///   of the query on the selected elements. Supported operators are `==`, `!=`, `<`, `<=`,
+///   `>` and `>=`. Literals can be `'raw strings'`, numbers, `true`, `false`, `null`
+///   or json literals enclosed in backticks.
+///
+/// Values that do not exist evaluate to `null`, and `null` values are removed from projections.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Query {
+  representation: String,
+  segments: Vec<Segment>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+  ArrayWildcard,
+  Field(String),
+  Filter(Vec<Segment>, Comparator, Value),
+  Flatten,
+  Index(i64),
+  ObjectWildcard,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Comparator {
+  Equal,
+  GreaterThan,
+  GreaterThanOrEqual,
+  LessThan,
+  LessThanOrEqual,
+  NotEqual,
+}
+
+impl Query {
+  /// # Applies the query to a serializable value
+  pub(crate) fn apply<T: Serialize>(&self, value: T) -> Result<Value, String> {
+    match serde_json::to_value(value) {
+      Ok(value) => Ok(evaluate(&self.segments, &value)),
+      Err(error) => Err(format!("could not convert value for query '{}' ({})", self.representation, error)),
+    }
+  }
+}
+
+impl TryFrom<&str> for Query {
+  type Error = String;
+
+  fn try_from(representation: &str) -> Result<Self, Self::Error> {
+    let mut parser = Parser { characters: representation.chars().collect(), position: 0 };
+    match parser.parse_expression() {
+      Ok(segments) => Ok(Self { representation: representation.to_string(), segments }),
+      Err(error) => Err(format!("invalid query '{}', {}", representation, error)),
+    }
+  }
+}
+
+fn evaluate(segments: &[Segment], value: &Value) -> Value {
+  match segments.split_first() {
+    None => value.clone(),
+    Some((segment, remainder)) => match (segment, value) {
+      (Segment::ArrayWildcard, Value::Array(elements)) => project(remainder, elements.iter()),
+      (Segment::Field(field), Value::Object(fields)) => match fields.get(field) {
+        Some(field_value) => evaluate(remainder, field_value),
+        None => Value::Null,
+      },
+      (Segment::Filter(path, comparator, literal), Value::Array(elements)) => {
+        project(remainder, elements.iter().filter(|element| compare(&evaluate(path, element), comparator, literal)))
+      }
+      (Segment::Flatten, Value::Array(elements)) => project(
+        remainder,
+        elements
+          .iter()
+          .flat_map(|element| match element {
+            Value::Array(nested_elements) => nested_elements.iter().collect::<Vec<_>>(),
+            other => vec![other],
+          })
+          .collect::<Vec<_>>()
+          .into_iter(),
+      ),
+      (Segment::Index(index), Value::Array(elements)) => {
+        let index = if *index < 0 { elements.len() as i64 + index } else { *index };
+        match usize::try_from(index).ok().and_then(|index| elements.get(index)) {
+          Some(element) => evaluate(remainder, element),
+          None => Value::Null,
+        }
+      }
+      (Segment::ObjectWildcard, Value::Object(fields)) => project(remainder, fields.values()),
+      _ => Value::Null,
+    },
+  }
+}
+
+fn project<'a>(segments: &[Segment], values: impl Iterator<Item = &'a Value>) -> Value {
+  Value::Array(values.map(|value| evaluate(segments, value)).filter(|value| !value.is_null()).collect::<Vec<_>>())
+}
+
+fn compare(value: &Value, comparator: &Comparator, literal: &Value) -> bool {
+  match comparator {
+    Comparator::Equal => value == literal,
+    Comparator::NotEqual => value != literal,
+    _ => {
+      let ordering = match (value, literal) {
+        (Value::Number(number), Value::Number(literal_number)) => number.as_f64().partial_cmp(&literal_number.as_f64()),
+        (Value::String(string), Value::String(literal_string)) => Some(string.cmp(literal_string)),
+        _ => None,
+      };
+      match ordering {
+        Some(ordering) => match comparator {
+          Comparator::GreaterThan => ordering.is_gt(),
+          Comparator::GreaterThanOrEqual => ordering.is_ge(),
+          Comparator::LessThan => ordering.is_lt(),
+          Comparator::LessThanOrEqual => ordering.is_le(),
+          Comparator::Equal | Comparator::NotEqual => unreachable!(),
+        },
+        None => false,
+      }
+    }
+  }
+}
+
+struct Parser {
+  characters: Vec<char>,
+  position: usize,
+}
+
+impl Parser {
+  fn parse_expression(&mut self) -> Result<Vec<Segment>, String> {
+    let segments = self.parse_segments()?;
+    match self.peek() {
+      None => Ok(segments),
+      Some(character) => Err(format!("unexpected character '{}' at position {}", character, self.position)),
+    }
+  }
+
+  // Parses segments until the end of the expression, or until a comparator when in a filter
+  fn parse_segments(&mut self) -> Result<Vec<Segment>, String> {
+    let mut segments = vec![];
+    self.skip_whitespace();
+    if self.peek() == Some('@') {
+      self.position += 1;
+    } else if self.peek() != Some('[') {
+      segments.push(self.parse_field()?);
+    }
+    loop {
+      self.skip_whitespace();
+      match self.peek() {
+        Some('.') => {
+          self.position += 1;
+          segments.push(self.parse_field()?);
+        }
+        Some('[') => segments.push(self.parse_bracket()?),
+        _ => return Ok(segments),
+      }
+    }
+  }
+
+  fn parse_field(&mut self) -> Result<Segment, String> {
+    self.skip_whitespace();
+    match self.peek() {
+      Some('*') => {
+        self.position += 1;
+        Ok(Segment::ObjectWildcard)
+      }
+      Some('"') => {
+        self.position += 1;
+        let field = self.take_while(|character| character != '"');
+        self.expect('"')?;
+        Ok(Segment::Field(field))
+      }
+      Some(character) if is_identifier_character(character) => Ok(Segment::Field(self.take_while(is_identifier_character))),
+      Some(character) => Err(format!("expected field name but found '{}' at position {}", character, self.position)),
+      None => Err("expected field name but found end of query".to_string()),
+    }
+  }
+
+  fn parse_bracket(&mut self) -> Result<Segment, String> {
+    self.expect('[')?;
+    self.skip_whitespace();
+    let segment = match self.peek() {
+      Some(']') => Segment::Flatten,
+      Some('*') => {
+        self.position += 1;
+        Segment::ArrayWildcard
+      }
+      Some('?') => {
+        self.position += 1;
+        let path = self.parse_segments()?;
+        let comparator = self.parse_comparator()?;
+        let literal = self.parse_literal()?;
+        Segment::Filter(path, comparator, literal)
+      }
+      Some(_) => {
+        let index = self.take_while(|character| character == '-' || character.is_ascii_digit());
+        Segment::Index(index.parse::<i64>().map_err(|_| format!("invalid index at position {}", self.position))?)
+      }
+      None => return Err("unexpected end of query".to_string()),
+    };
+    self.skip_whitespace();
+    self.expect(']')?;
+    Ok(segment)
+  }
+
+  fn parse_comparator(&mut self) -> Result<Comparator, String> {
+    self.skip_whitespace();
+    let comparator = self.take_while(|character| matches!(character, '=' | '!' | '<' | '>'));
+    match comparator.as_str() {
+      "==" => Ok(Comparator::Equal),
+      "!=" => Ok(Comparator::NotEqual),
+      "<" => Ok(Comparator::LessThan),
+      "<=" => Ok(Comparator::LessThanOrEqual),
+      ">" => Ok(Comparator::GreaterThan),
+      ">=" => Ok(Comparator::GreaterThanOrEqual),
+      _ => Err(format!("expected comparator (==, !=, <, <=, > or >=) at position {}", self.position)),
+    }
+  }
+
+  fn parse_literal(&mut self) -> Result<Value, String> {
+    self.skip_whitespace();
+    match self.peek() {
+      Some('\'') => {
+        self.position += 1;
+        let literal = self.take_while(|character| character != '\'');
+        self.expect('\'')?;
+        Ok(Value::String(literal))
+      }
+      Some('`') => {
+        self.position += 1;
+        let literal = self.take_while(|character| character != '`');
+        self.expect('`')?;
+        serde_json::from_str::<Value>(&literal).map_err(|_| format!("invalid json literal '{}'", literal))
+      }
+      _ => {
+        let literal = self.take_while(|character| character.is_alphanumeric() || matches!(character, '-' | '+' | '.'));
+        serde_json::from_str::<Value>(&literal).map_err(|_| format!("invalid literal '{}' at position {}", literal, self.position))
+      }
+    }
+  }
+
+  fn expect(&mut self, expected: char) -> Result<(), String> {
+    match self.peek() {
+      Some(character) if character == expected => {
+        self.position += 1;
+        Ok(())
+      }
+      Some(character) => Err(format!("expected '{}' but found '{}' at position {}", expected, character, self.position)),
+      None => Err(format!("expected '{}' but found end of query", expected)),
+    }
+  }
+
+  fn peek(&self) -> Option<char> {
+    self.characters.get(self.position).cloned()
+  }
+
+  fn skip_whitespace(&mut self) {
+    self.take_while(char::is_whitespace);
+  }
+
+  fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+    let start = self.position;
+    while self.peek().is_some_and(&predicate) {
+      self.position += 1;
+    }
+    self.characters[start..self.position].iter().collect()
+  }
+}
+
+fn is_identifier_character(character: char) -> bool {
+  character.is_alphanumeric() || character == '_' || character == '-'
+}
+
+/// # Prints the result of a query
+///
+/// Json, ndjson, toml and yaml output formats will serialize the result.
+/// For the csv, html, markdown, plain and table output formats, objects will be printed as key/value pairs,
+/// arrays of objects will be printed with one row per object and one column per field,
+/// and arrays of other values will be printed with one value per row.
+pub(crate) fn print_query_result(result: &Value, output_format: OutputFormat, context: &Context) -> Result<(), String> {
+  match output_format {
+    OutputFormat::Csv => {
+      let (header, records) = records(result);
+      if let Some(header) = header.filter(|_| context.show_headers()) {
+        context.print(
+          header
+            .iter()
+            .map(|column| context.csv_value(column))
+            .collect::<Result<Vec<_>, _>>()?
+            .join(context.csv_separator()),
+        );
+      }
+      for record in records {
+        context.print(
+          record
+            .iter()
+            .map(|value| context.csv_value(value))
+            .collect::<Result<Vec<_>, _>>()?
+            .join(context.csv_separator()),
+        );
+      }
+      Ok(())
+    }
+    OutputFormat::Html => {
+      let (header, records) = records(result);
+      context.print(html_table(header.as_deref().filter(|_| context.show_headers()), &records));
+      Ok(())
+    }
+    OutputFormat::Json => serde_json::to_string_pretty(result)
+      .map(|json| context.print(json))
+      .map_err(|error| format!("could not convert query result to json ({})", error)),
+    OutputFormat::JsonCompact => serde_json::to_string(result)
+      .map(|json| context.print(json))
+      .map_err(|error| format!("could not convert query result to compact json ({})", error)),
+    OutputFormat::Markdown => {
+      let (header, records) = records(result);
+      context.print(markdown_table(header.as_deref().filter(|_| context.show_headers()), &records));
+      Ok(())
+    }
+    OutputFormat::Ndjson => context.print_ndjson(result),
+    OutputFormat::Plain => {
+      let (header, records) = records(result);
+      if let Some(header) = header.filter(|_| context.show_headers()) {
+        context.print(header.join(","));
+      }
+      for record in records {
+        context.print(record.join(","));
+      }
+      Ok(())
+    }
+    OutputFormat::Quiet => Ok(()),
+    OutputFormat::Table => {
+      let mut table = create_table(result, context);
+      table.with(Padding::new(1, 1, 0, 0));
+      table.with(Style::sharp());
+      context.print(table.to_string());
+      Ok(())
+    }
+    OutputFormat::TableNoBorder => {
+      let mut table = create_table(result, context);
+      table.with(Padding::new(0, 2, 0, 0));
+      table.with(Style::empty());
+      context.print(table.to_string());
+      Ok(())
+    }
+    OutputFormat::Template => Err("template output is not supported for query results".to_string()),
+    OutputFormat::Toml => toml::to_string_pretty(result)
+      .map(|toml| context.print(toml))
+      .map_err(|error| format!("could not convert query result to toml ({})", error)),
+    OutputFormat::TomlCompact => toml::to_string(result)
+      .map(|toml| context.print(toml))
+      .map_err(|error| format!("could not convert query result to compact toml ({})", error)),
+    OutputFormat::Yaml => serde_yaml::to_string(result)
+      .map(|yaml| context.print(yaml))
+      .map_err(|error| format!("could not convert query result to yaml ({})", error)),
+  }
+}
+
+fn create_table(result: &Value, context: &Context) -> Table {
+  let (header, records) = records(result);
+  let mut tabled_builder = TabledBuilder::default();
+  if let Some(header) = header {
+    tabled_builder.push_record(header);
+  }
+  for record in records {
+    tabled_builder.push_record(record);
+  }
+  let mut table = tabled_builder.build();
+  if let Some(terminal_width) = context.terminal_width() {
+    table.with(Width::wrap(terminal_width).keep_words(true).priority(PriorityMax::new(true)));
+  }
+  table
+}
+
+// Converts a query result to an optional header and a list of records
+fn records(result: &Value) -> (Option<Vec<String>>, Vec<Vec<String>>) {
+  match result {
+    Value::Array(elements) if !elements.is_empty() && elements.iter().all(Value::is_object) => {
+      let objects = elements.iter().filter_map(Value::as_object).collect::<Vec<_>>();
+      let columns = objects.iter().flat_map(|object| object.keys()).unique().cloned().collect::<Vec<_>>();
+      let records = objects
+        .iter()
+        .map(|object| {
+          columns
+            .iter()
+            .map(|column| object.get(column).map(value_to_string).unwrap_or_default())
+            .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+      (Some(columns), records)
+    }
+    Value::Array(elements) => (None, elements.iter().map(|element| vec![value_to_string(element)]).collect::<Vec<_>>()),
+    Value::Object(fields) => (None, object_records(fields)),
+    Value::Null => (None, vec![]),
+    scalar => (None, vec![vec![value_to_string(scalar)]]),
+  }
+}
+
+fn object_records(fields: &Map<String, Value>) -> Vec<Vec<String>> {
+  fields.iter().map(|(key, value)| vec![key.to_string(), value_to_string(value)]).collect::<Vec<_>>()
+}
+
+fn value_to_string(value: &Value) -> String {
+  match value {
+    Value::Null => "".to_string(),
+    Value::String(string) => string.to_string(),
+    other => other.to_string(),
+  }
+}
+
+#[test]
+fn test_queries() {
+  let value = serde_json::json!({
+    "env": { "A": "1", "B": "2" },
+    "instances": 3,
+    "volumes": [{ "name": "v1", "size": 10 }, { "name": "v2", "size": 20 }],
+    "nested": [[1, 2], [3], 4],
+    "dashed-name": true
+  });
+  let query_results = vec![
+    ("@", value.clone()),
+    ("env", serde_json::json!({ "A": "1", "B": "2" })),
+    ("env.A", serde_json::json!("1")),
+    ("env.C", Value::Null),
+    ("instances", serde_json::json!(3)),
+    ("volumes[0].name", serde_json::json!("v1")),
+    ("volumes[-1].size", serde_json::json!(20)),
+    ("volumes[5]", Value::Null),
+    ("volumes[*].name", serde_json::json!(["v1", "v2"])),
+    ("volumes[?size > `10`].name", serde_json::json!(["v2"])),
+    ("volumes[?name == 'v1'].size", serde_json::json!([10])),
+    ("volumes[?@.size <= `10`]", serde_json::json!([{ "name": "v1", "size": 10 }])),
+    ("env.*", serde_json::json!(["1", "2"])),
+    ("nested[]", serde_json::json!([1, 2, 3, 4])),
+    ("\"dashed-name\"", serde_json::json!(true)),
+    ("dashed-name", serde_json::json!(true)),
+  ];
+  for (query, result) in query_results {
+    assert_eq!(Query::try_from(query).unwrap().apply(&value).unwrap(), result, "query '{}'", query);
+  }
+  for invalid_query in ["", "env.", "volumes[", "volumes[x]", "volumes[?size ~ 1]", "env..A"] {
+    assert!(Query::try_from(invalid_query).is_err(), "query '{}'", invalid_query);
+  }
+}
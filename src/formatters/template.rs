@@ -0,0 +1,130 @@
+use crate::formatters::formatter::{Label, SubjectFormatter};
+use crate::formatters::list_options::{find_label, normalize};
+
+/// # Output template
+///
+/// A template is a text that contains placeholders of the form `{{column}}`,
+/// e.g. `{{service id}}: {{image}}`. When the template is rendered, the placeholders
+/// will be replaced by the values of the corresponding columns, where the column names
+/// are the names from the table headers. For list output the template will be rendered
+/// once for each row, for unit output it will be rendered once.
+/// Use `{{{{` and `}}}}` to include literal braces.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Template {
+  parts: Vec<TemplatePart>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum TemplatePart {
+  Literal(String),
+  Placeholder(String),
+}
+
+impl Template {
+  /// # Renders the template for a value
+  ///
+  /// All labels of the value are available as placeholders.
+  /// When a target label is provided, it can be used as placeholder for the target id.
+  pub(crate) fn render<L: Label, V: SubjectFormatter<L>>(&self, labels: &[L], target_label: Option<&str>, target_id: &str, value: &V) -> Result<String, String> {
+    self.render_with(|column| match target_label {
+      Some(target_label) if normalize(column) == normalize(target_label) => Ok(target_id.to_string()),
+      _ => find_label(column, labels).map(|label| value.value(label, target_id)),
+    })
+  }
+
+  /// # Renders the template for a target id
+  ///
+  /// Only the label of the target id is available as placeholder.
+  pub(crate) fn render_target_id(&self, label: &str, target_id: &str) -> Result<String, String> {
+    self.render_with(|column| {
+      if normalize(column) == normalize(label) {
+        Ok(target_id.to_string())
+      } else {
+        Err(format!("unknown column '{}', available column is '{}'", column, normalize(label)))
+      }
+    })
+  }
+
+  fn render_with(&self, value: impl Fn(&str) -> Result<String, String>) -> Result<String, String> {
+    let mut rendered = String::new();
+    for part in &self.parts {
+      match part {
+        TemplatePart::Literal(literal) => rendered.push_str(literal),
+        TemplatePart::Placeholder(column) => rendered.push_str(&value(column)?),
+      }
+    }
+    Ok(rendered)
+  }
+}
+
+impl TryFrom<&str> for Template {
+  type Error = String;
+
+  fn try_from(representation: &str) -> Result<Self, Self::Error> {
+    let mut parts = vec![];
+    let mut literal = String::new();
+    let mut remainder = representation;
+    while !remainder.is_empty() {
+      if let Some(tail) = remainder.strip_prefix("{{{{") {
+        literal.push_str("{{");
+        remainder = tail;
+      } else if let Some(tail) = remainder.strip_prefix("}}}}") {
+        literal.push_str("}}");
+        remainder = tail;
+      } else if let Some(tail) = remainder.strip_prefix("{{") {
+        match tail.find("}}") {
+          Some(end) => {
+            let column = tail[..end].trim();
+            if column.is_empty() {
+              return Err(format!("empty placeholder in template '{}'", representation));
+            }
+            if !literal.is_empty() {
+              parts.push(TemplatePart::Literal(std::mem::take(&mut literal)));
+            }
+            parts.push(TemplatePart::Placeholder(column.to_string()));
+            remainder = &tail[end + 2..];
+          }
+          None => return Err(format!("unclosed placeholder in template '{}'", representation)),
+        }
+      } else {
+        let next_character = remainder.chars().next().unwrap_or_default();
+        literal.push(next_character);
+        remainder = &remainder[next_character.len_utf8()..];
+      }
+    }
+    if !literal.is_empty() {
+      parts.push(TemplatePart::Literal(literal));
+    }
+    Ok(Self { parts })
+  }
+}
+
+#[test]
+fn test_templates() {
+  assert_eq!(
+    Template::try_from("{{service id}}: {{ image }}").unwrap().parts,
+    vec![TemplatePart::Placeholder("service id".to_string()), TemplatePart::Literal(": ".to_string()), TemplatePart::Placeholder("image".to_string())]
+  );
+  assert_eq!(
+    Template::try_from("{{{{literal}}}} {{#}}").unwrap().parts,
+    vec![TemplatePart::Literal("{{literal}} ".to_string()), TemplatePart::Placeholder("#".to_string())]
+  );
+  assert_eq!(Template::try_from("").unwrap().parts, vec![]);
+  assert_eq!(
+    Template::try_from("- targets: ['{{id}}:9090']")
+      .unwrap()
+      .render_target_id("service id", "cmd")
+      .unwrap_err(),
+    "unknown column 'id', available column is 'service-id'"
+  );
+  assert_eq!(
+    Template::try_from("- targets: ['{{service-id}}:9090']")
+      .unwrap()
+      .render_target_id("service id", "cmd")
+      .unwrap(),
+    "- targets: ['cmd:9090']"
+  );
+  for invalid_template in ["{{image", "{{}}", "{{ }} text"] {
+    assert!(Template::try_from(invalid_template).is_err(), "template '{}'", invalid_template);
+  }
+}
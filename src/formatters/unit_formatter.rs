@@ -1,5 +1,7 @@
 use crate::context::Context;
 use crate::formatters::formatter::{Label, SubjectFormatter};
+use crate::formatters::markup::{html_table, markdown_table};
+use crate::formatters::query::print_query_result;
 use crate::formatters::OutputFormat;
 use serde::Serialize;
 use tabled::settings::peaker::PriorityMax;
@@ -21,15 +23,25 @@ where
     Self { target_id: target_id.into(), labels, target_label, context }
   }
 
+  /// # Prints the value
+  ///
+  /// If a query was provided, only the result of the query will be printed.
   pub fn print<V: SubjectFormatter<L> + Serialize>(&self, value: &V, default_output_format: Option<OutputFormat>) -> Result<(), String> {
+    if let Some(query) = self.context.query() {
+      return print_query_result(&query.apply(value)?, self.context.output_format(default_output_format), self.context);
+    }
     match self.context.output_format(default_output_format) {
       OutputFormat::Csv => self.print_csv(value),
+      OutputFormat::Html => self.print_html(value),
       OutputFormat::Json => self.print_json(value),
       OutputFormat::JsonCompact => self.print_json_compact(value),
+      OutputFormat::Markdown => self.print_markdown(value),
+      OutputFormat::Ndjson => self.context.print_ndjson(value),
       OutputFormat::Plain => Err("plain unit print not yet implemented".to_string()),
       OutputFormat::Quiet => Ok(()),
       OutputFormat::Table => self.print_table(value),
       OutputFormat::TableNoBorder => self.print_table_no_borders(value),
+      OutputFormat::Template => self.print_template(value),
       OutputFormat::Toml => self.print_toml(value),
       OutputFormat::TomlCompact => self.print_toml_compact(value),
       OutputFormat::Yaml => self.print_yaml(value),
@@ -39,12 +51,16 @@ where
   pub fn _print_non_serializable<V: SubjectFormatter<L>>(&self, value: &V, default_output_format: Option<OutputFormat>) -> Result<(), String> {
     match self.context.output_format(default_output_format) {
       OutputFormat::Csv => self.print_csv(value),
+      OutputFormat::Html => self.print_html(value),
       OutputFormat::Json => Err("serialization to json is not supported for this type".to_string()),
       OutputFormat::JsonCompact => Err("serialization to compact json is not supported for this type".to_string()),
+      OutputFormat::Markdown => self.print_markdown(value),
+      OutputFormat::Ndjson => Err("serialization to ndjson is not supported for this type".to_string()),
       OutputFormat::Plain => Err("plain unit print not yet implemented".to_string()),
       OutputFormat::Quiet => Ok(()),
       OutputFormat::Table => self.print_table(value),
       OutputFormat::TableNoBorder => self.print_table_no_borders(value),
+      OutputFormat::Template => self.print_template(value),
       OutputFormat::Toml => Err("serialization to toml is not supported for this type".to_string()),
       OutputFormat::TomlCompact => Err("serialization to compact toml is not supported for this type".to_string()),
       OutputFormat::Yaml => Err("serialization to yaml is not supported for this type".to_string()),
@@ -102,6 +118,44 @@ where
     table
   }
 
+  fn print_html<V: SubjectFormatter<L>>(&self, value: &V) -> Result<(), String> {
+    let (header, rows) = self.markup_header_and_rows(value);
+    self.context.print(html_table(header.as_deref(), &rows));
+    Ok(())
+  }
+
+  fn print_markdown<V: SubjectFormatter<L>>(&self, value: &V) -> Result<(), String> {
+    let (header, rows) = self.markup_header_and_rows(value);
+    self.context.print(markdown_table(header.as_deref(), &rows));
+    Ok(())
+  }
+
+  // When a target label is provided and headers are enabled, the target label and target id
+  // will be used as the header, else all labels will be included in the rows.
+  fn markup_header_and_rows<V: SubjectFormatter<L>>(&self, value: &V) -> (Option<Vec<&str>>, Vec<Vec<String>>) {
+    let mut rows = vec![];
+    let header = match self.target_label {
+      Some(target_label) => {
+        if !self.context.show_headers() {
+          rows.push(vec![target_label.to_string(), self.target_id.clone()]);
+        }
+        for label in self.labels {
+          if !label.is_target_label() && label.as_str_for_unit() != target_label {
+            rows.push(vec![label.as_str_for_unit().to_string(), value.value(label, self.target_id.as_str())]);
+          }
+        }
+        Some(vec![target_label, self.target_id.as_str()]).filter(|_| self.context.show_headers())
+      }
+      None => {
+        for label in self.labels {
+          rows.push(vec![label.as_str_for_unit().to_string(), value.value(label, self.target_id.as_str())]);
+        }
+        None
+      }
+    };
+    (header, rows)
+  }
+
   fn print_json<V: SubjectFormatter<L> + Serialize>(&self, value: &V) -> Result<(), String> {
     match serde_json::to_string_pretty(value) {
       Ok(json) => {
@@ -122,6 +176,13 @@ where
     }
   }
 
+  fn print_template<V: SubjectFormatter<L>>(&self, value: &V) -> Result<(), String> {
+    self
+      .context
+      .print(self.context.template()?.render(self.labels, self.target_label, self.target_id.as_str(), value)?);
+    Ok(())
+  }
+
   fn print_toml<V: SubjectFormatter<L> + Serialize>(&self, value: &V) -> Result<(), String> {
     match toml::to_string_pretty(value) {
       Ok(toml) => {
@@ -1,3 +1,4 @@
+use crate::completion::{target_platform_completer, target_tenant_completer};
 use crate::formatters::OutputFormat;
 use crate::verbosity::Verbosity;
 use builder::EnumValueParser;
@@ -5,25 +6,62 @@ use clap::builder::{PossibleValue, ValueParser};
 use clap::{builder, Arg, ArgAction};
 use dsh_api::platform::DshPlatform;
 
+pub(crate) const COLUMNS_ARGUMENT: &str = "columns-argument";
+pub(crate) const DESCENDING_ARGUMENT: &str = "descending-argument";
 pub(crate) const DRY_RUN_ARGUMENT: &str = "dry-run-argument";
 pub(crate) const FORCE_ARGUMENT: &str = "force-argument";
 // pub(crate) const FROM_CLIPBOARD_ARGUMENT: &str = "from-clipboard-argument";
 pub(crate) const NO_ESCAPE_ARGUMENT: &str = "no-escape-argument";
 pub(crate) const NO_HEADERS_ARGUMENT: &str = "no-headers-argument";
 pub(crate) const OUTPUT_FORMAT_ARGUMENT: &str = "output-format-argument";
+pub(crate) const QUERY_ARGUMENT: &str = "output-query-argument";
 pub(crate) const QUIET_ARGUMENT: &str = "quiet-argument";
 pub(crate) const SHOW_EXECUTION_TIME_ARGUMENT: &str = "show-execution-time-argument";
+pub(crate) const SORT_BY_ARGUMENT: &str = "sort-by-argument";
 pub(crate) const SUPPRESS_EXIT_STATUS_ARGUMENT: &str = "suppress-exit-status-argument";
 pub(crate) const TARGET_PASSWORD_FILE_ARGUMENT: &str = "target-password-file-argument";
 pub(crate) const TARGET_PLATFORM_ARGUMENT: &str = "target-platform-argument";
 pub(crate) const TARGET_TENANT_ARGUMENT: &str = "target-tenant-argument";
+pub(crate) const TEMPLATE_ARGUMENT: &str = "template-argument";
+pub(crate) const TEMPLATE_FILE_ARGUMENT: &str = "template-file-argument";
 pub(crate) const TERMINAL_WIDTH_ARGUMENT: &str = "terminal-width-argument";
 // pub(crate) const TO_CLIPBOARD_ARGUMENT: &str = "to-clipboard-argument";
 pub(crate) const VERBOSITY_ARGUMENT: &str = "set-verbosity-argument";
 pub(crate) const VERSION_ARGUMENT: &str = "version-argument";
+pub(crate) const WHERE_ARGUMENT: &str = "where-argument";
 
 pub(crate) const OUTPUT_OPTIONS_HEADING: &str = "Output options";
 
+pub(crate) fn columns_argument() -> Arg {
+  Arg::new(COLUMNS_ARGUMENT)
+    .long("columns")
+    .action(ArgAction::Set)
+    .value_parser(builder::NonEmptyStringValueParser::new())
+    .value_delimiter(',')
+    .value_name("COLUMNS")
+    .help("Select columns of list output")
+    .long_help(
+      "Comma separated list of the columns that will be included in list output, \
+          in the provided order. The column names are the names from the table headers. \
+          This option applies to all output formats.",
+    )
+    .hide_short_help(true)
+    .global(true)
+    .help_heading(OUTPUT_OPTIONS_HEADING)
+}
+
+pub(crate) fn descending_argument() -> Arg {
+  Arg::new(DESCENDING_ARGUMENT)
+    .long("desc")
+    .action(ArgAction::SetTrue)
+    .requires(SORT_BY_ARGUMENT)
+    .help("Sort list output in descending order")
+    .long_help("When this option is provided, list output will be sorted in descending order. Requires the --sort-by option.")
+    .hide_short_help(true)
+    .global(true)
+    .help_heading(OUTPUT_OPTIONS_HEADING)
+}
+
 pub(crate) fn dry_run_argument() -> Arg {
   Arg::new(DRY_RUN_ARGUMENT)
     .long("dry-run")
@@ -116,6 +154,26 @@ pub(crate) fn output_format_argument() -> Arg {
     .help_heading(OUTPUT_OPTIONS_HEADING)
 }
 
+pub(crate) fn query_argument() -> Arg {
+  Arg::new(QUERY_ARGUMENT)
+    .long("query")
+    .action(ArgAction::Set)
+    .value_parser(builder::NonEmptyStringValueParser::new())
+    .value_name("QUERY")
+    .help("Query structured output")
+    .long_help(
+      "Select a part of the structured output before it is formatted, using a subset \
+          of the JMESPath query language. Supported are the current value (@), field names (env, \"dashed-name\"), \
+          nested fields (a.b), array indices ([0], [-1]), projections ([*], *), \
+          flattening ([]) and filters ([?name=='value'], [?size>`10`]). \
+          The result will be printed in the selected output format. \
+          Example: --query \"volumes[*].name\".",
+    )
+    .hide_short_help(true)
+    .global(true)
+    .help_heading(OUTPUT_OPTIONS_HEADING)
+}
+
 pub(crate) fn quiet_argument() -> Arg {
   Arg::new(QUIET_ARGUMENT)
     .long("quiet")
@@ -160,6 +218,23 @@ pub(crate) fn show_execution_time_argument() -> Arg {
     .help_heading(OUTPUT_OPTIONS_HEADING)
 }
 
+pub(crate) fn sort_by_argument() -> Arg {
+  Arg::new(SORT_BY_ARGUMENT)
+    .long("sort-by")
+    .action(ArgAction::Set)
+    .value_parser(builder::NonEmptyStringValueParser::new())
+    .value_name("COLUMN")
+    .help("Sort list output on column")
+    .long_help(
+      "List output will be sorted on the values of this column. \
+          Columns that contain numerical values will be sorted numerically. \
+          This option applies to all output formats.",
+    )
+    .hide_short_help(true)
+    .global(true)
+    .help_heading(OUTPUT_OPTIONS_HEADING)
+}
+
 pub(crate) fn suppress_exit_status_argument() -> Arg {
   Arg::new(SUPPRESS_EXIT_STATUS_ARGUMENT)
     .long("suppress-exit-status")
@@ -213,6 +288,7 @@ pub(crate) fn target_platform_argument() -> Arg {
           as a default setting in the settings file, or else the user will be prompted. \
           The value between parentheses can be used as an alias for the platform name.",
     )
+    .add(target_platform_completer())
     .global(true)
 }
 
@@ -230,7 +306,46 @@ pub(crate) fn target_tenant_argument() -> Arg {
           the tenant should be specified via the environment variable DSH_CLI_TENANT, \
           as a default setting in the settings file, or else the user will be prompted.",
     )
+    .add(target_tenant_completer())
+    .global(true)
+}
+
+pub(crate) fn template_argument() -> Arg {
+  Arg::new(TEMPLATE_ARGUMENT)
+    .long("template")
+    .action(ArgAction::Set)
+    .value_parser(builder::NonEmptyStringValueParser::new())
+    .value_name("TEMPLATE")
+    .conflicts_with(TEMPLATE_FILE_ARGUMENT)
+    .help("Format output using a template")
+    .long_help(
+      "Format the output using this template. The template can contain placeholders \
+          of the form {{column}}, which will be replaced by the value of the column, \
+          where the column names are the names from the table headers. \
+          For list output the template will be rendered once for each row. \
+          This option implies '--output-format template'. \
+          Example: --template \"{{service id}}: {{image}}\".",
+    )
+    .hide_short_help(true)
+    .global(true)
+    .help_heading(OUTPUT_OPTIONS_HEADING)
+}
+
+pub(crate) fn template_file_argument() -> Arg {
+  Arg::new(TEMPLATE_FILE_ARGUMENT)
+    .long("template-file")
+    .action(ArgAction::Set)
+    .value_parser(ValueParser::path_buf())
+    .value_name("FILE")
+    .help("Format output using a template file")
+    .long_help(
+      "Format the output using the template from this file. \
+          See the --template option for the format of the template. \
+          This option implies '--output-format template'.",
+    )
+    .hide_short_help(true)
     .global(true)
+    .help_heading(OUTPUT_OPTIONS_HEADING)
 }
 
 pub(crate) fn terminal_width_argument() -> Arg {
@@ -272,3 +387,23 @@ pub(crate) fn version_argument() -> Arg {
     .exclusive(true)
     .hide_short_help(true)
 }
+
+pub(crate) fn where_argument() -> Arg {
+  Arg::new(WHERE_ARGUMENT)
+    .long("where")
+    .action(ArgAction::Append)
+    .value_parser(builder::NonEmptyStringValueParser::new())
+    .value_name("CONDITION")
+    .help("Filter list output")
+    .long_help(
+      "Only values that satisfy this condition will be included in list output. \
+          A condition has the form <column><operator><value>, \
+          where operator is one of = (equal), != (not equal), ~ (contains), !~ (does not contain), \
+          >, >=, < or <=. Numerical values will be compared numerically. \
+          This option can be provided multiple times, in which case all conditions must be satisfied. \
+          Example: --where \"#>0\" --where \"image~kafka\".",
+    )
+    .hide_short_help(true)
+    .global(true)
+    .help_heading(OUTPUT_OPTIONS_HEADING)
+}
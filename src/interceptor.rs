@@ -0,0 +1,297 @@
+//! # Interception of the http requests of a command
+//!
+//! The `dsh_api` crate does not allow to inject the http client that it uses,
+//! therefore the http requests of a command are intercepted by a local https proxy,
+//! while the command is run again in a child process that is configured to use this proxy
+//! and to trust the proxy's certificate. Only the environment of the child process is changed.
+//! The proxy's certificate is written to a temporary file that is removed
+//! when the child process has finished.
+//! The child process trusts the proxy via the `SSL_CERT_FILE` environment variable,
+//! which is only honoured on Linux.
+//!
+//! The intercepted requests are handled by an [`Interceptor`], e.g. the [`Forwarder`],
+//! that forwards the requests to the platform and logs them as api calls.
+
+use crate::log_writer::{logs_api_calls, ApiCallLog};
+use async_trait::async_trait;
+use hyper::body::Bytes;
+use hyper::header::{CONNECTION, CONTENT_LENGTH, CONTENT_TYPE, HOST, PROXY_AUTHORIZATION, TRANSFER_ENCODING};
+use hyper::server::conn::Http;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, HeaderMap, Method, Request, Response, Server, StatusCode};
+use log::{debug, info, warn};
+use rcgen::{BasicConstraints, CertificateParams, DnType, IsCa, KeyPair};
+use std::convert::Infallible;
+use std::env;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::process::{Command, ExitCode};
+use std::sync::Arc;
+use tokio_native_tls::native_tls::Identity;
+use tokio_native_tls::TlsAcceptor;
+
+// Set for the child process that runs the intercepted command, this variable is not meant to be set by users
+const ENV_VAR_INTERCEPTOR_PROXY: &str = "DSH_CLI_INTERCEPTOR_PROXY";
+
+/// # Http request that was sent by the intercepted command
+pub(crate) struct InterceptedRequest {
+  pub(crate) method: Method,
+  pub(crate) url: String,
+  pub(crate) headers: HeaderMap,
+  pub(crate) body: Bytes,
+}
+
+/// # Handles the intercepted http requests
+#[async_trait]
+pub(crate) trait Interceptor: Send + Sync {
+  /// # Returns the response that will be sent to the intercepted command
+  async fn intercept(&self, request: InterceptedRequest) -> Response<Body>;
+}
+
+/// # Interceptor that forwards the requests to the platform
+///
+/// Each forwarded request will be logged as an api call.
+pub(crate) struct Forwarder {
+  client: reqwest::Client,
+}
+
+/// # Response to a forwarded request
+pub(crate) struct ForwardedResponse {
+  pub(crate) status: u16,
+  pub(crate) headers: HeaderMap,
+  pub(crate) body: Bytes,
+}
+
+struct CertificateAuthority {
+  certificate: rcgen::Certificate,
+  key_pair: KeyPair,
+}
+
+/// # Returns whether the api calls of the command must be logged by the interceptor
+///
+/// This is the case when the api calls are logged (see [`logs_api_calls`]) on Linux,
+/// unless this process already is the intercepted child process.
+/// On other platforms, only the http requests that the dsh tool sends itself will be logged.
+pub(crate) fn runs_with_api_call_log() -> bool {
+  cfg!(target_os = "linux") && logs_api_calls() && !runs_intercepted()
+}
+
+/// # Returns whether this process is the intercepted child process
+pub(crate) fn runs_intercepted() -> bool {
+  env::var(ENV_VAR_INTERCEPTOR_PROXY).is_ok()
+}
+
+/// # Runs the command while logging all its api calls
+///
+/// ## Returns
+/// The exit code of the child process.
+pub(crate) async fn run_with_api_call_log() -> Result<ExitCode, String> {
+  run_intercepted(Forwarder::new()?).await
+}
+
+/// # Runs the command in a child process while intercepting its http requests
+///
+/// ## Returns
+/// The exit code of the child process.
+pub(crate) async fn run_intercepted(interceptor: impl Interceptor + 'static) -> Result<ExitCode, String> {
+  let interceptor = Arc::new(interceptor);
+  let certificate_authority = Arc::new(CertificateAuthority::generate()?);
+  let mut certificate_file = tempfile::Builder::new()
+    .prefix("dsh-interceptor-")
+    .suffix(".pem")
+    .tempfile()
+    .map_err(|error| format!("could not create interceptor certificate file ({})", error))?;
+  certificate_file
+    .write_all(certificate_authority.certificate.pem().as_bytes())
+    .map_err(|error| format!("could not write interceptor certificate file ({})", error))?;
+  let server = Server::try_bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+    .map_err(|error| format!("could not start interceptor ({})", error))?
+    .serve(make_service_fn(move |_| {
+      let interceptor = interceptor.clone();
+      let certificate_authority = certificate_authority.clone();
+      async move {
+        Ok::<_, Infallible>(service_fn(move |request| {
+          handle_connection_request(request, interceptor.clone(), certificate_authority.clone())
+        }))
+      }
+    }));
+  let proxy = format!("http://{}", server.local_addr());
+  debug!("interceptor listening on {}", proxy);
+  tokio::spawn(async move {
+    if let Err(error) = server.await {
+      warn!("interceptor failed ({})", error);
+    }
+  });
+  let current_exe = env::current_exe().map_err(|error| format!("could not determine the dsh executable ({})", error))?;
+  let mut child = Command::new(current_exe)
+    .args(env::args_os().skip(1))
+    .env(ENV_VAR_INTERCEPTOR_PROXY, &proxy)
+    .env("HTTPS_PROXY", &proxy)
+    .env("https_proxy", &proxy)
+    .env_remove("NO_PROXY")
+    .env_remove("no_proxy")
+    .env("SSL_CERT_FILE", certificate_file.path())
+    .spawn()
+    .map_err(|error| format!("could not start intercepted command ({})", error))?;
+  let status = tokio::task::spawn_blocking(move || child.wait())
+    .await
+    .map_err(|error| error.to_string())?
+    .map_err(|error| format!("intercepted command failed ({})", error))?;
+  certificate_file
+    .close()
+    .map_err(|error| format!("could not remove interceptor certificate file ({})", error))?;
+  Ok(status.code().map(|code| ExitCode::from(code as u8)).unwrap_or(ExitCode::FAILURE))
+}
+
+impl Forwarder {
+  pub(crate) fn new() -> Result<Self, String> {
+    let client = reqwest::Client::builder()
+      .redirect(reqwest::redirect::Policy::none())
+      .build()
+      .map_err(|error| format!("could not create interceptor client ({})", error))?;
+    Ok(Self { client })
+  }
+
+  /// # Forwards a request to the platform and logs it as an api call
+  pub(crate) async fn forward(&self, request: &InterceptedRequest) -> Result<ForwardedResponse, String> {
+    let mut upstream_request = self.client.request(request.method.clone(), &request.url).body(request.body.to_vec());
+    for (name, value) in request.headers.iter().filter(|(name, _)| **name != HOST && **name != PROXY_AUTHORIZATION) {
+      upstream_request = upstream_request.header(name, value);
+    }
+    let api_call_log = ApiCallLog::start(request.method.as_str(), &request.url, Some(&String::from_utf8_lossy(&request.body)));
+    let upstream_response = match upstream_request.send().await {
+      Ok(upstream_response) => upstream_response,
+      Err(error) => {
+        api_call_log.finish(None, None);
+        return Err(format!("could not forward {} {} ({})", request.method, request.url, error));
+      }
+    };
+    let status = upstream_response.status().as_u16();
+    let headers = upstream_response.headers().clone();
+    match upstream_response.bytes().await {
+      Ok(body) => {
+        api_call_log.finish(Some(status), Some(&String::from_utf8_lossy(&body)));
+        Ok(ForwardedResponse { status, headers, body })
+      }
+      Err(error) => {
+        api_call_log.finish(Some(status), None);
+        Err(format!("could not read response to {} {} ({})", request.method, request.url, error))
+      }
+    }
+  }
+}
+
+#[async_trait]
+impl Interceptor for Forwarder {
+  async fn intercept(&self, request: InterceptedRequest) -> Response<Body> {
+    match self.forward(&request).await {
+      Ok(forwarded_response) => {
+        let mut response = Response::builder().status(StatusCode::from_u16(forwarded_response.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR));
+        // The connection to the intercepted command is managed by the interceptor itself
+        for (name, value) in forwarded_response
+          .headers
+          .iter()
+          .filter(|(name, _)| **name != CONNECTION && **name != CONTENT_LENGTH && **name != TRANSFER_ENCODING)
+        {
+          response = response.header(name, value);
+        }
+        response.body(Body::from(forwarded_response.body)).unwrap_or_default()
+      }
+      Err(error) => {
+        warn!("{}", error);
+        status_response(StatusCode::BAD_GATEWAY, error)
+      }
+    }
+  }
+}
+
+impl CertificateAuthority {
+  fn generate() -> Result<Self, String> {
+    let key_pair = KeyPair::generate().map_err(|error| format!("could not generate interceptor key ({})", error))?;
+    let mut parameters = CertificateParams::default();
+    parameters.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    parameters.distinguished_name.push(DnType::CommonName, "dsh interceptor");
+    let certificate = parameters
+      .self_signed(&key_pair)
+      .map_err(|error| format!("could not generate interceptor certificate ({})", error))?;
+    Ok(Self { certificate, key_pair })
+  }
+
+  // Creates a tls acceptor with a certificate for host, signed by the interceptor's certificate
+  fn tls_acceptor(&self, host: &str) -> Result<TlsAcceptor, String> {
+    let key_pair = KeyPair::generate().map_err(|error| format!("could not generate key for '{}' ({})", host, error))?;
+    let certificate = CertificateParams::new(vec![host.to_string()])
+      .and_then(|parameters| parameters.signed_by(&key_pair, &self.certificate, &self.key_pair))
+      .map_err(|error| format!("could not generate certificate for '{}' ({})", host, error))?;
+    let identity = Identity::from_pkcs8(certificate.pem().as_bytes(), key_pair.serialize_pem().as_bytes()).map_err(|error| error.to_string())?;
+    Ok(TlsAcceptor::from(
+      tokio_native_tls::native_tls::TlsAcceptor::new(identity).map_err(|error| error.to_string())?,
+    ))
+  }
+}
+
+// Handles a request on a connection to the interceptor,
+// where a CONNECT request will start a tls tunnel with a certificate for the requested host
+async fn handle_connection_request(
+  request: Request<Body>,
+  interceptor: Arc<dyn Interceptor>,
+  certificate_authority: Arc<CertificateAuthority>,
+) -> Result<Response<Body>, Infallible> {
+  if request.method() == Method::CONNECT {
+    let authority = request.uri().authority().map(|authority| authority.to_string()).unwrap_or_default();
+    debug!("tunnel to {}", authority);
+    let tls_acceptor = match certificate_authority.tls_acceptor(authority.split(':').next().unwrap_or_default()) {
+      Ok(tls_acceptor) => tls_acceptor,
+      Err(error) => {
+        warn!("{}", error);
+        return Ok(status_response(StatusCode::BAD_GATEWAY, error));
+      }
+    };
+    tokio::spawn(async move {
+      match hyper::upgrade::on(request).await {
+        Ok(upgraded) => match tls_acceptor.accept(upgraded).await {
+          Ok(tls_stream) => {
+            let service = service_fn(move |request: Request<Body>| {
+              let url = format!(
+                "https://{}{}",
+                authority.trim_end_matches(":443"),
+                request.uri().path_and_query().map(|path| path.as_str()).unwrap_or("/")
+              );
+              intercept_request(request, url, interceptor.clone())
+            });
+            if let Err(error) = Http::new().http1_only(true).serve_connection(tls_stream, service).await {
+              debug!("tunnel connection closed ({})", error);
+            }
+          }
+          Err(error) => info!("tls handshake failed ({})", error),
+        },
+        Err(error) => info!("upgrade failed ({})", error),
+      }
+    });
+    Ok(Response::new(Body::empty()))
+  } else {
+    let url = request.uri().to_string();
+    intercept_request(request, url, interceptor).await
+  }
+}
+
+async fn intercept_request(request: Request<Body>, url: String, interceptor: Arc<dyn Interceptor>) -> Result<Response<Body>, Infallible> {
+  let method = request.method().clone();
+  let headers = request.headers().clone();
+  let body = hyper::body::to_bytes(request.into_body()).await.unwrap_or_default();
+  Ok(interceptor.intercept(InterceptedRequest { method, url, headers, body }).await)
+}
+
+/// # Creates a response with an optional content type
+pub(crate) fn response(status: u16, content_type: Option<&str>, body: Vec<u8>) -> Response<Body> {
+  let mut response = Response::builder().status(StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR));
+  if let Some(content_type) = content_type {
+    response = response.header(CONTENT_TYPE, content_type);
+  }
+  response.body(Body::from(body)).unwrap_or_default()
+}
+
+/// # Creates a plain text response
+pub(crate) fn status_response(status: StatusCode, message: String) -> Response<Body> {
+  response(status.as_u16(), Some("text/plain"), message.into_bytes())
+}
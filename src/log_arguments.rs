@@ -1,10 +1,52 @@
 use crate::global_arguments::OUTPUT_OPTIONS_HEADING;
-use crate::log_level::LogLevel;
+use crate::log_level::{LogFormat, LogLevel};
 use builder::EnumValueParser;
+use clap::builder::ValueParser;
 use clap::{builder, Arg, ArgAction};
 
+pub(crate) const LOG_FILE_ARGUMENT: &str = "log-file-argument";
+pub(crate) const LOG_FORMAT_ARGUMENT: &str = "log-format-argument";
 pub(crate) const LOG_LEVEL_API_ARGUMENT: &str = "log-level-api-argument";
 pub(crate) const LOG_LEVEL_ARGUMENT: &str = "log-level-argument";
+pub(crate) const TRACE_HTTP_ARGUMENT: &str = "trace-http-argument";
+
+pub(crate) fn log_file_argument() -> Arg {
+  Arg::new(LOG_FILE_ARGUMENT)
+    .long("log-file")
+    .action(ArgAction::Set)
+    .value_parser(ValueParser::path_buf())
+    .value_name("FILE")
+    .long_help(
+      "If this option is provided, the log records will be appended to this file \
+      instead of being printed to stderr. \
+      Each http request will also be recorded, \
+      with the method, the path, the status, the duration and a request id. \
+      On Linux the command is run in a child process that sends its requests \
+      via a local https proxy, such that all requests can be recorded. \
+      On other platforms only the requests that the dsh tool sends itself are recorded \
+      and the calls that are made via the dsh_api library are logged \
+      with the log records of that library (see --log-level-api).",
+    )
+    .hide_short_help(true)
+    .global(true)
+    .help_heading(OUTPUT_OPTIONS_HEADING)
+}
+
+pub(crate) fn log_format_argument() -> Arg {
+  Arg::new(LOG_FORMAT_ARGUMENT)
+    .long("log-format")
+    .action(ArgAction::Set)
+    .value_parser(EnumValueParser::<LogFormat>::new())
+    .value_name("FORMAT")
+    .long_help(
+      "If this option is provided, it will set the format of the log records. \
+      With format 'json' each log record and each call to the dsh api \
+      will be written as one json object per line. The default format is 'text'.",
+    )
+    .hide_short_help(true)
+    .global(true)
+    .help_heading(OUTPUT_OPTIONS_HEADING)
+}
 
 pub(crate) fn log_level_api_argument() -> Arg {
   Arg::new(LOG_LEVEL_API_ARGUMENT)
@@ -36,3 +78,17 @@ pub(crate) fn log_level_argument() -> Arg {
     .global(true)
     .help_heading(OUTPUT_OPTIONS_HEADING)
 }
+
+pub(crate) fn trace_http_argument() -> Arg {
+  Arg::new(TRACE_HTTP_ARGUMENT)
+    .long("trace-http")
+    .action(ArgAction::SetTrue)
+    .long_help(
+      "If this flag is provided, the request and response bodies of the http requests \
+      will be included in the log records. Authorization headers, tokens and secret values \
+      will be redacted, such that the log can be attached to a support ticket.",
+    )
+    .hide_short_help(true)
+    .global(true)
+    .help_heading(OUTPUT_OPTIONS_HEADING)
+}
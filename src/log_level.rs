@@ -1,5 +1,6 @@
 use crate::environment_variables::{ENV_VAR_LOG_LEVEL, ENV_VAR_LOG_LEVEL_API};
-use crate::log_arguments::{LOG_LEVEL_API_ARGUMENT, LOG_LEVEL_ARGUMENT};
+use crate::log_arguments::{LOG_FILE_ARGUMENT, LOG_FORMAT_ARGUMENT, LOG_LEVEL_API_ARGUMENT, LOG_LEVEL_ARGUMENT, TRACE_HTTP_ARGUMENT};
+use crate::log_writer::StructuredLogger;
 use crate::settings::Settings;
 use clap::ArgMatches;
 use log::LevelFilter;
@@ -8,6 +9,7 @@ use std::convert::TryFrom;
 use std::env;
 use std::fmt::{Display, Formatter};
 use std::io::{stdout, IsTerminal};
+use std::path::PathBuf;
 
 #[derive(clap::ValueEnum, Clone, Debug, Deserialize, PartialEq, PartialOrd, Serialize)]
 pub(crate) enum LogLevel {
@@ -31,6 +33,15 @@ pub(crate) enum LogLevel {
   Trace,
 }
 
+#[derive(clap::ValueEnum, Clone, Debug, Default, PartialEq)]
+pub(crate) enum LogFormat {
+  /// Log records will be written as plain text
+  #[default]
+  Text,
+  /// Log records will be written as json objects, one per line
+  Json,
+}
+
 pub(crate) fn initialize_logger(matches: &ArgMatches, settings: &Settings) -> Result<(), String> {
   let log_level_dsh = match matches.get_one::<LogLevel>(LOG_LEVEL_ARGUMENT) {
     Some(log_level_from_argument) => log_level_from_argument.clone(),
@@ -46,6 +57,18 @@ pub(crate) fn initialize_logger(matches: &ArgMatches, settings: &Settings) -> Re
       Err(_) => settings.log_level_api.clone().unwrap_or_default(),
     },
   };
+  let log_file = matches.get_one::<PathBuf>(LOG_FILE_ARGUMENT);
+  let log_format = matches.get_one::<LogFormat>(LOG_FORMAT_ARGUMENT).cloned().unwrap_or_default();
+  let trace_http = matches.get_flag(TRACE_HTTP_ARGUMENT);
+  if log_file.is_some() || log_format == LogFormat::Json || trace_http {
+    return StructuredLogger::initialize(
+      LevelFilter::from(log_level_dsh),
+      LevelFilter::from(log_level_dsh_api),
+      log_file,
+      log_format,
+      trace_http,
+    );
+  }
   if stdout().is_terminal() {
     env_logger::builder()
       .filter_module("dsh", LevelFilter::from(log_level_dsh))
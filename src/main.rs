@@ -10,33 +10,40 @@ use crate::environment_variables::{
   env_var_argument, env_vars_argument, get_set_environment_variables, print_environment_variable, print_environment_variables, ENV_VARS_ARGUMENT, ENV_VAR_ARGUMENT,
   ENV_VAR_HOME_DIRECTORY, ENV_VAR_PASSWORD, ENV_VAR_PASSWORD_FILE, ENV_VAR_PLATFORM, ENV_VAR_TENANT,
 };
+use crate::error::DshCliError;
 use crate::style::{apply_default_error_style, apply_default_warning_style};
 use autocomplete::{generate_autocomplete_file, generate_autocomplete_file_argument, AutocompleteShell, AUTOCOMPLETE_ARGUMENT};
 use clap::builder::styling::{AnsiColor, Color, Style};
 use clap::builder::{styling, Styles};
 use clap::error::{Error as ClapError, ErrorKind};
 use clap::{ArgMatches, Command};
+use clap_complete::CompleteEnv;
 use context::Context;
 use dsh_api::dsh_api_client::DshApiClient;
 use dsh_api::dsh_api_client_factory::DshApiClientFactory;
 use dsh_api::dsh_api_tenant::DshApiTenant;
 use dsh_api::platform::DshPlatform;
 use dsh_api::{crate_version, openapi_version};
+use fan_out::{all_targets_argument, execute_for_targets, selected_targets, targets_argument};
 use filter_flags::FilterFlagType;
 use global_arguments::{
-  dry_run_argument, force_argument, no_escape_argument, no_headers_argument, output_format_argument, quiet_argument, set_verbosity_argument, show_execution_time_argument,
-  suppress_exit_status_argument, target_password_file_argument, target_platform_argument, target_tenant_argument, terminal_width_argument, version_argument,
-  TARGET_PASSWORD_FILE_ARGUMENT, TARGET_PLATFORM_ARGUMENT, TARGET_TENANT_ARGUMENT, VERSION_ARGUMENT,
+  columns_argument, descending_argument, dry_run_argument, force_argument, no_escape_argument, no_headers_argument, output_format_argument, query_argument, quiet_argument,
+  set_verbosity_argument, show_execution_time_argument, sort_by_argument, suppress_exit_status_argument, target_password_file_argument, target_platform_argument,
+  target_tenant_argument, template_argument, template_file_argument, terminal_width_argument, version_argument, where_argument, TARGET_PASSWORD_FILE_ARGUMENT,
+  TARGET_PLATFORM_ARGUMENT, TARGET_TENANT_ARGUMENT, VERSION_ARGUMENT,
 };
 use homedir::my_home;
+use interceptor::{run_with_api_call_log, runs_with_api_call_log};
 use lazy_static::lazy_static;
 use log::{debug, trace};
-use log_arguments::{log_level_api_argument, log_level_argument};
+use log_arguments::{log_file_argument, log_format_argument, log_level_api_argument, log_level_argument, trace_http_argument};
 use log_level::initialize_logger;
+#[cfg(feature = "record")]
+use recorder::{record_argument, recorded_target, replay_argument, run_with_recorder, runs_with_recorder, save_recorded_target, RECORD_ARGUMENT, REPLAY_ARGUMENT, REPLAY_PASSWORD};
 use rpassword::prompt_password;
 use serde::{Deserialize, Serialize};
 use settings::{get_settings, Settings};
-use std::collections::HashMap;
+use shell::{shell_command, Shell, SHELL_COMMAND};
 use std::env::temp_dir;
 use std::fmt::Debug;
 use std::io::ErrorKind::NotFound;
@@ -44,12 +51,16 @@ use std::io::{stdin, stdout, IsTerminal, Write};
 use std::path::{Path, PathBuf};
 use std::process::{ExitCode, Termination};
 use std::{env, fs, process};
-use subject::Subject;
+use subject::{Subject, SubjectRegistry};
 use subjects::api::API_SUBJECT;
 use subjects::app::APP_SUBJECT;
 use subjects::bucket::BUCKET_SUBJECT;
 use subjects::certificate::CERTIFICATE_SUBJECT;
+use subjects::compare::COMPARE_SUBJECT;
+use subjects::completion::COMPLETION_SUBJECT;
 use subjects::env::ENV_SUBJECT;
+use subjects::graph::GRAPH_SUBJECT;
+use subjects::history::HISTORY_SUBJECT;
 use subjects::image::IMAGE_SUBJECT;
 use subjects::manifest::MANIFEST_SUBJECT;
 use subjects::metric::METRIC_SUBJECT;
@@ -67,27 +78,41 @@ use subjects::token::TOKEN_SUBJECT;
 use subjects::topic::TOPIC_SUBJECT;
 use subjects::vhost::VHOST_SUBJECT;
 use subjects::volume::VOLUME_SUBJECT;
-use targets::{get_target_password_from_keyring, read_target};
+use targets::{get_target_password_from_keyring, read_target, Target};
 
 mod arguments;
+mod audit;
 mod autocomplete;
 mod capability;
 mod capability_builder;
+mod completion;
 mod context;
 mod environment_variables;
+mod error;
+mod fan_out;
 mod filter_flags;
 mod flags;
 mod formatters;
 mod global_arguments;
+mod interceptor;
 mod limits_flags;
 mod log_arguments;
 mod log_level;
+mod log_writer;
 mod modifier_flags;
+mod openapi;
+mod platforms;
+#[cfg(feature = "record")]
+mod recorder;
+mod resource_ids;
 mod settings;
+mod shell;
+mod snapshots;
 mod style;
 mod subject;
 mod subjects;
 mod targets;
+mod usage;
 mod verbosity;
 mod version;
 
@@ -123,7 +148,7 @@ const TARGETS_SUBDIRECTORY: &str = "targets";
 const DEFAULT_DSH_CLI_SETTINGS_FILENAME: &str = "settings.toml";
 const TOML_FILENAME_EXTENSION: &str = "toml";
 
-type DshCliResult = Result<(), String>;
+type DshCliResult = Result<(), DshCliError>;
 
 #[derive(Debug)]
 enum DshCliExit {
@@ -131,7 +156,8 @@ enum DshCliExit {
   OkClap(ClapError),
   Err(String),
   ErrClap(ClapError),
-  ErrContext(String, Box<Context>),
+  ErrContext(DshCliError, Box<Context>),
+  Intercepted(ExitCode),
 }
 
 impl Termination for DshCliExit {
@@ -150,36 +176,41 @@ impl Termination for DshCliExit {
         let _ = clap_error.print();
         ExitCode::FAILURE
       }
-      DshCliExit::ErrContext(msg, context) => {
-        context.print_error(msg);
+      DshCliExit::ErrContext(error, context) => {
+        context.print_cli_error(&error);
         if context.suppress_exit_status() {
           context.print_warning("exit status suppressed");
           ExitCode::SUCCESS
         } else {
-          ExitCode::FAILURE
+          ExitCode::from(error)
         }
       }
+      DshCliExit::Intercepted(exit_code) => exit_code,
     }
   }
 }
 
-#[tokio::main]
-async fn main() -> DshCliExit {
-  inner_main().await
+fn main() -> DshCliExit {
+  // Merge the user defined platforms with the default platforms, before the platforms are used.
+  // This sets an environment variable, which is only safe before the async runtime starts its threads.
+  if let Err(error) = platforms::enable_user_platforms() {
+    eprintln!("{}", apply_default_warning_style(error));
+  }
+  match tokio::runtime::Builder::new_multi_thread().enable_all().build() {
+    Ok(runtime) => runtime.block_on(inner_main()),
+    Err(error) => DshCliExit::Err(format!("could not start async runtime ({})", error)),
+  }
 }
 
 async fn inner_main() -> DshCliExit {
-  let _ = ctrlc::set_handler(move || {
-    eprintln!("{}", apply_default_warning_style("interrupted"));
-    process::exit(0);
-  });
-
   let subjects: Vec<&(dyn Subject + Send + Sync)> = vec![
     API_SUBJECT.as_ref(),
     APP_SUBJECT.as_ref(),
     BUCKET_SUBJECT.as_ref(),
     CERTIFICATE_SUBJECT.as_ref(),
+    COMPARE_SUBJECT.as_ref(),
     ENV_SUBJECT.as_ref(),
+    GRAPH_SUBJECT.as_ref(),
     IMAGE_SUBJECT.as_ref(),
     MANIFEST_SUBJECT.as_ref(),
     METRIC_SUBJECT.as_ref(),
@@ -197,29 +228,22 @@ async fn inner_main() -> DshCliExit {
     VOLUME_SUBJECT.as_ref(),
     SETTING_SUBJECT.as_ref(),
     TARGET_SUBJECT.as_ref(),
+    HISTORY_SUBJECT.as_ref(),
+    COMPLETION_SUBJECT.as_ref(),
   ];
 
-  let mut subject_registry: HashMap<String, &(dyn Subject + Send + Sync)> = HashMap::new();
-  let mut subject_list_shortcut_registry: HashMap<String, &(dyn Subject + Send + Sync)> = HashMap::new();
-
-  let mut subject_commands: Vec<Command> = Vec::new();
-
-  for subject in subjects {
-    let (command_name, subject_command) = subject.subject_command();
-    subject_registry.insert(command_name.to_string(), subject);
-    subject_commands.push(subject_command);
-    if let Some((list_shortcut_name, clap_list_command_shortcut)) = subject.subject_list_shortcut_command() {
-      subject_list_shortcut_registry.insert(list_shortcut_name.to_string(), subject);
-      subject_commands.push(clap_list_command_shortcut);
-    }
-  }
+  let (subject_registry, subject_commands) = SubjectRegistry::create(subjects);
 
   let (settings, settings_log) = match get_settings(None) {
     Ok((setting, settings_log)) => (setting, settings_log),
     Err(msg) => return DshCliExit::Err(msg),
   };
 
-  let mut command = create_command(&subject_commands, &settings);
+  let mut command = create_command(&subject_commands, &settings).subcommand(shell_command());
+
+  // When the environment variable COMPLETE is set, generate the dynamic completions and exit
+  let completion_command = command.clone();
+  CompleteEnv::with_factory(move || completion_command.clone()).complete();
 
   let matches = match command.clone().try_get_matches() {
     Ok(matches) => matches,
@@ -250,6 +274,30 @@ async fn inner_main() -> DshCliExit {
     Err(msg) => return DshCliExit::Err(msg),
   }
 
+  // The recorded command runs in a child process, that will handle an interrupt itself
+  #[cfg(feature = "record")]
+  if runs_with_recorder(&matches) {
+    let _ = ctrlc::set_handler(|| ());
+    return match run_with_recorder(&matches).await {
+      Ok(exit_code) => DshCliExit::Intercepted(exit_code),
+      Err(msg) => DshCliExit::Err(msg),
+    };
+  }
+
+  // The command runs in a child process as well when its api calls are logged
+  if runs_with_api_call_log() {
+    let _ = ctrlc::set_handler(|| ());
+    return match run_with_api_call_log().await {
+      Ok(exit_code) => DshCliExit::Intercepted(exit_code),
+      Err(msg) => DshCliExit::Err(msg),
+    };
+  }
+
+  let _ = ctrlc::set_handler(move || {
+    eprintln!("{}", apply_default_warning_style("interrupted"));
+    process::exit(0);
+  });
+
   let context = match Context::create(&matches, settings) {
     Ok(context) => {
       trace!("{:#?}", context);
@@ -268,61 +316,47 @@ async fn inner_main() -> DshCliExit {
     return DshCliExit::Ok;
   }
 
+  if let Some((SHELL_COMMAND, _)) = matches.subcommand() {
+    let shell_command = create_command(&subject_commands, context.settings());
+    let result = match Shell::new(shell_command, &subject_registry, context.settings().clone(), &matches) {
+      Ok(mut shell) => shell.run(&context).await,
+      Err(error) => Err(DshCliError::from(error)),
+    };
+    return match result {
+      Ok(_) => DshCliExit::Ok,
+      Err(error) => DshCliExit::ErrContext(error, Box::new(context)),
+    };
+  }
+
   match matches.subcommand() {
-    Some((subject_command_name, sub_matches)) => match subject_registry.get(subject_command_name) {
-      Some(subject) => {
-        let requirements = subject.requirements(sub_matches);
-        debug!("{:?}", requirements);
+    Some((subject_command_name, sub_matches)) => {
+      let requirements = match subject_registry.requirements(subject_command_name, sub_matches) {
+        Ok(requirements) => requirements,
+        Err(error) => return DshCliExit::ErrContext(error, Box::new(context)),
+      };
+      debug!("{:?}", requirements);
+      let targets = match selected_targets(&matches) {
+        Ok(targets) => targets,
+        Err(error) => return DshCliExit::ErrContext(DshCliError::from(error), Box::new(context)),
+      };
+      let result = if let Some(targets) = targets {
         if requirements.needs_dsh_api_client() {
-          let client = match create_client(&matches, context.settings()).await {
-            Ok(client) => client,
-            Err(error) => return DshCliExit::ErrContext(error, Box::new(context)),
-          };
-          match subject.execute_subject_command_with_client(sub_matches, &client, &context).await {
-            Ok(_) => {}
-            Err(msg) => {
-              return DshCliExit::ErrContext(msg, Box::new(context));
-            }
-          }
+          execute_for_targets(&targets, &subject_registry, subject_command_name, sub_matches, &context).await
         } else {
-          match subject.execute_subject_command_without_client(sub_matches, &context).await {
-            Ok(_) => {}
-            Err(msg) => {
-              return DshCliExit::ErrContext(msg, Box::new(context));
-            }
-          }
+          Err(DshCliError::from(format!("command '{}' can not be run for multiple targets", subject_command_name)))
         }
-      }
-      None => match subject_list_shortcut_registry.get(subject_command_name) {
-        Some(subject_list_shortcut) => {
-          let requirements = subject_list_shortcut.requirements_list_shortcut(sub_matches);
-          debug!("{:?}", requirements);
-          if requirements.needs_dsh_api_client() {
-            let client = match create_client(&matches, context.settings()).await {
-              Ok(client) => client,
-              Err(error) => return DshCliExit::ErrContext(error, Box::new(context)),
-            };
-            match subject_list_shortcut
-              .execute_subject_list_shortcut_with_client(sub_matches, &client, &context)
-              .await
-            {
-              Ok(_) => {}
-              Err(msg) => {
-                return DshCliExit::ErrContext(msg, Box::new(context));
-              }
-            }
-          } else {
-            match subject_list_shortcut.execute_subject_list_shortcut_without_client(sub_matches, &context).await {
-              Ok(_) => {}
-              Err(msg) => {
-                return DshCliExit::ErrContext(msg, Box::new(context));
-              }
-            }
-          }
+      } else if requirements.needs_dsh_api_client() {
+        match create_client(&matches, context.settings()).await {
+          Ok(client) => subject_registry.execute(subject_command_name, sub_matches, Some(&client), &context).await,
+          Err(error) => Err(DshCliError::from(error)),
         }
-        None => return DshCliExit::Err("unexpected error, list shortcut not found".to_string()),
-      },
-    },
+      } else {
+        subject_registry.execute(subject_command_name, sub_matches, None, &context).await
+      };
+      if let Err(error) = result {
+        return DshCliExit::ErrContext(error, Box::new(context));
+      }
+    }
     None => return DshCliExit::Err("unexpected error, no command provided".to_string()),
   }
   DshCliExit::Ok
@@ -344,11 +378,27 @@ fn create_command(clap_commands: &Vec<Command>, settings: &Settings) -> Command
       target_password_file_argument(),
       dry_run_argument(),
       force_argument(),
+      targets_argument(),
+      all_targets_argument(),
+      #[cfg(feature = "record")]
+      record_argument(),
+      #[cfg(feature = "record")]
+      replay_argument(),
       log_level_argument(),
       log_level_api_argument(),
+      log_file_argument(),
+      log_format_argument(),
+      trace_http_argument(),
       no_escape_argument(),
       no_headers_argument(),
       output_format_argument(),
+      columns_argument(),
+      sort_by_argument(),
+      descending_argument(),
+      where_argument(),
+      query_argument(),
+      template_argument(),
+      template_file_argument(),
       quiet_argument(),
       set_verbosity_argument(),
       show_execution_time_argument(),
@@ -649,14 +699,40 @@ fn get_target_tenant(matches: &ArgMatches, settings: &Settings) -> Result<String
 /// ## Returns
 /// An `Ok<String>` containing the password, or an `Err<String>`.
 fn get_target_password(matches: &ArgMatches, dsh_api_tenant: &DshApiTenant) -> Result<String, String> {
-  match matches.get_one::<PathBuf>(TARGET_PASSWORD_FILE_ARGUMENT) {
-    Some(password_file_from_arg) => read_target_password_file(password_file_from_arg),
+  match get_target_password_non_interactive(matches.get_one::<PathBuf>(TARGET_PASSWORD_FILE_ARGUMENT), dsh_api_tenant)? {
+    Some(password) => Ok(password),
+    None => {
+      if stdin().is_terminal() {
+        read_single_line_password(format!("password for tenant {}: ", dsh_api_tenant).as_str())
+      } else {
+        Err("could not determine password and unable to to prompt user, please check configuration".to_string())
+      }
+    }
+  }
+}
+
+/// # Get the target password without user interaction
+///
+/// This function will try the same sources as `get_target_password`,
+/// except that it will never prompt the user.
+///
+/// ## Parameters
+/// * `password_file` - value of the command line argument `--password-file`, if provided
+/// * `dsh_api_tenant` - used to determine the target settings file
+///
+/// ## Returns
+/// `Ok(Some<String>)` - containing the password
+/// `Ok(None)` - when no password is available without asking the user
+/// `Err<String>` - when a password source could not be read
+fn get_target_password_non_interactive(password_file: Option<&PathBuf>, dsh_api_tenant: &DshApiTenant) -> Result<Option<String>, String> {
+  match password_file {
+    Some(password_file_from_arg) => read_target_password_file(password_file_from_arg).map(Some),
     None => match env::var(ENV_VAR_PASSWORD_FILE) {
-      Ok(password_file_from_env) => read_target_password_file(password_file_from_env),
+      Ok(password_file_from_env) => read_target_password_file(password_file_from_env).map(Some),
       Err(_) => match env::var(ENV_VAR_PASSWORD) {
         Ok(password_from_env_var) => {
           debug!("target password (environment variable '{}')", ENV_VAR_PASSWORD);
-          Ok(password_from_env_var)
+          Ok(Some(password_from_env_var))
         }
         Err(_) => match (
           read_target(dsh_api_tenant.platform(), dsh_api_tenant.name())?,
@@ -664,15 +740,9 @@ fn get_target_password(matches: &ArgMatches, dsh_api_tenant: &DshApiTenant) -> R
         ) {
           (Some(_), Some(password_from_keyring)) => {
             debug!("target exists, password read (keyring)");
-            Ok(password_from_keyring)
-          }
-          _ => {
-            if stdin().is_terminal() {
-              read_single_line_password(format!("password for tenant {}: ", dsh_api_tenant).as_str())
-            } else {
-              Err("could not determine password and unable to to prompt user, please check configuration".to_string())
-            }
+            Ok(Some(password_from_keyring))
           }
+          _ => Ok(None),
         },
       },
     },
@@ -834,11 +904,36 @@ where
   }
 }
 
+/// # Create a client for the target tenant
+///
+/// When the `--replay` option is provided, the client will target the platform and tenant
+/// of the recording and the responses will be served from the recording.
+/// When the `--record` option is provided, the target will be saved with the recording.
+/// In both cases this process is the child process of the recorder, see `run_with_recorder`.
 async fn create_client(matches: &ArgMatches, settings: &Settings) -> Result<DshApiClient, String> {
+  #[cfg(feature = "record")]
+  if let Some(replay_directory) = matches.get_one::<PathBuf>(REPLAY_ARGUMENT) {
+    let (recorded_platform, recorded_tenant_name) = recorded_target(replay_directory)?;
+    debug!("create client for recorded target '{}@{}'", recorded_tenant_name, recorded_platform);
+    let dsh_api_tenant = DshApiTenant::new(recorded_tenant_name, recorded_platform);
+    return Ok(DshApiClientFactory::create(dsh_api_tenant, REPLAY_PASSWORD.to_string())?.client().await?);
+  }
   let target_platform = get_target_platform(matches, settings)?;
   let target_tenant_name = get_target_tenant(matches, settings)?;
+  #[cfg(feature = "record")]
+  if let Some(record_directory) = matches.get_one::<PathBuf>(RECORD_ARGUMENT) {
+    save_recorded_target(record_directory, &target_platform, &target_tenant_name)?;
+  }
+  create_client_for_target(matches, &target_platform, &target_tenant_name).await
+}
+
+/// # Create a client for an explicit target
+///
+/// The password will be determined from `matches` like it is for the target tenant,
+/// see `get_target_password`.
+async fn create_client_for_target(matches: &ArgMatches, target_platform: &DshPlatform, target_tenant_name: &str) -> Result<DshApiClient, String> {
   debug!("create client for target '{}@{}'", target_tenant_name, target_platform);
-  let dsh_api_tenant = DshApiTenant::new(target_tenant_name.clone(), target_platform.clone());
+  let dsh_api_tenant = DshApiTenant::new(target_tenant_name.to_string(), target_platform.clone());
   let password = get_target_password(matches, &dsh_api_tenant)?;
   let dsh_api_client_factory = DshApiClientFactory::create(dsh_api_tenant, password)?;
   let dsh_api_client = dsh_api_client_factory.client().await?;
@@ -846,6 +941,47 @@ async fn create_client(matches: &ArgMatches, settings: &Settings) -> Result<DshA
   Ok(dsh_api_client)
 }
 
+/// # Create a client for a stored target
+///
+/// The password for the target is read from the keyring.
+/// Since clients for multiple targets can be created concurrently,
+/// the user will not be prompted for a missing password.
+pub(crate) async fn create_client_for_stored_target(target: &Target) -> Result<DshApiClient, String> {
+  debug!("create client for stored target '{}'", target);
+  let password = match get_target_password_from_keyring(&target.platform, &target.tenant)? {
+    Some(password_from_keyring) => password_from_keyring,
+    None => return Err(format!("could not find the password for target '{}' in the keyring", target)),
+  };
+  let dsh_api_tenant = DshApiTenant::new(target.tenant.clone(), target.platform.clone());
+  let dsh_api_client_factory = DshApiClientFactory::create(dsh_api_tenant, password)?;
+  Ok(dsh_api_client_factory.client().await?)
+}
+
+/// # Create a client for another tenant than the target tenant
+///
+/// Since the password arguments and environment variables apply to the target tenant,
+/// the password for `tenant_name` is read from the keyring.
+/// If the tenant is not a configured target, the user will be prompted for the password.
+pub(crate) async fn create_client_for_tenant(tenant_name: &str, platform: &DshPlatform) -> Result<DshApiClient, String> {
+  debug!("create client for tenant '{}@{}'", tenant_name, platform);
+  let dsh_api_tenant = DshApiTenant::new(tenant_name.to_string(), platform.clone());
+  let password = match (read_target(platform, tenant_name)?, get_target_password_from_keyring(platform, tenant_name)?) {
+    (Some(_), Some(password_from_keyring)) => password_from_keyring,
+    _ => {
+      if stdin().is_terminal() {
+        read_single_line_password(format!("password for tenant {}: ", dsh_api_tenant).as_str())?
+      } else {
+        return Err(format!(
+          "could not determine password for tenant '{}' and unable to prompt user, please check configuration",
+          tenant_name
+        ));
+      }
+    }
+  };
+  let dsh_api_client_factory = DshApiClientFactory::create(dsh_api_tenant, password)?;
+  Ok(dsh_api_client_factory.client().await?)
+}
+
 // Method will panic if rows vector is empty
 fn to_table(header: &str, rows: Vec<(&str, String)>) -> String {
   let bold_green = Style::new().bold().fg_color(Some(Color::Ansi(AnsiColor::Green)));
@@ -868,6 +1004,8 @@ fn enabled_features() -> Option<Vec<&'static str>> {
   let mut enabled_features = vec![];
   #[cfg(feature = "manage")]
   enabled_features.push("manage");
+  #[cfg(feature = "record")]
+  enabled_features.push("record");
   #[cfg(feature = "robot")]
   enabled_features.push("robot");
   if enabled_features.is_empty() {
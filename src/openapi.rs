@@ -0,0 +1,323 @@
+use dsh_api::dsh_api_client::DshApiClient;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_json::{Map, Value};
+use std::fmt::{Display, Formatter};
+
+/// Maximum depth of nested schemas in generated examples, to guard against recursive schemas
+const MAX_EXAMPLE_DEPTH: usize = 16;
+
+lazy_static! {
+  static ref OPENAPI_SPEC: Option<Value> = serde_json::from_str(DshApiClient::openapi_spec()).ok();
+}
+
+/// # Validation error of a request body
+///
+/// * `pointer` - json pointer to the location of the error in the body, like `/exposedPorts/8080/auth`
+/// * `message` - description of the error
+#[derive(Debug, PartialEq)]
+pub(crate) struct SchemaError {
+  pub(crate) pointer: String,
+  pub(crate) message: String,
+}
+
+impl Display for SchemaError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    if self.pointer.is_empty() {
+      write!(f, "/: {}", self.message)
+    } else {
+      write!(f, "{}: {}", self.pointer, self.message)
+    }
+  }
+}
+
+/// # Returns the schema of the request body of an operation
+///
+/// ## Parameters
+/// * `method` - http method of the operation, like `put`
+/// * `path` - path of the operation, like `/allocation/{tenant}/application/{appid}/configuration`
+/// * `body_type` - type of the request body, used when the operation does not define a schema
+pub(crate) fn request_body_schema(method: &str, path: &str, body_type: Option<&str>) -> Option<&'static Value> {
+  let spec = OPENAPI_SPEC.as_ref()?;
+  spec
+    .pointer(&format!("/paths/{}/{}/requestBody/content", escape_pointer_token(path), method))
+    .and_then(|content| content.as_object())
+    .and_then(|content| content.get("application/json").or_else(|| content.values().next()))
+    .and_then(|media_type| media_type.get("schema"))
+    .or_else(|| body_type.and_then(|body_type| spec.pointer(&format!("/components/schemas/{}", escape_pointer_token(body_type)))))
+}
+
+/// # Generates an example value that conforms to a schema
+///
+/// Default values, examples and the first enum values will be used when the schema provides them,
+/// else an empty value of the proper type will be generated.
+/// Objects contain all properties, maps contain a single entry with the key `key`.
+pub(crate) fn example_value(schema: &Value) -> Value {
+  example_value_with_depth(schema, 0)
+}
+
+fn example_value_with_depth(schema: &Value, depth: usize) -> Value {
+  let schema = resolve(schema);
+  if depth > MAX_EXAMPLE_DEPTH {
+    return Value::Null;
+  }
+  if let Some(default) = schema.get("default") {
+    return default.clone();
+  }
+  if let Some(first) = schema.get("enum").and_then(|values| values.as_array()).and_then(|values| values.first()) {
+    return first.clone();
+  }
+  if let Some(all_of) = schema.get("allOf").and_then(|all_of| all_of.as_array()) {
+    let mut merged = Map::new();
+    for sub_schema in all_of {
+      if let Value::Object(object) = example_value_with_depth(sub_schema, depth + 1) {
+        merged.extend(object);
+      }
+    }
+    return Value::Object(merged);
+  }
+  if let Some(first) = schema
+    .get("oneOf")
+    .or_else(|| schema.get("anyOf"))
+    .and_then(|alternatives| alternatives.as_array())
+    .and_then(|alternatives| alternatives.first())
+  {
+    return example_value_with_depth(first, depth + 1);
+  }
+  match schema_type(schema) {
+    Some("array") => Value::Array(
+      schema
+        .get("items")
+        .map(|items| vec![example_value_with_depth(items, depth + 1)])
+        .unwrap_or_default(),
+    ),
+    Some("boolean") => Value::Bool(false),
+    Some("integer") => Value::from(schema.get("minimum").and_then(|minimum| minimum.as_i64()).unwrap_or_default()),
+    Some("number") => Value::from(schema.get("minimum").and_then(|minimum| minimum.as_f64()).unwrap_or_default()),
+    Some("string") => schema
+      .get("example")
+      .filter(|example| example.is_string())
+      .cloned()
+      .unwrap_or(Value::String(String::new())),
+    _ => {
+      let mut object = Map::new();
+      if let Some(properties) = schema.get("properties").and_then(|properties| properties.as_object()) {
+        for (property, property_schema) in properties {
+          object.insert(property.clone(), example_value_with_depth(property_schema, depth + 1));
+        }
+      }
+      if let Some(additional_properties) = schema.get("additionalProperties").filter(|additional_properties| additional_properties.is_object()) {
+        object.insert("key".to_string(), example_value_with_depth(additional_properties, depth + 1));
+      }
+      Value::Object(object)
+    }
+  }
+}
+
+/// # Validates a value against a schema
+///
+/// Supports the schema keywords that are used in the DSH openapi specification:
+/// `$ref`, `type`, `nullable`, `enum`, `properties`, `required`, `additionalProperties`,
+/// `items`, `allOf`, `oneOf`, `anyOf`, `minimum`, `maximum` and `pattern`.
+///
+/// Returns the validation errors, which is empty when the value is valid.
+pub(crate) fn validate(schema: &Value, value: &Value) -> Vec<SchemaError> {
+  let mut errors = vec![];
+  validate_at(schema, value, "", &mut errors);
+  errors
+}
+
+fn validate_at(schema: &Value, value: &Value, pointer: &str, errors: &mut Vec<SchemaError>) {
+  let schema = resolve(schema);
+  if value.is_null() && schema.get("nullable").and_then(|nullable| nullable.as_bool()).unwrap_or_default() {
+    return;
+  }
+  if let Some(schema_type) = schema_type(schema) {
+    if !has_type(value, schema_type) {
+      push_error(errors, pointer, format!("expected {}, found {}", schema_type, value_type(value)));
+      return;
+    }
+  }
+  if let Some(values) = schema.get("enum").and_then(|values| values.as_array()) {
+    if !values.contains(value) {
+      push_error(
+        errors,
+        pointer,
+        format!(
+          "value {} is not one of {}",
+          value,
+          values.iter().map(|value| value.to_string()).collect::<Vec<_>>().join(", ")
+        ),
+      );
+    }
+  }
+  if let Some(number) = value.as_f64() {
+    if let Some(minimum) = schema.get("minimum").and_then(|minimum| minimum.as_f64()) {
+      if number < minimum {
+        push_error(errors, pointer, format!("value {} is less than minimum {}", value, minimum));
+      }
+    }
+    if let Some(maximum) = schema.get("maximum").and_then(|maximum| maximum.as_f64()) {
+      if number > maximum {
+        push_error(errors, pointer, format!("value {} is greater than maximum {}", value, maximum));
+      }
+    }
+  }
+  if let (Some(string), Some(pattern)) = (value.as_str(), schema.get("pattern").and_then(|pattern| pattern.as_str())) {
+    if Regex::new(pattern).is_ok_and(|regex| !regex.is_match(string)) {
+      push_error(errors, pointer, format!("value \"{}\" does not match pattern '{}'", string, pattern));
+    }
+  }
+  if let Some(all_of) = schema.get("allOf").and_then(|all_of| all_of.as_array()) {
+    for sub_schema in all_of {
+      validate_at(sub_schema, value, pointer, errors);
+    }
+  }
+  if let Some(alternatives) = schema.get("anyOf").and_then(|alternatives| alternatives.as_array()) {
+    if !alternatives.iter().any(|alternative| validate(alternative, value).is_empty()) {
+      push_error(errors, pointer, "value does not match any of the allowed schemas".to_string());
+    }
+  }
+  if let Some(alternatives) = schema.get("oneOf").and_then(|alternatives| alternatives.as_array()) {
+    match alternatives.iter().filter(|alternative| validate(alternative, value).is_empty()).count() {
+      0 => push_error(errors, pointer, "value does not match any of the allowed schemas".to_string()),
+      1 => {}
+      matches => push_error(errors, pointer, format!("value matches {} of the allowed schemas, instead of exactly one", matches)),
+    }
+  }
+  match value {
+    Value::Object(object) => {
+      let properties = schema.get("properties").and_then(|properties| properties.as_object());
+      if let Some(required) = schema.get("required").and_then(|required| required.as_array()) {
+        for required_property in required.iter().filter_map(|required_property| required_property.as_str()) {
+          if !object.contains_key(required_property) {
+            push_error(errors, pointer, format!("missing required property '{}'", required_property));
+          }
+        }
+      }
+      for (property, property_value) in object {
+        let property_pointer = format!("{}/{}", pointer, escape_pointer_token(property));
+        match (properties.and_then(|properties| properties.get(property)), schema.get("additionalProperties")) {
+          (Some(property_schema), _) => validate_at(property_schema, property_value, &property_pointer, errors),
+          (None, Some(Value::Bool(false))) => push_error(errors, &property_pointer, "unknown property".to_string()),
+          (None, Some(additional_properties @ Value::Object(_))) => validate_at(additional_properties, property_value, &property_pointer, errors),
+          _ => {}
+        }
+      }
+    }
+    Value::Array(array) => {
+      if let Some(items) = schema.get("items") {
+        for (index, item) in array.iter().enumerate() {
+          validate_at(items, item, &format!("{}/{}", pointer, index), errors);
+        }
+      }
+    }
+    _ => {}
+  }
+}
+
+fn push_error(errors: &mut Vec<SchemaError>, pointer: &str, message: String) {
+  errors.push(SchemaError { pointer: pointer.to_string(), message });
+}
+
+// Follows the `$ref` references to the component schemas
+pub(crate) fn resolve(schema: &Value) -> &Value {
+  let mut schema = schema;
+  for _ in 0..MAX_EXAMPLE_DEPTH {
+    match schema
+      .get("$ref")
+      .and_then(|reference| reference.as_str())
+      .and_then(|reference| reference.strip_prefix('#'))
+      .and_then(|reference| OPENAPI_SPEC.as_ref().and_then(|spec| spec.pointer(reference)))
+    {
+      Some(referenced_schema) => schema = referenced_schema,
+      None => break,
+    }
+  }
+  schema
+}
+
+fn schema_type(schema: &Value) -> Option<&str> {
+  schema.get("type").and_then(|schema_type| schema_type.as_str())
+}
+
+fn has_type(value: &Value, schema_type: &str) -> bool {
+  match schema_type {
+    "array" => value.is_array(),
+    "boolean" => value.is_boolean(),
+    "integer" => value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|number| number.fract() == 0.0),
+    "number" => value.is_number(),
+    "object" => value.is_object(),
+    "string" => value.is_string(),
+    _ => true,
+  }
+}
+
+fn value_type(value: &Value) -> &'static str {
+  match value {
+    Value::Array(_) => "array",
+    Value::Bool(_) => "boolean",
+    Value::Null => "null",
+    Value::Number(_) => "number",
+    Value::Object(_) => "object",
+    Value::String(_) => "string",
+  }
+}
+
+// Escapes a token in a json pointer, see RFC 6901
+pub(crate) fn escape_pointer_token(token: &str) -> String {
+  token.replace('~', "~0").replace('/', "~1")
+}
+
+#[test]
+fn test_example_value() {
+  let schema = request_body_schema("put", "/allocation/{tenant}/application/{appid}/configuration", Some("Application")).unwrap();
+  let example = example_value(schema);
+  assert_eq!(example.get("instances"), Some(&Value::from(1)));
+  assert_eq!(example.get("image"), Some(&Value::String(String::new())));
+  assert!(example.pointer("/exposedPorts/key").is_some_and(|port_mapping| port_mapping.is_object()));
+  assert!(validate(schema, &example).is_empty(), "{:?}", validate(schema, &example));
+}
+
+#[test]
+fn test_validate() {
+  let schema = request_body_schema("put", "/allocation/{tenant}/application/{appid}/configuration", None).unwrap();
+  let application = serde_json::json!({
+    "cpus": "0.5",
+    "env": { "LOG_LEVEL": 1 },
+    "exposedPorts": { "8080": { "auth": true } },
+    "image": "registry/image:1.0",
+    "mem": -1,
+    "user": "1000:1000"
+  });
+  let errors = validate(schema, &application).into_iter().map(|error| error.to_string()).collect::<Vec<_>>();
+  assert!(errors.contains(&"/cpus: expected number, found string".to_string()), "{:?}", errors);
+  assert!(errors.contains(&"/env/LOG_LEVEL: expected string, found number".to_string()), "{:?}", errors);
+  assert!(
+    errors.contains(&"/exposedPorts/8080/auth: expected string, found boolean".to_string()),
+    "{:?}",
+    errors
+  );
+  assert!(errors.contains(&"/mem: value -1 is less than minimum 0".to_string()), "{:?}", errors);
+  let schema = serde_json::json!({ "type": "object", "required": ["name"], "additionalProperties": false, "properties": { "name": { "type": "string", "enum": ["a", "b"] } } });
+  let errors = validate(&schema, &serde_json::json!({ "name": "c", "other": 1 }))
+    .into_iter()
+    .map(|error| error.to_string())
+    .collect::<Vec<_>>();
+  assert_eq!(
+    errors,
+    vec!["/name: value \"c\" is not one of \"a\", \"b\"".to_string(), "/other: unknown property".to_string()]
+  );
+  assert_eq!(
+    validate(&schema, &serde_json::json!({})).first().map(|error| error.to_string()),
+    Some("/: missing required property 'name'".to_string())
+  );
+  let schema = serde_json::json!({ "oneOf": [{ "type": "number" }, { "type": "integer" }] });
+  assert!(validate(&schema, &serde_json::json!(0.5)).is_empty());
+  assert_eq!(
+    validate(&schema, &serde_json::json!(1)).first().map(|error| error.to_string()),
+    Some("/: value matches 2 of the allowed schemas, instead of exactly one".to_string())
+  );
+  let schema = serde_json::json!({ "anyOf": [{ "type": "number" }, { "type": "integer" }] });
+  assert!(validate(&schema, &serde_json::json!(1)).is_empty());
+}
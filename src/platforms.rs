@@ -0,0 +1,359 @@
+use crate::dsh_directory;
+use crate::formatters::formatter::{Label, SubjectFormatter};
+use crate::log_writer::ApiCallLog;
+use dsh_api::platform::CloudProvider;
+use dsh_api::DEFAULT_PLATFORMS;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+// Environment variable is defined in the dsh_api crate
+const ENV_VAR_PLATFORMS_FILE_NAME: &str = "DSH_API_PLATFORMS_FILE";
+
+const USER_PLATFORMS_FILE: &str = "platforms.json";
+const MERGED_PLATFORMS_FILE: &str = "platforms-merged.json";
+
+/// # Platform definition
+///
+/// This struct mirrors the serialized form of `DshPlatform` from the `dsh_api` crate,
+/// whose fields are not accessible. The user defined platforms are stored in the file
+/// `platforms.json` in the `dsh` tool's home directory. At startup, these platforms are merged
+/// with the default platforms, where a user defined platform replaces the default platform
+/// with the same name.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub(crate) struct PlatformDefinition {
+  pub(crate) name: String,
+  pub(crate) description: String,
+  pub(crate) alias: String,
+  #[serde(rename = "is-production")]
+  pub(crate) is_production: bool,
+  #[serde(rename = "cloud-provider")]
+  pub(crate) cloud_provider: CloudProvider,
+  #[serde(rename = "access-token-endpoint")]
+  pub(crate) access_token_endpoint: String,
+  pub(crate) realm: String,
+  #[serde(rename = "public-domain")]
+  pub(crate) public_domain: String,
+  #[serde(rename = "private-domain", skip_serializing_if = "Option::is_none")]
+  pub(crate) private_domain: Option<String>,
+}
+
+impl PlatformDefinition {
+  pub(crate) fn console_url(&self) -> String {
+    format!("https://console.{}", self.public_domain)
+  }
+
+  pub(crate) fn rest_api_endpoint(&self) -> String {
+    format!("https://api.{}/resources/v0", self.public_domain)
+  }
+}
+
+/// # Returns the access token endpoint for a key-cloak url and realm
+pub(crate) fn key_cloak_access_token_endpoint(key_cloak_url: &str, realm: &str) -> String {
+  format!("{}/auth/realms/{}/protocol/openid-connect/token", key_cloak_url.trim_end_matches('/'), realm)
+}
+
+/// # Enables the user defined platforms
+///
+/// The platforms are taken from the first available source, in this order:
+/// 1. the file set in the environment variable `DSH_API_PLATFORMS_FILE`,
+///    in which case the user defined platforms and the default platforms are not used,
+/// 2. the user defined platforms file `platforms.json` in the `dsh` tool's home directory,
+///    merged with the default platforms, where a user defined platform replaces
+///    the default platform with the same name,
+/// 3. the default platforms from the `dsh_api` crate.
+///
+/// The merged list is written to the file `platforms-merged.json` in the `dsh` tool's
+/// home directory, which is only rewritten when it is older than one of its sources.
+/// The environment variable `DSH_API_PLATFORMS_FILE` will be set to point to this file,
+/// such that the `dsh_api` crate will pick it up.
+/// Since this changes the environment of the process, this function must be called
+/// before the async runtime is started and before the platforms are used for the first time.
+pub(crate) fn enable_user_platforms() -> Result<(), String> {
+  if env::var(ENV_VAR_PLATFORMS_FILE_NAME).is_ok() {
+    return Ok(());
+  }
+  let user_platforms_file = platforms_file(USER_PLATFORMS_FILE)?;
+  if !user_platforms_file.exists() {
+    return Ok(());
+  }
+  let merged_platforms_file = platforms_file(MERGED_PLATFORMS_FILE)?;
+  if !is_up_to_date(&merged_platforms_file, &user_platforms_file) {
+    let user_platforms = read_user_platforms()?;
+    if user_platforms.is_empty() {
+      return Ok(());
+    }
+    let merged_platforms = merge_platforms(default_platforms(), user_platforms);
+    if let Some(error) = validate_platforms(&merged_platforms).first() {
+      return Err(format!("user platforms are ignored ({})", error));
+    }
+    let merged_platforms_json = serde_json::to_string_pretty(&merged_platforms).map_err(|error| error.to_string())?;
+    fs::write(&merged_platforms_file, &merged_platforms_json)
+      .map_err(|error| format!("could not write merged platforms file '{}' ({})", merged_platforms_file.to_string_lossy(), error))?;
+  }
+  env::set_var(ENV_VAR_PLATFORMS_FILE_NAME, merged_platforms_file);
+  Ok(())
+}
+
+// The merged platforms file is up to date when it is newer than the user platforms file
+// and the dsh executable, which contains the default platforms
+fn is_up_to_date(merged_platforms_file: &Path, user_platforms_file: &Path) -> bool {
+  let modified = |file: &Path| fs::metadata(file).and_then(|metadata| metadata.modified()).ok();
+  match (
+    modified(merged_platforms_file),
+    modified(user_platforms_file),
+    env::current_exe().ok().and_then(|executable| modified(&executable)),
+  ) {
+    (Some(merged_modified), Some(user_modified), Some(executable_modified)) => merged_modified > user_modified && merged_modified > executable_modified,
+    _ => false,
+  }
+}
+
+/// # Returns whether the platforms are read from a file set by the user
+///
+/// When the environment variable `DSH_API_PLATFORMS_FILE` is set explicitly,
+/// the user platforms file will not be used.
+pub(crate) fn platforms_file_overridden() -> Result<bool, String> {
+  match env::var(ENV_VAR_PLATFORMS_FILE_NAME) {
+    Ok(platforms_file_from_env_var) => Ok(platforms_file(MERGED_PLATFORMS_FILE)?.as_os_str() != platforms_file_from_env_var.as_str()),
+    Err(_) => Ok(false),
+  }
+}
+
+/// # Returns the default platforms from the `dsh_api` crate
+pub(crate) fn default_platforms() -> Vec<PlatformDefinition> {
+  serde_json::from_str::<Vec<PlatformDefinition>>(DEFAULT_PLATFORMS).unwrap_or_default()
+}
+
+/// # Reads the user defined platforms
+///
+/// When the user platforms file does not exist, an empty list will be returned.
+pub(crate) fn read_user_platforms() -> Result<Vec<PlatformDefinition>, String> {
+  let user_platforms_file = platforms_file(USER_PLATFORMS_FILE)?;
+  match fs::read_to_string(&user_platforms_file) {
+    Ok(user_platforms) => serde_json::from_str::<Vec<PlatformDefinition>>(&user_platforms)
+      .map_err(|error| format!("invalid user platforms file '{}' ({})", user_platforms_file.to_string_lossy(), error)),
+    Err(error) if error.kind() == ErrorKind::NotFound => Ok(vec![]),
+    Err(error) => Err(format!(
+      "could not read user platforms file '{}' ({})",
+      user_platforms_file.to_string_lossy(),
+      error
+    )),
+  }
+}
+
+/// # Writes the user defined platforms
+///
+/// When the list of platforms is empty, the user platforms file will be removed.
+pub(crate) fn write_user_platforms(mut user_platforms: Vec<PlatformDefinition>) -> Result<PathBuf, String> {
+  let user_platforms_file = platforms_file(USER_PLATFORMS_FILE)?;
+  if user_platforms.is_empty() {
+    let _ = fs::remove_file(platforms_file(MERGED_PLATFORMS_FILE)?);
+    match fs::remove_file(&user_platforms_file) {
+      Ok(_) => (),
+      Err(error) if error.kind() == ErrorKind::NotFound => (),
+      Err(error) => {
+        return Err(format!(
+          "could not remove user platforms file '{}' ({})",
+          user_platforms_file.to_string_lossy(),
+          error
+        ))
+      }
+    }
+  } else {
+    user_platforms.sort_by(|platform_a, platform_b| platform_a.name.cmp(&platform_b.name));
+    let user_platforms_json = serde_json::to_string_pretty(&user_platforms).map_err(|error| error.to_string())?;
+    fs::write(&user_platforms_file, user_platforms_json).map_err(|error| format!("could not write user platforms file '{}' ({})", user_platforms_file.to_string_lossy(), error))?;
+  }
+  Ok(user_platforms_file)
+}
+
+/// # Merges the user defined platforms with the default platforms
+///
+/// A user defined platform replaces the default platform with the same name.
+/// The returned list is sorted by platform name.
+pub(crate) fn merge_platforms(default_platforms: Vec<PlatformDefinition>, user_platforms: Vec<PlatformDefinition>) -> Vec<PlatformDefinition> {
+  let mut merged_platforms = default_platforms
+    .into_iter()
+    .map(|platform| (platform.name.clone(), platform))
+    .collect::<BTreeMap<_, _>>();
+  for user_platform in user_platforms {
+    merged_platforms.insert(user_platform.name.clone(), user_platform);
+  }
+  merged_platforms.into_values().collect::<Vec<_>>()
+}
+
+/// # Validates a list of platforms
+///
+/// Checks the values of the individual platforms and checks that all names and aliases are unique,
+/// where the alias of a platform must also differ from its name.
+/// Returns a list of error messages, which will be empty when the platforms are valid.
+pub(crate) fn validate_platforms(platforms: &[PlatformDefinition]) -> Vec<String> {
+  let mut errors = vec![];
+  let mut names_and_aliases: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+  for platform in platforms {
+    for error in validate_platform(platform) {
+      errors.push(format!("platform '{}': {}", platform.name, error));
+    }
+    names_and_aliases.entry(platform.name.as_str()).or_default().push(platform.name.as_str());
+    names_and_aliases.entry(platform.alias.as_str()).or_default().push(platform.name.as_str());
+  }
+  for (name_or_alias, platform_names) in names_and_aliases {
+    if platform_names.len() > 1 {
+      errors.push(format!("name or alias '{}' is used by platforms {}", name_or_alias, platform_names.join(", ")));
+    }
+  }
+  errors
+}
+
+fn validate_platform(platform: &PlatformDefinition) -> Vec<String> {
+  let mut errors = vec![];
+  for (field, value) in [("name", &platform.name), ("alias", &platform.alias), ("realm", &platform.realm), ("public-domain", &platform.public_domain)] {
+    if value.is_empty() {
+      errors.push(format!("{} is empty", field));
+    } else if !is_valid_identifier_or_domain(value) {
+      errors.push(format!("{} '{}' contains invalid characters", field, value));
+    }
+  }
+  if let Some(ref private_domain) = platform.private_domain {
+    if !is_valid_identifier_or_domain(private_domain) {
+      errors.push(format!("private-domain '{}' contains invalid characters", private_domain));
+    }
+  }
+  if !platform.access_token_endpoint.starts_with("https://") {
+    errors.push(format!("access-token-endpoint '{}' is not an https url", platform.access_token_endpoint));
+  }
+  errors
+}
+
+fn is_valid_identifier_or_domain(value: &str) -> bool {
+  value.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.' || c == '_')
+}
+
+fn platforms_file(file_name: &str) -> Result<PathBuf, String> {
+  match dsh_directory()? {
+    Some(dsh_directory) => {
+      fs::create_dir_all(&dsh_directory).map_err(|error| format!("could not create directory '{}' ({})", dsh_directory.to_string_lossy(), error))?;
+      Ok(dsh_directory.join(file_name))
+    }
+    None => Err("user platforms are not available since the dsh tool's home directory is not set".to_string()),
+  }
+}
+
+/// # Result of a reachability check of a platform endpoint
+#[derive(Serialize)]
+pub(crate) struct EndpointCheck {
+  pub(crate) platform: String,
+  pub(crate) endpoint: &'static str,
+  pub(crate) url: String,
+  pub(crate) status: String,
+  pub(crate) reachable: bool,
+}
+
+#[derive(Eq, Hash, PartialEq, Serialize)]
+pub(crate) enum EndpointCheckLabel {
+  Endpoint,
+  Platform,
+  Reachable,
+  Status,
+  Url,
+}
+
+impl Label for EndpointCheckLabel {
+  fn as_str(&self) -> &str {
+    match self {
+      Self::Endpoint => "endpoint",
+      Self::Platform => "platform",
+      Self::Reachable => "reachable",
+      Self::Status => "status",
+      Self::Url => "url",
+    }
+  }
+
+  fn is_target_label(&self) -> bool {
+    matches!(self, Self::Platform)
+  }
+}
+
+impl SubjectFormatter<EndpointCheckLabel> for EndpointCheck {
+  fn value(&self, label: &EndpointCheckLabel, _target_id: &str) -> String {
+    match label {
+      EndpointCheckLabel::Endpoint => self.endpoint.to_string(),
+      EndpointCheckLabel::Platform => self.platform.clone(),
+      EndpointCheckLabel::Reachable => self.reachable.to_string(),
+      EndpointCheckLabel::Status => self.status.clone(),
+      EndpointCheckLabel::Url => self.url.clone(),
+    }
+  }
+
+  fn target_id(&self) -> Option<String> {
+    Some(self.platform.clone())
+  }
+}
+
+pub(crate) static ENDPOINT_CHECK_LABELS: [EndpointCheckLabel; 5] =
+  [EndpointCheckLabel::Platform, EndpointCheckLabel::Endpoint, EndpointCheckLabel::Url, EndpointCheckLabel::Reachable, EndpointCheckLabel::Status];
+
+/// # Checks the reachability of the endpoints of a platform
+///
+/// Any http response counts as reachable, since the endpoints will typically
+/// not accept an unauthenticated request.
+pub(crate) async fn check_endpoints(platform: &PlatformDefinition) -> Vec<EndpointCheck> {
+  let client = reqwest::Client::builder().timeout(std::time::Duration::from_secs(10)).build();
+  let mut checks = vec![];
+  for (endpoint, url) in [("access token", platform.access_token_endpoint.clone()), ("rest api", platform.rest_api_endpoint()), ("console", platform.console_url())] {
+    let (reachable, status) = match client {
+      Ok(ref client) => {
+        let api_call_log = ApiCallLog::start("GET", &url, None);
+        match client.get(&url).send().await {
+          Ok(response) => {
+            api_call_log.finish(Some(response.status().as_u16()), None);
+            (true, format!("http status {}", response.status().as_u16()))
+          }
+          Err(error) => {
+            api_call_log.finish(None, None);
+            (false, error.to_string())
+          }
+        }
+      }
+      Err(ref error) => (false, error.to_string()),
+    };
+    checks.push(EndpointCheck { platform: platform.name.clone(), endpoint, url, status, reachable });
+  }
+  checks
+}
+
+#[test]
+fn test_merge_and_validate_platforms() {
+  let default_platforms = default_platforms();
+  assert!(!default_platforms.is_empty());
+  assert!(validate_platforms(&default_platforms).is_empty());
+  let mut private_platform = default_platforms[0].clone();
+  private_platform.name = "private".to_string();
+  private_platform.alias = "prv".to_string();
+  private_platform.public_domain = "dsh.private.example.com".to_string();
+  let mut replaced_platform = default_platforms[0].clone();
+  replaced_platform.description = "replaced".to_string();
+  let merged_platforms = merge_platforms(default_platforms.clone(), vec![private_platform.clone(), replaced_platform]);
+  assert_eq!(merged_platforms.len(), default_platforms.len() + 1);
+  assert!(merged_platforms.iter().any(|platform| platform.description == "replaced"));
+  assert!(merged_platforms.windows(2).all(|pair| pair[0].name < pair[1].name));
+  assert!(validate_platforms(&merged_platforms).is_empty());
+  private_platform.name = "other".to_string();
+  private_platform.access_token_endpoint = "http://auth.example.com".to_string();
+  let errors = validate_platforms(&merge_platforms(merged_platforms, vec![private_platform]));
+  assert_eq!(
+    errors,
+    vec![
+      "platform 'other': access-token-endpoint 'http://auth.example.com' is not an https url".to_string(),
+      "name or alias 'prv' is used by platforms other, private".to_string()
+    ]
+  );
+  assert_eq!(
+    key_cloak_access_token_endpoint("https://auth.example.com/", "my-realm"),
+    "https://auth.example.com/auth/realms/my-realm/protocol/openid-connect/token"
+  );
+}
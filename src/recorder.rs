@@ -0,0 +1,359 @@
+use crate::fan_out::{ALL_TARGETS_ARGUMENT, TARGETS_ARGUMENT};
+use crate::interceptor::{response, run_intercepted, runs_intercepted, status_response, Forwarder, InterceptedRequest, Interceptor};
+use crate::log_writer::{utc_timestamp, ApiCallLog};
+use crate::VERSION;
+use async_trait::async_trait;
+use clap::builder::ValueParser;
+use clap::{Arg, ArgAction, ArgMatches};
+use dsh_api::platform::DshPlatform;
+use hyper::header::CONTENT_TYPE;
+use hyper::{Body, HeaderMap, Response, StatusCode};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+pub(crate) const RECORD_ARGUMENT: &str = "record-argument";
+pub(crate) const REPLAY_ARGUMENT: &str = "replay-argument";
+
+/// Password used for the client in replay mode, the token endpoint is replayed as well
+pub(crate) const REPLAY_PASSWORD: &str = "replay";
+
+const RECORDING_FILE: &str = "recording.json";
+const INTERACTION_FILE_PREFIX: &str = "interaction-";
+
+const REDACTED: &str = "***";
+const REDACTED_FIELDS: [&str; 3] = ["access_token", "id_token", "refresh_token"];
+
+pub(crate) fn record_argument() -> Arg {
+  Arg::new(RECORD_ARGUMENT)
+    .long("record")
+    .action(ArgAction::Set)
+    .value_parser(ValueParser::path_buf())
+    .value_name("DIRECTORY")
+    .conflicts_with_all([REPLAY_ARGUMENT, TARGETS_ARGUMENT, ALL_TARGETS_ARGUMENT])
+    .help("Record api interactions")
+    .long_help(
+      "If this option is provided, all http requests to the dsh api and their responses \
+      will be saved in this directory, one json file per interaction. \
+      The directory must be empty or not exist yet. \
+      The password and the access tokens are not recorded, \
+      but the recorded responses can contain the values of secrets. \
+      The recording can be used with the --replay option.",
+    )
+    .hide_short_help(true)
+    .global(true)
+}
+
+pub(crate) fn replay_argument() -> Arg {
+  Arg::new(REPLAY_ARGUMENT)
+    .long("replay")
+    .action(ArgAction::Set)
+    .value_parser(ValueParser::path_buf())
+    .value_name("DIRECTORY")
+    .conflicts_with_all([TARGETS_ARGUMENT, ALL_TARGETS_ARGUMENT])
+    .help("Replay recorded api interactions")
+    .long_help(
+      "If this option is provided, the responses that were recorded with the --record option \
+      in this directory will be served, instead of contacting the platform. \
+      The target platform and tenant of the recording will be used and no password is required. \
+      Requests are matched on their method and path. When a request is made more often \
+      than it was recorded, the last recorded response will be served again.",
+    )
+    .hide_short_help(true)
+    .global(true)
+}
+
+/// # Recording metadata
+///
+/// Stored in the file `recording.json` in the recording directory.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct Recording {
+  platform: String,
+  tenant: String,
+  dsh_version: String,
+  recorded_at: String,
+}
+
+/// # Recorded http request and response
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct Interaction {
+  method: String,
+  url: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  request_body: Option<Value>,
+  status: u16,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  content_type: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  response_body: Option<Value>,
+}
+
+enum RecorderMode {
+  Record { directory: PathBuf, forwarder: Forwarder, number_of_interactions: Mutex<usize> },
+  Replay { interactions: Mutex<Vec<(Interaction, bool)>> },
+}
+
+struct Recorder {
+  mode: RecorderMode,
+}
+
+/// # Returns whether the command must be run by the recorder
+///
+/// This is the case when the `--record` or `--replay` option is provided,
+/// unless this process already is the intercepted child process.
+pub(crate) fn runs_with_recorder(matches: &ArgMatches) -> bool {
+  (matches.contains_id(RECORD_ARGUMENT) || matches.contains_id(REPLAY_ARGUMENT)) && !runs_intercepted()
+}
+
+/// # Runs the command while recording or replaying the api interactions
+///
+/// The http requests of the command are intercepted by a local https proxy,
+/// which is only supported on Linux (see the [`interceptor`](crate::interceptor) module).
+///
+/// ## Returns
+/// The exit code of the child process.
+pub(crate) async fn run_with_recorder(matches: &ArgMatches) -> Result<ExitCode, String> {
+  if !cfg!(target_os = "linux") {
+    return Err("recording and replaying api interactions is only supported on linux".to_string());
+  }
+  let mode = match (matches.get_one::<PathBuf>(RECORD_ARGUMENT), matches.get_one::<PathBuf>(REPLAY_ARGUMENT)) {
+    (Some(record_directory), _) => record_mode(record_directory)?,
+    (None, Some(replay_directory)) => replay_mode(replay_directory)?,
+    (None, None) => return Err("no record or replay directory".to_string()),
+  };
+  run_intercepted(Recorder { mode }).await
+}
+
+/// # Saves the target of the recording
+///
+/// Called by the recorded child process, when the target platform and tenant are known.
+pub(crate) fn save_recorded_target(directory: &Path, platform: &DshPlatform, tenant_name: &str) -> Result<(), String> {
+  let recording =
+    Recording { platform: platform.name().to_string(), tenant: tenant_name.to_string(), dsh_version: VERSION.to_string(), recorded_at: utc_timestamp(SystemTime::now()) };
+  write_json_file(&directory.join(RECORDING_FILE), &recording)
+}
+
+/// # Returns the target platform and tenant name of a recording
+pub(crate) fn recorded_target(directory: &Path) -> Result<(DshPlatform, String), String> {
+  let recording = read_json_file::<Recording>(&directory.join(RECORDING_FILE))?;
+  Ok((DshPlatform::try_from(recording.platform.as_str())?, recording.tenant))
+}
+
+fn record_mode(directory: &Path) -> Result<RecorderMode, String> {
+  if fs::read_dir(directory).is_ok_and(|mut entries| entries.next().is_some()) {
+    return Err(format!("record directory '{}' is not empty", directory.to_string_lossy()));
+  }
+  fs::create_dir_all(directory).map_err(|error| format!("could not create record directory '{}' ({})", directory.to_string_lossy(), error))?;
+  info!("recording api interactions in '{}'", directory.to_string_lossy());
+  Ok(RecorderMode::Record { directory: directory.to_path_buf(), forwarder: Forwarder::new()?, number_of_interactions: Mutex::new(0) })
+}
+
+fn replay_mode(directory: &Path) -> Result<RecorderMode, String> {
+  // Fail early when the recording is not valid
+  recorded_target(directory)?;
+  let mut interaction_files = fs::read_dir(directory)
+    .map_err(|error| format!("could not read replay directory '{}' ({})", directory.to_string_lossy(), error))?
+    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+    .filter(|path| {
+      path
+        .file_name()
+        .is_some_and(|file_name| file_name.to_string_lossy().starts_with(INTERACTION_FILE_PREFIX))
+    })
+    .collect::<Vec<_>>();
+  interaction_files.sort();
+  let interactions = interaction_files
+    .iter()
+    .map(|interaction_file| read_json_file::<Interaction>(interaction_file).map(|interaction| (interaction, false)))
+    .collect::<Result<Vec<_>, _>>()?;
+  info!("replaying {} api interactions from '{}'", interactions.len(), directory.to_string_lossy());
+  Ok(RecorderMode::Replay { interactions: Mutex::new(interactions) })
+}
+
+#[async_trait]
+impl Interceptor for Recorder {
+  async fn intercept(&self, request: InterceptedRequest) -> Response<Body> {
+    let interaction = match &self.mode {
+      RecorderMode::Record { directory, forwarder, number_of_interactions } => {
+        let forwarded_response = match forwarder.forward(&request).await {
+          Ok(forwarded_response) => forwarded_response,
+          Err(error) => {
+            warn!("{}", error);
+            return status_response(StatusCode::BAD_GATEWAY, error);
+          }
+        };
+        let request_content_type = content_type(&request.headers);
+        let content_type = content_type(&forwarded_response.headers);
+        let interaction = Interaction {
+          method: request.method.to_string(),
+          url: request.url.clone(),
+          request_body: if is_form(request_content_type.as_deref()) { None } else { body_to_value(&request.body, request_content_type.as_deref()) },
+          status: forwarded_response.status,
+          response_body: body_to_value(&forwarded_response.body, content_type.as_deref()).map(redact_tokens),
+          content_type,
+        };
+        let interaction_number = {
+          let mut number_of_interactions = number_of_interactions.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+          *number_of_interactions += 1;
+          *number_of_interactions
+        };
+        let interaction_file = directory.join(format!("{}{:04}.json", INTERACTION_FILE_PREFIX, interaction_number));
+        if let Err(error) = write_json_file(&interaction_file, &interaction) {
+          warn!("{}", error);
+        }
+        // The recorded interaction has redacted tokens, so the original response is returned
+        return response(forwarded_response.status, interaction.content_type.as_deref(), forwarded_response.body.to_vec());
+      }
+      RecorderMode::Replay { interactions } => {
+        let api_call_log = ApiCallLog::start(request.method.as_str(), &request.url, Some(&String::from_utf8_lossy(&request.body)));
+        let interaction = replay(
+          &mut interactions.lock().unwrap_or_else(|poisoned| poisoned.into_inner()),
+          request.method.as_str(),
+          &request.url,
+        );
+        api_call_log.finish(
+          interaction.as_ref().map(|interaction| interaction.status),
+          interaction
+            .as_ref()
+            .and_then(|interaction| interaction.response_body.as_ref())
+            .map(|body| body.to_string())
+            .as_deref(),
+        );
+        interaction
+      }
+    };
+    info!(
+      "{} {} {}",
+      request.method,
+      request.url,
+      interaction.as_ref().map(|interaction| interaction.status).unwrap_or_default()
+    );
+    match interaction {
+      Some(interaction) => {
+        let body = match (&interaction.response_body, interaction.content_type.as_deref()) {
+          (Some(Value::String(text)), Some(content_type)) if !content_type.contains("json") => text.as_bytes().to_vec(),
+          (Some(value), _) => value.to_string().into_bytes(),
+          (None, _) => vec![],
+        };
+        response(interaction.status, interaction.content_type.as_deref(), body)
+      }
+      None => {
+        warn!("no recorded response for {} {}", request.method, request.url);
+        status_response(StatusCode::BAD_GATEWAY, format!("no recorded response for {} {}", request.method, request.url))
+      }
+    }
+  }
+}
+
+/// # Finds the recorded interaction for a request
+///
+/// Requests are matched on method and path (including the query), ignoring the host,
+/// in the order in which they were recorded. When all matching interactions have been used,
+/// the last matching interaction will be used again.
+fn replay(interactions: &mut [(Interaction, bool)], method: &str, url: &str) -> Option<Interaction> {
+  let path = path_and_query(url);
+  let mut matching_interactions = interactions
+    .iter_mut()
+    .filter(|(interaction, _)| interaction.method == method && path_and_query(&interaction.url) == path)
+    .collect::<Vec<_>>();
+  match matching_interactions.iter_mut().find(|(_, used)| !*used) {
+    Some((interaction, used)) => {
+      *used = true;
+      Some(interaction.clone())
+    }
+    None => matching_interactions.last().map(|(interaction, _)| interaction.clone()),
+  }
+}
+
+fn path_and_query(url: &str) -> &str {
+  match url.split_once("://") {
+    Some((_, without_scheme)) => without_scheme.find('/').map(|index| &without_scheme[index..]).unwrap_or("/"),
+    None => url,
+  }
+}
+
+// Json bodies are stored as json values, other bodies as strings
+fn body_to_value(body: &[u8], content_type: Option<&str>) -> Option<Value> {
+  if body.is_empty() {
+    None
+  } else if content_type.is_some_and(|content_type| content_type.contains("json")) {
+    serde_json::from_slice::<Value>(body)
+      .ok()
+      .or_else(|| Some(Value::String(String::from_utf8_lossy(body).to_string())))
+  } else {
+    Some(Value::String(String::from_utf8_lossy(body).to_string()))
+  }
+}
+
+fn redact_tokens(mut value: Value) -> Value {
+  if let Some(object) = value.as_object_mut() {
+    for field in REDACTED_FIELDS {
+      if let Some(token) = object.get_mut(field) {
+        *token = Value::from(REDACTED);
+      }
+    }
+  }
+  value
+}
+
+// Form bodies are only used to request a token and contain the password
+fn is_form(content_type: Option<&str>) -> bool {
+  content_type.is_some_and(|content_type| content_type.starts_with("application/x-www-form-urlencoded"))
+}
+
+fn content_type(headers: &HeaderMap) -> Option<String> {
+  headers
+    .get(CONTENT_TYPE)
+    .and_then(|content_type| content_type.to_str().ok())
+    .map(|content_type| content_type.to_string())
+}
+
+fn read_json_file<T>(file: &Path) -> Result<T, String>
+where
+  T: for<'de> Deserialize<'de>,
+{
+  let json = fs::read_to_string(file).map_err(|error| format!("could not read file '{}' ({})", file.to_string_lossy(), error))?;
+  serde_json::from_str::<T>(&json).map_err(|error| format!("could not deserialize file '{}' ({})", file.to_string_lossy(), error))
+}
+
+fn write_json_file<T>(file: &Path, value: &T) -> Result<(), String>
+where
+  T: Serialize,
+{
+  let json = serde_json::to_string_pretty(value).map_err(|error| format!("could not serialize data ({})", error))?;
+  fs::write(file, json).map_err(|error| format!("could not write file '{}' ({})", file.to_string_lossy(), error))
+}
+
+#[test]
+fn test_replay() {
+  let interaction = |url: &str, status: u16| Interaction { method: "GET".to_string(), url: url.to_string(), request_body: None, status, content_type: None, response_body: None };
+  let mut interactions = vec![
+    (interaction("https://api.dsh.localhost/resources/v0/allocation/t/volume/v", 200), false),
+    (interaction("https://api.dsh.localhost/resources/v0/allocation/t/volume/v", 404), false),
+    (interaction("https://api.dsh.localhost/resources/v0/allocation/t/secret", 200), false),
+  ];
+  let mut replay_status = |method: &str, url: &str| replay(&mut interactions, method, url).map(|interaction| interaction.status);
+  assert_eq!(replay_status("GET", "https://other.host/resources/v0/allocation/t/volume/v"), Some(200));
+  assert_eq!(replay_status("GET", "https://api.dsh.localhost/resources/v0/allocation/t/volume/v"), Some(404));
+  assert_eq!(replay_status("GET", "https://api.dsh.localhost/resources/v0/allocation/t/volume/v"), Some(404));
+  assert_eq!(replay_status("DELETE", "https://api.dsh.localhost/resources/v0/allocation/t/volume/v"), None);
+  assert_eq!(replay_status("GET", "https://api.dsh.localhost/resources/v0/allocation/t/volume"), None);
+  assert_eq!(path_and_query("http://127.0.0.1:8443/auth/token?x=y"), "/auth/token?x=y");
+}
+
+#[test]
+fn test_body_to_value() {
+  assert_eq!(body_to_value(b"", Some("application/json")), None);
+  assert_eq!(body_to_value(br#"{"a":1}"#, Some("application/json")), Some(serde_json::json!({ "a": 1 })));
+  assert_eq!(body_to_value(br#"{"a":1}"#, Some("text/plain")), Some(Value::from(r#"{"a":1}"#)));
+  assert_eq!(
+    redact_tokens(serde_json::json!({ "access_token": "eyJ", "expires_in": 300 })),
+    serde_json::json!({ "access_token": REDACTED, "expires_in": 300 })
+  );
+}
@@ -0,0 +1,47 @@
+#[cfg(feature = "manage")]
+use crate::arguments::MANAGED_TENANT_NAME_ARGUMENT;
+use crate::arguments::{
+  APP_ID_ARGUMENT, BUCKET_ID_ARGUMENT, CERTIFICATE_ID_ARGUMENT, MANIFEST_ID_ARGUMENT, PROXY_ID_ARGUMENT, SECRET_ID_ARGUMENT, SERVICE_ID_ARGUMENT, TOPIC_ID_ARGUMENT,
+  VHOST_ID_ARGUMENT, VOLUME_ID_ARGUMENT,
+};
+use dsh_api::dsh_api_client::DshApiClient;
+use dsh_api::DshApiError;
+
+/// # Returns the identifiers of the resources that can be used as value for an argument
+///
+/// This function is used for the completion of resource identifiers,
+/// e.g. `dsh service show <TAB>` will complete the service ids.
+///
+/// ## Parameters
+/// * `argument_id` - identifier of the clap argument, e.g. `SERVICE_ID_ARGUMENT`
+/// * `client` - client for the target tenant
+///
+/// ## Returns
+/// * `None` - when the argument does not refer to a resource
+/// * `Some(Ok(ids))` - sorted identifiers of the resources
+/// * `Some(Err(error))` - when the identifiers could not be fetched
+pub(crate) async fn fetch_resource_ids(argument_id: &str, client: &DshApiClient) -> Option<Result<Vec<String>, DshApiError>> {
+  let resource_ids = match argument_id {
+    APP_ID_ARGUMENT => client.list_app_ids().await,
+    BUCKET_ID_ARGUMENT => client.list_bucket_ids().await,
+    CERTIFICATE_ID_ARGUMENT => client.get_certificate_ids().await,
+    MANIFEST_ID_ARGUMENT => client.list_app_catalog_manifest_ids().await,
+    PROXY_ID_ARGUMENT => client.get_kafkaproxy_ids().await,
+    SECRET_ID_ARGUMENT => client.get_secret_ids().await,
+    SERVICE_ID_ARGUMENT => client.list_application_ids().await,
+    TOPIC_ID_ARGUMENT => client.get_topic_ids().await,
+    VHOST_ID_ARGUMENT => client
+      .list_vhosts_with_usage()
+      .await
+      .map(|vhosts_with_usage| vhosts_with_usage.into_iter().map(|(vhost_id, _)| vhost_id).collect()),
+    VOLUME_ID_ARGUMENT => client.get_volume_ids().await,
+    #[cfg(feature = "manage")]
+    MANAGED_TENANT_NAME_ARGUMENT => client.get_tenant_ids().await,
+    _ => return None,
+  };
+  Some(resource_ids.map(|mut resource_ids| {
+    resource_ids.sort();
+    resource_ids.dedup();
+    resource_ids
+  }))
+}
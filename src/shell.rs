@@ -0,0 +1,509 @@
+use crate::arguments::{platform_name_argument, PLATFORM_NAME_ARGUMENT};
+use crate::completion::{cached_resource_ids, target_tenants};
+use crate::context::Context;
+use crate::error::DshCliError;
+use crate::global_arguments::{TARGET_PASSWORD_FILE_ARGUMENT, TARGET_PLATFORM_ARGUMENT, TARGET_TENANT_ARGUMENT};
+use crate::settings::Settings;
+use crate::subject::SubjectRegistry;
+use crate::{
+  create_client_for_target, dsh_directory, get_target_platform, get_target_platform_non_interactive, get_target_tenant, get_target_tenant_non_interactive, DshCliResult,
+};
+use clap::parser::ValueSource;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use dsh_api::dsh_api_client::DshApiClient;
+use dsh_api::platform::DshPlatform;
+use log::debug;
+use rustyline::completion::Completer;
+use rustyline::config::{Behavior, Config};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Editor, Helper};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub(crate) const SHELL_COMMAND: &str = "shell";
+
+const EXIT_COMMAND: &str = "exit";
+const EXIT_COMMAND_ALIAS: &str = "quit";
+const HELP_COMMAND: &str = "help";
+const USE_COMMAND: &str = "use";
+const USE_PLATFORM_COMMAND: &str = "platform";
+const USE_TENANT_COMMAND: &str = "tenant";
+const USE_TENANT_ARGUMENT: &str = "use-tenant-argument";
+
+const SHELL_HISTORY_FILENAME: &str = "shell_history";
+
+/// Maximum number of lines that will be kept in the history
+const MAX_HISTORY: usize = 1000;
+
+/// Global arguments that will not be passed on from the shell command to the commands in the shell,
+/// since the target is managed by the shell
+const TARGET_ARGUMENTS: [&str; 3] = [TARGET_PASSWORD_FILE_ARGUMENT, TARGET_PLATFORM_ARGUMENT, TARGET_TENANT_ARGUMENT];
+
+/// # Returns the clap command for the `dsh shell` command
+pub(crate) fn shell_command() -> Command {
+  Command::new(SHELL_COMMAND).about("Start interactive shell").long_about(
+    "Start an interactive shell, that accepts the same subject and capability commands \
+      as the dsh tool, without the 'dsh' prefix. The target platform, tenant and password \
+      will only be determined once, and the connection to the target will be kept alive \
+      for all commands. Commands and options, including resource identifiers on the target, \
+      can be completed with the tab key. \
+      Use 'use platform <PLATFORM>' and 'use tenant <TENANT>' to switch targets, \
+      'help' to show the available commands and 'exit' (or ctrl-d) to leave the shell. \
+      Global options that are provided to the shell command will be applied to all commands, \
+      unless they are overridden for a command. \
+      The command history is stored in the file 'shell_history' in the dsh tool's home directory.",
+  )
+}
+
+/// # Returns the commands that are only available in the shell
+fn shell_builtin_commands() -> Vec<Command> {
+  vec![
+    Command::new(EXIT_COMMAND).alias(EXIT_COMMAND_ALIAS).about("Exit the shell"),
+    Command::new(HELP_COMMAND).about("Show the available commands"),
+    Command::new(USE_COMMAND)
+      .about("Switch target platform or tenant")
+      .subcommand_required(true)
+      .arg_required_else_help(true)
+      .subcommand(
+        Command::new(USE_PLATFORM_COMMAND)
+          .about("Switch target platform")
+          .arg(platform_name_argument().required(true)),
+      )
+      .subcommand(
+        Command::new(USE_TENANT_COMMAND).about("Switch target tenant").arg(
+          Arg::new(USE_TENANT_ARGUMENT)
+            .action(ArgAction::Set)
+            .value_name("TENANT")
+            .help("Tenant name")
+            .required(true),
+        ),
+      ),
+  ]
+}
+
+/// # Interactive shell
+///
+/// The shell keeps the settings and the clients for the targets that were used alive,
+/// such that the target, password and token need to be determined only once.
+pub(crate) struct Shell<'a> {
+  command: Command,
+  subject_registry: &'a SubjectRegistry<'a>,
+  settings: Settings,
+  shell_matches: &'a ArgMatches,
+  global_arguments: Vec<String>,
+  platform: Option<DshPlatform>,
+  tenant: Option<String>,
+  clients: HashMap<String, Arc<DshApiClient>>,
+}
+
+impl<'a> Shell<'a> {
+  /// # Creates a shell
+  ///
+  /// ## Parameters
+  /// * `command` - clap command for the `dsh` tool, without the `shell` command
+  /// * `subject_registry` - registry used to execute the subject commands
+  /// * `settings` - settings that will be used for all commands
+  /// * `shell_matches` - matches of the command line that started the shell
+  pub(crate) fn new(command: Command, subject_registry: &'a SubjectRegistry<'a>, settings: Settings, shell_matches: &'a ArgMatches) -> Result<Self, String> {
+    let global_arguments = global_arguments(&command, shell_matches);
+    let command = command
+      .subcommands(shell_builtin_commands())
+      .no_binary_name(true)
+      .args_override_self(true)
+      .arg_required_else_help(false);
+    let platform = get_target_platform_non_interactive(shell_matches, &settings)?;
+    let tenant = get_target_tenant_non_interactive(shell_matches, &settings);
+    Ok(Self { command, subject_registry, settings, shell_matches, global_arguments, platform, tenant, clients: HashMap::new() })
+  }
+
+  /// # Runs the shell until the user exits or the end of the input is reached
+  pub(crate) async fn run(&mut self, context: &Context) -> DshCliResult {
+    context.print_explanation("interactive dsh shell, enter 'help' to show the available commands or 'exit' to leave the shell");
+    if let (Some(platform), Some(tenant)) = (self.platform.clone(), self.tenant.clone()) {
+      if let Err(error) = self.client(&platform, &tenant).await {
+        context.print_error(error);
+      }
+    }
+    let history_file = dsh_directory().ok().flatten().map(|dsh_directory| dsh_directory.join(SHELL_HISTORY_FILENAME));
+    let mut editor = create_editor(self.command.clone())?;
+    if let Some(ref history_file) = history_file {
+      let _ = editor.load_history(history_file);
+    }
+    loop {
+      if let Some(helper) = editor.helper_mut() {
+        helper.platform = self.platform.clone();
+        helper.tenant = self.tenant.clone();
+        helper.client = self.current_client();
+      }
+      let line = match editor.readline(&self.prompt()) {
+        Ok(line) => line,
+        Err(ReadlineError::Interrupted) => continue,
+        Err(ReadlineError::Eof) => break,
+        Err(error) => return Err(DshCliError::from(format!("could not read line ({})", error))),
+      };
+      if line.trim().is_empty() {
+        continue;
+      }
+      let _ = editor.add_history_entry(line.trim());
+      if let Some(ref history_file) = history_file {
+        let _ = editor.append_history(history_file);
+      }
+      let arguments = match shlex::split(&line) {
+        Some(arguments) => arguments,
+        None => {
+          context.print_error("invalid command, check the quotes");
+          continue;
+        }
+      };
+      let line_matches = match self.command.clone().try_get_matches_from(self.global_arguments.iter().cloned().chain(arguments)) {
+        Ok(line_matches) => line_matches,
+        Err(clap_error) => {
+          let _ = clap_error.print();
+          continue;
+        }
+      };
+      let line_context = match Context::create(&line_matches, self.settings.clone()) {
+        Ok(line_context) => line_context,
+        Err(error) => {
+          context.print_error(error);
+          continue;
+        }
+      };
+      match self.execute(&line_matches, &line_context).await {
+        Ok(true) => {}
+        Ok(false) => break,
+        Err(error) => line_context.print_cli_error(&error),
+      }
+    }
+    Ok(())
+  }
+
+  /// # Executes a command
+  ///
+  /// Returns `Ok(false)` when the shell must be exited.
+  async fn execute(&mut self, line_matches: &ArgMatches, context: &Context) -> Result<bool, DshCliError> {
+    match line_matches.subcommand() {
+      Some((EXIT_COMMAND, _)) => return Ok(false),
+      Some((HELP_COMMAND, _)) => {
+        let _ = self.command.clone().print_help();
+      }
+      Some((USE_COMMAND, use_matches)) => self.use_target(use_matches, context).await?,
+      Some((subject_command_name, sub_matches)) => {
+        let subject_registry = self.subject_registry;
+        let requirements = subject_registry.requirements(subject_command_name, sub_matches)?;
+        debug!("{:?}", requirements);
+        if requirements.needs_dsh_api_client() {
+          let platform = match line_matches.get_one::<String>(TARGET_PLATFORM_ARGUMENT) {
+            Some(_) => get_target_platform(line_matches, &self.settings)?,
+            None => match self.platform {
+              Some(ref platform) => platform.clone(),
+              None => {
+                let platform = get_target_platform(self.shell_matches, &self.settings)?;
+                self.platform = Some(platform.clone());
+                platform
+              }
+            },
+          };
+          let tenant = match line_matches.get_one::<String>(TARGET_TENANT_ARGUMENT) {
+            Some(tenant) => tenant.clone(),
+            None => match self.tenant {
+              Some(ref tenant) => tenant.clone(),
+              None => {
+                let tenant = get_target_tenant(self.shell_matches, &self.settings)?;
+                self.tenant = Some(tenant.clone());
+                tenant
+              }
+            },
+          };
+          let client = self.client(&platform, &tenant).await?;
+          subject_registry.execute(subject_command_name, sub_matches, Some(client), context).await?;
+        } else {
+          subject_registry.execute(subject_command_name, sub_matches, None, context).await?;
+        }
+      }
+      None => context.print_error("no command provided, enter 'help' to show the available commands"),
+    }
+    Ok(true)
+  }
+
+  /// # Switches the target platform or tenant
+  ///
+  /// When both the platform and tenant are known, the shell will first connect to the new target.
+  /// If that fails, the target will not be switched.
+  async fn use_target(&mut self, use_matches: &ArgMatches, context: &Context) -> DshCliResult {
+    let (platform, tenant) = match use_matches.subcommand() {
+      Some((USE_PLATFORM_COMMAND, platform_matches)) => {
+        let platform = DshPlatform::try_from(
+          platform_matches
+            .get_one::<String>(PLATFORM_NAME_ARGUMENT)
+            .unwrap_or_else(|| unreachable!())
+            .as_str(),
+        )?;
+        (Some(platform), self.tenant.clone())
+      }
+      Some((USE_TENANT_COMMAND, tenant_matches)) => (self.platform.clone(), tenant_matches.get_one::<String>(USE_TENANT_ARGUMENT).cloned()),
+      _ => unreachable!(),
+    };
+    if let (Some(platform), Some(tenant)) = (&platform, &tenant) {
+      self.client(platform, tenant).await?;
+    }
+    self.platform = platform;
+    self.tenant = tenant;
+    context.print_outcome(match (&self.platform, &self.tenant) {
+      (Some(platform), Some(tenant)) => format!("target is {}", target_key(platform, tenant)),
+      (Some(platform), None) => format!("target platform is {}", platform.name()),
+      (None, Some(tenant)) => format!("target tenant is {}", tenant),
+      (None, None) => unreachable!(),
+    });
+    Ok(())
+  }
+
+  /// # Returns the client for a target
+  ///
+  /// When there is no client for the target yet, it will be created.
+  /// This might require the user to enter the password.
+  async fn client(&mut self, platform: &DshPlatform, tenant: &str) -> Result<&DshApiClient, String> {
+    let target = target_key(platform, tenant);
+    if !self.clients.contains_key(&target) {
+      let client = create_client_for_target(self.shell_matches, platform, tenant).await?;
+      self.clients.insert(target.clone(), Arc::new(client));
+    }
+    Ok(self.clients.get(&target).unwrap_or_else(|| unreachable!()))
+  }
+
+  /// # Returns the client for the current target, if it was already created
+  fn current_client(&self) -> Option<Arc<DshApiClient>> {
+    self
+      .platform
+      .as_ref()
+      .zip(self.tenant.as_ref())
+      .and_then(|(platform, tenant)| self.clients.get(&target_key(platform, tenant)))
+      .cloned()
+  }
+
+  fn prompt(&self) -> String {
+    match (&self.platform, &self.tenant) {
+      (Some(platform), Some(tenant)) => format!("dsh {}> ", target_key(platform, tenant)),
+      _ => "dsh> ".to_string(),
+    }
+  }
+}
+
+fn target_key(platform: &DshPlatform, tenant: &str) -> String {
+  format!("{}@{}", tenant, platform.name())
+}
+
+/// # Creates the line editor
+///
+/// The prompt is written to the terminal, not to stdout, such that the output
+/// of the commands can be redirected. The history will be kept in memory
+/// for at most `MAX_HISTORY` lines, without consecutive duplicates.
+fn create_editor(command: Command) -> Result<Editor<ShellHelper, DefaultHistory>, String> {
+  let config = Config::builder()
+    .behavior(Behavior::PreferTerm)
+    .max_history_size(MAX_HISTORY)
+    .and_then(|builder| builder.history_ignore_dups(true))
+    .map_err(|error| format!("could not configure line editor ({})", error))?
+    .build();
+  let mut editor = Editor::with_config(config).map_err(|error| format!("could not create line editor ({})", error))?;
+  editor.set_helper(Some(ShellHelper { command, platform: None, tenant: None, client: None }));
+  Ok(editor)
+}
+
+/// # Completion helper for the line editor
+///
+/// The current target will be updated before each line is read,
+/// such that the resource identifiers are completed for the current target.
+struct ShellHelper {
+  command: Command,
+  platform: Option<DshPlatform>,
+  tenant: Option<String>,
+  client: Option<Arc<DshApiClient>>,
+}
+
+impl Completer for ShellHelper {
+  type Candidate = String;
+
+  fn complete(&self, line: &str, pos: usize, _: &rustyline::Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+    let line = &line[..pos];
+    let start = line.trim_end_matches(|character: char| !character.is_whitespace()).len();
+    let candidates = complete(
+      &self.command,
+      line,
+      &mut |argument_id| match (self.platform.as_ref(), self.tenant.as_ref(), self.client.as_ref()) {
+        _ if argument_id == TARGET_TENANT_ARGUMENT => target_tenants(self.platform.as_ref()),
+        (Some(platform), Some(tenant), Some(client)) => cached_resource_ids(platform, tenant, argument_id, client),
+        _ => vec![],
+      },
+    );
+    Ok((start, candidates))
+  }
+}
+
+impl Hinter for ShellHelper {
+  type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+/// # Returns the global arguments that were explicitly provided to the shell command
+///
+/// The returned arguments will be prepended to all commands in the shell,
+/// except for the target arguments.
+fn global_arguments(command: &Command, shell_matches: &ArgMatches) -> Vec<String> {
+  let mut global_arguments = vec![];
+  for argument in command.get_arguments().filter(|argument| argument.is_global_set()) {
+    let argument_id = argument.get_id().as_str();
+    if TARGET_ARGUMENTS.contains(&argument_id) || shell_matches.value_source(argument_id) != Some(ValueSource::CommandLine) {
+      continue;
+    }
+    let long = match argument.get_long() {
+      Some(long) => format!("--{}", long),
+      None => continue,
+    };
+    if argument.get_action().takes_values() {
+      for value in shell_matches.get_raw(argument_id).into_iter().flatten() {
+        global_arguments.push(long.clone());
+        global_arguments.push(value.to_string_lossy().to_string());
+      }
+    } else {
+      global_arguments.push(long);
+    }
+  }
+  global_arguments
+}
+
+/// # Returns the completion candidates for the last word of a line
+///
+/// The candidates are derived from the clap command tree. Depending on the position,
+/// the candidates are the subcommands, the options or the values for an option or argument.
+///
+/// ## Parameters
+/// * `command` - clap command tree
+/// * `line` - line up to the cursor position
+/// * `resource_ids` - function that returns the resource identifiers for an argument id
+fn complete(command: &Command, line: &str, resource_ids: &mut dyn FnMut(&str) -> Vec<String>) -> Vec<String> {
+  let mut words = line.split_whitespace().collect::<Vec<_>>();
+  let prefix = if line.is_empty() || line.ends_with(char::is_whitespace) { "" } else { words.pop().unwrap_or_default() };
+  let mut current = command;
+  let mut global_arguments: Vec<&Arg> = vec![];
+  let mut expected_value: Option<&Arg> = None;
+  let mut positional_index = 0;
+  for word in words {
+    global_arguments.extend(current.get_arguments().filter(|argument| argument.is_global_set()));
+    if expected_value.take().is_some() {
+      continue;
+    }
+    if let Some(long) = word.strip_prefix("--") {
+      if !long.contains('=') {
+        expected_value = find_argument(current, &global_arguments, |argument| argument.get_long() == Some(long)).filter(|argument| argument.get_action().takes_values());
+      }
+    } else if let Some(short) = word.strip_prefix('-').filter(|short| short.chars().count() == 1) {
+      let short = short.chars().next();
+      expected_value = find_argument(current, &global_arguments, |argument| argument.get_short() == short).filter(|argument| argument.get_action().takes_values());
+    } else if let Some(subcommand) = current
+      .get_subcommands()
+      .find(|subcommand| subcommand.get_name() == word || subcommand.get_all_aliases().any(|alias| alias == word))
+    {
+      current = subcommand;
+      positional_index = 0;
+    } else {
+      positional_index += 1;
+    }
+  }
+  global_arguments.extend(current.get_arguments().filter(|argument| argument.is_global_set()));
+  let mut candidates = if let Some(argument) = expected_value {
+    argument_values(argument, resource_ids)
+  } else if let Some((long, value_prefix)) = prefix.strip_prefix("--").and_then(|option| option.split_once('=')) {
+    match find_argument(current, &global_arguments, |argument| argument.get_long() == Some(long)) {
+      Some(argument) => argument_values(argument, resource_ids)
+        .into_iter()
+        .filter(|value| value.starts_with(value_prefix))
+        .map(|value| format!("--{}={}", long, value))
+        .collect(),
+      None => vec![],
+    }
+  } else if prefix.starts_with('-') {
+    current
+      .get_arguments()
+      .chain(global_arguments.iter().cloned())
+      .filter(|argument| !argument.is_hide_set())
+      .filter_map(|argument| argument.get_long().map(|long| format!("--{}", long)))
+      .collect()
+  } else {
+    let mut candidates = current
+      .get_subcommands()
+      .filter(|subcommand| !subcommand.is_hide_set())
+      .map(|subcommand| subcommand.get_name().to_string())
+      .collect::<Vec<_>>();
+    if let Some(positional) = current.get_positionals().nth(positional_index) {
+      candidates.append(&mut argument_values(positional, resource_ids));
+    }
+    candidates
+  };
+  candidates.retain(|candidate| candidate.starts_with(prefix));
+  candidates.sort();
+  candidates.dedup();
+  candidates
+}
+
+fn find_argument<'c>(command: &'c Command, global_arguments: &[&'c Arg], predicate: impl Fn(&Arg) -> bool) -> Option<&'c Arg> {
+  command
+    .get_arguments()
+    .find(|argument| predicate(argument))
+    .or_else(|| global_arguments.iter().find(|argument| predicate(argument)).cloned())
+}
+
+fn argument_values(argument: &Arg, resource_ids: &mut dyn FnMut(&str) -> Vec<String>) -> Vec<String> {
+  let possible_values = argument.get_possible_values();
+  if possible_values.is_empty() {
+    resource_ids(argument.get_id().as_str())
+  } else {
+    possible_values
+      .iter()
+      .filter(|possible_value| !possible_value.is_hide_set())
+      .map(|possible_value| possible_value.get_name().to_string())
+      .collect()
+  }
+}
+
+#[test]
+fn test_complete() {
+  let command = Command::new("dsh")
+    .arg(Arg::new("output-format").long("output-format").global(true).value_parser(["json", "table"]))
+    .arg(Arg::new("dry-run").long("dry-run").global(true).action(ArgAction::SetTrue))
+    .subcommand(
+      Command::new("service")
+        .subcommand(Command::new("show").arg(Arg::new("service-id-argument")))
+        .subcommand(
+          Command::new("start")
+            .arg(Arg::new("service-id-argument"))
+            .arg(Arg::new("instances").long("instances")),
+        ),
+    )
+    .subcommand(Command::new("secret").alias("s"));
+  let mut resource_ids = |argument_id: &str| match argument_id {
+    "service-id-argument" => vec!["my-service".to_string(), "other-service".to_string()],
+    _ => vec![],
+  };
+  let mut complete = |line: &str| complete(&command, line, &mut resource_ids);
+  assert_eq!(complete(""), vec!["secret", "service"]);
+  assert_eq!(complete("se"), vec!["secret", "service"]);
+  assert_eq!(complete("serv"), vec!["service"]);
+  assert_eq!(complete("service s"), vec!["show", "start"]);
+  assert_eq!(complete("service show "), vec!["my-service", "other-service"]);
+  assert_eq!(complete("service show m"), vec!["my-service"]);
+  assert_eq!(complete("service show my-service "), Vec::<String>::new());
+  assert_eq!(complete("service start --instances 2 o"), vec!["other-service"]);
+  assert_eq!(complete("service start --"), vec!["--dry-run", "--instances", "--output-format"]);
+  assert_eq!(complete("--output-format "), vec!["json", "table"]);
+  assert_eq!(complete("service show --output-format=j"), vec!["--output-format=json"]);
+}
@@ -0,0 +1,255 @@
+use crate::dsh_directory;
+use crate::formatters::formatter::{Label, SubjectFormatter};
+use crate::log_writer::utc_timestamp;
+use dsh_api::types::Application;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+const SNAPSHOTS_SUBDIRECTORY: &str = "snapshots";
+
+/// Maximum number of snapshots that will be kept per service
+const MAX_SNAPSHOTS: usize = 50;
+
+/// # Snapshot of a service configuration
+///
+/// Before the configuration of a service is changed, a snapshot of the previous
+/// configuration is stored in the `dsh` tool's home directory,
+/// in the file `snapshots/<platform>/<tenant>/<service>.jsonl`.
+/// The file contains one json object per line, the most recent snapshot last.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub(crate) struct ServiceSnapshot {
+  pub(crate) timestamp: String,
+  pub(crate) configuration: Application,
+}
+
+/// # Stores a snapshot of a service configuration
+///
+/// When there are more than `MAX_SNAPSHOTS` snapshots for the service,
+/// the oldest snapshots will be removed.
+pub(crate) fn save_service_snapshot(platform: &str, tenant: &str, service_id: &str, configuration: &Application) -> Result<(), String> {
+  let snapshots_file = snapshots_file(platform, tenant, service_id)?;
+  let snapshot = ServiceSnapshot { timestamp: utc_timestamp(SystemTime::now()), configuration: configuration.clone() };
+  let line = serde_json::to_string(&snapshot).map_err(|error| error.to_string())?;
+  let mut file = OpenOptions::new()
+    .create(true)
+    .append(true)
+    .open(&snapshots_file)
+    .map_err(|error| format!("could not open snapshots file '{}' ({})", snapshots_file.to_string_lossy(), error))?;
+  writeln!(file, "{}", line).map_err(|error| format!("could not write snapshots file '{}' ({})", snapshots_file.to_string_lossy(), error))?;
+  let snapshots = read_service_snapshots(platform, tenant, service_id)?;
+  if snapshots.len() > MAX_SNAPSHOTS {
+    let lines = snapshots[snapshots.len() - MAX_SNAPSHOTS..]
+      .iter()
+      .map(|snapshot| serde_json::to_string(snapshot).map_err(|error| error.to_string()))
+      .collect::<Result<Vec<_>, _>>()?;
+    fs::write(&snapshots_file, format!("{}\n", lines.join("\n"))).map_err(|error| format!("could not write snapshots file '{}' ({})", snapshots_file.to_string_lossy(), error))?;
+  }
+  Ok(())
+}
+
+/// # Reads all snapshots of a service configuration
+///
+/// The snapshots will be returned in the order in which they were stored.
+/// When there are no snapshots for the service, an empty list will be returned.
+pub(crate) fn read_service_snapshots(platform: &str, tenant: &str, service_id: &str) -> Result<Vec<ServiceSnapshot>, String> {
+  let snapshots_file = snapshots_file(platform, tenant, service_id)?;
+  let file = match File::open(&snapshots_file) {
+    Ok(file) => file,
+    Err(error) if error.kind() == ErrorKind::NotFound => return Ok(vec![]),
+    Err(error) => return Err(format!("could not open snapshots file '{}' ({})", snapshots_file.to_string_lossy(), error)),
+  };
+  let mut snapshots = vec![];
+  for (index, line) in BufReader::new(file).lines().enumerate() {
+    let line = line.map_err(|error| format!("could not read snapshots file '{}' ({})", snapshots_file.to_string_lossy(), error))?;
+    if !line.trim().is_empty() {
+      snapshots.push(serde_json::from_str::<ServiceSnapshot>(&line).map_err(|error| {
+        format!(
+          "invalid snapshot in snapshots file '{}' at line {} ({})",
+          snapshots_file.to_string_lossy(),
+          index + 1,
+          error
+        )
+      })?);
+    }
+  }
+  Ok(snapshots)
+}
+
+/// # Selects the snapshot to roll back to
+///
+/// * `snapshots` - snapshots in the order in which they were stored
+/// * `steps` - when `to` is `None`, select the snapshot taken before the `steps`-th most recent change
+/// * `to` - select the configuration as it was at this moment,
+///   which is the first snapshot that was taken after this moment
+///
+/// Returns the selected snapshot, or `None` when there is no matching snapshot.
+pub(crate) fn select_snapshot<'a>(snapshots: &'a [ServiceSnapshot], steps: usize, to: Option<&str>) -> Option<&'a ServiceSnapshot> {
+  match to {
+    Some(to) => snapshots.iter().find(|snapshot| snapshot.timestamp.as_str() > to),
+    None => {
+      if steps == 0 || steps > snapshots.len() {
+        None
+      } else {
+        snapshots.get(snapshots.len() - steps)
+      }
+    }
+  }
+}
+
+/// # Difference between two configurations
+#[derive(Serialize)]
+pub(crate) struct ConfigurationDifference {
+  pub(crate) item: String,
+  pub(crate) change: &'static str,
+  pub(crate) from: Option<String>,
+  pub(crate) to: Option<String>,
+}
+
+/// # Compares two configurations
+///
+/// The configurations are compared by their json representation.
+/// Each difference identifies the changed item by its path in the json representation,
+/// like `env.LOG_LEVEL` or `exposedPorts.8080.auth`.
+pub(crate) fn configuration_differences<C: Serialize>(from: &C, to: &C) -> Result<Vec<ConfigurationDifference>, String> {
+  let mut from_items = BTreeMap::new();
+  flatten_value("", &serde_json::to_value(from).map_err(|error| error.to_string())?, &mut from_items);
+  let mut to_items = BTreeMap::new();
+  flatten_value("", &serde_json::to_value(to).map_err(|error| error.to_string())?, &mut to_items);
+  let mut items = from_items.keys().chain(to_items.keys()).collect::<Vec<_>>();
+  items.sort();
+  items.dedup();
+  Ok(
+    items
+      .into_iter()
+      .filter_map(|item| {
+        let from = from_items.get(item).cloned();
+        let to = to_items.get(item).cloned();
+        let change = match (&from, &to) {
+          (Some(from), Some(to)) if from != to => "changed",
+          (Some(_), None) => "removed",
+          (None, Some(_)) => "added",
+          _ => return None,
+        };
+        Some(ConfigurationDifference { item: item.to_string(), change, from, to })
+      })
+      .collect(),
+  )
+}
+
+fn flatten_value(path: &str, value: &Value, items: &mut BTreeMap<String, String>) {
+  match value {
+    Value::Null => {}
+    Value::Object(map) if !map.is_empty() => {
+      for (key, value) in map {
+        flatten_value(&join_path(path, key), value, items);
+      }
+    }
+    Value::String(string) => {
+      items.insert(path.to_string(), string.clone());
+    }
+    value => {
+      items.insert(path.to_string(), value.to_string());
+    }
+  }
+}
+
+fn join_path(path: &str, key: &str) -> String {
+  if path.is_empty() {
+    key.to_string()
+  } else {
+    format!("{}.{}", path, key)
+  }
+}
+
+fn snapshots_file(platform: &str, tenant: &str, service_id: &str) -> Result<PathBuf, String> {
+  match dsh_directory()? {
+    Some(dsh_directory) => {
+      let snapshots_directory = dsh_directory.join(SNAPSHOTS_SUBDIRECTORY).join(platform).join(tenant);
+      fs::create_dir_all(&snapshots_directory).map_err(|error| format!("could not create snapshots directory '{}' ({})", snapshots_directory.to_string_lossy(), error))?;
+      Ok(snapshots_directory.join(format!("{}.jsonl", service_id)))
+    }
+    None => Err("snapshots are not available since the dsh tool's home directory is not set".to_string()),
+  }
+}
+
+#[derive(Eq, Hash, PartialEq, Serialize)]
+pub(crate) enum ConfigurationDifferenceLabel {
+  Change,
+  From,
+  Item,
+  To,
+}
+
+impl Label for ConfigurationDifferenceLabel {
+  fn as_str(&self) -> &str {
+    match self {
+      Self::Change => "change",
+      Self::From => "from",
+      Self::Item => "item",
+      Self::To => "to",
+    }
+  }
+
+  fn is_target_label(&self) -> bool {
+    matches!(self, Self::Item)
+  }
+}
+
+impl SubjectFormatter<ConfigurationDifferenceLabel> for ConfigurationDifference {
+  fn value(&self, label: &ConfigurationDifferenceLabel, _target_id: &str) -> String {
+    match label {
+      ConfigurationDifferenceLabel::Change => self.change.to_string(),
+      ConfigurationDifferenceLabel::From => self.from.clone().unwrap_or_default(),
+      ConfigurationDifferenceLabel::Item => self.item.clone(),
+      ConfigurationDifferenceLabel::To => self.to.clone().unwrap_or_default(),
+    }
+  }
+
+  fn target_id(&self) -> Option<String> {
+    Some(self.item.clone())
+  }
+}
+
+pub(crate) static CONFIGURATION_DIFFERENCE_LABELS: [ConfigurationDifferenceLabel; 4] =
+  [ConfigurationDifferenceLabel::Item, ConfigurationDifferenceLabel::Change, ConfigurationDifferenceLabel::From, ConfigurationDifferenceLabel::To];
+
+#[test]
+fn test_configuration_differences() {
+  let from = serde_json::json!({ "cpus": 1.0, "env": { "A": "1", "B": "2" }, "image": "image:1" });
+  let to = serde_json::json!({ "cpus": 0.5, "env": { "A": "1", "C": "3" }, "image": "image:1" });
+  let differences = configuration_differences(&from, &to)
+    .unwrap()
+    .into_iter()
+    .map(|difference| (difference.item, difference.change, difference.from, difference.to))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    differences,
+    vec![
+      ("cpus".to_string(), "changed", Some("1.0".to_string()), Some("0.5".to_string())),
+      ("env.B".to_string(), "removed", Some("2".to_string()), None),
+      ("env.C".to_string(), "added", None, Some("3".to_string())),
+    ]
+  );
+}
+
+#[test]
+fn test_select_snapshot() {
+  let snapshot = |timestamp: &str| ServiceSnapshot {
+    timestamp: timestamp.to_string(),
+    configuration: serde_json::from_value(serde_json::json!({ "cpus": 1.0, "image": "image", "mem": 256, "user": "1000:1000" })).unwrap(),
+  };
+  let snapshots = vec![snapshot("2025-03-01T10:00:00.000Z"), snapshot("2025-03-01T12:00:00.000Z"), snapshot("2025-03-02T08:00:00.000Z")];
+  assert_eq!(select_snapshot(&snapshots, 1, None).unwrap().timestamp, "2025-03-02T08:00:00.000Z");
+  assert_eq!(select_snapshot(&snapshots, 3, None).unwrap().timestamp, "2025-03-01T10:00:00.000Z");
+  assert!(select_snapshot(&snapshots, 4, None).is_none());
+  assert!(select_snapshot(&snapshots, 0, None).is_none());
+  assert_eq!(select_snapshot(&snapshots, 1, Some("2025-03-01T11")).unwrap().timestamp, "2025-03-01T12:00:00.000Z");
+  assert_eq!(select_snapshot(&snapshots, 1, Some("2025-03-01")).unwrap().timestamp, "2025-03-01T10:00:00.000Z");
+  assert!(select_snapshot(&snapshots, 1, Some("2025-03-03")).is_none());
+}
@@ -1,9 +1,11 @@
 use crate::capability::{Capability, LIST_COMMAND};
 use crate::context::Context;
+use crate::error::DshCliError;
 use crate::DshCliResult;
 use async_trait::async_trait;
 use clap::{ArgMatches, Command};
 use dsh_api::dsh_api_client::DshApiClient;
+use std::collections::HashMap;
 
 #[derive(Debug, PartialEq)]
 pub struct Requirements {
@@ -140,3 +142,59 @@ pub trait Subject {
       .await
   }
 }
+
+/// # Registry of the subjects
+///
+/// Maps the subject commands and the subject list shortcut commands to their subjects.
+pub(crate) struct SubjectRegistry<'a> {
+  subjects: HashMap<String, &'a (dyn Subject + Send + Sync)>,
+  list_shortcuts: HashMap<String, &'a (dyn Subject + Send + Sync)>,
+}
+
+impl<'a> SubjectRegistry<'a> {
+  /// # Creates the registry and returns it together with the clap commands of the subjects
+  pub(crate) fn create(subjects: Vec<&'a (dyn Subject + Send + Sync)>) -> (Self, Vec<Command>) {
+    let mut registry = Self { subjects: HashMap::new(), list_shortcuts: HashMap::new() };
+    let mut subject_commands: Vec<Command> = Vec::new();
+    for subject in subjects {
+      let (command_name, subject_command) = subject.subject_command();
+      registry.subjects.insert(command_name.to_string(), subject);
+      subject_commands.push(subject_command);
+      if let Some((list_shortcut_name, clap_list_command_shortcut)) = subject.subject_list_shortcut_command() {
+        registry.list_shortcuts.insert(list_shortcut_name.to_string(), subject);
+        subject_commands.push(clap_list_command_shortcut);
+      }
+    }
+    (registry, subject_commands)
+  }
+
+  /// # Returns the requirements of a subject command or subject list shortcut command
+  pub(crate) fn requirements(&self, subject_command_name: &str, sub_matches: &ArgMatches) -> Result<Requirements, DshCliError> {
+    match self.subjects.get(subject_command_name) {
+      Some(subject) => Ok(subject.requirements(sub_matches)),
+      None => match self.list_shortcuts.get(subject_command_name) {
+        Some(subject_list_shortcut) => Ok(subject_list_shortcut.requirements_list_shortcut(sub_matches)),
+        None => Err(DshCliError::General("unexpected error, list shortcut not found".to_string())),
+      },
+    }
+  }
+
+  /// # Executes a subject command or subject list shortcut command
+  ///
+  /// When the command requires a client, `client` must not be `None`.
+  pub(crate) async fn execute(&self, subject_command_name: &str, sub_matches: &ArgMatches, client: Option<&DshApiClient>, context: &Context) -> DshCliResult {
+    match self.subjects.get(subject_command_name) {
+      Some(subject) => match client {
+        Some(client) => subject.execute_subject_command_with_client(sub_matches, client, context).await,
+        None => subject.execute_subject_command_without_client(sub_matches, context).await,
+      },
+      None => match self.list_shortcuts.get(subject_command_name) {
+        Some(subject_list_shortcut) => match client {
+          Some(client) => subject_list_shortcut.execute_subject_list_shortcut_with_client(sub_matches, client, context).await,
+          None => subject_list_shortcut.execute_subject_list_shortcut_without_client(sub_matches, context).await,
+        },
+        None => Err(DshCliError::General("unexpected error, list shortcut not found".to_string())),
+      },
+    }
+  }
+}
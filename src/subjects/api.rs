@@ -1,18 +1,28 @@
 use crate::capability::{Capability, CommandExecutor, SHOW_COMMAND, SHOW_COMMAND_ALIAS};
 use crate::capability_builder::CapabilityBuilder;
 use crate::context::Context;
+use crate::error::{DshCliError, UNEXPECTED_RESPONSE_PREFIX};
 use crate::formatters::OutputFormat;
+use crate::log_writer::ApiCallLog;
+use crate::openapi::{example_value, request_body_schema, validate};
 use crate::subject::{Requirements, Subject};
 use crate::DshCliResult;
 use async_trait::async_trait;
+use clap::builder::ValueParser;
 use clap::{builder, Arg, ArgAction, ArgMatches, Command};
 use dsh_api::dsh_api_client::DshApiClient;
 use dsh_api::generic::{MethodDescriptor, DELETE_METHODS, GET_METHODS, POST_METHODS, PUT_METHODS};
 #[cfg(feature = "manage")]
 use dsh_api::generic::{HEAD_METHODS, PATCH_METHODS};
+use dsh_api::DshApiError;
 use itertools::Itertools;
 use lazy_static::lazy_static;
 use log::debug;
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use reqwest::Method;
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
 
 pub(crate) struct ApiSubject {}
 
@@ -41,6 +51,7 @@ impl Subject for ApiSubject {
   fn capability(&self, capability_command: &str) -> Option<&(dyn Capability + Send + Sync)> {
     match capability_command {
       DELETE_COMMAND => Some(API_DELETE_CAPABILITY.as_ref()),
+      EXAMPLE_COMMAND => Some(API_EXAMPLE_CAPABILITY.as_ref()),
       GET_COMMAND => Some(API_GET_CAPABILITY.as_ref()),
       #[cfg(feature = "manage")]
       HEAD_COMMAND => Some(API_HEAD_CAPABILITY.as_ref()),
@@ -61,6 +72,11 @@ impl Subject for ApiSubject {
 #[cfg(feature = "manage")]
 lazy_static! {
   static ref API_DELETE_CAPABILITY: Box<(dyn Capability + Send + Sync)> = create_generic_capability(DELETE_COMMAND, DELETE_ABOUT, DELETE_LONG_ABOUT, &ApiDelete {});
+  static ref API_EXAMPLE_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
+    CapabilityBuilder::new(EXAMPLE_COMMAND, None, &ApiExample {}, EXAMPLE_ABOUT)
+      .set_long_about(EXAMPLE_LONG_ABOUT)
+      .add_extra_arguments(vec![example_method_argument(), example_selector_argument()])
+  );
   static ref API_GET_CAPABILITY: Box<(dyn Capability + Send + Sync)> = create_generic_capability(GET_COMMAND, GET_ABOUT, GET_LONG_ABOUT, &ApiGet {});
   static ref API_HEAD_CAPABILITY: Box<(dyn Capability + Send + Sync)> = create_generic_capability(HEAD_COMMAND, HEAD_ABOUT, HEAD_LONG_ABOUT, &ApiHead {});
   static ref API_PATCH_CAPABILITY: Box<(dyn Capability + Send + Sync)> = create_generic_capability(PATCH_COMMAND, PATCH_ABOUT, PATCH_LONG_ABOUT, &ApiPatch {});
@@ -74,6 +90,7 @@ lazy_static! {
   ));
   static ref API_CAPABILITIES: Vec<&'static (dyn Capability + Send + Sync)> = vec![
     API_DELETE_CAPABILITY.as_ref(),
+    API_EXAMPLE_CAPABILITY.as_ref(),
     API_GET_CAPABILITY.as_ref(),
     API_HEAD_CAPABILITY.as_ref(),
     API_PATCH_CAPABILITY.as_ref(),
@@ -86,6 +103,11 @@ lazy_static! {
 #[cfg(not(feature = "manage"))]
 lazy_static! {
   static ref API_DELETE_CAPABILITY: Box<(dyn Capability + Send + Sync)> = create_generic_capability(DELETE_COMMAND, DELETE_ABOUT, DELETE_LONG_ABOUT, &ApiDelete {});
+  static ref API_EXAMPLE_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
+    CapabilityBuilder::new(EXAMPLE_COMMAND, None, &ApiExample {}, EXAMPLE_ABOUT)
+      .set_long_about(EXAMPLE_LONG_ABOUT)
+      .add_extra_arguments(vec![example_method_argument(), example_selector_argument()])
+  );
   static ref API_GET_CAPABILITY: Box<(dyn Capability + Send + Sync)> = create_generic_capability(GET_COMMAND, GET_ABOUT, GET_LONG_ABOUT, &ApiGet {});
   static ref API_POST_CAPABILITY: Box<(dyn Capability + Send + Sync)> = create_generic_capability(POST_COMMAND, POST_ABOUT, POST_LONG_ABOUT, &ApiPost {});
   static ref API_PUT_CAPABILITY: Box<(dyn Capability + Send + Sync)> = create_generic_capability(PUT_COMMAND, PUT_ABOUT, PUT_LONG_ABOUT, &ApiPut {});
@@ -95,11 +117,18 @@ lazy_static! {
     &ApiShow {},
     "Print the open api specification"
   ));
-  static ref API_CAPABILITIES: Vec<&'static (dyn Capability + Send + Sync)> =
-    vec![API_DELETE_CAPABILITY.as_ref(), API_GET_CAPABILITY.as_ref(), API_POST_CAPABILITY.as_ref(), API_PUT_CAPABILITY.as_ref(), API_SHOW_CAPABILITY.as_ref(),];
+  static ref API_CAPABILITIES: Vec<&'static (dyn Capability + Send + Sync)> = vec![
+    API_DELETE_CAPABILITY.as_ref(),
+    API_EXAMPLE_CAPABILITY.as_ref(),
+    API_GET_CAPABILITY.as_ref(),
+    API_POST_CAPABILITY.as_ref(),
+    API_PUT_CAPABILITY.as_ref(),
+    API_SHOW_CAPABILITY.as_ref(),
+  ];
 }
 
 const DELETE_COMMAND: &str = "delete";
+const EXAMPLE_COMMAND: &str = "example";
 const GET_COMMAND: &str = "get";
 #[cfg(feature = "manage")]
 const HEAD_COMMAND: &str = "head";
@@ -108,7 +137,16 @@ const PATCH_COMMAND: &str = "patch";
 const POST_COMMAND: &str = "post";
 const PUT_COMMAND: &str = "put";
 
-fn method_descriptors(method: &str) -> &'static [(&str, MethodDescriptor)] {
+const BODY_ARGUMENT: &str = "body-argument";
+const EXAMPLE_METHOD_ARGUMENT: &str = "example-method-argument";
+const EXAMPLE_SELECTOR_ARGUMENT: &str = "example-selector-argument";
+const BODY_FILE_ARGUMENT: &str = "body-file-argument";
+const RAW_ARGUMENT: &str = "raw-argument";
+const SET_ARGUMENT: &str = "set-argument";
+
+const BODY_PROMPT: &str = "enter json request body (terminate input with ctrl-d after last line)";
+
+fn method_descriptors(method: &str) -> &'static [(&'static str, MethodDescriptor)] {
   match method {
     DELETE_COMMAND => &DELETE_METHODS,
     GET_COMMAND => &GET_METHODS,
@@ -171,9 +209,95 @@ fn create_generic_capability_selector_command(method_command: &str, selector: &s
         .collect::<Vec<_>>(),
     )
   }
+  command = command.arg(raw_argument());
+  if method_descriptor.body_type.is_some() {
+    command = command.arg(body_argument()).arg(body_file_argument());
+    if let Some(get_selector) = matching_get_selector(method_descriptor) {
+      command = command.arg(set_argument(get_selector));
+    }
+  }
   command
 }
 
+fn example_method_argument() -> Arg {
+  Arg::new(EXAMPLE_METHOD_ARGUMENT)
+    .action(ArgAction::Set)
+    .value_parser([
+      #[cfg(feature = "manage")]
+      PATCH_COMMAND,
+      POST_COMMAND,
+      PUT_COMMAND,
+    ])
+    .value_name("METHOD")
+    .required(true)
+    .help("Method of the operation")
+}
+
+fn example_selector_argument() -> Arg {
+  Arg::new(EXAMPLE_SELECTOR_ARGUMENT)
+    .action(ArgAction::Set)
+    .value_parser(builder::NonEmptyStringValueParser::new())
+    .value_name("SELECTOR")
+    .required(true)
+    .help("Selector of the operation")
+    .long_help("Selector of the operation, like 'application-configuration', or the path of the operation.")
+}
+
+fn body_argument() -> Arg {
+  Arg::new(BODY_ARGUMENT)
+    .long("body")
+    .action(ArgAction::Set)
+    .value_parser(builder::NonEmptyStringValueParser::new())
+    .value_name("JSON")
+    .conflicts_with(BODY_FILE_ARGUMENT)
+    .help("Json request body")
+    .long_help("Provide the json request body on the command line, instead of reading it from stdin.")
+}
+
+fn body_file_argument() -> Arg {
+  Arg::new(BODY_FILE_ARGUMENT)
+    .long("body-file")
+    .action(ArgAction::Set)
+    .value_parser(ValueParser::path_buf())
+    .value_name("FILE")
+    .help("File with json request body")
+    .long_help("Read the json request body from this file, instead of reading it from stdin.")
+}
+
+fn raw_argument() -> Arg {
+  Arg::new(RAW_ARGUMENT).long("raw").action(ArgAction::SetTrue).help("Print raw response").long_help(
+    "Print the unformatted response body to stdout, as it was returned by the server. \
+      The http status of the response will be printed to stderr. \
+      The response body will also be printed when the server returns an error status.",
+  )
+}
+
+fn set_argument(get_selector: &str) -> Arg {
+  Arg::new(SET_ARGUMENT)
+    .long("set")
+    .action(ArgAction::Append)
+    .value_parser(builder::NonEmptyStringValueParser::new())
+    .value_name("PATH=VALUE")
+    .help("Set value in request body")
+    .long_help(format!(
+      "Set the value at a path in the request body, like 'env.LOG_LEVEL=info' or 'cpus=0.5'. \
+      The path consists of the keys of the nested json objects (or array indices), separated by dots. \
+      When the value is valid json it will be used as json, else it will be used as a string. \
+      This option can be used more than once. \
+      When no request body is provided via --body or --body-file, \
+      the current value will be fetched with 'get {}', such that only the set values will be changed.",
+      get_selector
+    ))
+}
+
+/// Returns the selector of the get operation that has the same path as `method_descriptor`
+fn matching_get_selector(method_descriptor: &MethodDescriptor) -> Option<&'static str> {
+  GET_METHODS
+    .iter()
+    .find(|(_, get_method_descriptor)| get_method_descriptor.path == method_descriptor.path && get_method_descriptor.parameters.len() == method_descriptor.parameters.len())
+    .map(|(get_selector, _)| *get_selector)
+}
+
 fn create_about(description: &str) -> String {
   let first = match description.split(". ").collect::<Vec<&str>>().first() {
     Some(first) => first.to_string(),
@@ -228,12 +352,12 @@ fn create_long_about(method_command: &str, method_descriptor: &MethodDescriptor,
         .map(|body_type| {
           if body_type == "String" {
             format!(
-              "Requires a string. This string can either be piped from another application, redirected from a file or provided by the user interactively. {}",
+              "Requires a string. This string can either be provided via --body or --body-file, piped from another application, redirected from a file or provided by the user interactively. {}",
               response_string
             )
           } else {
             format!(
-          "Requires string data representing a {}. This string data can either be piped from another application, redirected from a file or provided by the user interactively. {}",
+          "Requires string data representing a {}. This string data can either be provided via --body or --body-file, piped from another application, redirected from a file or provided by the user interactively. {}",
           body_type, response_string
         )
           }
@@ -264,12 +388,16 @@ impl CommandExecutor for ApiDelete {
           .map(|(parameter_name, _, _)| matches.get_one::<String>(parameter_name).unwrap().as_str())
           .collect::<Vec<_>>();
         let start_instant = context.now();
-        client.delete(selector, &parameters).await?;
+        if matches.get_flag(RAW_ARGUMENT) {
+          raw_request(Method::DELETE, method_descriptor, &parameters, None, client, context).await?;
+        } else {
+          client.delete(selector, &parameters).await?;
+        }
         context.print_execution_time(start_instant);
-        context.print_outcome("deleted");
+        context.print_audited_outcome(client, api_resource(selector, &parameters), "deleted");
       }
     } else {
-      context.print_outcome("cancelled, nothing deleted");
+      return Err(DshCliError::Cancelled("cancelled, nothing deleted".to_string()));
     }
     Ok(())
   }
@@ -279,6 +407,42 @@ impl CommandExecutor for ApiDelete {
   }
 }
 
+struct ApiExample {}
+
+#[async_trait]
+impl CommandExecutor for ApiExample {
+  async fn execute_without_client(&self, _: Option<String>, _: Option<String>, matches: &ArgMatches, context: &Context) -> DshCliResult {
+    let method = matches.get_one::<String>(EXAMPLE_METHOD_ARGUMENT).unwrap();
+    let selector = matches.get_one::<String>(EXAMPLE_SELECTOR_ARGUMENT).unwrap();
+    let method_descriptor = method_descriptors(method)
+      .iter()
+      .find(|(method_selector, method_descriptor)| method_selector == selector || method_descriptor.path == selector)
+      .map(|(_, method_descriptor)| method_descriptor)
+      .ok_or_else(|| DshCliError::NotFound(format!("{} operation '{}' does not exist", method, selector)))?;
+    context.print_explanation(format!("example request body for {} {}", method.to_ascii_uppercase(), method_descriptor.path));
+    if method_descriptor.body_type.is_none() {
+      return Err(DshCliError::Validation(format!(
+        "{} operation '{}' does not require a request body",
+        method, selector
+      )));
+    }
+    match request_body_schema(method, method_descriptor.path, method_descriptor.body_type) {
+      Some(schema) => {
+        context.print_serializable(example_value(schema), Some(OutputFormat::Json))?;
+        Ok(())
+      }
+      None => Err(DshCliError::NotFound(format!(
+        "no schema found for request body of {} operation '{}'",
+        method, selector
+      ))),
+    }
+  }
+
+  fn requirements(&self, _: &ArgMatches) -> Requirements {
+    Requirements::standard_without_api()
+  }
+}
+
 struct ApiGet {}
 
 #[async_trait]
@@ -293,9 +457,14 @@ impl CommandExecutor for ApiGet {
       .map(|(parameter_name, _, _)| matches.get_one::<String>(parameter_name).unwrap().as_str())
       .collect::<Vec<_>>();
     let start_instant = context.now();
-    let response = client.get(selector, &parameters).await?;
-    context.print_execution_time(start_instant);
-    context.print_serializable(response, Some(OutputFormat::Json));
+    if matches.get_flag(RAW_ARGUMENT) {
+      raw_request(Method::GET, method_descriptor, &parameters, None, client, context).await?;
+      context.print_execution_time(start_instant);
+    } else {
+      let response = client.get(selector, &parameters).await?;
+      context.print_execution_time(start_instant);
+      context.print_serializable(response, Some(OutputFormat::Json))?;
+    }
     Ok(())
   }
 
@@ -320,9 +489,14 @@ impl CommandExecutor for ApiHead {
       .map(|(parameter_name, _, _)| matches.get_one::<String>(parameter_name).unwrap().as_str())
       .collect::<Vec<_>>();
     let start_instant = context.now();
-    client.head(selector, &parameters).await?;
-    context.print_execution_time(start_instant);
-    context.print_outcome("ok");
+    if matches.get_flag(RAW_ARGUMENT) {
+      raw_request(Method::HEAD, method_descriptor, &parameters, None, client, context).await?;
+      context.print_execution_time(start_instant);
+    } else {
+      client.head(selector, &parameters).await?;
+      context.print_execution_time(start_instant);
+      context.print_outcome("ok");
+    }
     Ok(())
   }
 
@@ -346,21 +520,27 @@ impl CommandExecutor for ApiPatch {
       .iter()
       .map(|(parameter_name, _, _)| matches.get_one::<String>(parameter_name).unwrap().as_str())
       .collect::<Vec<_>>();
-    let body = if method_descriptor.body_type.is_some() { Some(context.read_multi_line("enter json request body (terminate input with ctrl-d after last line)")?) } else { None };
+    let body = request_body(PATCH_COMMAND, method_descriptor, &parameters, matches, client, context).await?;
     if context.dry_run() {
+      print_dry_run_body(&body, matches, context)?;
       context.print_warning("dry-run mode, nothing patched");
       Ok(())
     } else {
       let start_instant = context.now();
-      match client.patch(selector, &parameters, body).await {
+      let result = if matches.get_flag(RAW_ARGUMENT) {
+        raw_request(Method::PATCH, method_descriptor, &parameters, body, client, context).await
+      } else {
+        client.patch(selector, &parameters, body).await.map_err(DshCliError::from)
+      };
+      match result {
         Ok(_) => {
           context.print_execution_time(start_instant);
-          context.print_outcome("patched");
+          context.print_audited_outcome(client, api_resource(selector, &parameters), "patched");
           Ok(())
         }
         Err(error) => {
           debug!("{:#?}", error);
-          Err(error.to_string())
+          Err(error)
         }
       }
     }
@@ -384,15 +564,20 @@ impl CommandExecutor for ApiPost {
       .iter()
       .map(|(parameter_name, _, _)| matches.get_one::<String>(parameter_name).unwrap().as_str())
       .collect::<Vec<_>>();
-    let body = if method_descriptor.body_type.is_some() { Some(context.read_multi_line("enter json request body (terminate input with ctrl-d after last line)")?) } else { None };
+    let body = request_body(POST_COMMAND, method_descriptor, &parameters, matches, client, context).await?;
     if context.dry_run() {
+      print_dry_run_body(&body, matches, context)?;
       context.print_warning("dry-run mode, nothing posted");
       Ok(())
     } else {
       let start_instant = context.now();
-      client.post(selector, &parameters, body).await?;
+      if matches.get_flag(RAW_ARGUMENT) {
+        raw_request(Method::POST, method_descriptor, &parameters, body, client, context).await?;
+      } else {
+        client.post(selector, &parameters, body).await?;
+      }
       context.print_execution_time(start_instant);
-      context.print_outcome("posted");
+      context.print_audited_outcome(client, api_resource(selector, &parameters), "posted");
       Ok(())
     }
   }
@@ -415,15 +600,20 @@ impl CommandExecutor for ApiPut {
       .iter()
       .map(|(parameter_name, _, _)| matches.get_one::<String>(parameter_name).unwrap().as_str())
       .collect::<Vec<_>>();
-    let body = if method_descriptor.body_type.is_some() { Some(context.read_multi_line("enter json request body (terminate input with ctrl-d after last line)")?) } else { None };
+    let body = request_body(PUT_COMMAND, method_descriptor, &parameters, matches, client, context).await?;
     if context.dry_run() {
+      print_dry_run_body(&body, matches, context)?;
       context.print_warning("dry-run mode, nothing put");
       Ok(())
     } else {
       let start_instant = context.now();
-      client.put(selector, &parameters, body).await?;
+      if matches.get_flag(RAW_ARGUMENT) {
+        raw_request(Method::PUT, method_descriptor, &parameters, body, client, context).await?;
+      } else {
+        client.put(selector, &parameters, body).await?;
+      }
       context.print_execution_time(start_instant);
-      context.print_outcome("put");
+      context.print_audited_outcome(client, api_resource(selector, &parameters), "put");
       Ok(())
     }
   }
@@ -462,6 +652,14 @@ const DELETE_LONG_ABOUT: &str = "Call a delete operation on the DSH resource man
    The method will return an error message when the server did not accept the request, \
    for example if the resource specified by the identifier does not exist.";
 
+const EXAMPLE_ABOUT: &str = "Generate example request body";
+const EXAMPLE_LONG_ABOUT: &str = "Generate an example request body for a patch, post or put operation \
+   on the DSH resource management api, from the schema in the open api specification. \
+   The operation is specified by its method and selector, like 'put application-configuration'. \
+   The example contains all properties, with the default values from the specification \
+   or else empty values, and can be used as a starting point for the --body-file option. \
+   Request bodies will be validated against the same schema before they are sent.";
+
 const GET_ABOUT: &str = "Call get operation";
 const GET_LONG_ABOUT: &str = "Call a get operation on the DSH resource management api. \
    Get operations are typically used to request configuration or other data \
@@ -504,8 +702,8 @@ const PATCH_LONG_ABOUT: &str = "Call a patch operation on the DSH resource manag
    The resource instance that will be patched must be specified by one or more required identifiers, \
    which must follow the selector command. \
    Patch operations sometimes require extra data or values, \
-   which cannot be provided directly via the command line. \
-   It can either be piped or redirected via the shell command, \
+   which can be provided via the --body or --body-file options, \
+   piped from another application or redirected from a file via the shell command, \
    or the user will be asked to provide it interactively. \
    A patch operation will only give a confirmation that the server accepted the request, \
    but this does not necessarily mean that the resource will be successfully patched. \
@@ -520,8 +718,8 @@ const POST_LONG_ABOUT: &str = "Call a post operation on the DSH resource managem
    The resource instance that will be created must be identified by one or more required identifiers, \
    which must follow the selector command. \
    Post operations usually require extra data or values, \
-   which cannot be provided directly via the command line. \
-   It can either be piped from another application or redirected from a file via the shell command, \
+   which can be provided via the --body or --body-file options, \
+   piped from another application or redirected from a file via the shell command, \
    or the user will be asked to provide it interactively. \
    A post operation will only give a confirmation that the server accepted the request, \
    but this does not necessarily mean that the resource will be successfully created. \
@@ -537,10 +735,216 @@ const PUT_LONG_ABOUT: &str = "Call a put operation on the DSH resource managemen
    The resource instance that will be updated/created must be identified \
    by one or more required identifiers, which must follow the selector command. \
    Put operations usually require extra data or values, \
-   which cannot be provided directly via the command line. \
-   It can either be piped from another application or redirected from a file via the shell command, \
+   which can be provided via the --body or --body-file options, \
+   piped from another application or redirected from a file via the shell command, \
    or the user will be asked to provide it interactively. \
+   With the --set option, values in the current data can be changed (read-modify-write). \
    A put operation will only give a confirmation that the server accepted the request, \
    but this does not necessarily mean that the resource will be successfully updated. \
    The method will return an error message when the server did not accept the request, \
    for example if the resource specified by the identifier does not exist.";
+
+// Resource of a generic api call in the audit log, consisting of the selector and the parameters
+fn api_resource(selector: &str, parameters: &[&str]) -> String {
+  [&["api", selector], parameters].concat().join("/")
+}
+
+/// # Determines the request body of a patch, post or put operation
+///
+/// The body is taken from the `--body` or `--body-file` argument, which must contain valid json,
+/// or else read from stdin.
+/// When `--set` arguments are provided, they are applied to the body.
+/// When there is no `--body` or `--body-file` argument in that case,
+/// the body is fetched with the matching get operation (read-modify-write).
+/// Before it is returned, the body is validated against the schema from the openapi specification.
+async fn request_body(
+  method: &str,
+  method_descriptor: &MethodDescriptor,
+  parameters: &[&str],
+  matches: &ArgMatches,
+  client: &DshApiClient,
+  context: &Context,
+) -> Result<Option<String>, DshCliError> {
+  if method_descriptor.body_type.is_none() {
+    return Ok(None);
+  }
+  let body = match (matches.get_one::<String>(BODY_ARGUMENT), matches.get_one::<PathBuf>(BODY_FILE_ARGUMENT)) {
+    (Some(body), _) => Some(body.to_string()),
+    (None, Some(body_file)) => {
+      Some(fs::read_to_string(body_file).map_err(|error| DshCliError::Validation(format!("could not read body file '{}' ({})", body_file.to_string_lossy(), error)))?)
+    }
+    (None, None) => None,
+  };
+  let assignments = matches
+    .get_many::<String>(SET_ARGUMENT)
+    .map(|assignments| assignments.collect::<Vec<_>>())
+    .unwrap_or_default();
+  if assignments.is_empty() {
+    let body = match body {
+      Some(body) => body,
+      None => context.read_multi_line(BODY_PROMPT)?,
+    };
+    if !body.trim().is_empty() {
+      validate_body(method, method_descriptor, &parse_body(&body)?)?;
+    }
+    return Ok(Some(body));
+  }
+  let mut body = match body {
+    Some(body) => parse_body(&body)?,
+    None => {
+      let get_selector = matching_get_selector(method_descriptor).unwrap_or_else(|| unreachable!());
+      context.print_explanation(format!("GET {}", method_descriptor.path));
+      serde_json::to_value(client.get(get_selector, parameters).await?).map_err(|error| error.to_string())?
+    }
+  };
+  for assignment in assignments {
+    set_body_value(&mut body, assignment).map_err(DshCliError::Validation)?;
+  }
+  validate_body(method, method_descriptor, &body)?;
+  Ok(Some(serde_json::to_string(&body).map_err(|error| error.to_string())?))
+}
+
+/// # Validates a request body against the schema from the openapi specification
+///
+/// The error message lists all validation errors, with their json pointer locations.
+fn validate_body(method: &str, method_descriptor: &MethodDescriptor, body: &Value) -> Result<(), DshCliError> {
+  if let Some(schema) = request_body_schema(method, method_descriptor.path, method_descriptor.body_type) {
+    let errors = validate(schema, body);
+    if !errors.is_empty() {
+      return Err(DshCliError::Validation(format!(
+        "request body is not a valid {}\n{}",
+        method_descriptor.body_type.unwrap_or("request body"),
+        errors.iter().map(|error| format!("  {}", error)).collect::<Vec<_>>().join("\n")
+      )));
+    }
+  }
+  Ok(())
+}
+
+fn parse_body(body: &str) -> Result<Value, DshCliError> {
+  serde_json::from_str::<Value>(body).map_err(|error| DshCliError::Validation(format!("request body is not valid json ({})", error)))
+}
+
+// In dry-run mode, print the body that would have been sent when it was modified by --set arguments
+fn print_dry_run_body(body: &Option<String>, matches: &ArgMatches, context: &Context) -> Result<(), String> {
+  if let Some(body) = body {
+    if matches.contains_id(SET_ARGUMENT) {
+      if let Ok(body) = serde_json::from_str::<Value>(body) {
+        context.print_serializable(body, Some(OutputFormat::Json))?;
+      }
+    }
+  }
+  Ok(())
+}
+
+/// # Sets a value in a json body
+///
+/// The `assignment` has the form `path=value`, where `path` consists of the keys of
+/// the nested objects or the indices of the nested arrays, separated by dots.
+/// Missing objects on the path will be created.
+/// When `value` is valid json it will be set as json, else it will be set as a string.
+fn set_body_value(body: &mut Value, assignment: &str) -> Result<(), String> {
+  let (path, value) = assignment
+    .split_once('=')
+    .ok_or_else(|| format!("invalid set value '{}', expected path=value", assignment))?;
+  if path.is_empty() {
+    return Err(format!("invalid set value '{}', path is empty", assignment));
+  }
+  let value = serde_json::from_str::<Value>(value).unwrap_or_else(|_| Value::String(value.to_string()));
+  let mut current = body;
+  for key in path.split('.') {
+    if current.is_null() {
+      *current = Value::Object(serde_json::Map::new());
+    }
+    current = match current {
+      Value::Object(map) => map.entry(key.to_string()).or_insert(Value::Null),
+      Value::Array(array) => {
+        let index = key
+          .parse::<usize>()
+          .map_err(|_| format!("invalid set value '{}', '{}' is not an array index", assignment, key))?;
+        if index == array.len() {
+          array.push(Value::Null);
+        }
+        array
+          .get_mut(index)
+          .ok_or_else(|| format!("invalid set value '{}', array index {} is out of bounds", assignment, index))?
+      }
+      _ => return Err(format!("invalid set value '{}', '{}' is not an object or array", assignment, key)),
+    };
+  }
+  *current = value;
+  Ok(())
+}
+
+/// # Sends a request and prints the raw response
+///
+/// The `dsh_api` crate does not expose the raw responses of the api calls,
+/// therefore the request is sent directly to the resource management api,
+/// using the token and the tenant of `client`.
+/// The http status will be printed to stderr, regardless of the verbosity,
+/// and the unformatted response body will be printed to stdout.
+/// When the server returns an error status, the response body will be printed
+/// and an error matching the status will be returned.
+async fn raw_request(method: Method, method_descriptor: &MethodDescriptor, parameters: &[&str], body: Option<String>, client: &DshApiClient, context: &Context) -> DshCliResult {
+  let mut path = method_descriptor.path.replace("{tenant}", client.tenant_name());
+  for ((parameter_name, _, _), parameter) in method_descriptor.parameters.iter().zip(parameters) {
+    path = path.replace(&format!("{{{}}}", parameter_name), parameter);
+  }
+  let url = format!("{}{}", client.platform().rest_api_endpoint(), path);
+  debug!("raw request {} {}", method, url);
+  let api_call_log = ApiCallLog::start(method.as_str(), &url, body.as_deref());
+  let mut request = reqwest::Client::new().request(method, &url).header(AUTHORIZATION, client.token().await?);
+  if let Some(body) = body {
+    request = request.header(CONTENT_TYPE, "application/json").body(body);
+  }
+  let response = match request.send().await {
+    Ok(response) => response,
+    Err(error) => {
+      api_call_log.finish(None, None);
+      return Err(DshCliError::Api(DshApiError::Unexpected(
+        format!("communication error (reqwest error: {})", error),
+        None,
+      )));
+    }
+  };
+  let status = response.status();
+  let response_body = response.text().await;
+  api_call_log.finish(Some(status.as_u16()), response_body.as_deref().ok());
+  let response_body = response_body.map_err(|error| DshCliError::Api(DshApiError::Unexpected(format!("communication error (reqwest error: {})", error), None)))?;
+  context.print_status(format!("http status {}", status));
+  if !response_body.is_empty() {
+    context.print(&response_body);
+  }
+  if status.is_success() {
+    Ok(())
+  } else {
+    let details = Some(response_body).filter(|response_body| !response_body.is_empty());
+    Err(DshCliError::Api(match status.as_u16() {
+      400 => DshApiError::BadRequest(details.unwrap_or_default()),
+      401 | 403 => DshApiError::NotAuthorized(details),
+      404 => DshApiError::NotFound(details),
+      422 => DshApiError::Unprocessable(details),
+      _ => DshApiError::Unexpected(format!("{}{}", UNEXPECTED_RESPONSE_PREFIX, status), details),
+    }))
+  }
+}
+
+#[test]
+fn test_set_body_value() {
+  let mut body = serde_json::json!({ "cpus": 1.0, "env": { "A": "1" }, "metrics": null, "volumes": ["a", "b"] });
+  set_body_value(&mut body, "cpus=0.5").unwrap();
+  set_body_value(&mut body, "env.A=debug").unwrap();
+  set_body_value(&mut body, "env.B={\"key\": true}").unwrap();
+  set_body_value(&mut body, "metrics.port=9090").unwrap();
+  set_body_value(&mut body, "volumes.1=c").unwrap();
+  set_body_value(&mut body, "volumes.2=d").unwrap();
+  assert_eq!(
+    body,
+    serde_json::json!({ "cpus": 0.5, "env": { "A": "debug", "B": { "key": true } }, "metrics": { "port": 9090 }, "volumes": ["a", "c", "d"] })
+  );
+  assert!(set_body_value(&mut body, "cpus").is_err());
+  assert!(set_body_value(&mut body, "=1").is_err());
+  assert!(set_body_value(&mut body, "cpus.value=1").is_err());
+  assert!(set_body_value(&mut body, "volumes.x=1").is_err());
+  assert!(set_body_value(&mut body, "volumes.5=1").is_err());
+}
@@ -1,17 +1,28 @@
+use crate::error::DshCliError;
 use crate::formatters::formatter::{Label, SubjectFormatter};
 use async_trait::async_trait;
-use clap::ArgMatches;
+use clap::builder::ValueParser;
+use clap::{builder, Arg, ArgAction, ArgMatches};
+use dsh_api::app_manifest::{Manifest, Property};
 use dsh_api::dsh_api_client::DshApiClient;
-use dsh_api::types::AppCatalogApp;
+use dsh_api::types::{AppCatalogApp, AppCatalogAppConfiguration};
+use dsh_api::DshApiError;
+use futures::try_join;
 use lazy_static::lazy_static;
 use serde::Serialize;
 use serde_json::de::from_str;
+use serde_yaml::Value;
 use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
 
 use dsh_api::types::AppCatalogAppResourcesValue;
 
-use crate::arguments::app_id_argument;
-use crate::capability::{Capability, CommandExecutor, LIST_COMMAND, LIST_COMMAND_ALIAS, SHOW_COMMAND, SHOW_COMMAND_ALIAS};
+use crate::arguments::{app_id_argument, manifest_id_argument};
+use crate::capability::{
+  Capability, CommandExecutor, DELETE_COMMAND, DEPLOY_COMMAND, LIST_COMMAND, LIST_COMMAND_ALIAS, OUTDATED_COMMAND, SHOW_COMMAND, SHOW_COMMAND_ALIAS, UPDATE_COMMAND,
+};
 use crate::capability_builder::CapabilityBuilder;
 use crate::context::Context;
 use crate::flags::FlagType;
@@ -22,10 +33,12 @@ use crate::formatters::OutputFormat;
 use crate::subject::{Requirements, Subject};
 use crate::subjects::bucket::BUCKET_LABELS;
 use crate::subjects::certificate::CERTIFICATE_LABELS_SHOW;
+use crate::subjects::manifest::{find_manifest, manifest_urn, parse_manifest_urn};
 use crate::subjects::service::SERVICE_LABELS_SHOW;
 use crate::subjects::topic::TOPIC_LABELS;
 use crate::subjects::vhost::VHOST_LABELS;
 use crate::subjects::volume::VOLUME_LABELS;
+use crate::version::Version;
 use crate::DshCliResult;
 
 pub(crate) struct AppSubject {}
@@ -48,8 +61,12 @@ impl Subject for AppSubject {
 
   fn capability(&self, capability_command: &str) -> Option<&(dyn Capability + Send + Sync)> {
     match capability_command {
+      DELETE_COMMAND => Some(APP_DELETE_CAPABILITY.as_ref()),
+      DEPLOY_COMMAND => Some(APP_DEPLOY_CAPABILITY.as_ref()),
       LIST_COMMAND => Some(APP_LIST_CAPABILITY.as_ref()),
+      OUTDATED_COMMAND => Some(APP_OUTDATED_CAPABILITY.as_ref()),
       SHOW_COMMAND => Some(APP_SHOW_CAPABILITY.as_ref()),
+      UPDATE_COMMAND => Some(APP_UPDATE_CAPABILITY.as_ref()),
       _ => None,
     }
   }
@@ -59,18 +76,318 @@ impl Subject for AppSubject {
   }
 }
 
+const HELP_HEADING: &str = "App options";
+
+const APP_CONFIG_ARGUMENT: &str = "app-config-argument";
+const APP_NAME_ARGUMENT: &str = "app-name-argument";
+const MANIFEST_VERSION_ARGUMENT: &str = "manifest-version-argument";
+
 lazy_static! {
+  static ref APP_DELETE_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
+    CapabilityBuilder::new(DELETE_COMMAND, None, &AppDelete {}, "Delete app")
+      .set_long_about("Delete an app that was deployed from the DSH app catalog.")
+      .add_target_argument(app_id_argument().required(true))
+  );
+  static ref APP_DEPLOY_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
+    CapabilityBuilder::new(DEPLOY_COMMAND, None, &AppDeploy {}, "Deploy app from the app catalog")
+      .set_long_about(
+        "Deploy an app from a manifest in the DSH app catalog. \
+         The configuration values will be validated against the configuration schema \
+         of the manifest. Configuration parameters with a default value that are not provided \
+         will get their default value. \
+         When required configuration parameters are missing, you will be prompted for their values."
+      )
+      .add_target_argument(manifest_id_argument().required(true))
+      .add_extra_argument(app_name_argument().required(true).help_heading(HELP_HEADING))
+      .add_extra_argument(manifest_version_argument().help_heading(HELP_HEADING))
+      .add_extra_argument(app_config_argument().help_heading(HELP_HEADING))
+  );
   static ref APP_LIST_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
     CapabilityBuilder::new(LIST_COMMAND, Some(LIST_COMMAND_ALIAS), &AppListConfiguration {}, "List deployed apps")
       .set_long_about("Lists all apps deployed from the DSH app catalog.")
       .add_command_executor(FlagType::Ids, &AppListIds {}, None)
   );
+  static ref APP_OUTDATED_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
+    CapabilityBuilder::new(OUTDATED_COMMAND, None, &AppOutdated {}, "List outdated apps").set_long_about(
+      "Compare the manifest version of each deployed app with the latest version \
+         of the manifest in the DSH app catalog, and list the apps that can be upgraded. \
+         Draft manifests are not considered as upgrade candidates."
+    )
+  );
   static ref APP_SHOW_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
     CapabilityBuilder::new(SHOW_COMMAND, Some(SHOW_COMMAND_ALIAS), &AppShowAll {}, "Show app configuration")
       .set_long_about("Show the configuration of an app deployed from the DSH app catalog.")
       .add_target_argument(app_id_argument().required(true))
   );
-  static ref APP_CAPABILITIES: Vec<&'static (dyn Capability + Send + Sync)> = vec![APP_LIST_CAPABILITY.as_ref(), APP_SHOW_CAPABILITY.as_ref()];
+  static ref APP_UPDATE_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
+    CapabilityBuilder::new(UPDATE_COMMAND, None, &AppUpdate {}, "Update app")
+      .set_long_about(
+        "Update the configuration and/or the manifest version of an app \
+         that was deployed from the DSH app catalog. \
+         The provided configuration values will be merged with the current configuration \
+         and validated against the configuration schema of the manifest."
+      )
+      .add_target_argument(app_id_argument().required(true))
+      .add_extra_argument(manifest_version_argument().help_heading(HELP_HEADING))
+      .add_extra_argument(app_config_argument().help_heading(HELP_HEADING))
+  );
+  static ref APP_CAPABILITIES: Vec<&'static (dyn Capability + Send + Sync)> = vec![
+    APP_DELETE_CAPABILITY.as_ref(),
+    APP_DEPLOY_CAPABILITY.as_ref(),
+    APP_LIST_CAPABILITY.as_ref(),
+    APP_OUTDATED_CAPABILITY.as_ref(),
+    APP_SHOW_CAPABILITY.as_ref(),
+    APP_UPDATE_CAPABILITY.as_ref()
+  ];
+}
+
+fn app_config_argument() -> Arg {
+  Arg::new(APP_CONFIG_ARGUMENT)
+    .long("config")
+    .action(ArgAction::Set)
+    .value_parser(ValueParser::path_buf())
+    .value_name("FILE")
+    .help("App configuration values file")
+    .long_help("Yaml file with the configuration values for the app, as a map from parameter names to values.")
+}
+
+fn app_name_argument() -> Arg {
+  Arg::new(APP_NAME_ARGUMENT)
+    .long("name")
+    .action(ArgAction::Set)
+    .value_parser(builder::NonEmptyStringValueParser::new())
+    .value_name("APP")
+    .help("Name of the deployed app")
+    .long_help("Name that will identify the app deployed from the app catalog.")
+}
+
+fn manifest_version_argument() -> Arg {
+  Arg::new(MANIFEST_VERSION_ARGUMENT)
+    .long("version")
+    .action(ArgAction::Set)
+    .value_parser(builder::NonEmptyStringValueParser::new())
+    .value_name("VERSION")
+    .help("Manifest version")
+    .long_help("Version of the app catalog manifest. If this option is not provided, the latest version will be used.")
+}
+
+struct AppDelete {}
+
+#[async_trait]
+impl CommandExecutor for AppDelete {
+  async fn execute_with_client(&self, target: Option<String>, _: Option<String>, _: &ArgMatches, client: &DshApiClient, context: &Context) -> DshCliResult {
+    let app_id = target.unwrap_or_else(|| unreachable!());
+    context.print_explanation(format!("delete app '{}'", app_id));
+    match client.get_appcatalog_app_configuration(&app_id).await {
+      Ok(_) => (),
+      Err(DshApiError::NotFound(_)) => return Err(DshCliError::NotFound(format!("app '{}' does not exist", app_id))),
+      Err(error) => return Err(DshCliError::from(error)),
+    }
+    if context.confirmed(format!("delete app '{}'?", app_id))? {
+      if context.dry_run() {
+        context.print_warning("dry-run mode, app not deleted");
+      } else {
+        client.delete_appcatalog_app_configuration(&app_id).await?;
+        context.print_audited_outcome(client, format!("app/{}", app_id), format!("app '{}' deleted", app_id));
+      }
+    } else {
+      return Err(DshCliError::Cancelled(format!("cancelled, app '{}' not deleted", app_id)));
+    }
+    Ok(())
+  }
+
+  fn requirements(&self, _: &ArgMatches) -> Requirements {
+    Requirements::standard_with_api()
+  }
+}
+
+struct AppDeploy {}
+
+#[async_trait]
+impl CommandExecutor for AppDeploy {
+  async fn execute_with_client(&self, target: Option<String>, _: Option<String>, matches: &ArgMatches, client: &DshApiClient, context: &Context) -> DshCliResult {
+    let manifest_id = target.unwrap_or_else(|| unreachable!());
+    let app_id = matches.get_one::<String>(APP_NAME_ARGUMENT).unwrap_or_else(|| unreachable!()).to_string();
+    let version = matches
+      .get_one::<String>(MANIFEST_VERSION_ARGUMENT)
+      .map(|version| Version::from_str(version))
+      .transpose()?;
+    let values = match matches.get_one::<PathBuf>(APP_CONFIG_ARGUMENT) {
+      Some(config_file) => read_configuration_values(config_file)?,
+      None => HashMap::new(),
+    };
+    match version {
+      Some(ref version) => context.print_explanation(format!("deploy app '{}' from manifest '{}', version {}", app_id, manifest_id, version)),
+      None => context.print_explanation(format!("deploy app '{}' from manifest '{}', latest version", app_id, manifest_id)),
+    }
+    match client.get_appcatalog_app_configuration(&app_id).await {
+      Ok(_) => return Err(DshCliError::Conflict(format!("app '{}' already exists", app_id))),
+      Err(DshApiError::NotFound(_)) => (),
+      Err(error) => return Err(DshCliError::from(error)),
+    }
+    let manifest = find_manifest(&manifest_id, version.as_ref(), client).await?;
+    let configuration = resolve_configuration(&manifest, values, context)?;
+    let app_configuration = AppCatalogAppConfiguration { configuration, manifest_urn: manifest_urn(&manifest), name: app_id.clone(), stopped: false };
+    if context.dry_run() {
+      context.print_serializable(&app_configuration, None)?;
+      context.print_warning("dry-run mode, app not deployed");
+    } else {
+      client.put_appcatalog_app_configuration(&app_id, &app_configuration).await?;
+      context.print_audited_outcome(
+        client,
+        format!("app/{}", app_id),
+        format!("app '{}' deployed from manifest '{}', version {}", app_id, manifest_id, manifest.version),
+      );
+    }
+    Ok(())
+  }
+
+  fn requirements(&self, _: &ArgMatches) -> Requirements {
+    Requirements::standard_with_api()
+  }
+}
+
+struct AppUpdate {}
+
+#[async_trait]
+impl CommandExecutor for AppUpdate {
+  async fn execute_with_client(&self, target: Option<String>, _: Option<String>, matches: &ArgMatches, client: &DshApiClient, context: &Context) -> DshCliResult {
+    let app_id = target.unwrap_or_else(|| unreachable!());
+    let version = matches
+      .get_one::<String>(MANIFEST_VERSION_ARGUMENT)
+      .map(|version| Version::from_str(version))
+      .transpose()?;
+    let values = match matches.get_one::<PathBuf>(APP_CONFIG_ARGUMENT) {
+      Some(config_file) => read_configuration_values(config_file)?,
+      None => HashMap::new(),
+    };
+    context.print_explanation(format!("update app '{}'", app_id));
+    let current_configuration = match client.get_appcatalog_app_configuration(&app_id).await {
+      Ok(current_configuration) => current_configuration,
+      Err(DshApiError::NotFound(_)) => return Err(DshCliError::NotFound(format!("app '{}' does not exist", app_id))),
+      Err(error) => return Err(DshCliError::from(error)),
+    };
+    let (_, manifest_id, current_version) = parse_manifest_urn(&current_configuration.manifest_urn)
+      .ok_or_else(|| format!("could not parse manifest urn '{}' of app '{}'", current_configuration.manifest_urn, app_id))?;
+    let manifest = find_manifest(&manifest_id, Some(version.as_ref().unwrap_or(&current_version)), client).await?;
+    let mut merged_values = current_configuration.configuration.clone();
+    merged_values.extend(values);
+    let configuration = resolve_configuration(&manifest, merged_values, context)?;
+    let app_configuration = AppCatalogAppConfiguration { configuration, manifest_urn: manifest_urn(&manifest), ..current_configuration.clone() };
+    if app_configuration == current_configuration {
+      context.print_warning("provided arguments match current configuration, app not updated");
+    } else if context.dry_run() {
+      context.print_serializable(&app_configuration, None)?;
+      context.print_warning("dry-run mode, app not updated");
+    } else {
+      client.put_appcatalog_app_configuration(&app_id, &app_configuration).await?;
+      context.print_audited_change(
+        client,
+        format!("app/{}", app_id),
+        format!("app '{}' updated (manifest '{}', version {})", app_id, manifest_id, manifest.version),
+        Some(&current_configuration),
+        Some(&app_configuration),
+      );
+    }
+    Ok(())
+  }
+
+  fn requirements(&self, _: &ArgMatches) -> Requirements {
+    Requirements::standard_with_api()
+  }
+}
+
+/// # Reads configuration values from a yaml file
+///
+/// The file must contain a map from parameter names to scalar values.
+fn read_configuration_values(config_file: &PathBuf) -> Result<HashMap<String, String>, String> {
+  let config_string = fs::read_to_string(config_file).map_err(|error| format!("could not read config file '{}' ({})", config_file.to_string_lossy(), error))?;
+  let values =
+    serde_yaml::from_str::<HashMap<String, Value>>(&config_string).map_err(|error| format!("could not parse config file '{}' ({})", config_file.to_string_lossy(), error))?;
+  values
+    .into_iter()
+    .map(|(parameter, value)| match value {
+      Value::Bool(boolean) => Ok((parameter, boolean.to_string())),
+      Value::Number(number) => Ok((parameter, number.to_string())),
+      Value::String(string) => Ok((parameter, string)),
+      _ => Err(format!("value of configuration parameter '{}' must be a string, number or boolean", parameter)),
+    })
+    .collect()
+}
+
+/// # Resolves the app configuration
+///
+/// Validates the provided values against the configuration schema of the manifest.
+/// Parameters that are not provided will get their default value.
+/// The user will be prompted for missing required parameters.
+/// Parameters starting with `@` are managed by the platform and will not be validated.
+fn resolve_configuration(manifest: &Manifest, mut values: HashMap<String, String>, context: &Context) -> Result<HashMap<String, String>, String> {
+  let properties = manifest
+    .configuration
+    .as_ref()
+    .map(|configuration| configuration.properties.clone())
+    .unwrap_or_default();
+  let mut unknown_parameters = values
+    .keys()
+    .filter(|parameter| !parameter.starts_with("@") && !properties.contains_key(*parameter))
+    .cloned()
+    .collect::<Vec<_>>();
+  if !unknown_parameters.is_empty() {
+    unknown_parameters.sort();
+    return Err(format!(
+      "unknown configuration parameter(s) {} for manifest '{}'",
+      unknown_parameters.join(", "),
+      manifest.id
+    ));
+  }
+  let mut parameters = properties.keys().collect::<Vec<_>>();
+  parameters.sort();
+  for parameter in parameters {
+    let property = properties.get(parameter).unwrap();
+    let value = match values.get(parameter) {
+      Some(value) => value.clone(),
+      None => match property.default {
+        Some(ref default) => default.clone(),
+        None => {
+          if context.stdin_is_terminal() {
+            context.read_single_line(format!("enter value for {} ({}): ", parameter, property.description))?
+          } else {
+            return Err(format!("required configuration parameter '{}' is missing", parameter));
+          }
+        }
+      },
+    };
+    validate_value(parameter, &value, property)?;
+    values.insert(parameter.to_string(), value);
+  }
+  Ok(values)
+}
+
+fn validate_value(parameter: &str, value: &str, property: &Property) -> Result<(), String> {
+  if let Some(ref enumeration) = property.r#enum {
+    if !enumeration.iter().any(|option| option == value) {
+      return Err(format!(
+        "value '{}' for configuration parameter '{}' must be one of {}",
+        value,
+        parameter,
+        enumeration.join(", ")
+      ));
+    }
+  }
+  let valid = match property.r#type.as_str() {
+    "boolean" => value == "true" || value == "false",
+    "integer" => value.parse::<i64>().is_ok(),
+    "number" => value.parse::<f64>().is_ok(),
+    _ => true,
+  };
+  if valid {
+    Ok(())
+  } else {
+    Err(format!(
+      "value '{}' for configuration parameter '{}' must be of type {}",
+      value, parameter, property.r#type
+    ))
+  }
 }
 
 struct AppListConfiguration {}
@@ -118,6 +435,50 @@ impl CommandExecutor for AppListIds {
   }
 }
 
+struct AppOutdated {}
+
+#[async_trait]
+impl CommandExecutor for AppOutdated {
+  async fn execute_with_client(&self, _: Option<String>, _: Option<String>, _: &ArgMatches, client: &DshApiClient, context: &Context) -> DshCliResult {
+    context.print_explanation("list deployed apps for which a newer manifest version is available");
+    let start_instant = context.now();
+    let (apps, app_catalog_manifests) = try_join!(client.get_appcatalogapp_configuration_map(), client.get_appcatalog_manifests())?;
+    context.print_execution_time(start_instant);
+    let mut latest_versions: HashMap<String, Version> = HashMap::new();
+    for manifest in app_catalog_manifests.iter().filter(|acm| !acm.draft).filter_map(|acm| Manifest::try_from(acm).ok()) {
+      if let Ok(version) = Version::from_str(&manifest.version) {
+        if latest_versions.get(&manifest.id).is_none_or(|latest_version| latest_version < &version) {
+          latest_versions.insert(manifest.id, version);
+        }
+      }
+    }
+    let mut outdated_apps = apps
+      .iter()
+      .filter_map(|(app_id, app)| {
+        parse_manifest_urn(&app.manifest_urn).and_then(|(_, manifest_id, current_version)| match latest_versions.get(&manifest_id) {
+          Some(latest_version) if latest_version > &current_version => {
+            Some(OutdatedApp { app: app_id.to_string(), manifest: manifest_id, current_version, latest_version: latest_version.clone() })
+          }
+          _ => None,
+        })
+      })
+      .collect::<Vec<_>>();
+    if outdated_apps.is_empty() {
+      context.print_outcome("all deployed apps use the latest manifest version");
+    } else {
+      outdated_apps.sort_by(|outdated_app_a, outdated_app_b| outdated_app_a.app.cmp(&outdated_app_b.app));
+      let mut formatter = ListFormatter::new(&OUTDATED_APP_LABELS, None, context);
+      formatter.push_values(&outdated_apps);
+      formatter.print(None)?;
+    }
+    Ok(())
+  }
+
+  fn requirements(&self, _: &ArgMatches) -> Requirements {
+    Requirements::standard_with_api()
+  }
+}
+
 struct AppShowAll {}
 
 #[async_trait]
@@ -210,3 +571,61 @@ impl SubjectFormatter<AppCatalogAppLabel> for AppCatalogApp {
 }
 
 pub static APP_CATALOG_APP_LABELS: [AppCatalogAppLabel; 3] = [AppCatalogAppLabel::Target, AppCatalogAppLabel::ManifestUrl, AppCatalogAppLabel::Configuration];
+
+/// # Deployed app for which a newer manifest version is available
+#[derive(Serialize)]
+pub(crate) struct OutdatedApp {
+  app: String,
+  manifest: String,
+  current_version: Version,
+  latest_version: Version,
+}
+
+#[derive(Eq, Hash, PartialEq, Serialize)]
+pub(crate) enum OutdatedAppLabel {
+  App,
+  CurrentVersion,
+  LatestVersion,
+  Manifest,
+}
+
+impl Label for OutdatedAppLabel {
+  fn as_str(&self) -> &str {
+    match self {
+      OutdatedAppLabel::App => "app id",
+      OutdatedAppLabel::CurrentVersion => "current version",
+      OutdatedAppLabel::LatestVersion => "latest version",
+      OutdatedAppLabel::Manifest => "manifest id",
+    }
+  }
+
+  fn as_str_for_list(&self) -> &str {
+    match self {
+      OutdatedAppLabel::App => "app",
+      OutdatedAppLabel::CurrentVersion => "current",
+      OutdatedAppLabel::LatestVersion => "latest",
+      OutdatedAppLabel::Manifest => "manifest",
+    }
+  }
+
+  fn is_target_label(&self) -> bool {
+    matches!(self, Self::App)
+  }
+}
+
+impl SubjectFormatter<OutdatedAppLabel> for OutdatedApp {
+  fn value(&self, label: &OutdatedAppLabel, _target_id: &str) -> String {
+    match label {
+      OutdatedAppLabel::App => self.app.clone(),
+      OutdatedAppLabel::CurrentVersion => self.current_version.to_string(),
+      OutdatedAppLabel::LatestVersion => self.latest_version.to_string(),
+      OutdatedAppLabel::Manifest => self.manifest.clone(),
+    }
+  }
+
+  fn target_id(&self) -> Option<String> {
+    Some(self.app.clone())
+  }
+}
+
+static OUTDATED_APP_LABELS: [OutdatedAppLabel; 4] = [OutdatedAppLabel::App, OutdatedAppLabel::Manifest, OutdatedAppLabel::CurrentVersion, OutdatedAppLabel::LatestVersion];
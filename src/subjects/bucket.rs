@@ -121,7 +121,8 @@ impl CommandExecutor for BucketShowAll {
     let start_instant = context.now();
     let bucket = client.get_bucket(&bucket_id).await?;
     context.print_execution_time(start_instant);
-    UnitFormatter::new(bucket_id, &BUCKET_STATUS_LABELS, None, context).print(&bucket, None)
+    UnitFormatter::new(bucket_id, &BUCKET_STATUS_LABELS, None, context).print(&bucket, None)?;
+    Ok(())
   }
 
   fn requirements(&self, _: &ArgMatches) -> Requirements {
@@ -228,7 +228,8 @@ impl CommandExecutor for CertificateShowAllocationStatus {
     let start_instant = context.now();
     let allocation_status = client.get_certificate_status(&certificate_id).await?;
     context.print_execution_time(start_instant);
-    UnitFormatter::new(certificate_id, &DEFAULT_ALLOCATION_STATUS_LABELS, Some("certificate id"), context).print(&allocation_status, None)
+    UnitFormatter::new(certificate_id, &DEFAULT_ALLOCATION_STATUS_LABELS, Some("certificate id"), context).print(&allocation_status, None)?;
+    Ok(())
   }
 
   fn requirements(&self, _: &ArgMatches) -> Requirements {
@@ -0,0 +1,410 @@
+use crate::capability::{Capability, CommandExecutor};
+use crate::capability_builder::CapabilityBuilder;
+use crate::context::Context;
+use crate::error::DshCliError;
+use crate::formatters::formatter::{Label, SubjectFormatter};
+use crate::formatters::list_formatter::ListFormatter;
+use crate::snapshots::configuration_differences;
+use crate::subject::{Requirements, Subject};
+use crate::subjects::vhost::parse_vhost_string;
+use crate::{create_client_for_tenant, DshCliResult};
+use async_trait::async_trait;
+use clap::builder;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use dsh_api::app::{application_resources_from_app, vhosts_from_app};
+use dsh_api::dsh_api_client::DshApiClient;
+use dsh_api::platform::DshPlatform;
+use dsh_api::types::Application;
+use futures::try_join;
+use lazy_static::lazy_static;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::{Display, Formatter};
+
+pub(crate) struct CompareSubject {}
+
+const COMPARE_SUBJECT_TARGET: &str = "compare";
+
+const FROM_ARGUMENT: &str = "from-argument";
+const TO_ARGUMENT: &str = "to-argument";
+
+lazy_static! {
+  pub static ref COMPARE_SUBJECT: Box<dyn Subject + Send + Sync> = Box::new(CompareSubject {});
+}
+
+/// The compare subject has no capability subcommands,
+/// `dsh compare` directly executes the compare capability.
+#[async_trait]
+impl Subject for CompareSubject {
+  fn subject(&self) -> &'static str {
+    COMPARE_SUBJECT_TARGET
+  }
+
+  fn subject_command_about(&self) -> String {
+    "Compare the resources of two targets.".to_string()
+  }
+
+  fn subject_command_long_about(&self) -> String {
+    "Compare the resources of two targets, e.g. the acceptance and production environments of a tenant. \
+    The report lists the services, apps, topics, secrets, volumes, buckets and vhosts that exist \
+    for only one of the targets. For the services and apps that exist for both targets, \
+    the differences in their configurations are listed, like image, cpus, mem, instances and env values. \
+    Secrets are only compared by name. The vhosts are collected from the services, \
+    and from the vhost resources and services of the apps. \
+    For the target tenant the password is determined as usual. For another tenant, \
+    the password is read from the keyring when the tenant is a stored target, \
+    else the user will be prompted for the password."
+      .to_string()
+  }
+
+  fn capability(&self, _capability_command: &str) -> Option<&(dyn Capability + Send + Sync)> {
+    None
+  }
+
+  fn capabilities(&self) -> &Vec<&(dyn Capability + Send + Sync)> {
+    &COMPARE_CAPABILITIES
+  }
+
+  fn subject_command(&self) -> (String, Command) {
+    let subject_command = COMPARE_TARGETS_CAPABILITY
+      .clap_capability_command(self.subject())
+      .about(self.subject_command_about())
+      .long_about(self.subject_command_long_about());
+    (self.subject().to_string(), subject_command)
+  }
+
+  fn requirements(&self, subject_matches: &ArgMatches) -> Requirements {
+    COMPARE_TARGETS_CAPABILITY.requirements(subject_matches)
+  }
+
+  async fn execute_subject_command_with_client<'a>(&self, subject_matches: &'a ArgMatches, dsh_api_client: &DshApiClient, context: &Context) -> DshCliResult {
+    COMPARE_TARGETS_CAPABILITY
+      .execute_capability_with_client(None, None, subject_matches, dsh_api_client, context)
+      .await
+  }
+}
+
+lazy_static! {
+  static ref COMPARE_TARGETS_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
+    CapabilityBuilder::new(COMPARE_SUBJECT_TARGET, None, &CompareTargets {}, "Compare the resources of two targets")
+      .add_extra_argument(from_argument())
+      .add_extra_argument(to_argument())
+  );
+  static ref COMPARE_CAPABILITIES: Vec<&'static (dyn Capability + Send + Sync)> = vec![];
+}
+
+fn from_argument() -> Arg {
+  Arg::new(FROM_ARGUMENT)
+    .long("from")
+    .action(ArgAction::Set)
+    .value_parser(builder::NonEmptyStringValueParser::new())
+    .value_name("TARGET")
+    .help("Target to compare from")
+    .long_help(
+      "Target to compare from, as 'tenant@platform' (e.g. 'greenbox-dev@nplz'), \
+      or just the tenant name for a tenant on the target platform. \
+      When this argument is not provided, the target tenant will be used.",
+    )
+}
+
+fn to_argument() -> Arg {
+  Arg::new(TO_ARGUMENT)
+    .long("to")
+    .action(ArgAction::Set)
+    .value_parser(builder::NonEmptyStringValueParser::new())
+    .value_name("TARGET")
+    .required(true)
+    .help("Target to compare to")
+    .long_help(
+      "Target to compare to, as 'tenant@platform' (e.g. 'greenbox@prod'), \
+      or just the tenant name for a tenant on the target platform.",
+    )
+}
+
+struct CompareTargets {}
+
+#[async_trait]
+impl CommandExecutor for CompareTargets {
+  async fn execute_with_client(&self, _: Option<String>, _: Option<String>, matches: &ArgMatches, client: &DshApiClient, context: &Context) -> DshCliResult {
+    let (from_platform, from_tenant) = compared_target(matches.get_one::<String>(FROM_ARGUMENT), client)?;
+    let (to_platform, to_tenant) = compared_target(matches.get_one::<String>(TO_ARGUMENT), client)?;
+    let from_client = compared_client(&from_platform, &from_tenant, client).await?;
+    let to_client = compared_client(&to_platform, &to_tenant, client).await?;
+    context.print_explanation(format!("compare {}@{} with {}@{}", from_tenant, from_platform, to_tenant, to_platform));
+    let start_instant = context.now();
+    let (from_resources, to_resources) = try_join!(
+      TargetResources::read(from_client.as_ref().unwrap_or(client)),
+      TargetResources::read(to_client.as_ref().unwrap_or(client))
+    )?;
+    context.print_execution_time(start_instant);
+    let differences = target_differences(&from_resources, &to_resources)?;
+    if differences.is_empty() {
+      context.print_outcome("no differences found");
+    } else {
+      let mut formatter = ListFormatter::new(&TARGET_DIFFERENCE_LABELS, None, context);
+      formatter.push_values(&differences);
+      formatter.print(None)?;
+    }
+    Ok(())
+  }
+
+  fn requirements(&self, _: &ArgMatches) -> Requirements {
+    Requirements::standard_with_api()
+  }
+}
+
+// Determines the platform and tenant of a compared target,
+// from a `tenant@platform` or `tenant` argument, or else the target tenant
+fn compared_target(target: Option<&String>, client: &DshApiClient) -> Result<(DshPlatform, String), String> {
+  match target {
+    Some(target) => match target.split_once('@') {
+      Some((tenant, platform)) => Ok((DshPlatform::try_from(platform)?, tenant.to_string())),
+      None => Ok((client.platform().clone(), target.to_string())),
+    },
+    None => Ok((client.platform().clone(), client.tenant_name().to_string())),
+  }
+}
+
+// Creates a client for a compared target, with its own password,
+// or returns `None` when the compared target is the target tenant
+async fn compared_client(platform: &DshPlatform, tenant: &str, client: &DshApiClient) -> Result<Option<DshApiClient>, String> {
+  if platform == client.platform() && tenant == client.tenant_name() {
+    Ok(None)
+  } else {
+    Ok(Some(create_client_for_tenant(tenant, platform).await?))
+  }
+}
+
+/// # Resources of a target
+///
+/// The configurations of services and apps are kept as json values,
+/// such that they can be compared field by field.
+#[derive(Default)]
+struct TargetResources {
+  services: BTreeMap<String, Value>,
+  apps: BTreeMap<String, Value>,
+  topics: BTreeSet<String>,
+  secrets: BTreeSet<String>,
+  volumes: BTreeSet<String>,
+  buckets: BTreeSet<String>,
+  vhosts: BTreeSet<String>,
+}
+
+impl TargetResources {
+  async fn read(client: &DshApiClient) -> Result<Self, DshCliError> {
+    let (services, apps, topics, secrets, volumes, buckets) = try_join!(
+      client.get_application_configuration_map(),
+      client.get_appcatalogapp_configuration_map(),
+      client.get_topic_ids(),
+      client.get_secret_ids(),
+      client.get_volume_ids(),
+      client.get_bucket_ids()
+    )?;
+    let mut vhosts = services.values().flat_map(vhosts_from_service).collect::<BTreeSet<_>>();
+    for app in apps.values() {
+      vhosts.extend(vhosts_from_app(app).into_iter().map(|(vhost, _)| vhost));
+      for (_, service) in application_resources_from_app(app).unwrap_or_default() {
+        vhosts.extend(vhosts_from_service(service));
+      }
+    }
+    Ok(Self {
+      services: to_values(services)?,
+      apps: to_values(apps)?,
+      topics: topics.into_iter().collect(),
+      secrets: secrets.into_iter().collect(),
+      volumes: volumes.into_iter().collect(),
+      buckets: buckets.into_iter().collect(),
+      vhosts,
+    })
+  }
+}
+
+fn vhosts_from_service(service: &Application) -> Vec<String> {
+  service
+    .exposed_ports
+    .values()
+    .filter_map(|port_mapping| port_mapping.vhost.as_ref())
+    .filter_map(|vhost_string| parse_vhost_string(vhost_string).ok())
+    .map(|(vhost, _, _, _)| vhost)
+    .collect()
+}
+
+fn to_values<C: Serialize>(configurations: HashMap<String, C>) -> Result<BTreeMap<String, Value>, String> {
+  configurations
+    .into_iter()
+    .map(|(id, configuration)| serde_json::to_value(configuration).map(|value| (id, value)).map_err(|error| error.to_string()))
+    .collect()
+}
+
+/// # Difference between the resources of two targets
+#[derive(Debug, PartialEq, Serialize)]
+struct TargetDifference {
+  kind: &'static str,
+  resource: String,
+  item: Option<String>,
+  difference: Difference,
+  from: Option<String>,
+  to: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum Difference {
+  OnlyFrom,
+  OnlyTo,
+  Changed,
+}
+
+impl Display for Difference {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Difference::OnlyFrom => write!(f, "only from"),
+      Difference::OnlyTo => write!(f, "only to"),
+      Difference::Changed => write!(f, "changed"),
+    }
+  }
+}
+
+fn target_differences(from: &TargetResources, to: &TargetResources) -> Result<Vec<TargetDifference>, String> {
+  let mut differences = vec![];
+  push_configuration_differences(&mut differences, "service", &from.services, &to.services)?;
+  push_configuration_differences(&mut differences, "app", &from.apps, &to.apps)?;
+  push_id_differences(&mut differences, "topic", &from.topics, &to.topics);
+  push_id_differences(&mut differences, "secret", &from.secrets, &to.secrets);
+  push_id_differences(&mut differences, "volume", &from.volumes, &to.volumes);
+  push_id_differences(&mut differences, "bucket", &from.buckets, &to.buckets);
+  push_id_differences(&mut differences, "vhost", &from.vhosts, &to.vhosts);
+  Ok(differences)
+}
+
+fn push_configuration_differences(differences: &mut Vec<TargetDifference>, kind: &'static str, from: &BTreeMap<String, Value>, to: &BTreeMap<String, Value>) -> Result<(), String> {
+  let ids = from.keys().chain(to.keys()).collect::<BTreeSet<_>>();
+  for id in ids {
+    match (from.get(id), to.get(id)) {
+      (Some(from_configuration), Some(to_configuration)) => {
+        for configuration_difference in configuration_differences(from_configuration, to_configuration)? {
+          differences.push(TargetDifference {
+            kind,
+            resource: id.to_string(),
+            item: Some(configuration_difference.item),
+            difference: Difference::Changed,
+            from: configuration_difference.from,
+            to: configuration_difference.to,
+          });
+        }
+      }
+      (Some(_), None) => differences.push(TargetDifference::only_from(kind, id)),
+      (None, Some(_)) => differences.push(TargetDifference::only_to(kind, id)),
+      (None, None) => (),
+    }
+  }
+  Ok(())
+}
+
+fn push_id_differences(differences: &mut Vec<TargetDifference>, kind: &'static str, from: &BTreeSet<String>, to: &BTreeSet<String>) {
+  for id in from.union(to) {
+    match (from.contains(id), to.contains(id)) {
+      (true, false) => differences.push(TargetDifference::only_from(kind, id)),
+      (false, true) => differences.push(TargetDifference::only_to(kind, id)),
+      _ => (),
+    }
+  }
+}
+
+impl TargetDifference {
+  fn only_from(kind: &'static str, resource: &str) -> Self {
+    Self { kind, resource: resource.to_string(), item: None, difference: Difference::OnlyFrom, from: Some(resource.to_string()), to: None }
+  }
+
+  fn only_to(kind: &'static str, resource: &str) -> Self {
+    Self { kind, resource: resource.to_string(), item: None, difference: Difference::OnlyTo, from: None, to: Some(resource.to_string()) }
+  }
+}
+
+#[derive(Eq, Hash, PartialEq, Serialize)]
+enum TargetDifferenceLabel {
+  Difference,
+  From,
+  Item,
+  Kind,
+  Resource,
+  To,
+}
+
+impl Label for TargetDifferenceLabel {
+  fn as_str(&self) -> &str {
+    match self {
+      Self::Difference => "difference",
+      Self::From => "from",
+      Self::Item => "item",
+      Self::Kind => "kind",
+      Self::Resource => "resource",
+      Self::To => "to",
+    }
+  }
+
+  fn is_target_label(&self) -> bool {
+    matches!(self, Self::Resource)
+  }
+}
+
+impl SubjectFormatter<TargetDifferenceLabel> for TargetDifference {
+  fn value(&self, label: &TargetDifferenceLabel, _target_id: &str) -> String {
+    match label {
+      TargetDifferenceLabel::Difference => self.difference.to_string(),
+      TargetDifferenceLabel::From => self.from.clone().unwrap_or_default(),
+      TargetDifferenceLabel::Item => self.item.clone().unwrap_or_default(),
+      TargetDifferenceLabel::Kind => self.kind.to_string(),
+      TargetDifferenceLabel::Resource => self.resource.clone(),
+      TargetDifferenceLabel::To => self.to.clone().unwrap_or_default(),
+    }
+  }
+
+  fn target_id(&self) -> Option<String> {
+    Some(self.resource.clone())
+  }
+}
+
+static TARGET_DIFFERENCE_LABELS: [TargetDifferenceLabel; 6] = [
+  TargetDifferenceLabel::Kind,
+  TargetDifferenceLabel::Resource,
+  TargetDifferenceLabel::Difference,
+  TargetDifferenceLabel::Item,
+  TargetDifferenceLabel::From,
+  TargetDifferenceLabel::To,
+];
+
+#[test]
+fn test_target_differences() {
+  use serde_json::json;
+  let from = TargetResources {
+    services: BTreeMap::from([
+      ("consumer".to_string(), json!({ "image": "consumer:1.0.0", "instances": 1 })),
+      ("greeter".to_string(), json!({ "image": "greeter:1.0.0", "env": { "GREETING": "hello" } })),
+    ]),
+    topics: BTreeSet::from(["scratch.greetings".to_string()]),
+    secrets: BTreeSet::from(["api-key".to_string(), "db-password".to_string()]),
+    ..Default::default()
+  };
+  let to = TargetResources {
+    services: BTreeMap::from([("greeter".to_string(), json!({ "image": "greeter:1.1.0", "env": { "GREETING": "hello" } }))]),
+    topics: BTreeSet::from(["scratch.greetings".to_string()]),
+    secrets: BTreeSet::from(["api-key".to_string(), "token".to_string()]),
+    ..Default::default()
+  };
+  let differences = target_differences(&from, &to)
+    .unwrap()
+    .into_iter()
+    .map(|difference| (difference.kind, difference.resource, difference.item, difference.difference))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    differences,
+    vec![
+      ("service", "consumer".to_string(), None, Difference::OnlyFrom),
+      ("service", "greeter".to_string(), Some("image".to_string()), Difference::Changed),
+      ("secret", "db-password".to_string(), None, Difference::OnlyFrom),
+      ("secret", "token".to_string(), None, Difference::OnlyTo),
+    ]
+  );
+}
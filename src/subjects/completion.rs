@@ -0,0 +1,82 @@
+use crate::capability::{Capability, CommandExecutor, REFRESH_COMMAND};
+use crate::capability_builder::CapabilityBuilder;
+use crate::completion::refresh_completion_cache;
+use crate::context::Context;
+use crate::subject::{Requirements, Subject};
+use crate::DshCliResult;
+use async_trait::async_trait;
+use clap::ArgMatches;
+use dsh_api::dsh_api_client::DshApiClient;
+use lazy_static::lazy_static;
+
+pub(crate) struct CompletionSubject {}
+
+const COMPLETION_SUBJECT_TARGET: &str = "completion";
+
+lazy_static! {
+  pub static ref COMPLETION_SUBJECT: Box<dyn Subject + Send + Sync> = Box::new(CompletionSubject {});
+}
+
+#[async_trait]
+impl Subject for CompletionSubject {
+  fn subject(&self) -> &'static str {
+    COMPLETION_SUBJECT_TARGET
+  }
+
+  fn subject_command_about(&self) -> String {
+    "Manage the shell completion cache.".to_string()
+  }
+
+  fn subject_command_long_about(&self) -> String {
+    "Manage the cache that is used for the dynamic shell completion of resource identifiers. \
+    Dynamic completion is enabled by sourcing the output of 'COMPLETE=<shell> dsh' \
+    from the shell's startup script, e.g. 'source <(COMPLETE=bash dsh)'. \
+    The resource identifiers are cached per target for five minutes, \
+    in the directory 'completion' in the dsh tool's home directory."
+      .to_string()
+  }
+
+  fn capability(&self, capability_command: &str) -> Option<&(dyn Capability + Send + Sync)> {
+    match capability_command {
+      REFRESH_COMMAND => Some(COMPLETION_REFRESH_CAPABILITY.as_ref()),
+      _ => None,
+    }
+  }
+
+  fn capabilities(&self) -> &Vec<&(dyn Capability + Send + Sync)> {
+    &COMPLETION_CAPABILITIES
+  }
+}
+
+lazy_static! {
+  static ref COMPLETION_REFRESH_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
+    CapabilityBuilder::new(REFRESH_COMMAND, None, &CompletionRefresh {}, "Rebuild completion cache")
+      .set_long_about("Fetch the identifiers of all resources of the target tenant and rebuild the shell completion cache.")
+  );
+  static ref COMPLETION_CAPABILITIES: Vec<&'static (dyn Capability + Send + Sync)> = vec![COMPLETION_REFRESH_CAPABILITY.as_ref()];
+}
+
+struct CompletionRefresh {}
+
+#[async_trait]
+impl CommandExecutor for CompletionRefresh {
+  async fn execute_with_client(&self, _: Option<String>, _: Option<String>, _: &ArgMatches, client: &DshApiClient, context: &Context) -> DshCliResult {
+    context.print_explanation("rebuild completion cache");
+    let start_instant = context.now();
+    let counts = refresh_completion_cache(client).await?;
+    context.print_execution_time(start_instant);
+    context.print_outcome(format!(
+      "completion cache rebuilt ({})",
+      counts
+        .iter()
+        .map(|(resource_type, count)| format!("{}: {}", resource_type, count))
+        .collect::<Vec<_>>()
+        .join(", ")
+    ));
+    Ok(())
+  }
+
+  fn requirements(&self, _: &ArgMatches) -> Requirements {
+    Requirements::standard_with_api()
+  }
+}
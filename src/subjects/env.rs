@@ -14,7 +14,7 @@ use dsh_api::dsh_api_client::DshApiClient;
 use dsh_api::query_processor::{ExactMatchQueryProcessor, QueryProcessor, RegexQueryProcessor};
 use lazy_static::lazy_static;
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::hash::Hash;
 
 pub(crate) struct EnvSubject {}
@@ -86,7 +86,7 @@ impl CommandExecutor for EnvFind {
     let mut service_pairs = services.iter().collect::<Vec<_>>();
     service_pairs.sort_by(|(service_id_a, _), (service_id_b, _)| service_id_a.cmp(service_id_b));
 
-    let mut matching_services: Vec<(String, HashMap<ServiceEnvLabel, String>)> = vec![];
+    let mut matching_services: Vec<(String, BTreeMap<ServiceEnvLabel, String>)> = vec![];
     for (service_id, service) in service_pairs {
       if (service.instances > 0 && include_started) || (service.instances == 0 && include_stopped) {
         let mut envs: Vec<(String, String)> = service
@@ -100,7 +100,7 @@ impl CommandExecutor for EnvFind {
           .collect();
         envs.sort_by_key(|env| env.0.clone());
         for (key, value) in envs {
-          let mut env_map: HashMap<ServiceEnvLabel, String> = HashMap::new();
+          let mut env_map: BTreeMap<ServiceEnvLabel, String> = BTreeMap::new();
           env_map.insert(ServiceEnvLabel::Instances, service.instances.to_string());
           env_map.insert(ServiceEnvLabel::EnvVar, key);
           env_map.insert(ServiceEnvLabel::Value, value);
@@ -123,7 +123,8 @@ impl CommandExecutor for EnvFind {
   }
 }
 
-#[derive(Debug, Eq, Hash, PartialEq, Serialize)]
+#[derive(Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "kebab-case")]
 enum ServiceEnvLabel {
   EnvVar,
   Instances,
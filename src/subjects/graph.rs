@@ -0,0 +1,496 @@
+use crate::capability::{Capability, CommandExecutor};
+use crate::capability_builder::CapabilityBuilder;
+use crate::context::Context;
+use crate::error::DshCliError;
+use crate::subject::{Requirements, Subject};
+use crate::DshCliResult;
+use async_trait::async_trait;
+use clap::builder::{EnumValueParser, NonEmptyStringValueParser};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use dsh_api::app::bucket_resources_from_app;
+use dsh_api::dsh_api_client::DshApiClient;
+use dsh_api::UsedBy;
+use futures::try_join;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::fmt::{Display, Formatter};
+
+pub(crate) struct GraphSubject {}
+
+const GRAPH_SUBJECT_TARGET: &str = "graph";
+
+const AROUND_ARGUMENT: &str = "around-argument";
+const DEPTH_ARGUMENT: &str = "depth-argument";
+const FORMAT_ARGUMENT: &str = "format-argument";
+
+const DEFAULT_DEPTH: usize = 1;
+
+const APP: &str = "app";
+const BUCKET: &str = "bucket";
+const CERTIFICATE: &str = "certificate";
+const SECRET: &str = "secret";
+const SERVICE: &str = "service";
+const STREAM: &str = "stream";
+const TOPIC: &str = "topic";
+const VHOST: &str = "vhost";
+const VOLUME: &str = "volume";
+
+const NODE_KINDS: [&str; 9] = [APP, BUCKET, CERTIFICATE, SECRET, SERVICE, STREAM, TOPIC, VHOST, VOLUME];
+
+lazy_static! {
+  static ref STREAM_REGEX: Regex = Regex::new(r"\bstream\.([a-zA-Z0-9-]+)").unwrap();
+}
+
+#[derive(clap::ValueEnum, Clone, Debug, Default, PartialEq)]
+pub(crate) enum GraphFormat {
+  /// Graphviz dot language
+  #[default]
+  Dot,
+  /// Json object with the nodes and edges
+  Json,
+  /// Mermaid flowchart
+  Mermaid,
+}
+
+lazy_static! {
+  pub static ref GRAPH_SUBJECT: Box<dyn Subject + Send + Sync> = Box::new(GraphSubject {});
+}
+
+/// The graph subject has no capability subcommands,
+/// `dsh graph` directly executes the graph export capability.
+#[async_trait]
+impl Subject for GraphSubject {
+  fn subject(&self) -> &'static str {
+    GRAPH_SUBJECT_TARGET
+  }
+
+  fn subject_command_about(&self) -> String {
+    "Export the dependency graph of the target tenant.".to_string()
+  }
+
+  fn subject_command_long_about(&self) -> String {
+    "Export the dependency graph of the target tenant. The graph contains the services and apps \
+    of the tenant and the secrets, volumes, buckets, topics, streams, vhosts and certificates they use, \
+    with an edge from each service or app to each resource that it uses. \
+    The edges are labeled with the injections, e.g. the environment variables. \
+    Usage of streams is derived from the readable and writable streams of the services, \
+    labeled read and write, supplemented with the streams referenced in their environment variables. \
+    Usage of buckets and topics is derived from the environment variables of the services, \
+    like the --usage flag does. The graph can be exported in the graphviz dot language, \
+    as a mermaid flowchart or as json."
+      .to_string()
+  }
+
+  fn capability(&self, _capability_command: &str) -> Option<&(dyn Capability + Send + Sync)> {
+    None
+  }
+
+  fn capabilities(&self) -> &Vec<&(dyn Capability + Send + Sync)> {
+    &GRAPH_CAPABILITIES
+  }
+
+  fn subject_command(&self) -> (String, Command) {
+    let subject_command = GRAPH_EXPORT_CAPABILITY
+      .clap_capability_command(self.subject())
+      .about(self.subject_command_about())
+      .long_about(self.subject_command_long_about());
+    (self.subject().to_string(), subject_command)
+  }
+
+  fn requirements(&self, subject_matches: &ArgMatches) -> Requirements {
+    GRAPH_EXPORT_CAPABILITY.requirements(subject_matches)
+  }
+
+  async fn execute_subject_command_with_client<'a>(&self, subject_matches: &'a ArgMatches, dsh_api_client: &DshApiClient, context: &Context) -> DshCliResult {
+    GRAPH_EXPORT_CAPABILITY
+      .execute_capability_with_client(None, None, subject_matches, dsh_api_client, context)
+      .await
+  }
+}
+
+lazy_static! {
+  static ref GRAPH_EXPORT_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
+    CapabilityBuilder::new(GRAPH_SUBJECT_TARGET, None, &GraphExport {}, "Export the dependency graph of the target tenant")
+      .add_extra_argument(format_argument())
+      .add_extra_argument(around_argument())
+      .add_extra_argument(depth_argument())
+  );
+  static ref GRAPH_CAPABILITIES: Vec<&'static (dyn Capability + Send + Sync)> = vec![];
+}
+
+fn format_argument() -> Arg {
+  Arg::new(FORMAT_ARGUMENT)
+    .long("format")
+    .action(ArgAction::Set)
+    .value_parser(EnumValueParser::<GraphFormat>::new())
+    .value_name("FORMAT")
+    .help("Graph format")
+    .long_help("Format of the exported graph. The default format is dot.")
+}
+
+fn around_argument() -> Arg {
+  Arg::new(AROUND_ARGUMENT)
+    .long("around")
+    .action(ArgAction::Set)
+    .value_parser(NonEmptyStringValueParser::new())
+    .value_name("KIND:ID")
+    .help("Only export the graph around a resource")
+    .long_help(
+      "Only export the part of the graph around a resource, specified by its kind and identifier, \
+      e.g. 'secret:db-password' or 'service:my-service'. \
+      The possible kinds are app, bucket, certificate, secret, service, stream, topic, vhost and volume. \
+      The part of the graph contains all nodes that can be reached from the resource \
+      in at most --depth steps, regardless of the direction of the edges.",
+    )
+}
+
+fn depth_argument() -> Arg {
+  Arg::new(DEPTH_ARGUMENT)
+    .long("depth")
+    .action(ArgAction::Set)
+    .value_parser(value_parser!(usize))
+    .value_name("DEPTH")
+    .requires(AROUND_ARGUMENT)
+    .help("Depth of the graph around a resource")
+    .long_help("Maximum number of steps from the resource provided with --around. The default depth is 1.")
+}
+
+struct GraphExport {}
+
+#[async_trait]
+impl CommandExecutor for GraphExport {
+  async fn execute_with_client(&self, _: Option<String>, _: Option<String>, matches: &ArgMatches, client: &DshApiClient, context: &Context) -> DshCliResult {
+    let format = matches.get_one::<GraphFormat>(FORMAT_ARGUMENT).cloned().unwrap_or_default();
+    let around = matches
+      .get_one::<String>(AROUND_ARGUMENT)
+      .map(|around| Node::try_from(around.as_str()))
+      .transpose()?;
+    context.print_explanation(format!("export dependency graph of tenant {}@{}", client.tenant_name(), client.platform()));
+    let start_instant = context.now();
+    let mut graph = DependencyGraph::read(client).await?;
+    context.print_execution_time(start_instant);
+    if let Some(around) = around {
+      graph = graph.around(&around, matches.get_one::<usize>(DEPTH_ARGUMENT).cloned().unwrap_or(DEFAULT_DEPTH))?;
+    }
+    let graph_name = format!("{}@{}", client.tenant_name(), client.platform());
+    match format {
+      GraphFormat::Dot => context.print(graph.to_dot(&graph_name)),
+      GraphFormat::Json => context.print(serde_json::to_string_pretty(&graph).map_err(|error| DshCliError::General(format!("could not convert graph to json ({})", error)))?),
+      GraphFormat::Mermaid => context.print(graph.to_mermaid()),
+    }
+    Ok(())
+  }
+
+  fn requirements(&self, _: &ArgMatches) -> Requirements {
+    Requirements::standard_with_api()
+  }
+}
+
+/// # Node of the dependency graph
+///
+/// Nodes are identified by their kind and identifier, like `secret:db-password`.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+struct Node {
+  kind: &'static str,
+  id: String,
+}
+
+impl Node {
+  fn new(kind: &'static str, id: impl Into<String>) -> Self {
+    Self { kind, id: id.into() }
+  }
+}
+
+impl Display for Node {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}:{}", self.kind, self.id)
+  }
+}
+
+impl TryFrom<&str> for Node {
+  type Error = String;
+
+  fn try_from(node: &str) -> Result<Self, Self::Error> {
+    match node.split_once(':') {
+      Some((kind, id)) if !id.is_empty() => match NODE_KINDS.iter().find(|node_kind| **node_kind == kind) {
+        Some(node_kind) => Ok(Node::new(node_kind, id)),
+        None => Err(format!("invalid kind '{}', possible kinds are {}", kind, NODE_KINDS.join(", "))),
+      },
+      _ => Err(format!("invalid resource '{}', expected 'kind:id', e.g. 'secret:db-password'", node)),
+    }
+  }
+}
+
+/// # Edge of the dependency graph, from a service or app to a resource that it uses
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+struct Edge {
+  from: Node,
+  to: Node,
+  injections: Vec<String>,
+}
+
+/// # Dependency graph of a tenant
+#[derive(Debug, Default, Serialize)]
+struct DependencyGraph {
+  nodes: BTreeSet<Node>,
+  edges: BTreeSet<Edge>,
+}
+
+impl DependencyGraph {
+  async fn read(client: &DshApiClient) -> Result<Self, DshCliError> {
+    let (services, apps, bucket_ids, certificates, secrets, topics, vhosts, volumes) = try_join!(
+      client.get_application_configuration_map(),
+      client.get_appcatalogapp_configuration_map(),
+      client.get_bucket_ids(),
+      client.list_certificates_with_usage(),
+      client.list_secrets_with_usage(),
+      client.list_topics_with_usage(),
+      client.list_vhosts_with_usage(),
+      client.list_volumes_with_usage()
+    )?;
+    let mut graph = DependencyGraph::default();
+    for service_id in services.keys() {
+      graph.nodes.insert(Node::new(SERVICE, service_id));
+    }
+    for app_id in apps.keys() {
+      graph.nodes.insert(Node::new(APP, app_id));
+    }
+    for (certificate_id, _, used_bys) in certificates {
+      graph.add_used_resource(Node::new(CERTIFICATE, certificate_id), used_bys);
+    }
+    for (kind, resources_with_usage) in [(SECRET, secrets), (TOPIC, topics), (VHOST, vhosts), (VOLUME, volumes)] {
+      for (resource_id, used_bys) in resources_with_usage {
+        graph.add_used_resource(Node::new(kind, resource_id), used_bys);
+      }
+    }
+    for bucket_id in bucket_ids {
+      let mut used_bys = vec![];
+      for (service_id, service) in &services {
+        let injections = env_variables_that_contain(&service.env, &bucket_id);
+        if !injections.is_empty() {
+          used_bys.push((Node::new(SERVICE, service_id), injections));
+        }
+      }
+      for (app_id, app) in &apps {
+        let resources = bucket_resources_from_app(app)
+          .unwrap_or_default()
+          .into_iter()
+          .map(|(resource_id, _)| resource_id.to_string())
+          .filter(|resource_id| contains_token(resource_id, &bucket_id))
+          .collect::<Vec<_>>();
+        if !resources.is_empty() {
+          used_bys.push((Node::new(APP, app_id), resources));
+        }
+      }
+      graph.add_resource(Node::new(BUCKET, bucket_id), used_bys);
+    }
+    for (service_id, service) in &services {
+      let mut stream_injections = BTreeMap::<String, Vec<String>>::new();
+      for (streams, access) in [(&service.readable_streams, "read"), (&service.writable_streams, "write")] {
+        for stream in streams {
+          let stream = stream.to_string();
+          let stream_id = stream.strip_prefix("stream.").unwrap_or(&stream).to_string();
+          stream_injections.entry(stream_id).or_default().push(access.to_string());
+        }
+      }
+      // Streams that are only referenced from the environment variables
+      for (env_variable, value) in &service.env {
+        for captures in STREAM_REGEX.captures_iter(value) {
+          stream_injections.entry(captures[1].to_string()).or_default().push(env_variable.to_string());
+        }
+      }
+      for (stream_id, mut injections) in stream_injections {
+        injections.sort();
+        injections.dedup();
+        graph.add_resource(Node::new(STREAM, stream_id), vec![(Node::new(SERVICE, service_id), injections)]);
+      }
+    }
+    Ok(graph)
+  }
+
+  fn add_used_resource(&mut self, resource: Node, used_bys: Vec<UsedBy>) {
+    let used_bys = used_bys
+      .into_iter()
+      .map(|used_by| match used_by {
+        UsedBy::App(app_id, resources) => (Node::new(APP, app_id), resources),
+        UsedBy::Application(service_id, _, injections) => (Node::new(SERVICE, service_id), injections.iter().map(|injection| injection.to_string()).collect()),
+      })
+      .collect();
+    self.add_resource(resource, used_bys)
+  }
+
+  fn add_resource(&mut self, resource: Node, used_bys: Vec<(Node, Vec<String>)>) {
+    for (user, injections) in used_bys {
+      self.nodes.insert(user.clone());
+      self.edges.insert(Edge { from: user, to: resource.clone(), injections });
+    }
+    self.nodes.insert(resource);
+  }
+
+  /// # Returns the part of the graph around a node
+  ///
+  /// The returned graph contains the nodes that can be reached from `center`
+  /// in at most `depth` steps, regardless of the direction of the edges,
+  /// and the edges between these nodes.
+  fn around(self, center: &Node, depth: usize) -> Result<Self, DshCliError> {
+    if !self.nodes.contains(center) {
+      return Err(DshCliError::NotFound(format!("resource '{}' is not part of the graph", center)));
+    }
+    let mut distances = BTreeMap::from([(center.clone(), 0)]);
+    let mut queue = VecDeque::from([center.clone()]);
+    while let Some(node) = queue.pop_front() {
+      let distance = distances[&node];
+      if distance == depth {
+        continue;
+      }
+      for edge in &self.edges {
+        let neighbour = if edge.from == node {
+          &edge.to
+        } else if edge.to == node {
+          &edge.from
+        } else {
+          continue;
+        };
+        if !distances.contains_key(neighbour) {
+          distances.insert(neighbour.clone(), distance + 1);
+          queue.push_back(neighbour.clone());
+        }
+      }
+    }
+    Ok(Self {
+      nodes: self.nodes.into_iter().filter(|node| distances.contains_key(node)).collect(),
+      edges: self
+        .edges
+        .into_iter()
+        .filter(|edge| distances.contains_key(&edge.from) && distances.contains_key(&edge.to))
+        .collect(),
+    })
+  }
+
+  fn to_dot(&self, graph_name: &str) -> String {
+    let mut dot = vec![format!("digraph \"{}\" {{", escape_dot(graph_name)), "  rankdir=LR;".to_string()];
+    for node in &self.nodes {
+      dot.push(format!(
+        "  \"{}\" [label=\"{}\\n{}\", shape={}];",
+        escape_dot(&node.to_string()),
+        node.kind,
+        escape_dot(&node.id),
+        dot_shape(node.kind)
+      ));
+    }
+    for edge in &self.edges {
+      dot.push(format!(
+        "  \"{}\" -> \"{}\" [label=\"{}\"];",
+        escape_dot(&edge.from.to_string()),
+        escape_dot(&edge.to.to_string()),
+        escape_dot(&edge.injections.join(", "))
+      ));
+    }
+    dot.push("}".to_string());
+    dot.join("\n")
+  }
+
+  fn to_mermaid(&self) -> String {
+    let node_ids = self
+      .nodes
+      .iter()
+      .enumerate()
+      .map(|(index, node)| (node, format!("n{}", index)))
+      .collect::<BTreeMap<_, _>>();
+    let mut mermaid = vec!["flowchart LR".to_string()];
+    for (node, node_id) in &node_ids {
+      let label = format!("{}<br>{}", node.kind, escape_mermaid(&node.id));
+      match node.kind {
+        APP | SERVICE => mermaid.push(format!("  {}[\"{}\"]", node_id, label)),
+        _ => mermaid.push(format!("  {}([\"{}\"])", node_id, label)),
+      }
+    }
+    for edge in &self.edges {
+      if edge.injections.is_empty() {
+        mermaid.push(format!("  {} --> {}", node_ids[&edge.from], node_ids[&edge.to]));
+      } else {
+        mermaid.push(format!(
+          "  {} -->|\"{}\"| {}",
+          node_ids[&edge.from],
+          escape_mermaid(&edge.injections.join(", ")),
+          node_ids[&edge.to]
+        ));
+      }
+    }
+    mermaid.join("\n")
+  }
+}
+
+fn env_variables_that_contain(env: &HashMap<String, String>, id: &str) -> Vec<String> {
+  let mut env_variables = env
+    .iter()
+    .filter(|(_, value)| contains_token(value, id))
+    .map(|(env_variable, _)| env_variable.to_string())
+    .collect::<Vec<_>>();
+  env_variables.sort();
+  env_variables
+}
+
+/// # Checks whether a text contains an id as a whole token
+///
+/// An occurrence of `id` only counts when it is not preceded or followed by a character
+/// that can be part of an id, e.g. bucket `data` is not contained in `my-data-bucket`.
+fn contains_token(text: &str, id: &str) -> bool {
+  let is_id_character = |character: char| character.is_alphanumeric() || character == '-' || character == '_';
+  text
+    .match_indices(id)
+    .any(|(index, _)| !text[..index].ends_with(is_id_character) && !text[index + id.len()..].starts_with(is_id_character))
+}
+
+fn dot_shape(kind: &str) -> &'static str {
+  match kind {
+    APP | SERVICE => "box",
+    _ => "ellipse",
+  }
+}
+
+fn escape_dot(text: &str) -> String {
+  text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_mermaid(text: &str) -> String {
+  text.replace('"', "#quot;")
+}
+
+#[test]
+fn test_around() {
+  let mut graph = DependencyGraph::default();
+  graph.add_resource(
+    Node::new(SECRET, "db-password"),
+    vec![(Node::new(SERVICE, "a"), vec![]), (Node::new(SERVICE, "b"), vec![])],
+  );
+  graph.add_resource(Node::new(VOLUME, "data"), vec![(Node::new(SERVICE, "b"), vec![])]);
+  graph.add_resource(Node::new(TOPIC, "events"), vec![(Node::new(SERVICE, "c"), vec![])]);
+  let around = |depth: usize| {
+    DependencyGraph { nodes: graph.nodes.clone(), edges: graph.edges.clone() }
+      .around(&Node::try_from("secret:db-password").unwrap(), depth)
+      .unwrap()
+      .nodes
+      .iter()
+      .map(|node| node.to_string())
+      .collect::<Vec<_>>()
+  };
+  assert_eq!(around(0), vec!["secret:db-password"]);
+  assert_eq!(around(1), vec!["secret:db-password", "service:a", "service:b"]);
+  assert_eq!(around(2), vec!["secret:db-password", "service:a", "service:b", "volume:data"]);
+  assert!(Node::try_from("database:db").is_err());
+  assert!(Node::try_from("secret:").is_err());
+}
+
+#[test]
+fn test_contains_token() {
+  assert!(contains_token("data", "data"));
+  assert!(contains_token("s3://data/path", "data"));
+  assert!(contains_token("allocation/mock/bucket/data", "data"));
+  assert!(contains_token("my-data-bucket,data", "data"));
+  assert!(!contains_token("my-data-bucket", "data"));
+  assert!(!contains_token("data_2", "data"));
+  assert!(!contains_token("", "data"));
+}
@@ -0,0 +1,193 @@
+use crate::audit::{read_audit_records, since_timestamp, AuditRecord};
+use crate::capability::{Capability, CommandExecutor};
+use crate::capability_builder::CapabilityBuilder;
+use crate::context::Context;
+use crate::error::DshCliError;
+use crate::formatters::formatter::{Label, SubjectFormatter};
+use crate::formatters::list_formatter::ListFormatter;
+use crate::subject::{Requirements, Subject};
+use crate::DshCliResult;
+use async_trait::async_trait;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use lazy_static::lazy_static;
+use serde::Serialize;
+
+pub(crate) struct HistorySubject {}
+
+const HISTORY_SUBJECT_TARGET: &str = "history";
+
+const RESOURCE_FLAG: &str = "resource";
+const SINCE_FLAG: &str = "since";
+
+lazy_static! {
+  pub static ref HISTORY_SUBJECT: Box<dyn Subject + Send + Sync> = Box::new(HistorySubject {});
+}
+
+/// The history subject has no capability subcommands,
+/// `dsh history` directly executes the history list capability.
+#[async_trait]
+impl Subject for HistorySubject {
+  fn subject(&self) -> &'static str {
+    HISTORY_SUBJECT_TARGET
+  }
+
+  fn subject_command_about(&self) -> String {
+    "Show the history of changes.".to_string()
+  }
+
+  fn subject_command_long_about(&self) -> String {
+    "Show the audit log with the changes to resources on the DSH platform that were made \
+    with the dsh tool from this computer. Changes made in dry-run mode are not recorded. \
+    The audit log is stored in the file 'audit.log' in the dsh tool's home directory."
+      .to_string()
+  }
+
+  fn capability(&self, _capability_command: &str) -> Option<&(dyn Capability + Send + Sync)> {
+    None
+  }
+
+  fn capabilities(&self) -> &Vec<&(dyn Capability + Send + Sync)> {
+    &HISTORY_CAPABILITIES
+  }
+
+  fn subject_command(&self) -> (String, Command) {
+    let subject_command = HISTORY_LIST_CAPABILITY
+      .clap_capability_command(self.subject())
+      .about(self.subject_command_about())
+      .long_about(self.subject_command_long_about());
+    (self.subject().to_string(), subject_command)
+  }
+
+  fn requirements(&self, subject_matches: &ArgMatches) -> Requirements {
+    HISTORY_LIST_CAPABILITY.requirements(subject_matches)
+  }
+
+  async fn execute_subject_command_without_client<'a>(&self, subject_matches: &'a ArgMatches, context: &Context) -> DshCliResult {
+    HISTORY_LIST_CAPABILITY
+      .execute_capability_without_client(None, None, subject_matches, context)
+      .await
+  }
+}
+
+lazy_static! {
+  static ref HISTORY_LIST_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
+    CapabilityBuilder::new(HISTORY_SUBJECT_TARGET, None, &HistoryList {}, "Show the history of changes")
+      .add_extra_argument(since_flag())
+      .add_extra_argument(resource_flag())
+  );
+  static ref HISTORY_CAPABILITIES: Vec<&'static (dyn Capability + Send + Sync)> = vec![];
+}
+
+fn resource_flag() -> Arg {
+  Arg::new(RESOURCE_FLAG)
+    .long(RESOURCE_FLAG)
+    .action(ArgAction::Set)
+    .value_name("RESOURCE")
+    .help("Only show changes to matching resources")
+    .long_help(
+      "Only show changes to resources that contain this value. \
+      Resources have the form <kind>/<id>, e.g. 'service/my-service' or 'secret/my-secret'.",
+    )
+}
+
+fn since_flag() -> Arg {
+  Arg::new(SINCE_FLAG)
+    .long(SINCE_FLAG)
+    .action(ArgAction::Set)
+    .value_name("SINCE")
+    .help("Only show changes since this moment")
+    .long_help(
+      "Only show changes made since this moment. The value can be a utc date, like 2025-03-01, \
+      a utc date and time, like 2025-03-01T12:00, or a period before now, \
+      like 30m (minutes), 12h (hours), 7d (days) or 2w (weeks).",
+    )
+}
+
+struct HistoryList {}
+
+#[async_trait]
+impl CommandExecutor for HistoryList {
+  async fn execute_without_client(&self, _: Option<String>, _: Option<String>, matches: &ArgMatches, context: &Context) -> DshCliResult {
+    let since = matches
+      .get_one::<String>(SINCE_FLAG)
+      .map(|since| since_timestamp(since))
+      .transpose()
+      .map_err(DshCliError::Validation)?;
+    let resource = matches.get_one::<String>(RESOURCE_FLAG);
+    context.print_explanation("show history of changes");
+    let audit_records = read_audit_records()?
+      .into_iter()
+      .filter(|audit_record| since.as_ref().is_none_or(|since| audit_record.timestamp.as_str() >= since.as_str()))
+      .filter(|audit_record| resource.is_none_or(|resource| audit_record.resource.contains(resource.as_str())))
+      .collect::<Vec<_>>();
+    if audit_records.is_empty() {
+      context.print_outcome("no changes found");
+    } else {
+      let mut formatter = ListFormatter::new(&AUDIT_RECORD_LABELS, None, context);
+      formatter.push_values(&audit_records);
+      formatter.print(None)?;
+    }
+    Ok(())
+  }
+
+  fn requirements(&self, _: &ArgMatches) -> Requirements {
+    Requirements::standard_without_api()
+  }
+}
+
+#[derive(Eq, Hash, PartialEq, Serialize)]
+pub(crate) enum AuditRecordLabel {
+  Command,
+  Outcome,
+  Platform,
+  Resource,
+  Tenant,
+  Timestamp,
+  User,
+}
+
+impl Label for AuditRecordLabel {
+  fn as_str(&self) -> &str {
+    match self {
+      Self::Command => "command",
+      Self::Outcome => "outcome",
+      Self::Platform => "platform",
+      Self::Resource => "resource",
+      Self::Tenant => "tenant",
+      Self::Timestamp => "timestamp",
+      Self::User => "user",
+    }
+  }
+
+  fn is_target_label(&self) -> bool {
+    matches!(self, Self::Timestamp)
+  }
+}
+
+impl SubjectFormatter<AuditRecordLabel> for AuditRecord {
+  fn value(&self, label: &AuditRecordLabel, _target_id: &str) -> String {
+    match label {
+      AuditRecordLabel::Command => self.command.clone(),
+      AuditRecordLabel::Outcome => self.outcome.clone(),
+      AuditRecordLabel::Platform => self.platform.clone(),
+      AuditRecordLabel::Resource => self.resource.clone(),
+      AuditRecordLabel::Tenant => self.tenant.clone(),
+      AuditRecordLabel::Timestamp => self.timestamp.clone(),
+      AuditRecordLabel::User => self.user.clone(),
+    }
+  }
+
+  fn target_id(&self) -> Option<String> {
+    Some(self.timestamp.clone())
+  }
+}
+
+pub static AUDIT_RECORD_LABELS: [AuditRecordLabel; 7] = [
+  AuditRecordLabel::Timestamp,
+  AuditRecordLabel::User,
+  AuditRecordLabel::Platform,
+  AuditRecordLabel::Tenant,
+  AuditRecordLabel::Resource,
+  AuditRecordLabel::Outcome,
+  AuditRecordLabel::Command,
+];
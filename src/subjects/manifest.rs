@@ -1,7 +1,8 @@
 use crate::arguments::{manifest_id_argument, version_argument, VERSION_ARGUMENT};
-use crate::capability::{Capability, CommandExecutor, EXPORT_COMMAND, LIST_COMMAND, LIST_COMMAND_ALIAS, SHOW_COMMAND, SHOW_COMMAND_ALIAS};
+use crate::capability::{Capability, CommandExecutor, DIFF_COMMAND, EXPORT_COMMAND, LIST_COMMAND, LIST_COMMAND_ALIAS, SHOW_COMMAND, SHOW_COMMAND_ALIAS};
 use crate::capability_builder::CapabilityBuilder;
 use crate::context::Context;
+use crate::error::DshCliError;
 use crate::filter_flags::FilterFlagType;
 use crate::flags::FlagType;
 use crate::formatters::formatter::{hashmap_to_table, vec_to_table, Label, SubjectFormatter};
@@ -13,7 +14,7 @@ use crate::subject::{Requirements, Subject};
 use crate::version::Version;
 use crate::DshCliResult;
 use async_trait::async_trait;
-use clap::ArgMatches;
+use clap::{builder, Arg, ArgAction, ArgMatches};
 use dsh_api::app_manifest::{Manifest, Numerical, Property, Resource};
 use dsh_api::dsh_api_client::DshApiClient;
 use dsh_api::types::AppCatalogManifest;
@@ -48,6 +49,7 @@ impl Subject for ManifestSubject {
 
   fn capability(&self, capability_command: &str) -> Option<&(dyn Capability + Send + Sync)> {
     match capability_command {
+      DIFF_COMMAND => Some(MANIFEST_DIFF_CAPABILITY.as_ref()),
       EXPORT_COMMAND => Some(MANIFEST_EXPORT_CAPABILITY.as_ref()),
       LIST_COMMAND => Some(MANIFEST_LIST_CAPABILITY.as_ref()),
       SHOW_COMMAND => Some(MANIFEST_SHOW_CAPABILITY.as_ref()),
@@ -60,7 +62,25 @@ impl Subject for ManifestSubject {
   }
 }
 
+const TO_VERSION_ARGUMENT: &str = "to-version-argument";
+
 lazy_static! {
+  static ref MANIFEST_DIFF_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
+    CapabilityBuilder::new(DIFF_COMMAND, None, &ManifestDiff {}, "Compare manifest versions")
+      .set_long_about(
+        "Compare two versions of a manifest from the App Catalog. \
+         Shows the changed manifest properties, configuration parameters and defaults, \
+         and resources."
+      )
+      .add_target_argument(manifest_id_argument().required(true))
+      .add_target_argument(
+        version_argument()
+          .required(true)
+          .help("Version to compare from")
+          .long_help("Identifies the version to compare from.")
+      )
+      .add_extra_argument(to_version_argument())
+  );
   static ref MANIFEST_EXPORT_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
     CapabilityBuilder::new(EXPORT_COMMAND, None, &ManifestExport {}, "Export manifest")
       .set_long_about("Export a manifest file from the App Catalog.")
@@ -79,7 +99,50 @@ lazy_static! {
       .add_filter_flag(FilterFlagType::Complete, None)
   );
   static ref MANIFEST_CAPABILITIES: Vec<&'static (dyn Capability + Send + Sync)> =
-    vec![MANIFEST_EXPORT_CAPABILITY.as_ref(), MANIFEST_LIST_CAPABILITY.as_ref(), MANIFEST_SHOW_CAPABILITY.as_ref()];
+    vec![MANIFEST_DIFF_CAPABILITY.as_ref(), MANIFEST_EXPORT_CAPABILITY.as_ref(), MANIFEST_LIST_CAPABILITY.as_ref(), MANIFEST_SHOW_CAPABILITY.as_ref()];
+}
+
+fn to_version_argument() -> Arg {
+  Arg::new(TO_VERSION_ARGUMENT)
+    .action(ArgAction::Set)
+    .value_parser(builder::NonEmptyStringValueParser::new())
+    .value_name("TO_VERSION")
+    .required(true)
+    .help("Version to compare to")
+    .long_help("Identifies the version to compare to.")
+}
+
+struct ManifestDiff {}
+
+#[async_trait]
+impl CommandExecutor for ManifestDiff {
+  async fn execute_with_client(&self, target: Option<String>, _: Option<String>, matches: &ArgMatches, client: &DshApiClient, context: &Context) -> DshCliResult {
+    let manifest_id = target.unwrap_or_else(|| unreachable!());
+    let from_version = Version::from_str(matches.get_one::<String>(VERSION_ARGUMENT).unwrap())?;
+    let to_version = Version::from_str(matches.get_one::<String>(TO_VERSION_ARGUMENT).unwrap())?;
+    context.print_explanation(format!(
+      "compare app catalog manifest '{}', version {} with version {}",
+      manifest_id, from_version, to_version
+    ));
+    let start_instant = context.now();
+    let manifests = app_catalog_manifests(&manifest_id, client).await?;
+    context.print_execution_time(start_instant);
+    let from_manifest = select_manifest(&manifests, &manifest_id, Some(&from_version))?;
+    let to_manifest = select_manifest(&manifests, &manifest_id, Some(&to_version))?;
+    let differences = manifest_differences(&from_manifest, &to_manifest);
+    if differences.is_empty() {
+      context.print_outcome(format!("manifest '{}' versions {} and {} are equal", manifest_id, from_version, to_version));
+    } else {
+      let mut formatter = ListFormatter::new(&MANIFEST_DIFFERENCE_LABELS, None, context);
+      formatter.push_values(&differences);
+      formatter.print(None)?;
+    }
+    Ok(())
+  }
+
+  fn requirements(&self, _: &ArgMatches) -> Requirements {
+    Requirements::standard_with_api()
+  }
 }
 
 struct ManifestExport {}
@@ -102,7 +165,7 @@ impl CommandExecutor for ManifestExport {
         context.print_outcome(format!("manifest '{}' not found", manifest_id));
         Ok(())
       }
-      Err(e) => Err(e.to_string()),
+      Err(e) => Err(DshCliError::from(e)),
     }
   }
 
@@ -206,6 +269,190 @@ impl CommandExecutor for ManifestShowAll {
   }
 }
 
+/// # Finds a manifest in the app catalog
+///
+/// Returns the manifest with the provided id and version.
+/// If no version is provided, the latest version of the manifest will be returned.
+pub(crate) async fn find_manifest(manifest_id: &str, version: Option<&Version>, client: &DshApiClient) -> Result<Manifest, String> {
+  select_manifest(&app_catalog_manifests(manifest_id, client).await?, manifest_id, version)
+}
+
+// Returns all versions of the manifest with the provided id, sorted by version
+async fn app_catalog_manifests(manifest_id: &str, client: &DshApiClient) -> Result<Vec<(Version, Manifest)>, String> {
+  match client.get_app_catalog_manifests(manifest_id).await {
+    Ok(manifests) => Ok(
+      manifests
+        .into_iter()
+        .filter_map(|(version, manifest)| Version::from_str(version.as_str()).ok().map(|version| (version, manifest)))
+        .sorted_by(|(version_a, _), (version_b, _)| version_a.cmp(version_b))
+        .collect::<Vec<_>>(),
+    ),
+    Err(DshApiError::NotFound(_)) => Err(format!("manifest '{}' not found", manifest_id)),
+    Err(error) => Err(error.to_string()),
+  }
+}
+
+/// # Selects a version from a sorted list of manifest versions
+///
+/// Returns the manifest with the provided version.
+/// If no version is provided, the latest version of the manifest will be returned.
+fn select_manifest(manifests: &[(Version, Manifest)], manifest_id: &str, version: Option<&Version>) -> Result<Manifest, String> {
+  match version {
+    Some(version) => match manifests.iter().find(|(manifest_version, _)| manifest_version == version) {
+      Some((_, manifest)) => Ok(manifest.clone()),
+      None => Err(format!("manifest '{}' has no version {}", manifest_id, version)),
+    },
+    None => manifests
+      .last()
+      .map(|(_, manifest)| manifest.clone())
+      .ok_or_else(|| format!("manifest '{}' not found", manifest_id)),
+  }
+}
+
+/// # Difference between two manifest versions
+#[derive(Serialize)]
+pub(crate) struct ManifestDifference {
+  item: String,
+  change: &'static str,
+  from: Option<String>,
+  to: Option<String>,
+}
+
+/// # Compares two manifests
+///
+/// Returns the differences in the manifest properties, configuration parameters and resources.
+fn manifest_differences(from: &Manifest, to: &Manifest) -> Vec<ManifestDifference> {
+  let mut differences = vec![];
+  let from_properties = manifest_properties(from);
+  let to_properties = manifest_properties(to);
+  for ((item, from_value), (_, to_value)) in from_properties.into_iter().zip(to_properties) {
+    push_difference(&mut differences, item.to_string(), from_value, to_value);
+  }
+  let from_parameters = configuration_parameters(from);
+  let to_parameters = configuration_parameters(to);
+  for parameter in from_parameters.keys().chain(to_parameters.keys()).unique().sorted() {
+    push_difference(
+      &mut differences,
+      format!("configuration: {}", parameter),
+      from_parameters.get(parameter).cloned(),
+      to_parameters.get(parameter).cloned(),
+    );
+  }
+  let from_resources = from
+    .resources
+    .values()
+    .map(|resource| (resource_to_key(resource), resource_to_strings(resource).join("\n")))
+    .collect::<HashMap<_, _>>();
+  let to_resources = to
+    .resources
+    .values()
+    .map(|resource| (resource_to_key(resource), resource_to_strings(resource).join("\n")))
+    .collect::<HashMap<_, _>>();
+  for resource in from_resources.keys().chain(to_resources.keys()).unique().sorted() {
+    push_difference(
+      &mut differences,
+      resource.to_string(),
+      from_resources.get(resource).cloned(),
+      to_resources.get(resource).cloned(),
+    );
+  }
+  differences
+}
+
+fn push_difference(differences: &mut Vec<ManifestDifference>, item: String, from: Option<String>, to: Option<String>) {
+  let change = match (&from, &to) {
+    (Some(from), Some(to)) if from != to => "changed",
+    (Some(_), None) => "removed",
+    (None, Some(_)) => "added",
+    _ => return,
+  };
+  differences.push(ManifestDifference { item, change, from, to });
+}
+
+fn manifest_properties(manifest: &Manifest) -> Vec<(&'static str, Option<String>)> {
+  vec![
+    ("name", Some(manifest.name.clone())),
+    ("vendor", Some(manifest.vendor.clone())),
+    ("kind", manifest.kind.clone()),
+    ("api version", manifest.api_version.clone()),
+    ("description", manifest.description.clone()),
+    ("contact", Some(manifest.contact.clone())),
+    ("more info", manifest.more_info.clone()),
+  ]
+}
+
+fn configuration_parameters(manifest: &Manifest) -> HashMap<String, String> {
+  match manifest.configuration {
+    Some(ref configuration) => configuration
+      .properties
+      .iter()
+      .map(|(parameter, property)| (parameter.to_string(), property_to_string(property)))
+      .collect::<HashMap<_, _>>(),
+    None => HashMap::new(),
+  }
+}
+
+#[derive(Eq, Hash, PartialEq, Serialize)]
+pub(crate) enum ManifestDifferenceLabel {
+  Change,
+  From,
+  Item,
+  To,
+}
+
+impl Label for ManifestDifferenceLabel {
+  fn as_str(&self) -> &str {
+    match self {
+      Self::Change => "change",
+      Self::From => "from",
+      Self::Item => "item",
+      Self::To => "to",
+    }
+  }
+
+  fn is_target_label(&self) -> bool {
+    matches!(self, Self::Item)
+  }
+}
+
+impl SubjectFormatter<ManifestDifferenceLabel> for ManifestDifference {
+  fn value(&self, label: &ManifestDifferenceLabel, _target_id: &str) -> String {
+    match label {
+      ManifestDifferenceLabel::Change => self.change.to_string(),
+      ManifestDifferenceLabel::From => self.from.clone().unwrap_or_default(),
+      ManifestDifferenceLabel::Item => self.item.clone(),
+      ManifestDifferenceLabel::To => self.to.clone().unwrap_or_default(),
+    }
+  }
+
+  fn target_id(&self) -> Option<String> {
+    Some(self.item.clone())
+  }
+}
+
+static MANIFEST_DIFFERENCE_LABELS: [ManifestDifferenceLabel; 4] =
+  [ManifestDifferenceLabel::Item, ManifestDifferenceLabel::Change, ManifestDifferenceLabel::From, ManifestDifferenceLabel::To];
+
+/// # Returns the urn that identifies a manifest
+///
+/// The urn has the form `appcatalog/manifest/<vendor>/<manifest id>/<version>`.
+pub(crate) fn manifest_urn(manifest: &Manifest) -> String {
+  format!("appcatalog/manifest/{}/{}/{}", manifest.vendor, manifest.id, manifest.version)
+}
+
+/// # Parses a manifest urn
+///
+/// Returns the vendor, manifest id and version from a manifest urn,
+/// or `None` when the urn could not be parsed.
+pub(crate) fn parse_manifest_urn(manifest_urn: &str) -> Option<(String, String, Version)> {
+  match manifest_urn.split('/').collect::<Vec<_>>().as_slice() {
+    ["appcatalog", "manifest", vendor, manifest_id, version] => Version::from_str(version)
+      .ok()
+      .map(|version| (vendor.to_string(), manifest_id.to_string(), version)),
+    _ => None,
+  }
+}
+
 #[derive(Debug, Eq, Hash, PartialEq, Serialize)]
 pub(crate) enum ManifestLabel {
   ApiVersion,
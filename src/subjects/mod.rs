@@ -2,7 +2,11 @@ pub(crate) mod api;
 pub(crate) mod app;
 pub(crate) mod bucket;
 pub(crate) mod certificate;
+pub(crate) mod compare;
+pub(crate) mod completion;
 pub(crate) mod env;
+pub(crate) mod graph;
+pub(crate) mod history;
 pub(crate) mod image;
 pub(crate) mod manifest;
 pub(crate) mod metric;
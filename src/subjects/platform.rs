@@ -2,19 +2,29 @@ use crate::arguments::{
   app_id_argument, platform_name_argument, service_id_argument, vendor_name_argument, vhost_id_argument, APP_ID_ARGUMENT, PLATFORM_NAME_ARGUMENT, SERVICE_ID_ARGUMENT,
   VENDOR_NAME_ARGUMENT, VHOST_ID_ARGUMENT,
 };
-use crate::capability::{Capability, CommandExecutor, EXPORT_COMMAND, LIST_COMMAND, LIST_COMMAND_ALIAS, OPEN_COMMAND, OPEN_COMMAND_ALIAS, SHOW_COMMAND, SHOW_COMMAND_ALIAS};
+use crate::capability::{
+  Capability, CommandExecutor, ADD_COMMAND, EXPORT_COMMAND, LIST_COMMAND, LIST_COMMAND_ALIAS, OPEN_COMMAND, OPEN_COMMAND_ALIAS, REMOVE_COMMAND, SHOW_COMMAND, SHOW_COMMAND_ALIAS,
+  UPDATE_COMMAND, USAGE_COMMAND, VALIDATE_COMMAND,
+};
 use crate::capability_builder::CapabilityBuilder;
 use crate::context::Context;
+use crate::error::DshCliError;
 use crate::formatters::formatter::{Label, SubjectFormatter};
 use crate::formatters::list_formatter::ListFormatter;
 use crate::formatters::unit_formatter::UnitFormatter;
+use crate::platforms::{
+  check_endpoints, default_platforms, key_cloak_access_token_endpoint, merge_platforms, platforms_file_overridden, read_user_platforms, validate_platforms, write_user_platforms,
+  PlatformDefinition, ENDPOINT_CHECK_LABELS,
+};
 use crate::subject::{Requirements, Subject};
+use crate::usage::{TenantUsage, UsageLimits, USAGE_LABELS};
 use crate::{get_target_platform, get_target_tenant, get_target_tenant_non_interactive, read_single_line, DshCliResult};
 use arboard::Clipboard;
 use async_trait::async_trait;
-use clap::{ArgMatches, Command};
+use clap::builder::PossibleValue;
+use clap::{builder, Arg, ArgAction, ArgMatches, Command};
 use dsh_api::dsh_api_client::DshApiClient;
-use dsh_api::platform::DshPlatform;
+use dsh_api::platform::{CloudProvider, DshPlatform};
 use dsh_api::DEFAULT_PLATFORMS;
 use lazy_static::lazy_static;
 use log::{debug, warn};
@@ -32,6 +42,17 @@ const OPEN_SWAGGER: &str = "swagger";
 const OPEN_TENANT: &str = "tenant";
 const OPEN_TRACING: &str = "tracing";
 
+const ACCESS_TOKEN_ENDPOINT_ARGUMENT: &str = "access-token-endpoint-argument";
+const ALIAS_ARGUMENT: &str = "alias-argument";
+const CLOUD_PROVIDER_ARGUMENT: &str = "cloud-provider-argument";
+const DESCRIPTION_ARGUMENT: &str = "description-argument";
+const KEY_CLOAK_URL_ARGUMENT: &str = "key-cloak-url-argument";
+const PRIVATE_DOMAIN_ARGUMENT: &str = "private-domain-argument";
+const PRODUCTION_ARGUMENT: &str = "production-argument";
+const PUBLIC_DOMAIN_ARGUMENT: &str = "public-domain-argument";
+const REALM_ARGUMENT: &str = "realm-argument";
+const USER_PLATFORM_NAME_ARGUMENT: &str = "user-platform-name-argument";
+
 lazy_static! {
   pub static ref PLATFORM_SUBJECT: Box<dyn Subject + Send + Sync> = Box::new(PlatformSubject {});
 }
@@ -46,16 +67,30 @@ impl Subject for PlatformSubject {
     "Show, list and open platform resources.".to_string()
   }
 
+  fn subject_command_long_about(&self) -> String {
+    "Show, list and open platform resources, and manage user defined platforms. \
+    User defined platforms are stored in the file 'platforms.json' in the dsh tool's home directory \
+    and are merged with the default platforms, where a user defined platform replaces \
+    the default platform with the same name. \
+    User defined platforms are not used when the environment variable DSH_API_PLATFORMS_FILE is set."
+      .to_string()
+  }
+
   fn subject_command_alias(&self) -> Option<&str> {
     Some("p")
   }
 
   fn capability(&self, capability_command: &str) -> Option<&(dyn Capability + Send + Sync)> {
     match capability_command {
+      ADD_COMMAND => Some(PLATFORM_ADD_CAPABILITY.as_ref()),
       EXPORT_COMMAND => Some(PLATFORM_EXPORT_CAPABILITY.as_ref()),
       LIST_COMMAND => Some(PLATFORM_LIST_CAPABILITY.as_ref()),
       OPEN_COMMAND => Some(PLATFORM_OPEN_CAPABILITY.as_ref()),
+      REMOVE_COMMAND => Some(PLATFORM_REMOVE_CAPABILITY.as_ref()),
       SHOW_COMMAND => Some(PLATFORM_SHOW_CAPABILITY.as_ref()),
+      UPDATE_COMMAND => Some(PLATFORM_UPDATE_CAPABILITY.as_ref()),
+      USAGE_COMMAND => Some(PLATFORM_USAGE_CAPABILITY.as_ref()),
+      VALIDATE_COMMAND => Some(PLATFORM_VALIDATE_CAPABILITY.as_ref()),
       _ => None,
     }
   }
@@ -66,6 +101,19 @@ impl Subject for PlatformSubject {
 }
 
 lazy_static! {
+  static ref PLATFORM_ADD_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
+    CapabilityBuilder::new(ADD_COMMAND, None, &PlatformAdd {}, "Add user defined platform")
+      .set_long_about(
+        "Add a user defined platform, e.g. for a private DSH installation. \
+        The platform will be stored in the file 'platforms.json' in the dsh tool's home directory. \
+        The rest api endpoint and the console url are derived from the public domain, \
+        as 'https://api.<public-domain>/resources/v0' and 'https://console.<public-domain>'. \
+        The access token endpoint can either be provided explicitly, \
+        or it can be derived from the key-cloak url and the realm."
+      )
+      .add_target_argument(user_platform_name_argument().required(true))
+      .add_extra_arguments(platform_definition_arguments(true))
+  );
   static ref PLATFORM_EXPORT_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
     CapabilityBuilder::new(EXPORT_COMMAND, None, &PlatformExport {}, "Export default platform configuration").set_long_about(
       "Export the default platform configuration json file from the dsh-api library. \
@@ -95,6 +143,14 @@ lazy_static! {
         Command::new(OPEN_TRACING).about("Open the tracing application for the target platform"),
       ])
   );
+  static ref PLATFORM_REMOVE_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
+    CapabilityBuilder::new(REMOVE_COMMAND, None, &PlatformRemove {}, "Remove user defined platform")
+      .set_long_about(
+        "Remove a user defined platform from the file 'platforms.json' in the dsh tool's home directory. \
+        When the user defined platform replaced a default platform, the default platform will be restored."
+      )
+      .add_target_argument(user_platform_name_argument().required(true))
+  );
   static ref PLATFORM_SHOW_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
     CapabilityBuilder::new(SHOW_COMMAND, Some(SHOW_COMMAND_ALIAS), &PlatformShow {}, "Show platform data")
       .set_long_about("Show platform data.")
@@ -106,8 +162,95 @@ lazy_static! {
         vhost_id_argument().long("vhost")
       ])
   );
-  static ref PLATFORM__CAPABILITIES: Vec<&'static (dyn Capability + Send + Sync)> =
-    vec![PLATFORM_EXPORT_CAPABILITY.as_ref(), PLATFORM_LIST_CAPABILITY.as_ref(), PLATFORM_OPEN_CAPABILITY.as_ref(), PLATFORM_SHOW_CAPABILITY.as_ref()];
+  static ref PLATFORM_UPDATE_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
+    CapabilityBuilder::new(UPDATE_COMMAND, None, &PlatformUpdate {}, "Update user defined platform")
+      .set_long_about(
+        "Update a user defined platform. Only the provided values will be changed. \
+        When a default platform is updated, a user defined copy of the default platform will be created, \
+        which will replace the default platform."
+      )
+      .add_target_argument(user_platform_name_argument().required(true))
+      .add_extra_arguments(platform_definition_arguments(false))
+  );
+  static ref PLATFORM_USAGE_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
+    CapabilityBuilder::new(USAGE_COMMAND, None, &PlatformUsage {}, "Show resource usage of target tenant").set_long_about(
+      "Show the resources used by the target tenant. \
+       The cpu and memory usage are the sums over all services of the number of instances \
+       multiplied by the cpus and memory per instance. \
+       Limits are only available to managing tenants, via the 'tenant usage' command."
+    )
+  );
+  static ref PLATFORM_VALIDATE_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
+    CapabilityBuilder::new(VALIDATE_COMMAND, None, &PlatformValidate {}, "Validate user defined platforms").set_long_about(
+      "Validate the user defined platforms in the file 'platforms.json' in the dsh tool's home directory. \
+      The file is checked against the platform schema, the names and aliases of the merged platforms must be unique, \
+      and the access token endpoint, rest api endpoint and console of each user defined platform must be reachable."
+    )
+  );
+  static ref PLATFORM__CAPABILITIES: Vec<&'static (dyn Capability + Send + Sync)> = vec![
+    PLATFORM_ADD_CAPABILITY.as_ref(),
+    PLATFORM_EXPORT_CAPABILITY.as_ref(),
+    PLATFORM_LIST_CAPABILITY.as_ref(),
+    PLATFORM_OPEN_CAPABILITY.as_ref(),
+    PLATFORM_REMOVE_CAPABILITY.as_ref(),
+    PLATFORM_SHOW_CAPABILITY.as_ref(),
+    PLATFORM_UPDATE_CAPABILITY.as_ref(),
+    PLATFORM_USAGE_CAPABILITY.as_ref(),
+    PLATFORM_VALIDATE_CAPABILITY.as_ref()
+  ];
+}
+
+struct PlatformAdd {}
+
+#[async_trait]
+impl CommandExecutor for PlatformAdd {
+  async fn execute_without_client(&self, platform_name: Option<String>, _: Option<String>, matches: &ArgMatches, context: &Context) -> DshCliResult {
+    let platform_name = platform_name.unwrap_or_else(|| unreachable!());
+    context.print_explanation(format!("add user defined platform '{}'", platform_name));
+    warn_if_platforms_file_overridden(context)?;
+    let mut user_platforms = read_user_platforms()?;
+    if user_platforms.iter().any(|platform| platform.name == platform_name) || DshPlatform::all().iter().any(|platform| platform.name() == platform_name) {
+      return Err(DshCliError::Conflict(format!("platform '{}' already exists", platform_name)));
+    }
+    let realm = matches.get_one::<String>(REALM_ARGUMENT).unwrap_or_else(|| unreachable!()).to_string();
+    let access_token_endpoint = match (
+      matches.get_one::<String>(ACCESS_TOKEN_ENDPOINT_ARGUMENT),
+      matches.get_one::<String>(KEY_CLOAK_URL_ARGUMENT),
+    ) {
+      (Some(access_token_endpoint), _) => access_token_endpoint.to_string(),
+      (None, Some(key_cloak_url)) => key_cloak_access_token_endpoint(key_cloak_url, &realm),
+      (None, None) => {
+        return Err(DshCliError::Validation(
+          "either the access token endpoint or the key-cloak url must be provided".to_string(),
+        ))
+      }
+    };
+    let mut platform = PlatformDefinition {
+      name: platform_name.clone(),
+      description: platform_name.clone(),
+      alias: matches.get_one::<String>(ALIAS_ARGUMENT).unwrap_or_else(|| unreachable!()).to_string(),
+      is_production: false,
+      cloud_provider: CloudProvider::AWS,
+      access_token_endpoint,
+      realm,
+      public_domain: matches.get_one::<String>(PUBLIC_DOMAIN_ARGUMENT).unwrap_or_else(|| unreachable!()).to_string(),
+      private_domain: None,
+    };
+    apply_platform_arguments(&mut platform, matches);
+    user_platforms.push(platform);
+    check_user_platforms(&user_platforms)?;
+    if context.dry_run() {
+      context.print_warning(format!("dry-run mode, platform '{}' not added", platform_name));
+    } else {
+      let user_platforms_file = write_user_platforms(user_platforms)?;
+      context.print_outcome(format!("platform '{}' added to '{}'", platform_name, user_platforms_file.to_string_lossy()));
+    }
+    Ok(())
+  }
+
+  fn requirements(&self, _: &ArgMatches) -> Requirements {
+    Requirements::standard_without_api()
+  }
 }
 
 struct PlatformExport {}
@@ -158,7 +301,7 @@ impl CommandExecutor for PlatformOpen {
         OPEN_TRACING => Self::open_tracing(arg_matches, context),
         _ => unreachable!(),
       },
-      None => Err("missing target argument".to_string()),
+      None => Err("missing target argument".into()),
     }
   }
 
@@ -168,7 +311,7 @@ impl CommandExecutor for PlatformOpen {
         OPEN_SWAGGER => Self::open_swagger(arg_matches, client, context).await,
         _ => unreachable!(),
       },
-      None => Err("missing target argument".to_string()),
+      None => Err("missing target argument".into()),
     }
   }
 
@@ -249,7 +392,7 @@ impl PlatformOpen {
             format!("swagger application for platform '{}'", platform)
           }
         },
-        None => return Err("token has incorrect format".to_string()),
+        None => return Err(DshCliError::Validation("token has incorrect format".to_string())),
       },
       None => format!("swagger application for platform '{}'", platform),
     };
@@ -269,9 +412,48 @@ impl PlatformOpen {
     } else {
       debug!("open url '{}'", url);
       context.print_explanation(format!("open {}", opening_target));
-      open::that(url).map_err(|error| format!("could not open browser ({})", error))
+      open::that(url).map_err(|error| DshCliError::General(format!("could not open browser ({})", error)))
+    }
+  }
+}
+
+struct PlatformRemove {}
+
+#[async_trait]
+impl CommandExecutor for PlatformRemove {
+  async fn execute_without_client(&self, platform_name: Option<String>, _: Option<String>, _: &ArgMatches, context: &Context) -> DshCliResult {
+    let platform_name = platform_name.unwrap_or_else(|| unreachable!());
+    context.print_explanation(format!("remove user defined platform '{}'", platform_name));
+    warn_if_platforms_file_overridden(context)?;
+    let mut user_platforms = read_user_platforms()?;
+    let platform_name = match user_platforms
+      .iter()
+      .position(|platform| platform.name == platform_name || platform.alias == platform_name)
+    {
+      Some(index) => user_platforms.remove(index).name,
+      None => return Err(DshCliError::NotFound(format!("user defined platform '{}' does not exist", platform_name))),
+    };
+    let restores_default_platform = default_platforms().iter().any(|platform| platform.name == platform_name);
+    if context.confirmed(format!("remove user defined platform '{}'?", platform_name))? {
+      if context.dry_run() {
+        context.print_warning(format!("dry-run mode, platform '{}' not removed", platform_name));
+      } else {
+        write_user_platforms(user_platforms)?;
+        if restores_default_platform {
+          context.print_outcome(format!("user defined platform '{}' removed, default platform restored", platform_name));
+        } else {
+          context.print_outcome(format!("user defined platform '{}' removed", platform_name));
+        }
+      }
+      Ok(())
+    } else {
+      Err(DshCliError::Cancelled("cancelled".to_string()))
     }
   }
+
+  fn requirements(&self, _: &ArgMatches) -> Requirements {
+    Requirements::standard_without_api()
+  }
 }
 
 struct PlatformShow {}
@@ -310,7 +492,121 @@ impl CommandExecutor for PlatformShow {
         vhost.unwrap_or_default(),
       ),
       None,
-    )
+    )?;
+    Ok(())
+  }
+
+  fn requirements(&self, _: &ArgMatches) -> Requirements {
+    Requirements::standard_without_api()
+  }
+}
+
+struct PlatformUpdate {}
+
+#[async_trait]
+impl CommandExecutor for PlatformUpdate {
+  async fn execute_without_client(&self, platform_name: Option<String>, _: Option<String>, matches: &ArgMatches, context: &Context) -> DshCliResult {
+    let platform_name = platform_name.unwrap_or_else(|| unreachable!());
+    context.print_explanation(format!("update user defined platform '{}'", platform_name));
+    warn_if_platforms_file_overridden(context)?;
+    let mut user_platforms = read_user_platforms()?;
+    let mut platform = match user_platforms
+      .iter()
+      .position(|platform| platform.name == platform_name || platform.alias == platform_name)
+    {
+      Some(index) => user_platforms.remove(index),
+      None => match default_platforms()
+        .into_iter()
+        .find(|platform| platform.name == platform_name || platform.alias == platform_name)
+      {
+        Some(default_platform) => {
+          context.print_warning(format!("default platform '{}' will be replaced by a user defined platform", platform_name));
+          default_platform
+        }
+        None => return Err(DshCliError::NotFound(format!("platform '{}' does not exist", platform_name))),
+      },
+    };
+    let platform_name = platform.name.clone();
+    if let Some(realm) = matches.get_one::<String>(REALM_ARGUMENT) {
+      platform.realm = realm.to_string();
+    }
+    if let Some(access_token_endpoint) = matches.get_one::<String>(ACCESS_TOKEN_ENDPOINT_ARGUMENT) {
+      platform.access_token_endpoint = access_token_endpoint.to_string();
+    } else if let Some(key_cloak_url) = matches.get_one::<String>(KEY_CLOAK_URL_ARGUMENT) {
+      platform.access_token_endpoint = key_cloak_access_token_endpoint(key_cloak_url, &platform.realm);
+    }
+    if let Some(public_domain) = matches.get_one::<String>(PUBLIC_DOMAIN_ARGUMENT) {
+      platform.public_domain = public_domain.to_string();
+    }
+    apply_platform_arguments(&mut platform, matches);
+    user_platforms.push(platform);
+    check_user_platforms(&user_platforms)?;
+    if context.dry_run() {
+      context.print_warning(format!("dry-run mode, platform '{}' not updated", platform_name));
+    } else {
+      let user_platforms_file = write_user_platforms(user_platforms)?;
+      context.print_outcome(format!("platform '{}' updated in '{}'", platform_name, user_platforms_file.to_string_lossy()));
+    }
+    Ok(())
+  }
+
+  fn requirements(&self, _: &ArgMatches) -> Requirements {
+    Requirements::standard_without_api()
+  }
+}
+
+struct PlatformUsage {}
+
+#[async_trait]
+impl CommandExecutor for PlatformUsage {
+  async fn execute_with_client(&self, _: Option<String>, _: Option<String>, _: &ArgMatches, client: &DshApiClient, context: &Context) -> DshCliResult {
+    context.print_explanation(format!("show resource usage of tenant '{}'", client.tenant_name()));
+    let start_instant = context.now();
+    let tenant_usage = TenantUsage::fetch(client).await?;
+    context.print_execution_time(start_instant);
+    let resource_usages = tenant_usage.resource_usages(&UsageLimits::default(), context);
+    let mut formatter = ListFormatter::new(&USAGE_LABELS, None, context);
+    formatter.push_values(&resource_usages);
+    formatter.print(None)?;
+    Ok(())
+  }
+
+  fn requirements(&self, _: &ArgMatches) -> Requirements {
+    Requirements::standard_with_api()
+  }
+}
+
+struct PlatformValidate {}
+
+#[async_trait]
+impl CommandExecutor for PlatformValidate {
+  async fn execute_without_client(&self, _: Option<String>, _: Option<String>, _: &ArgMatches, context: &Context) -> DshCliResult {
+    context.print_explanation("validate user defined platforms");
+    warn_if_platforms_file_overridden(context)?;
+    let user_platforms = read_user_platforms().map_err(DshCliError::Validation)?;
+    if user_platforms.is_empty() {
+      context.print_outcome("no user defined platforms");
+      return Ok(());
+    }
+    let errors = validate_platforms(&merge_platforms(default_platforms(), user_platforms.clone()));
+    let start_instant = context.now();
+    let mut endpoint_checks = vec![];
+    for platform in &user_platforms {
+      endpoint_checks.append(&mut check_endpoints(platform).await);
+    }
+    context.print_execution_time(start_instant);
+    let mut formatter = ListFormatter::new(&ENDPOINT_CHECK_LABELS, None, context);
+    formatter.push_values(&endpoint_checks);
+    formatter.print(None)?;
+    let unreachable_endpoints = endpoint_checks.iter().filter(|endpoint_check| !endpoint_check.reachable).count();
+    if !errors.is_empty() {
+      Err(DshCliError::Validation(format!("user defined platforms are not valid\n  {}", errors.join("\n  "))))
+    } else if unreachable_endpoints > 0 {
+      Err(DshCliError::Validation(format!("{} unreachable endpoint(s)", unreachable_endpoints)))
+    } else {
+      context.print_outcome(format!("{} user defined platform(s) are valid", user_platforms.len()));
+      Ok(())
+    }
   }
 
   fn requirements(&self, _: &ArgMatches) -> Requirements {
@@ -332,6 +628,129 @@ fn get_service_argument_or_prompt(matches: &ArgMatches) -> Result<String, String
   }
 }
 
+fn user_platform_name_argument() -> Arg {
+  Arg::new(USER_PLATFORM_NAME_ARGUMENT)
+    .action(ArgAction::Set)
+    .value_parser(builder::NonEmptyStringValueParser::new())
+    .value_name("PLATFORM")
+    .help("Platform name")
+    .long_help("Name of the user defined platform.")
+}
+
+fn platform_definition_arguments(add: bool) -> Vec<Arg> {
+  vec![
+    Arg::new(ACCESS_TOKEN_ENDPOINT_ARGUMENT)
+      .long("access-token-endpoint")
+      .action(ArgAction::Set)
+      .value_parser(builder::NonEmptyStringValueParser::new())
+      .value_name("URL")
+      .conflicts_with(KEY_CLOAK_URL_ARGUMENT)
+      .help("Access token endpoint")
+      .long_help("Key-cloak endpoint from which the access tokens for the rest api will be requested."),
+    Arg::new(ALIAS_ARGUMENT)
+      .long("alias")
+      .action(ArgAction::Set)
+      .value_parser(builder::NonEmptyStringValueParser::new())
+      .value_name("ALIAS")
+      .required(add)
+      .help("Platform alias")
+      .long_help("Short alias that can be used instead of the platform name. The alias must differ from the name of the platform."),
+    Arg::new(CLOUD_PROVIDER_ARGUMENT)
+      .long("cloud-provider")
+      .action(ArgAction::Set)
+      .value_parser([PossibleValue::new("aws").help("Amazon Web Services"), PossibleValue::new("azure").help("Microsoft Azure")])
+      .value_name("PROVIDER")
+      .help("Cloud provider")
+      .long_help("Cloud provider that hosts the platform. When adding a platform, the default cloud provider is 'aws'."),
+    Arg::new(DESCRIPTION_ARGUMENT)
+      .long("description")
+      .action(ArgAction::Set)
+      .value_parser(builder::NonEmptyStringValueParser::new())
+      .value_name("DESCRIPTION")
+      .help("Platform description"),
+    Arg::new(KEY_CLOAK_URL_ARGUMENT)
+      .long("key-cloak-url")
+      .action(ArgAction::Set)
+      .value_parser(builder::NonEmptyStringValueParser::new())
+      .value_name("URL")
+      .help("Key-cloak url")
+      .long_help(
+        "Base url of the key-cloak server of the platform, e.g. 'https://auth.example.com'. \
+        The access token endpoint will be derived from this url and the realm.",
+      ),
+    Arg::new(PRIVATE_DOMAIN_ARGUMENT)
+      .long("private-domain")
+      .action(ArgAction::Set)
+      .value_parser(builder::NonEmptyStringValueParser::new())
+      .value_name("DOMAIN")
+      .help("Private domain")
+      .long_help("Domain of the private services and vhosts of the platform."),
+    Arg::new(PRODUCTION_ARGUMENT)
+      .long("production")
+      .action(ArgAction::Set)
+      .value_parser(builder::BoolishValueParser::new())
+      .value_name("BOOLEAN")
+      .help("Whether the platform is a production platform")
+      .long_help("Whether the platform is a production platform. When adding a platform, the default is 'false'."),
+    Arg::new(PUBLIC_DOMAIN_ARGUMENT)
+      .long("public-domain")
+      .action(ArgAction::Set)
+      .value_parser(builder::NonEmptyStringValueParser::new())
+      .value_name("DOMAIN")
+      .required(add)
+      .help("Public domain")
+      .long_help(
+        "Public domain of the platform. \
+        The rest api endpoint and the console url are derived from this domain, \
+        as 'https://api.<public-domain>/resources/v0' and 'https://console.<public-domain>'.",
+      ),
+    Arg::new(REALM_ARGUMENT)
+      .long("realm")
+      .action(ArgAction::Set)
+      .value_parser(builder::NonEmptyStringValueParser::new())
+      .value_name("REALM")
+      .required(add)
+      .help("Key-cloak realm")
+      .long_help("Key-cloak realm of the platform, used for authentication."),
+  ]
+}
+
+// Apply the optional platform arguments that do not depend on other arguments
+fn apply_platform_arguments(platform: &mut PlatformDefinition, matches: &ArgMatches) {
+  if let Some(alias) = matches.get_one::<String>(ALIAS_ARGUMENT) {
+    platform.alias = alias.to_string();
+  }
+  if let Some(cloud_provider) = matches.get_one::<String>(CLOUD_PROVIDER_ARGUMENT) {
+    platform.cloud_provider = if cloud_provider == "azure" { CloudProvider::Azure } else { CloudProvider::AWS };
+  }
+  if let Some(description) = matches.get_one::<String>(DESCRIPTION_ARGUMENT) {
+    platform.description = description.to_string();
+  }
+  if let Some(private_domain) = matches.get_one::<String>(PRIVATE_DOMAIN_ARGUMENT) {
+    platform.private_domain = Some(private_domain.to_string());
+  }
+  if let Some(production) = matches.get_one::<bool>(PRODUCTION_ARGUMENT) {
+    platform.is_production = *production;
+  }
+}
+
+// Check whether the user platforms are valid when merged with the default platforms
+fn check_user_platforms(user_platforms: &[PlatformDefinition]) -> Result<(), DshCliError> {
+  let errors = validate_platforms(&merge_platforms(default_platforms(), user_platforms.to_vec()));
+  if errors.is_empty() {
+    Ok(())
+  } else {
+    Err(DshCliError::Validation(format!("invalid platform definition\n  {}", errors.join("\n  "))))
+  }
+}
+
+fn warn_if_platforms_file_overridden(context: &Context) -> Result<(), String> {
+  if platforms_file_overridden()? {
+    context.print_warning("environment variable DSH_API_PLATFORMS_FILE is set, user defined platforms will not be used");
+  }
+  Ok(())
+}
+
 #[derive(Clone, Eq, Hash, PartialEq, Serialize, Debug)]
 pub(crate) enum DshPlatformLabel {
   AccessTokenEndpoint,
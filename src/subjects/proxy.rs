@@ -1,3 +1,4 @@
+use crate::error::DshCliError;
 use crate::formatters::formatter::{Label, SubjectFormatter};
 use async_trait::async_trait;
 use clap::ArgMatches;
@@ -80,17 +81,17 @@ impl CommandExecutor for ProxyDelete {
     let proxy_id = target.unwrap_or_else(|| unreachable!());
     context.print_explanation(format!("delete proxy '{}'", proxy_id));
     if client.get_kafkaproxy_configuration(&proxy_id).await.is_err() {
-      return Err(format!("proxy '{}' does not exists", proxy_id));
+      return Err(DshCliError::NotFound(format!("proxy '{}' does not exists", proxy_id)));
     }
     if context.confirmed(format!("delete proxy '{}'?", proxy_id))? {
       if context.dry_run() {
         context.print_warning("dry-run mode, proxy not deleted");
       } else {
         client.delete_kafkaproxy_configuration(&proxy_id).await?;
-        context.print_outcome(format!("proxy '{}' deleted", proxy_id));
+        context.print_audited_outcome(client, format!("proxy/{}", proxy_id), format!("proxy '{}' deleted", proxy_id));
       }
     } else {
-      context.print_outcome(format!("cancelled, proxy '{}' not deleted", proxy_id));
+      return Err(DshCliError::Cancelled(format!("cancelled, proxy '{}' not deleted", proxy_id)));
     }
     Ok(())
   }
@@ -151,7 +152,8 @@ impl CommandExecutor for ProxyShowConfiguration {
     let start_instant = context.now();
     let proxy = client.get_kafkaproxy_configuration(&proxy_id).await?;
     context.print_execution_time(start_instant);
-    UnitFormatter::new(proxy_id, &PROXY_LABELS_SHOW, None, context).print(&proxy, None)
+    UnitFormatter::new(proxy_id, &PROXY_LABELS_SHOW, None, context).print(&proxy, None)?;
+    Ok(())
   }
 
   fn requirements(&self, _: &ArgMatches) -> Requirements {
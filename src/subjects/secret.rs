@@ -4,6 +4,7 @@ use crate::capability::{
 };
 use crate::capability_builder::CapabilityBuilder;
 use crate::context::Context;
+use crate::error::DshCliError;
 use crate::filter_flags::FilterFlagType;
 use crate::flags::FlagType;
 use crate::formatters::ids_formatter::IdsFormatter;
@@ -112,7 +113,7 @@ impl CommandExecutor for SecretCreate {
   async fn execute_with_client(&self, target: Option<String>, _: Option<String>, matches: &ArgMatches, client: &DshApiClient, context: &Context) -> DshCliResult {
     let secret_id = target.unwrap_or_else(|| unreachable!());
     if client.get_secret(&secret_id).await.is_ok() {
-      return Err(format!("secret '{}' already exists", secret_id));
+      return Err(DshCliError::Conflict(format!("secret '{}' already exists", secret_id)));
     }
     if context.stdin_is_terminal() {
       if matches.get_flag(ModifierFlagType::MultiLine.id()) {
@@ -123,7 +124,7 @@ impl CommandExecutor for SecretCreate {
           context.print_warning("dry-run mode, secret not created");
         } else {
           client.post_secret(&secret).await?;
-          context.print_outcome(format!("secret '{}' created", secret_id));
+          context.print_audited_outcome(client, format!("secret/{}", secret_id), format!("secret '{}' created", secret_id));
         }
       } else {
         context.print_explanation(format!("create new single line secret '{}'", secret_id));
@@ -133,7 +134,7 @@ impl CommandExecutor for SecretCreate {
           context.print_warning("dry-run mode, secret not created");
         } else {
           client.post_secret(&secret).await?;
-          context.print_outcome(format!("secret '{}' created", secret_id));
+          context.print_audited_outcome(client, format!("secret/{}", secret_id), format!("secret '{}' created", secret_id));
         }
       }
     } else {
@@ -143,7 +144,7 @@ impl CommandExecutor for SecretCreate {
         context.print_warning("dry-run mode, secret not created");
       } else {
         client.post_secret(&secret).await?;
-        context.print_outcome(format!("secret '{}' created", secret_id));
+        context.print_audited_outcome(client, format!("secret/{}", secret_id), format!("secret '{}' created", secret_id));
       }
     }
     Ok(())
@@ -162,17 +163,17 @@ impl CommandExecutor for SecretDelete {
     let secret_id = target.unwrap_or_else(|| unreachable!());
     context.print_explanation(format!("delete secret '{}'", secret_id));
     if client.get_secret_configuration(&secret_id).await.is_err() {
-      return Err(format!("secret '{}' does not exist", secret_id));
+      return Err(DshCliError::NotFound(format!("secret '{}' does not exist", secret_id)));
     }
     if context.confirmed(format!("delete secret '{}'?", secret_id))? {
       if context.dry_run() {
         context.print_warning("dry-run mode, secret not deleted");
       } else {
         client.delete_secret_configuration(&secret_id).await?;
-        context.print_outcome(format!("secret '{}' deleted", secret_id));
+        context.print_audited_outcome(client, format!("secret/{}", secret_id), format!("secret '{}' deleted", secret_id));
       }
     } else {
-      context.print_outcome(format!("cancelled, secret '{}' not deleted", secret_id));
+      return Err(DshCliError::Cancelled(format!("cancelled, secret '{}' not deleted", secret_id)));
     }
     Ok(())
   }
@@ -298,7 +299,8 @@ impl CommandExecutor for SecretShowAllocationStatus {
     let start_instant = context.now();
     let allocation_status = client.get_secret_status(&secret_id).await?;
     context.print_execution_time(start_instant);
-    UnitFormatter::new(secret_id, &DEFAULT_ALLOCATION_STATUS_LABELS, Some("secret id"), context).print(&allocation_status, None)
+    UnitFormatter::new(secret_id, &DEFAULT_ALLOCATION_STATUS_LABELS, Some("secret id"), context).print(&allocation_status, None)?;
+    Ok(())
   }
 
   fn requirements(&self, _: &ArgMatches) -> Requirements {
@@ -357,7 +359,7 @@ impl CommandExecutor for SecretUpdate {
   async fn execute_with_client(&self, target: Option<String>, _: Option<String>, matches: &ArgMatches, client: &DshApiClient, context: &Context) -> DshCliResult {
     let secret_id = target.unwrap_or_else(|| unreachable!());
     if client.get_secret(&secret_id).await.is_err() {
-      return Err(format!("secret '{}' does not exist", secret_id));
+      return Err(DshCliError::NotFound(format!("secret '{}' does not exist", secret_id)));
     }
     if context.stdin_is_terminal() {
       if matches.get_flag(ModifierFlagType::MultiLine.id()) {
@@ -367,7 +369,7 @@ impl CommandExecutor for SecretUpdate {
           context.print_warning("dry-run mode, secret not updated");
         } else {
           client.put_secret(&secret_id, secret).await?;
-          context.print_outcome(format!("secret '{}' updated", secret_id));
+          context.print_audited_outcome(client, format!("secret/{}", secret_id), format!("secret '{}' updated", secret_id));
         }
       } else {
         context.print_explanation(format!("update single line secret '{}'", secret_id));
@@ -376,7 +378,7 @@ impl CommandExecutor for SecretUpdate {
           context.print_warning("dry-run mode, secret not updated");
         } else {
           client.put_secret(&secret_id, secret).await?;
-          context.print_outcome(format!("secret '{}' updated", secret_id));
+          context.print_audited_outcome(client, format!("secret/{}", secret_id), format!("secret '{}' updated", secret_id));
         }
       }
     } else {
@@ -385,7 +387,7 @@ impl CommandExecutor for SecretUpdate {
         context.print_warning("dry-run mode, secret not updated");
       } else {
         client.put_secret(&secret_id, secret).await?;
-        context.print_outcome(format!("secret '{}' updated", secret_id));
+        context.print_audited_outcome(client, format!("secret/{}", secret_id), format!("secret '{}' updated", secret_id));
       }
     }
     Ok(())
@@ -1,10 +1,12 @@
 use crate::arguments::service_id_argument;
+use crate::audit::since_timestamp;
 use crate::capability::{
   Capability, CommandExecutor, CREATE_COMMAND, CREATE_COMMAND_ALIAS, DELETE_COMMAND, DUPLICATE_COMMAND, EDIT_COMMAND, EXPORT_COMMAND, EXPORT_COMMAND_ALIAS, LIST_COMMAND,
-  LIST_COMMAND_ALIAS, RESTART_COMMAND, SHOW_COMMAND, SHOW_COMMAND_ALIAS, START_COMMAND, STOP_COMMAND, UPDATE_COMMAND,
+  LIST_COMMAND_ALIAS, RESTART_COMMAND, ROLLBACK_COMMAND, SHOW_COMMAND, SHOW_COMMAND_ALIAS, START_COMMAND, STOP_COMMAND, UPDATE_COMMAND,
 };
 use crate::capability_builder::CapabilityBuilder;
 use crate::context::Context;
+use crate::error::DshCliError;
 use crate::filter_flags::FilterFlagType;
 use crate::flags::FlagType;
 use crate::formatters::formatter::{hashmap_to_table, Label, SubjectFormatter};
@@ -12,6 +14,7 @@ use crate::formatters::ids_formatter::IdsFormatter;
 use crate::formatters::list_formatter::ListFormatter;
 use crate::formatters::unit_formatter::UnitFormatter;
 use crate::formatters::OutputFormat;
+use crate::snapshots::{configuration_differences, read_service_snapshots, save_service_snapshot, select_snapshot, CONFIGURATION_DIFFERENCE_LABELS};
 use crate::subject::{Requirements, Subject};
 use crate::subjects::DEFAULT_ALLOCATION_STATUS_LABELS;
 use crate::{edit_configuration, include_started_stopped, read_single_line, DshCliResult};
@@ -24,6 +27,8 @@ use dsh_api::types::{Application, TaskState};
 use dsh_api::types::{Task, TaskStatus};
 use dsh_api::DshApiError;
 use futures::future::try_join_all;
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
 use lazy_static::lazy_static;
 use serde::Serialize;
 use std::thread::sleep;
@@ -87,6 +92,22 @@ lazy_static! {
       .set_long_about("Restarts an already running service.")
       .add_target_argument(service_id_argument().required(true))
   );
+  static ref SERVICE_ROLLBACK_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
+    CapabilityBuilder::new(ROLLBACK_COMMAND, None, &ServiceRollback {}, "Roll back service configuration")
+      .set_long_about(
+        "Restore a previous configuration of a DSH service. \
+        Before the configuration of a service is changed or the service is deleted \
+        with the dsh tool, a snapshot of the previous configuration is stored \
+        in the dsh tool's home directory. \
+        By default the configuration from before the most recent change will be restored. \
+        The differences with the current configuration will be shown before the rollback \
+        needs to be confirmed. Since a rollback is also a change, a rollback can itself \
+        be rolled back."
+      )
+      .add_target_argument(service_id_argument().required(true))
+      .add_extra_argument(steps_flag().help_heading(HELP_HEADING))
+      .add_extra_argument(to_flag().help_heading(HELP_HEADING))
+  );
   static ref SERVICE_SHOW_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
     CapabilityBuilder::new(SHOW_COMMAND, Some(SHOW_COMMAND_ALIAS), &ServiceShowAll {}, "Show service configuration")
       .set_long_about("Show the configuration of a DSH service.")
@@ -123,6 +144,7 @@ lazy_static! {
     SERVICE_EXPORT_CAPABILITY.as_ref(),
     SERVICE_LIST_CAPABILITY.as_ref(),
     SERVICE_RESTART_CAPABILITY.as_ref(),
+    SERVICE_ROLLBACK_CAPABILITY.as_ref(),
     SERVICE_SHOW_CAPABILITY.as_ref(),
     SERVICE_START_CAPABILITY.as_ref(),
     SERVICE_STOP_CAPABILITY.as_ref(),
@@ -153,6 +175,7 @@ impl Subject for ServiceSubject {
       DUPLICATE_COMMAND => Some(SERVICE_DUPLICATE_CAPABILITY.as_ref()),
       LIST_COMMAND => Some(SERVICE_LIST_CAPABILITY.as_ref()),
       RESTART_COMMAND => Some(SERVICE_RESTART_CAPABILITY.as_ref()),
+      ROLLBACK_COMMAND => Some(SERVICE_ROLLBACK_CAPABILITY.as_ref()),
       SHOW_COMMAND => Some(SERVICE_SHOW_CAPABILITY.as_ref()),
       START_COMMAND => Some(SERVICE_START_CAPABILITY.as_ref()),
       STOP_COMMAND => Some(SERVICE_STOP_CAPABILITY.as_ref()),
@@ -205,6 +228,37 @@ fn mem_flag() -> Arg {
     .long_help("Set amount of memory available for the service (MiB).")
 }
 
+const STEPS_FLAG: &str = "steps";
+
+fn steps_flag() -> Arg {
+  Arg::new(STEPS_FLAG)
+    .long(STEPS_FLAG)
+    .action(ArgAction::Set)
+    .value_parser(builder::RangedU64ValueParser::<usize>::new().range(1..))
+    .value_name("STEPS")
+    .help("Number of changes to roll back")
+    .long_help(
+      "Number of changes to roll back. \
+      The default value 1 restores the configuration from before the most recent change.",
+    )
+    .conflicts_with(TO_FLAG)
+}
+
+const TO_FLAG: &str = "to";
+
+fn to_flag() -> Arg {
+  Arg::new(TO_FLAG)
+    .long(TO_FLAG)
+    .action(ArgAction::Set)
+    .value_name("MOMENT")
+    .help("Restore the configuration as it was at this moment")
+    .long_help(
+      "Restore the configuration as it was at this moment. The value can be a utc date, \
+      like 2025-03-01, a utc date and time, like 2025-03-01T12:00, or a period before now, \
+      like 30m (minutes), 12h (hours), 7d (days) or 2w (weeks).",
+    )
+}
+
 struct ServiceCreate {}
 
 #[async_trait]
@@ -212,7 +266,7 @@ impl CommandExecutor for ServiceCreate {
   async fn execute_with_client(&self, target: Option<String>, _: Option<String>, _: &ArgMatches, client: &DshApiClient, context: &Context) -> DshCliResult {
     let service_id = target.unwrap_or_else(|| unreachable!());
     if client.get_application_configuration(&service_id).await.is_ok() {
-      return Err(format!("service '{}' already exists", service_id));
+      return Err(DshCliError::Conflict(format!("service '{}' already exists", service_id)));
     }
     context.print_explanation(format!("create new service '{}'", service_id));
     let configuration = context.read_multi_line("enter json configuration (terminate input with ctrl-d after last line)")?;
@@ -222,11 +276,11 @@ impl CommandExecutor for ServiceCreate {
           context.print_warning("dry-run mode, service not created");
         } else {
           client.put_application_configuration(&service_id, &service).await?;
-          context.print_outcome(format!("service '{}' created", service_id));
+          context.print_audited_outcome(client, format!("service/{}", service_id), format!("service '{}' created", service_id));
         }
         Ok(())
       }
-      Err(error) => Err(format!("invalid json configuration ({})", error)),
+      Err(error) => Err(DshCliError::Validation(format!("invalid json configuration ({})", error))),
     }
   }
 
@@ -242,18 +296,20 @@ impl CommandExecutor for ServiceDelete {
   async fn execute_with_client(&self, target: Option<String>, _: Option<String>, _: &ArgMatches, client: &DshApiClient, context: &Context) -> DshCliResult {
     let service_id = target.unwrap_or_else(|| unreachable!());
     context.print_explanation(format!("delete service '{}'", service_id));
-    if client.get_application_configuration(&service_id).await.is_err() {
-      return Err(format!("service '{}' does not exist", service_id));
-    }
+    let configuration = match client.get_application_configuration(&service_id).await {
+      Ok(configuration) => configuration,
+      Err(_) => return Err(DshCliError::NotFound(format!("service '{}' does not exist", service_id))),
+    };
     if context.confirmed(format!("delete service '{}'?", service_id))? {
       if context.dry_run() {
         context.print_warning("dry-run mode, service not deleted");
       } else {
+        save_snapshot(client, &service_id, &configuration, context);
         client.delete_application_configuration(&service_id).await?;
-        context.print_outcome(format!("service '{}' deleted", service_id));
+        context.print_audited_outcome(client, format!("service/{}", service_id), format!("service '{}' deleted", service_id));
       }
     } else {
-      context.print_outcome(format!("cancelled, service '{}' not deleted", service_id));
+      return Err(DshCliError::Cancelled(format!("cancelled, service '{}' not deleted", service_id)));
     }
     Ok(())
   }
@@ -273,8 +329,7 @@ impl CommandExecutor for ServiceDuplicate {
     let verbatim = matches.get_flag("verbatim-flag");
     let duplicate_service_id = read_single_line("service name for new service: ")?;
     if client.get_application_configuration(&duplicate_service_id).await.is_ok() {
-      context.print_error(format!("service '{}' already exists", duplicate_service_id));
-      return Ok(());
+      return Err(DshCliError::Conflict(format!("service '{}' already exists", duplicate_service_id)));
     }
     match client.get_application_configuration(&service_id).await {
       Ok(mut application) => {
@@ -294,7 +349,11 @@ impl CommandExecutor for ServiceDuplicate {
             context.print_warning("dry-run mode, duplicate service not created");
           } else {
             client.put_application_configuration(&duplicate_service_id, &application).await?;
-            context.print_outcome(format!("new service '{}' created from service '{}'", duplicate_service_id, service_id));
+            context.print_audited_outcome(
+              client,
+              format!("service/{}", duplicate_service_id),
+              format!("new service '{}' created from service '{}'", duplicate_service_id, service_id),
+            );
           }
         }
         Ok(())
@@ -304,7 +363,7 @@ impl CommandExecutor for ServiceDuplicate {
           context.print_error(format!("service '{}' does not exist", service_id));
           Ok(())
         }
-        error => Err(String::from(error)),
+        error => Err(DshCliError::from(error)),
       },
     }
   }
@@ -334,8 +393,15 @@ impl CommandExecutor for ServiceEdit {
               if context.dry_run() {
                 context.print_warning("dry-run mode, service configuration not updated");
               } else {
+                save_snapshot(client, &service_id, &application, context);
                 client.put_application_configuration(&service_id, &updated_application).await?;
-                context.print_outcome(format!("service '{}' configuration updated", service_id));
+                context.print_audited_change(
+                  client,
+                  format!("service/{}", service_id),
+                  format!("service '{}' configuration updated", service_id),
+                  Some(&application),
+                  Some(&updated_application),
+                );
               }
             }
           }
@@ -348,7 +414,7 @@ impl CommandExecutor for ServiceEdit {
           context.print_error(format!("service '{}' does not exist", service_id));
           Ok(())
         }
-        error => Err(String::from(error)),
+        error => Err(DshCliError::from(error)),
       },
     }
   }
@@ -368,7 +434,8 @@ impl CommandExecutor for ServiceExport {
     let start_instant = context.now();
     let service = client.get_application_configuration(&service_id).await?;
     context.print_execution_time(start_instant);
-    UnitFormatter::new(service_id, &SERVICE_LABELS_SHOW, Some("service id"), context).print(&service, Some(OutputFormat::Json))
+    UnitFormatter::new(service_id, &SERVICE_LABELS_SHOW, Some("service id"), context).print(&service, Some(OutputFormat::Json))?;
+    Ok(())
   }
 
   fn requirements(&self, _: &ArgMatches) -> Requirements {
@@ -465,6 +532,19 @@ impl CommandExecutor for ServiceListTasks {
     context.print_explanation("list all services with their tasks");
     let start_instant = context.now();
     let services_with_tasks = client.get_task_ids().await?;
+    let mut formatter: ListFormatter<ServiceLabel, String> = ListFormatter::new(&[ServiceLabel::Target, ServiceLabel::Tasks], None, context);
+    if formatter.is_streaming(None) {
+      let mut tasks_futures = services_with_tasks
+        .iter()
+        .map(|service_id| async move { client.get_task_appid_ids(service_id).await.map(|tasks| (service_id, tasks)) })
+        .collect::<FuturesUnordered<_>>();
+      while let Some(service_id_tasks) = tasks_futures.next().await {
+        let (service_id, tasks) = service_id_tasks?;
+        formatter.print_value(service_id, &tasks_to_string(tasks))?;
+      }
+      context.print_execution_time(start_instant);
+      return Ok(());
+    }
     let tasks: Vec<Vec<String>> = try_join_all(services_with_tasks.iter().map(|service_id| client.get_task_appid_ids(service_id))).await?;
     context.print_execution_time(start_instant);
     let service_id_tasks_pairs: Vec<(String, String)> = services_with_tasks
@@ -472,8 +552,7 @@ impl CommandExecutor for ServiceListTasks {
       .zip(tasks)
       .map(|(id, tasks)| (id.to_string(), tasks_to_string(tasks)))
       .collect::<Vec<_>>();
-    let mut formatter: ListFormatter<ServiceLabel, (String, String)> = ListFormatter::new(&[ServiceLabel::Target, ServiceLabel::Tasks], None, context);
-    formatter.push_values(&service_id_tasks_pairs);
+    formatter.push_target_id_value_pairs(&service_id_tasks_pairs);
     formatter.print(None)?;
     Ok(())
   }
@@ -525,16 +604,24 @@ impl CommandExecutor for ServiceRestart {
             }
           }
           if running_task_ids.len() == 1 {
-            context.print_outcome(format!("\nservice '{}' stopped", service_id));
+            context.print_audited_outcome(client, format!("service/{}", service_id), format!("\nservice '{}' stopped", service_id));
           } else {
-            context.print_outcome(format!("\nservice '{}' stopped ({} instances)", service_id, running_task_ids.len()));
+            context.print_audited_outcome(
+              client,
+              format!("service/{}", service_id),
+              format!("\nservice '{}' stopped ({} instances)", service_id, running_task_ids.len()),
+            );
           }
           configuration.instances = instances;
           client.put_application_configuration(&service_id, &configuration).await?;
           if instances == 1 {
-            context.print_outcome(format!("service '{}' started", service_id));
+            context.print_audited_outcome(client, format!("service/{}", service_id), format!("service '{}' started", service_id));
           } else {
-            context.print_outcome(format!("service '{}' started ({} instances)", service_id, instances));
+            context.print_audited_outcome(
+              client,
+              format!("service/{}", service_id),
+              format!("service '{}' started ({} instances)", service_id, instances),
+            );
           }
         }
         Ok(())
@@ -544,9 +631,85 @@ impl CommandExecutor for ServiceRestart {
           context.print_error(format!("service '{}' does not exist", service_id));
           Ok(())
         }
-        error => Err(String::from(error)),
+        error => Err(DshCliError::from(error)),
+      },
+    }
+  }
+
+  fn requirements(&self, _: &ArgMatches) -> Requirements {
+    Requirements::standard_with_api()
+  }
+}
+
+struct ServiceRollback {}
+
+#[async_trait]
+impl CommandExecutor for ServiceRollback {
+  async fn execute_with_client(&self, target: Option<String>, _: Option<String>, matches: &ArgMatches, client: &DshApiClient, context: &Context) -> DshCliResult {
+    let service_id = target.unwrap_or_else(|| unreachable!());
+    let steps = matches.get_one::<usize>(STEPS_FLAG).cloned().unwrap_or(1);
+    let to = matches
+      .get_one::<String>(TO_FLAG)
+      .map(|to| since_timestamp(to))
+      .transpose()
+      .map_err(DshCliError::Validation)?;
+    context.print_explanation(format!("roll back service '{}' configuration", service_id));
+    let snapshots = read_service_snapshots(client.platform().name(), client.tenant_name(), &service_id)?;
+    let snapshot = match select_snapshot(&snapshots, steps, to.as_deref()) {
+      Some(snapshot) => snapshot,
+      None if snapshots.is_empty() => return Err(DshCliError::NotFound(format!("no snapshots found for service '{}'", service_id))),
+      None => match matches.get_one::<String>(TO_FLAG) {
+        Some(to) => return Err(DshCliError::NotFound(format!("no changes to service '{}' found after {}", service_id, to))),
+        None => {
+          return Err(DshCliError::NotFound(format!(
+            "only {} snapshot(s) found for service '{}'",
+            snapshots.len(),
+            service_id
+          )))
+        }
       },
+    };
+    let current_configuration = match client.get_application_configuration(&service_id).await {
+      Ok(configuration) => Some(configuration),
+      Err(DshApiError::NotFound(_)) => None,
+      Err(error) => return Err(DshCliError::from(error)),
+    };
+    match current_configuration {
+      Some(ref current_configuration) => {
+        let differences = configuration_differences(current_configuration, &snapshot.configuration)?;
+        if differences.is_empty() {
+          context.print_outcome(format!(
+            "configuration from {} is equal to the current configuration, service '{}' not rolled back",
+            snapshot.timestamp, service_id
+          ));
+          return Ok(());
+        }
+        let mut formatter = ListFormatter::new(&CONFIGURATION_DIFFERENCE_LABELS, None, context);
+        formatter.push_values(&differences);
+        formatter.print(None)?;
+      }
+      None => context.print_warning(format!("service '{}' does not exist, it will be created", service_id)),
     }
+    if context.confirmed(format!("restore service '{}' configuration from {}?", service_id, snapshot.timestamp))? {
+      if context.dry_run() {
+        context.print_warning("dry-run mode, service not rolled back");
+      } else {
+        if let Some(ref current_configuration) = current_configuration {
+          save_snapshot(client, &service_id, current_configuration, context);
+        }
+        client.put_application_configuration(&service_id, &snapshot.configuration).await?;
+        context.print_audited_change(
+          client,
+          format!("service/{}", service_id),
+          format!("service '{}' configuration restored from {}", service_id, snapshot.timestamp),
+          current_configuration.as_ref(),
+          Some(&snapshot.configuration),
+        );
+      }
+    } else {
+      return Err(DshCliError::Cancelled(format!("cancelled, service '{}' not rolled back", service_id)));
+    }
+    Ok(())
   }
 
   fn requirements(&self, _: &ArgMatches) -> Requirements {
@@ -564,7 +727,8 @@ impl CommandExecutor for ServiceShowAll {
     let start_instant = context.now();
     let service = client.get_application_configuration(&service_id).await?;
     context.print_execution_time(start_instant);
-    UnitFormatter::new(service_id, &SERVICE_LABELS_SHOW, Some("service id"), context).print(&service, None)
+    UnitFormatter::new(service_id, &SERVICE_LABELS_SHOW, Some("service id"), context).print(&service, None)?;
+    Ok(())
   }
 
   fn requirements(&self, _: &ArgMatches) -> Requirements {
@@ -582,7 +746,8 @@ impl CommandExecutor for ServiceShowAllocationStatus {
     let start_instant = context.now();
     let allocation_status = client.get_application_status(&service_id).await?;
     context.print_execution_time(start_instant);
-    UnitFormatter::new(service_id, &DEFAULT_ALLOCATION_STATUS_LABELS, Some("service id"), context).print(&allocation_status, None)
+    UnitFormatter::new(service_id, &DEFAULT_ALLOCATION_STATUS_LABELS, Some("service id"), context).print(&allocation_status, None)?;
+    Ok(())
   }
 
   fn requirements(&self, _: &ArgMatches) -> Requirements {
@@ -633,12 +798,17 @@ impl CommandExecutor for ServiceStart {
         } else if context.dry_run() {
           context.print_warning("dry-run mode, service not started");
         } else {
+          save_snapshot(client, &service_id, &configuration, context);
           configuration.instances = instances;
           client.put_application_configuration(&service_id, &configuration).await?;
           if instances == 1 {
-            context.print_outcome(format!("service '{}' started", service_id));
+            context.print_audited_outcome(client, format!("service/{}", service_id), format!("service '{}' started", service_id));
           } else {
-            context.print_outcome(format!("service '{}' started ({} instances)", service_id, instances));
+            context.print_audited_outcome(
+              client,
+              format!("service/{}", service_id),
+              format!("service '{}' started ({} instances)", service_id, instances),
+            );
           }
         }
         Ok(())
@@ -648,7 +818,7 @@ impl CommandExecutor for ServiceStart {
           context.print_error(format!("service '{}' does not exist", service_id));
           Ok(())
         }
-        error => Err(String::from(error)),
+        error => Err(DshCliError::from(error)),
       },
     }
   }
@@ -673,12 +843,17 @@ impl CommandExecutor for ServiceStop {
         } else if context.dry_run() {
           context.print_warning("dry-run mode, service not stopped");
         } else {
+          save_snapshot(client, &service_id, &configuration, context);
           configuration.instances = 0;
           client.put_application_configuration(&service_id, &configuration).await?;
           if running_instances == 1 {
-            context.print_outcome(format!("service '{}' stopped", service_id));
+            context.print_audited_outcome(client, format!("service/{}", service_id), format!("service '{}' stopped", service_id));
           } else {
-            context.print_outcome(format!("service '{}' stopped ({} instances)", service_id, running_instances));
+            context.print_audited_outcome(
+              client,
+              format!("service/{}", service_id),
+              format!("service '{}' stopped ({} instances)", service_id, running_instances),
+            );
           }
         }
         Ok(())
@@ -688,7 +863,7 @@ impl CommandExecutor for ServiceStop {
           context.print_error(format!("service '{}' does not exist", service_id));
           Ok(())
         }
-        error => Err(String::from(error)),
+        error => Err(DshCliError::from(error)),
       },
     }
   }
@@ -709,7 +884,7 @@ impl CommandExecutor for ServiceUpdate {
         if cpus >= 0.1 {
           Some(cpus)
         } else {
-          return Err("cpus should be greater than or equal to 0.1".to_string());
+          return Err(DshCliError::Validation("cpus should be greater than or equal to 0.1".to_string()));
         }
       }
       None => None,
@@ -727,6 +902,8 @@ impl CommandExecutor for ServiceUpdate {
             if context.dry_run() {
               context.print_warning("dry-run mode, service not updated");
             } else {
+              let current_configuration = configuration.clone();
+              save_snapshot(client, &service_id, &current_configuration, context);
               if let Some(cpus) = cpus {
                 configuration.cpus = cpus
               }
@@ -737,7 +914,13 @@ impl CommandExecutor for ServiceUpdate {
                 configuration.mem = mem
               }
               client.put_application_configuration(&service_id, &configuration).await?;
-              context.print_outcome(format!("service '{}' updated", service_id));
+              context.print_audited_change(
+                client,
+                format!("service/{}", service_id),
+                format!("service '{}' updated", service_id),
+                Some(&current_configuration),
+                Some(&configuration),
+              );
             }
             Ok(())
           } else {
@@ -749,15 +932,24 @@ impl CommandExecutor for ServiceUpdate {
           let update_configuration_json = context.read_multi_line("enter json configuration (terminate input with ctrl-d after last line)")?;
           match serde_json::from_str::<Application>(&update_configuration_json) {
             Ok(update_configuration) => {
-              if context.dry_run() {
+              if update_configuration == configuration {
+                context.print_outcome("provided configuration is equal to the current configuration, service not updated");
+              } else if context.dry_run() {
                 context.print_warning("dry-run mode, service not updated");
               } else {
+                save_snapshot(client, &service_id, &configuration, context);
                 client.put_application_configuration(&service_id, &update_configuration).await?;
-                context.print_outcome(format!("service '{}' updated", service_id));
+                context.print_audited_change(
+                  client,
+                  format!("service/{}", service_id),
+                  format!("service '{}' updated", service_id),
+                  Some(&configuration),
+                  Some(&update_configuration),
+                );
               }
               Ok(())
             }
-            Err(error) => Err(format!("invalid json configuration ({})", error)),
+            Err(error) => Err(DshCliError::Validation(format!("invalid json configuration ({})", error))),
           }
         }
       }
@@ -766,7 +958,7 @@ impl CommandExecutor for ServiceUpdate {
           context.print_error(format!("service '{}' does not exist", service_id));
           Ok(())
         }
-        error => Err(String::from(error)),
+        error => Err(DshCliError::from(error)),
       },
     }
   }
@@ -776,6 +968,18 @@ impl CommandExecutor for ServiceUpdate {
   }
 }
 
+/// # Stores a snapshot of the current service configuration
+///
+/// Must be called before the service configuration is changed, and only when the new
+/// configuration differs from the current configuration. Operations that end with
+/// the same configuration, like a restart, must not store a snapshot.
+/// Failing to store the snapshot will not fail the command, but will result in a warning.
+fn save_snapshot(client: &DshApiClient, service_id: &str, configuration: &Application, context: &Context) {
+  if let Err(error) = save_service_snapshot(client.platform().name(), client.tenant_name(), service_id, configuration) {
+    context.print_warning(error);
+  }
+}
+
 #[derive(Eq, Hash, PartialEq, Serialize)]
 pub(crate) enum ServiceLabel {
   Cpus,
@@ -1,3 +1,4 @@
+use crate::error::DshCliError;
 use crate::formatters::formatter::{Label, SubjectFormatter};
 use crate::settings::{upsert_settings, Settings};
 use async_trait::async_trait;
@@ -302,10 +303,10 @@ impl CommandExecutor for SettingDefault {
     let platform = get_platform_argument_or_prompt(matches)?;
     let tenant = get_tenant_argument_or_prompt(matches)?;
     if read_target(&platform, &tenant)?.is_none() {
-      return Err(format!("target '{}@{}' does not exist", tenant, platform));
+      return Err(DshCliError::NotFound(format!("target '{}@{}' does not exist", tenant, platform)));
     };
     if get_target_password_from_keyring(&platform, &tenant)?.is_none() {
-      return Err(format!("keyring contains no password for target '{}@{}'", tenant, platform));
+      return Err(DshCliError::NotFound(format!("keyring contains no password for target '{}@{}'", tenant, platform)));
     }
     upsert_settings(None, |settings| Ok(Settings { default_platform: Some(platform.to_string()), ..settings }))?;
     context.print_outcome(format!("default platform set to {}", platform));
@@ -367,7 +368,7 @@ impl CommandExecutor for SettingSet {
         let mut csv_quote_chars = matches.get_one::<String>(SETTING_CSV_QUOTE).unwrap().chars();
         let csv_quote = csv_quote_chars.next().unwrap();
         if csv_quote_chars.next().is_some() {
-          return Err("csv quote must be a single character".to_string());
+          return Err(DshCliError::Validation("csv quote must be a single character".to_string()));
         } else {
           upsert_settings(None, |settings| Ok(Settings { csv_quote: Some(csv_quote), ..settings }))?;
           context.print_outcome(format!("csv quote character set to '{}'", csv_quote));
@@ -480,7 +481,7 @@ impl CommandExecutor for SettingSet {
       SETTING_TERMINAL_WIDTH => {
         let terminal_width = matches.get_one::<usize>(SETTING_TERMINAL_WIDTH).unwrap();
         if *terminal_width < 40 {
-          return Err("terminal width must be greater than or equal to 40".to_string());
+          return Err(DshCliError::Validation("terminal width must be greater than or equal to 40".to_string()));
         } else {
           upsert_settings(None, |settings| Ok(Settings { terminal_width: Some(*terminal_width), ..settings }))?;
           context.print_outcome(format!("terminal width set to {}", terminal_width));
@@ -1,3 +1,4 @@
+use crate::error::DshCliError;
 use crate::subject::Requirements;
 
 use async_trait::async_trait;
@@ -14,7 +15,7 @@ use dsh_api::types::{
 };
 
 use crate::arguments::{managed_stream_argument, MANAGED_STREAM_ARGUMENT};
-use crate::capability::{Capability, CommandExecutor, CREATE_COMMAND, DELETE_COMMAND, LIST_COMMAND, LIST_COMMAND_ALIAS, SHOW_COMMAND, SHOW_COMMAND_ALIAS};
+use crate::capability::{Capability, CommandExecutor, CREATE_COMMAND, DELETE_COMMAND, LIST_COMMAND, LIST_COMMAND_ALIAS, SHOW_COMMAND, SHOW_COMMAND_ALIAS, UPDATE_COMMAND};
 use crate::capability_builder::CapabilityBuilder;
 use crate::filter_flags::FilterFlagType;
 use crate::flags::FlagType;
@@ -25,9 +26,9 @@ use crate::formatters::unit_formatter::UnitFormatter;
 use crate::formatters::OutputFormat;
 use crate::subject::Subject;
 use crate::subjects::topic::{
-  cleanup_policy_flag, compression_type_flag, create_topic, delete_retention_ms_flag, get_implicit_properties, max_message_size_flag, message_timestamp_type_flag, partitions_flag,
-  retention_bytes_flag, retention_ms_flag, segment_bytes_flag, CLEANUP_POLICY_PROPERTY, COMPRESSION_TYPE_PROPERTY, DELETE_RETENTION_MS_PROPERTY, MAX_MESSAGE_BYTES_PROPERTY,
-  MESSAGE_TIMESTAMP_PROPERTY, RETENTION_BYTES_PROPERTY, RETENTION_MS_PROPERTY, SEGMENT_BYTES_PROPERTY,
+  cleanup_policy_flag, compression_type_flag, create_topic, delete_retention_ms_flag, get_implicit_properties, kafka_properties_from_matches, max_message_size_flag,
+  message_timestamp_type_flag, partitions_flag, retention_bytes_flag, retention_ms_flag, segment_bytes_flag, CLEANUP_POLICY_PROPERTY, COMPRESSION_TYPE_PROPERTY,
+  DELETE_RETENTION_MS_PROPERTY, MAX_MESSAGE_BYTES_PROPERTY, MESSAGE_TIMESTAMP_PROPERTY, PARTITIONS_FLAG, RETENTION_BYTES_PROPERTY, RETENTION_MS_PROPERTY, SEGMENT_BYTES_PROPERTY,
 };
 use crate::{read_single_line, Context, DshCliResult};
 use dsh_api::types::{PublicManagedStream, PublicManagedStreamContractPartitioner};
@@ -62,6 +63,7 @@ impl Subject for StreamSubject {
       DELETE_COMMAND => Some(STREAM_DELETE_CAPABILITY.as_ref()),
       LIST_COMMAND => Some(STREAM_LIST_CAPABILITY.as_ref()),
       SHOW_COMMAND => Some(STREAM_SHOW_CAPABILITY.as_ref()),
+      UPDATE_COMMAND => Some(STREAM_UPDATE_CAPABILITY.as_ref()),
       _ => None,
     }
   }
@@ -94,7 +96,7 @@ lazy_static! {
       )
       .add_target_argument(managed_stream_argument())
       .add_extra_arguments(vec![
-        can_be_retained_flag(STREAM_OPTIONS_HEADING),
+        can_be_retained_argument(STREAM_OPTIONS_HEADING),
         cleanup_policy_flag(STREAM_OPTIONS_HEADING),
         compression_type_flag(STREAM_OPTIONS_HEADING),
         delete_retention_ms_flag(STREAM_OPTIONS_HEADING),
@@ -126,20 +128,55 @@ lazy_static! {
   pub static ref STREAM_SHOW_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
     CapabilityBuilder::new(SHOW_COMMAND, Some(SHOW_COMMAND_ALIAS), &StreamShowAll {}, "Show managed stream configuration")
       .add_target_argument(managed_stream_argument().required(true))
+      .add_command_executor(
+        FlagType::Access,
+        &StreamShowAccess {},
+        Some("List all managed tenants with the access rights that they have been granted for the managed stream.".to_string())
+      )
+  );
+  static ref STREAM_UPDATE_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
+    CapabilityBuilder::new(UPDATE_COMMAND, None, &StreamUpdate {}, "Update managed stream")
+      .set_long_about(
+        "Update the configuration of an internal or public managed stream. \
+         Only the provided stream options will be changed, \
+         all other options will keep their current values. \
+         The resource management api has no separate update operation for managed streams, \
+         so the updated configuration is submitted with the same request that creates a stream. \
+         The platform can reject changes that cannot be applied to an existing stream, \
+         e.g. decreasing the number of partitions."
+      )
+      .add_target_argument(managed_stream_argument().required(true))
+      .add_extra_arguments(vec![
+        can_be_retained_argument(STREAM_OPTIONS_HEADING),
+        cleanup_policy_flag(STREAM_OPTIONS_HEADING),
+        compression_type_flag(STREAM_OPTIONS_HEADING),
+        delete_retention_ms_flag(STREAM_OPTIONS_HEADING),
+        kafka_default_partitioner_flag(STREAM_OPTIONS_HEADING),
+        max_message_size_flag(STREAM_OPTIONS_HEADING),
+        message_timestamp_type_flag(STREAM_OPTIONS_HEADING),
+        partitions_flag(STREAM_OPTIONS_HEADING),
+        retention_bytes_flag(STREAM_OPTIONS_HEADING),
+        retention_ms_flag(STREAM_OPTIONS_HEADING),
+        segment_bytes_flag(STREAM_OPTIONS_HEADING),
+        topic_level_partitioner_arg(STREAM_OPTIONS_HEADING),
+      ])
   );
   static ref STREAM_CAPABILITIES: Vec<&'static (dyn Capability + Send + Sync)> =
-    vec![STREAM_CREATE_CAPABILITY.as_ref(), STREAM_DELETE_CAPABILITY.as_ref(), STREAM_LIST_CAPABILITY.as_ref(), STREAM_SHOW_CAPABILITY.as_ref()];
+    vec![STREAM_CREATE_CAPABILITY.as_ref(), STREAM_DELETE_CAPABILITY.as_ref(), STREAM_LIST_CAPABILITY.as_ref(), STREAM_SHOW_CAPABILITY.as_ref(), STREAM_UPDATE_CAPABILITY.as_ref()];
 }
 
-const CAN_BE_RETAINED_FLAG: &str = "can-be-retained";
+const CAN_BE_RETAINED_ARGUMENT: &str = "can-be-retained";
 
-fn can_be_retained_flag(heading: &'static str) -> Arg {
-  Arg::new(CAN_BE_RETAINED_FLAG)
+fn can_be_retained_argument(heading: &'static str) -> Arg {
+  Arg::new(CAN_BE_RETAINED_ARGUMENT)
     .long("can-be-retained")
-    .action(ArgAction::SetTrue)
+    .action(ArgAction::Set)
+    .value_parser(builder::BoolValueParser::new())
+    .value_name("BOOLEAN")
     .help("Can be retained")
     .long_help(
-      "Whether MQTT records can have the 'retained' flag. \
+      "Whether MQTT records can have the 'retained' flag, true or false. \
+    When creating a stream the default value is false. \
     This option is only meaningful for public managed streams.",
     )
     .help_heading(heading)
@@ -184,8 +221,8 @@ impl CommandExecutor for StreamCreate {
     let managed_stream_id = get_managed_stream_id(matches, client.tenant_name())?;
     if let Some(managed_stream) = client.get_stream_configuration(&managed_stream_id).await? {
       match managed_stream {
-        Stream::Internal(_) => return Err(format!("internal managed stream '{}' already exists", managed_stream_id)),
-        Stream::Public(_) => return Err(format!("public managed stream '{}' already exists", managed_stream_id)),
+        Stream::Internal(_) => return Err(DshCliError::Conflict(format!("internal managed stream '{}' already exists", managed_stream_id))),
+        Stream::Public(_) => return Err(DshCliError::Conflict(format!("public managed stream '{}' already exists", managed_stream_id))),
       }
     }
     let topic = create_topic(matches)?;
@@ -200,7 +237,7 @@ impl CommandExecutor for StreamCreate {
         }),
       };
       context.print_explanation(format!("create new public managed stream '{}'", managed_stream_id));
-      let can_be_retained = matches.get_flag(CAN_BE_RETAINED_FLAG);
+      let can_be_retained = matches.get_one::<bool>(CAN_BE_RETAINED_ARGUMENT).cloned().unwrap_or_default();
       let contract = PublicManagedStreamContract { can_be_retained, partitioner };
       let public_managed_stream =
         PublicManagedStream { contract, kafka_properties: topic.kafka_properties, partitions: topic.partitions, replication_factor: topic.replication_factor };
@@ -208,7 +245,11 @@ impl CommandExecutor for StreamCreate {
         context.print_warning("dry-run mode, public managed stream not created");
       } else {
         client.post_stream_public_configuration(&managed_stream_id, &public_managed_stream).await?;
-        context.print_outcome(format!("public managed stream '{}' created", managed_stream_id));
+        context.print_audited_outcome(
+          client,
+          format!("stream/{}", managed_stream_id),
+          format!("public managed stream '{}' created", managed_stream_id),
+        );
       }
     } else {
       context.print_explanation(format!("create new internal managed stream '{}'", managed_stream_id));
@@ -217,7 +258,11 @@ impl CommandExecutor for StreamCreate {
         context.print_warning("dry-run mode, internal managed stream not created");
       } else {
         client.post_stream_internal_configuration(&managed_stream_id, &managed_stream).await?;
-        context.print_outcome(format!("internal managed stream '{}' created", managed_stream_id));
+        context.print_audited_outcome(
+          client,
+          format!("stream/{}", managed_stream_id),
+          format!("internal managed stream '{}' created", managed_stream_id),
+        );
       }
     }
     Ok(())
@@ -242,10 +287,17 @@ impl CommandExecutor for StreamDelete {
             context.print_warning("dry-run mode, internal managed stream not deleted");
           } else {
             client.delete_stream_internal_configuration(&managed_stream_id).await?;
-            context.print_outcome(format!("internal managed stream '{}' deleted", managed_stream_id));
+            context.print_audited_outcome(
+              client,
+              format!("stream/{}", managed_stream_id),
+              format!("internal managed stream '{}' deleted", managed_stream_id),
+            );
           }
         } else {
-          context.print_outcome(format!("cancelled, internal managed stream '{}' not deleted", managed_stream_id));
+          return Err(DshCliError::Cancelled(format!(
+            "cancelled, internal managed stream '{}' not deleted",
+            managed_stream_id
+          )));
         }
         Ok(())
       }
@@ -256,14 +308,21 @@ impl CommandExecutor for StreamDelete {
             context.print_warning("dry-run mode, public managed stream not deleted");
           } else {
             client.delete_stream_public_configuration(&managed_stream_id).await?;
-            context.print_outcome(format!("public managed stream '{}' deleted", managed_stream_id));
+            context.print_audited_outcome(
+              client,
+              format!("stream/{}", managed_stream_id),
+              format!("public managed stream '{}' deleted", managed_stream_id),
+            );
           }
         } else {
-          context.print_outcome(format!("cancelled, public managed stream '{}' not deleted", managed_stream_id));
+          return Err(DshCliError::Cancelled(format!(
+            "cancelled, public managed stream '{}' not deleted",
+            managed_stream_id
+          )));
         }
         Ok(())
       }
-      None => Err(format!("managed stream '{}' does not exist", managed_stream_id)),
+      None => Err(DshCliError::NotFound(format!("managed stream '{}' does not exist", managed_stream_id))),
     }
   }
 
@@ -291,7 +350,8 @@ impl CommandExecutor for StreamListAll {
         for (stream_id, stream) in streams.iter() {
           formatter.push_target_id_value(stream_id.to_string(), stream);
         }
-        formatter.print(None)
+        formatter.print(None)?;
+        Ok(())
       }
       (true, false) => {
         context.print_explanation("list all internal managed streams");
@@ -302,7 +362,8 @@ impl CommandExecutor for StreamListAll {
         for (internal_stream_id, internal_stream) in internal_streams.iter() {
           formatter.push_target_id_value(internal_stream_id.to_string(), internal_stream);
         }
-        formatter.print(None)
+        formatter.print(None)?;
+        Ok(())
       }
       (false, true) => {
         context.print_explanation("list all public managed streams");
@@ -313,7 +374,8 @@ impl CommandExecutor for StreamListAll {
         for (public_stream_id, public_stream) in public_streams.iter() {
           formatter.push_target_id_value(public_stream_id.to_string(), public_stream);
         }
-        formatter.print(None)
+        formatter.print(None)?;
+        Ok(())
       }
     }
   }
@@ -373,15 +435,17 @@ impl CommandExecutor for StreamShowAll {
     )? {
       (Some(Stream::Internal(internal_managed_stream)), access_rights) => {
         context.print_execution_time(start_instant);
-        UnitFormatter::new(managed_stream_id, &INTERNAL_STREAM_LABELS, None, context).print(&(Stream::Internal(internal_managed_stream), &access_rights), None)
+        UnitFormatter::new(managed_stream_id, &INTERNAL_STREAM_LABELS, None, context).print(&(Stream::Internal(internal_managed_stream), &access_rights), None)?;
+        Ok(())
       }
       (Some(Stream::Public(public_managed_stream)), access_rights) => {
         context.print_execution_time(start_instant);
-        UnitFormatter::new(managed_stream_id, &PUBLIC_STREAM_LABELS, None, context).print(&(Stream::Public(public_managed_stream), &access_rights), None)
+        UnitFormatter::new(managed_stream_id, &PUBLIC_STREAM_LABELS, None, context).print(&(Stream::Public(public_managed_stream), &access_rights), None)?;
+        Ok(())
       }
       (None, _) => {
         context.print_execution_time(start_instant);
-        Err(format!("managed stream '{}' does not exist", managed_stream_id))
+        Err(DshCliError::NotFound(format!("managed stream '{}' does not exist", managed_stream_id)))
       }
     }
   }
@@ -391,6 +455,139 @@ impl CommandExecutor for StreamShowAll {
   }
 }
 
+struct StreamShowAccess {}
+
+#[async_trait]
+impl CommandExecutor for StreamShowAccess {
+  async fn execute_with_client(&self, _: Option<String>, _: Option<String>, matches: &ArgMatches, client: &DshApiClient, context: &Context) -> DshCliResult {
+    let managed_stream_id = get_managed_stream_id(matches, client.tenant_name())?;
+    context.print_explanation(format!(
+      "list all managed tenants with their access rights for managed stream '{}'",
+      managed_stream_id
+    ));
+    let start_instant = context.now();
+    let (tenant_ids, access_rights) = try_join!(client.get_tenant_ids(), client.get_tenants_with_access_rights(&managed_stream_id))?;
+    context.print_execution_time(start_instant);
+    let tenants_access_rights: Vec<(String, Option<AccessRights>)> = tenant_ids
+      .into_iter()
+      .map(|tenant_id| {
+        let tenant_access_rights = access_rights
+          .iter()
+          .find_map(|(granted_tenant_id, granted_access_rights)| if granted_tenant_id == &tenant_id { Some(granted_access_rights.clone()) } else { None });
+        (tenant_id, tenant_access_rights)
+      })
+      .collect::<Vec<_>>();
+    if tenants_access_rights.is_empty() {
+      context.print_outcome("no managed tenants or you are not authorized to manage tenants");
+      Ok(())
+    } else {
+      let mut formatter = ListFormatter::new(&STREAM_ACCESS_RIGHTS_LABELS, None, context);
+      formatter.push_target_id_value_pairs(&tenants_access_rights);
+      formatter.print(None)?;
+      Ok(())
+    }
+  }
+
+  fn requirements(&self, _: &ArgMatches) -> Requirements {
+    Requirements::standard_with_api()
+  }
+}
+
+struct StreamUpdate {}
+
+#[async_trait]
+impl CommandExecutor for StreamUpdate {
+  async fn execute_with_client(&self, _: Option<String>, _: Option<String>, matches: &ArgMatches, client: &DshApiClient, context: &Context) -> DshCliResult {
+    let managed_stream_id = get_managed_stream_id(matches, client.tenant_name())?;
+    let kafka_properties = kafka_properties_from_matches(matches);
+    let partitions = matches.get_one::<u32>(PARTITIONS_FLAG).map(|partitions| *partitions as i64);
+    match client.get_stream_configuration(&managed_stream_id).await? {
+      Some(Stream::Internal(internal_managed_stream)) => {
+        if matches.contains_id(CAN_BE_RETAINED_ARGUMENT) || matches.get_flag(KAFKA_DEFAULT_PARTITIONER) || matches.contains_id(TOPIC_LEVEL_PARTITIONER) {
+          return Err(DshCliError::Validation(
+            "retained and partitioner options are only meaningful for public managed streams".to_string(),
+          ));
+        }
+        context.print_explanation(format!("update internal managed stream '{}'", managed_stream_id));
+        let mut updated_managed_stream = internal_managed_stream.clone();
+        updated_managed_stream.0.kafka_properties.extend(kafka_properties);
+        if let Some(partitions) = partitions {
+          check_partitions(internal_managed_stream.0.partitions, partitions)?;
+          updated_managed_stream.0.partitions = partitions;
+        }
+        if updated_managed_stream == internal_managed_stream {
+          context.print_warning("provided arguments match current values, internal managed stream not updated");
+        } else if context.dry_run() {
+          context.print_warning("dry-run mode, internal managed stream not updated");
+        } else {
+          client.post_stream_internal_configuration(&managed_stream_id, &updated_managed_stream).await?;
+          context.print_audited_change(
+            client,
+            format!("stream/{}", managed_stream_id),
+            format!("internal managed stream '{}' updated", managed_stream_id),
+            Some(&internal_managed_stream),
+            Some(&updated_managed_stream),
+          );
+        }
+        Ok(())
+      }
+      Some(Stream::Public(public_managed_stream)) => {
+        context.print_explanation(format!("update public managed stream '{}'", managed_stream_id));
+        let mut updated_managed_stream = public_managed_stream.clone();
+        updated_managed_stream.kafka_properties.extend(kafka_properties);
+        if let Some(partitions) = partitions {
+          check_partitions(public_managed_stream.partitions, partitions)?;
+          updated_managed_stream.partitions = partitions;
+        }
+        if let Some(can_be_retained) = matches.get_one::<bool>(CAN_BE_RETAINED_ARGUMENT) {
+          updated_managed_stream.contract.can_be_retained = *can_be_retained;
+        }
+        if let Some(topic_level) = matches.get_one::<i64>(TOPIC_LEVEL_PARTITIONER) {
+          updated_managed_stream.contract.partitioner = PublicManagedStreamContractPartitioner::TopicLevelPartitioner(PublicManagedStreamTopicLevelPartitioner {
+            kind: PublicManagedStreamTopicLevelPartitionerKind::TopicLevel,
+            topic_level: *topic_level,
+          });
+        } else if matches.get_flag(KAFKA_DEFAULT_PARTITIONER) {
+          updated_managed_stream.contract.partitioner = PublicManagedStreamContractPartitioner::KafkaDefaultPartitioner(PublicManagedStreamKafkaDefaultPartitioner {
+            kind: PublicManagedStreamKafkaDefaultPartitionerKind::KafkaDefault,
+          });
+        }
+        if updated_managed_stream == public_managed_stream {
+          context.print_warning("provided arguments match current values, public managed stream not updated");
+        } else if context.dry_run() {
+          context.print_warning("dry-run mode, public managed stream not updated");
+        } else {
+          client.post_stream_public_configuration(&managed_stream_id, &updated_managed_stream).await?;
+          context.print_audited_change(
+            client,
+            format!("stream/{}", managed_stream_id),
+            format!("public managed stream '{}' updated", managed_stream_id),
+            Some(&public_managed_stream),
+            Some(&updated_managed_stream),
+          );
+        }
+        Ok(())
+      }
+      None => Err(DshCliError::NotFound(format!("managed stream '{}' does not exist", managed_stream_id))),
+    }
+  }
+
+  fn requirements(&self, _: &ArgMatches) -> Requirements {
+    Requirements::standard_with_api()
+  }
+}
+
+fn check_partitions(current_partitions: i64, new_partitions: i64) -> Result<(), String> {
+  if new_partitions < current_partitions {
+    Err(format!(
+      "number of partitions cannot be decreased (current number of partitions is {})",
+      current_partitions
+    ))
+  } else {
+    Ok(())
+  }
+}
+
 #[derive(Eq, Hash, PartialEq, Serialize)]
 pub enum ManagedStreamLabel {
   CanBeRetained,
@@ -602,6 +799,67 @@ static LIST_PUBLIC_STREAM_LABELS: [ManagedStreamLabel; 10] = [
   ManagedStreamLabel::CanBeRetained,
 ];
 
+#[derive(Eq, Hash, PartialEq, Serialize)]
+pub enum StreamAccessRightsLabel {
+  AccessRights,
+  ReadAccess,
+  Tenant,
+  WriteAccess,
+}
+
+impl Label for StreamAccessRightsLabel {
+  fn as_str(&self) -> &str {
+    match self {
+      Self::AccessRights => "access rights",
+      Self::ReadAccess => "read access",
+      Self::Tenant => "managed tenant",
+      Self::WriteAccess => "write access",
+    }
+  }
+
+  fn as_str_for_list(&self) -> &str {
+    match self {
+      Self::AccessRights => "rights",
+      Self::ReadAccess => "read",
+      Self::Tenant => "tenant",
+      Self::WriteAccess => "write",
+    }
+  }
+
+  fn is_target_label(&self) -> bool {
+    matches!(self, Self::Tenant)
+  }
+}
+
+impl SubjectFormatter<StreamAccessRightsLabel> for Option<AccessRights> {
+  fn value(&self, label: &StreamAccessRightsLabel, target_id: &str) -> String {
+    match label {
+      StreamAccessRightsLabel::AccessRights => match self {
+        Some(access_rights) => access_rights.to_string(),
+        None => "none".to_string(),
+      },
+      StreamAccessRightsLabel::ReadAccess => {
+        if self.as_ref().is_some_and(|access_rights| access_rights.has_read_access()) {
+          "granted".to_string()
+        } else {
+          "denied".to_string()
+        }
+      }
+      StreamAccessRightsLabel::Tenant => target_id.to_string(),
+      StreamAccessRightsLabel::WriteAccess => {
+        if self.as_ref().is_some_and(|access_rights| access_rights.has_write_access()) {
+          "granted".to_string()
+        } else {
+          "denied".to_string()
+        }
+      }
+    }
+  }
+}
+
+static STREAM_ACCESS_RIGHTS_LABELS: [StreamAccessRightsLabel; 4] =
+  [StreamAccessRightsLabel::Tenant, StreamAccessRightsLabel::AccessRights, StreamAccessRightsLabel::ReadAccess, StreamAccessRightsLabel::WriteAccess];
+
 fn get_managed_stream_id(matches: &ArgMatches, managing_tenant: &str) -> Result<ManagedStreamId, String> {
   match matches.get_one::<String>(MANAGED_STREAM_ARGUMENT) {
     Some(managed_stream_argument) => Ok(ManagedStreamId::try_from(managed_stream_argument).map_err(|error| error.to_string())?),
@@ -1,3 +1,4 @@
+use crate::error::DshCliError;
 use crate::formatters::formatter::{Label, SubjectFormatter};
 use async_trait::async_trait;
 use clap::ArgMatches;
@@ -97,10 +98,10 @@ impl CommandExecutor for TargetCreate {
     let platform = get_platform_argument_or_prompt(matches)?;
     let tenant = get_tenant_argument_or_prompt(matches)?;
     if let Some(existing_target) = read_target(&platform, &tenant)? {
-      return Err(format!(
+      return Err(DshCliError::Conflict(format!(
         "target configuration '{}' already exists (first delete the existing target configuration)",
         existing_target
-      ));
+      )));
     };
     let password = context.read_single_line_password("enter password: ")?;
     let target = Target::new(platform, tenant, Some(password))?;
@@ -149,10 +150,10 @@ impl CommandExecutor for TargetDelete {
             }
           }
         } else {
-          context.print_outcome("cancelled");
+          return Err(DshCliError::Cancelled("cancelled".to_string()));
         }
       }
-      None => return Err(format!("target '{}@{}' does not exist", tenant, platform)),
+      None => return Err(DshCliError::NotFound(format!("target '{}@{}' does not exist", tenant, platform))),
     }
     Ok(())
   }
@@ -1,10 +1,11 @@
 use crate::arguments::managed_tenant_argument;
 use crate::capability::{
-  Capability, CommandExecutor, CREATE_COMMAND, CREATE_COMMAND_ALIAS, DELETE_COMMAND, GRANT_COMMAND, LIST_COMMAND, LIST_COMMAND_ALIAS, REVOKE_COMMAND, SHOW_COMMAND,
-  SHOW_COMMAND_ALIAS, UPDATE_COMMAND,
+  Capability, CommandExecutor, CREATE_COMMAND, CREATE_COMMAND_ALIAS, DELETE_COMMAND, GRANT_COMMAND, LIST_COMMAND, LIST_COMMAND_ALIAS, PROVISION_COMMAND, REVOKE_COMMAND,
+  SHOW_COMMAND, SHOW_COMMAND_ALIAS, UPDATE_COMMAND, USAGE_COMMAND,
 };
 use crate::capability_builder::CapabilityBuilder;
 use crate::context::Context;
+use crate::error::DshCliError;
 use crate::flags::FlagType;
 use crate::formatters::formatter::{Label, SubjectFormatter};
 use crate::formatters::ids_formatter::IdsFormatter;
@@ -18,9 +19,11 @@ use crate::limits_flags::{
   VPN_FLAG,
 };
 use crate::subject::{Requirements, Subject};
-use crate::DshCliResult;
+use crate::usage::{TenantUsage, UsageLimits, USAGE_LABELS};
+use crate::{create_client_for_tenant, DshCliResult};
 use async_trait::async_trait;
-use clap::ArgMatches;
+use clap::builder::ValueParser;
+use clap::{Arg, ArgAction, ArgMatches};
 use dsh_api::dsh_api_client::DshApiClient;
 use dsh_api::stream::Stream;
 use dsh_api::tenant::TenantLimits;
@@ -33,8 +36,11 @@ use dsh_api::types::{
 use dsh_api::{AccessRights, DshApiError};
 use futures::future::try_join_all;
 use futures::try_join;
+use itertools::Itertools;
 use lazy_static::lazy_static;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
 
 pub(crate) struct TenantSubject {}
 
@@ -77,6 +83,19 @@ lazy_static! {
       .add_command_executor(FlagType::Ids, &TenantListIds {}, None)
       .add_command_executor(FlagType::Stream, &TenantListStreams {}, None)
   );
+  static ref TENANT_PROVISION_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
+    CapabilityBuilder::new(PROVISION_COMMAND, None, &TenantProvision {}, "Provision managed tenants from manifest")
+      .set_long_about(
+        "Provision managed tenants from a yaml manifest file. \
+         The managed tenants, their services, limits and managed stream access rights \
+         will be reconciled with the manifest. \
+         Managed tenants that do not exist will be created. \
+         The required changes will be listed before they are applied. \
+         Use the --dry-run option to only list the changes."
+      )
+      .add_extra_argument(provision_file_argument().help_heading(HELP_HEADING))
+      .add_extra_argument(prune_flag().help_heading(HELP_HEADING))
+  );
   static ref TENANT_REVOKE_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
     CapabilityBuilder::new(REVOKE_COMMAND, None, &TenantRevoke {}, "Revoke access rights")
       .set_long_about(
@@ -111,14 +130,29 @@ lazy_static! {
       .add_extra_argument(tracing_flag().help_heading(HELP_HEADING))
       .add_extra_argument(vpn_flag().help_heading(HELP_HEADING))
   );
+  static ref TENANT_USAGE_CAPABILITY: Box<(dyn Capability + Send + Sync)> = Box::new(
+    CapabilityBuilder::new(USAGE_COMMAND, None, &TenantUsageReport {}, "Show managed tenant resource usage")
+      .set_long_about(
+        "Compare the limits of a managed tenant with its actual resource usage. \
+         The cpu and memory usage are the sums over all services of the number of instances \
+         multiplied by the cpus and memory per instance. \
+         Since the resources of the managed tenant are inspected, \
+         its password must be available from the keyring, else you will be prompted. \
+         If no managed tenant is provided, the usage of the target tenant will be shown, \
+         without limits."
+      )
+      .add_target_argument(managed_tenant_argument())
+  );
   static ref TENANT_CAPABILITIES: Vec<&'static (dyn Capability + Send + Sync)> = vec![
     TENANT_CREATE_CAPABILITY.as_ref(),
     TENANT_DELETE_CAPABILITY.as_ref(),
     TENANT_GRANT_CAPABILITY.as_ref(),
     TENANT_LIST_CAPABILITY.as_ref(),
+    TENANT_PROVISION_CAPABILITY.as_ref(),
     TENANT_REVOKE_CAPABILITY.as_ref(),
     TENANT_SHOW_CAPABILITY.as_ref(),
-    TENANT_UPDATE_LIMIT_CAPABILITY.as_ref()
+    TENANT_UPDATE_LIMIT_CAPABILITY.as_ref(),
+    TENANT_USAGE_CAPABILITY.as_ref()
   ];
 }
 
@@ -138,9 +172,11 @@ impl Subject for TenantSubject {
       DELETE_COMMAND => Some(TENANT_DELETE_CAPABILITY.as_ref()),
       GRANT_COMMAND => Some(TENANT_GRANT_CAPABILITY.as_ref()),
       LIST_COMMAND => Some(TENANT_LIST_CAPABILITY.as_ref()),
+      PROVISION_COMMAND => Some(TENANT_PROVISION_CAPABILITY.as_ref()),
       REVOKE_COMMAND => Some(TENANT_REVOKE_CAPABILITY.as_ref()),
       SHOW_COMMAND => Some(TENANT_SHOW_CAPABILITY.as_ref()),
       UPDATE_COMMAND => Some(TENANT_UPDATE_LIMIT_CAPABILITY.as_ref()),
+      USAGE_COMMAND => Some(TENANT_USAGE_CAPABILITY.as_ref()),
       _ => None,
     }
   }
@@ -157,27 +193,17 @@ impl CommandExecutor for TenantCreate {
   async fn execute_with_client(&self, target: Option<String>, _: Option<String>, matches: &ArgMatches, client: &DshApiClient, context: &Context) -> DshCliResult {
     let tenant_id = target.unwrap_or_else(|| unreachable!());
     if client.get_tenant_configuration(&tenant_id).await.is_ok() {
-      return Err(format!("managed tenant '{}' already exists", tenant_id));
+      return Err(DshCliError::Conflict(format!("managed tenant '{}' already exists", tenant_id)));
     }
     let enable_tracing = matches.get_one::<bool>(TRACING_FLAG);
     let enable_vpn = matches.get_one::<bool>(VPN_FLAG);
     context.print_explanation(format!("create new managed tenant '{}'", tenant_id));
-    let mut services = vec![
-      // Monitoring service is mandatory.
-      ManagedTenantServices { enabled: true, name: ManagedTenantServicesName::Monitoring },
-    ];
-    if let Some(tracing_enabled) = enable_tracing {
-      services.push(ManagedTenantServices { enabled: *tracing_enabled, name: ManagedTenantServicesName::Tracing });
-    }
-    if let Some(vpn_enabled) = enable_vpn {
-      services.push(ManagedTenantServices { enabled: *vpn_enabled, name: ManagedTenantServicesName::Vpn });
-    }
-    let managed_tenant = ManagedTenant { manager: client.tenant_name().to_string(), name: tenant_id.clone(), services };
+    let managed_tenant = new_managed_tenant(client.tenant_name(), &tenant_id, enable_tracing.cloned(), enable_vpn.cloned());
     if context.dry_run() {
       context.print_warning("dry-run mode, tenant not created");
     } else {
       client.put_tenant_configuration(&tenant_id, &managed_tenant).await?;
-      context.print_outcome(format!("tenant '{}' created", tenant_id));
+      context.print_audited_outcome(client, format!("tenant/{}", tenant_id), format!("tenant '{}' created", tenant_id));
     }
     Ok(())
   }
@@ -195,17 +221,20 @@ impl CommandExecutor for TenantDelete {
     let tenant_id = target.unwrap_or_else(|| unreachable!());
     context.print_explanation(format!("delete tenant '{}'", tenant_id));
     if client.get_tenant_configuration(&tenant_id).await.is_err() {
-      return Err(format!("tenant '{}' does not exist or you are not authorized to manage it", tenant_id));
+      return Err(DshCliError::NotFound(format!(
+        "tenant '{}' does not exist or you are not authorized to manage it",
+        tenant_id
+      )));
     }
     if context.confirmed(format!("delete tenant '{}'?", tenant_id))? {
       if context.dry_run() {
         context.print_warning("dry-run mode, tenant not deleted");
       } else {
         client.delete_tenant_configuration(&tenant_id).await?;
-        context.print_outcome(format!("tenant '{}' deleted", tenant_id));
+        context.print_audited_outcome(client, format!("tenant/{}", tenant_id), format!("tenant '{}' deleted", tenant_id));
       }
     } else {
-      context.print_outcome(format!("cancelled, tenant '{}' not deleted", tenant_id));
+      return Err(DshCliError::Cancelled(format!("cancelled, tenant '{}' not deleted", tenant_id)));
     }
     Ok(())
   }
@@ -229,7 +258,11 @@ impl CommandExecutor for TenantGrant {
     let kind = client
       .grant_managed_stream_access_rights(&managed_stream_id, &managed_tenant_id, &access_rights)
       .await?;
-    context.print_outcome(format!("access granted to {} managed stream", kind));
+    context.print_audited_outcome(
+      client,
+      format!("tenant/{}", managed_tenant_id),
+      format!("{} access to {} managed stream '{}' granted", access_rights, kind, managed_stream_id),
+    );
     Ok(())
   }
 
@@ -325,6 +358,370 @@ impl CommandExecutor for TenantListStreams {
   }
 }
 
+const PROVISION_FILE_ARGUMENT: &str = "provision-file-argument";
+const PRUNE_FLAG: &str = "prune-flag";
+
+fn provision_file_argument() -> Arg {
+  Arg::new(PROVISION_FILE_ARGUMENT)
+    .long("file")
+    .action(ArgAction::Set)
+    .value_parser(ValueParser::path_buf())
+    .value_name("FILE")
+    .required(true)
+    .help("Managed tenants manifest file")
+    .long_help(
+      "Yaml file that describes the managed tenants, \
+       with their services, limits and managed stream access rights.",
+    )
+}
+
+fn prune_flag() -> Arg {
+  Arg::new(PRUNE_FLAG)
+    .long("prune")
+    .action(ArgAction::SetTrue)
+    .help("Revoke access rights not in manifest")
+    .long_help(
+      "When this flag is provided, access rights to managed streams that are not listed \
+       in the manifest file will be revoked from the managed tenants in the manifest. \
+       Without this flag these access rights will be left untouched.",
+    )
+}
+
+struct TenantProvision {}
+
+#[async_trait]
+impl CommandExecutor for TenantProvision {
+  async fn execute_with_client(&self, _: Option<String>, _: Option<String>, matches: &ArgMatches, client: &DshApiClient, context: &Context) -> DshCliResult {
+    let manifest_file = matches.get_one::<PathBuf>(PROVISION_FILE_ARGUMENT).unwrap_or_else(|| unreachable!());
+    let tenants_manifest = read_tenants_manifest(manifest_file)?;
+    let prune = matches.get_flag(PRUNE_FLAG);
+    context.print_explanation(format!(
+      "provision {} managed tenant(s) from manifest file '{}'",
+      tenants_manifest.tenants.len(),
+      manifest_file.to_string_lossy()
+    ));
+    let start_instant = context.now();
+    let changes = provision_changes(&tenants_manifest, prune, client).await?;
+    context.print_execution_time(start_instant);
+    if changes.is_empty() {
+      context.print_outcome("managed tenants match manifest, nothing to provision");
+      return Ok(());
+    }
+    let mut formatter = ListFormatter::new(&PROVISION_CHANGE_LABELS, None, context);
+    formatter.push_values(&changes);
+    formatter.print(None)?;
+    if context.dry_run() {
+      context.print_warning("dry-run mode, managed tenants not provisioned");
+    } else if context.confirmed(format!("apply {} change(s) to managed tenants?", changes.len()))? {
+      for change in &changes {
+        change.apply(client).await?;
+        context.audit_change(
+          client,
+          format!("tenant/{}", change.tenant),
+          format!("{} {}", change.action, change.resource),
+          change.current.as_ref(),
+          Some(&change.desired),
+        );
+      }
+      context.print_outcome(format!("{} change(s) applied to managed tenants", changes.len()));
+    } else {
+      return Err(DshCliError::Cancelled("cancelled, managed tenants not provisioned".to_string()));
+    }
+    Ok(())
+  }
+
+  fn requirements(&self, _: &ArgMatches) -> Requirements {
+    Requirements::standard_with_api()
+  }
+}
+
+/// # Describes the desired state of managed tenants
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TenantsManifest {
+  tenants: Vec<TenantManifest>,
+}
+
+/// # Describes the desired state of one managed tenant
+///
+/// Services, limits and streams that are not specified will not be changed.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TenantManifest {
+  name: String,
+  tracing: Option<bool>,
+  vpn: Option<bool>,
+  #[serde(default)]
+  limits: LimitsManifest,
+  #[serde(default)]
+  streams: Vec<StreamGrantManifest>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct LimitsManifest {
+  certificate_count: Option<i64>,
+  consumer_rate: Option<i64>,
+  cpu: Option<f64>,
+  kafka_acl_group_count: Option<i64>,
+  mem: Option<i64>,
+  partition_count: Option<i64>,
+  producer_rate: Option<i64>,
+  request_rate: Option<i64>,
+  secret_count: Option<i64>,
+  topic_count: Option<i64>,
+}
+
+impl From<&LimitsManifest> for TenantLimits {
+  fn from(limits: &LimitsManifest) -> Self {
+    TenantLimits {
+      certificate_count: limits.certificate_count,
+      consumer_rate: limits.consumer_rate,
+      cpu: limits.cpu,
+      kafka_acl_group_count: limits.kafka_acl_group_count,
+      mem: limits.mem,
+      partition_count: limits.partition_count,
+      producer_rate: limits.producer_rate,
+      request_rate: limits.request_rate,
+      secret_count: limits.secret_count,
+      topic_count: limits.topic_count,
+    }
+  }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StreamGrantManifest {
+  stream: String,
+  access: AccessRightsManifest,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+enum AccessRightsManifest {
+  #[serde(rename = "read")]
+  Read,
+  #[serde(rename = "read-write")]
+  ReadWrite,
+  #[serde(rename = "write")]
+  Write,
+}
+
+impl AccessRightsManifest {
+  fn access_rights(&self) -> AccessRights {
+    match self {
+      AccessRightsManifest::Read => AccessRights::Read,
+      AccessRightsManifest::ReadWrite => AccessRights::ReadWrite,
+      AccessRightsManifest::Write => AccessRights::Write,
+    }
+  }
+}
+
+fn read_tenants_manifest(manifest_file: &PathBuf) -> Result<TenantsManifest, String> {
+  let manifest_string = fs::read_to_string(manifest_file).map_err(|error| format!("could not read manifest file '{}' ({})", manifest_file.to_string_lossy(), error))?;
+  let tenants_manifest =
+    serde_yaml::from_str::<TenantsManifest>(&manifest_string).map_err(|error| format!("could not parse manifest file '{}' ({})", manifest_file.to_string_lossy(), error))?;
+  let mut tenant_ids = tenants_manifest.tenants.iter().map(|tenant| tenant.name.as_str()).collect::<Vec<_>>();
+  tenant_ids.sort();
+  if let Some((duplicate_tenant_id, _)) = tenant_ids.iter().tuple_windows().find(|(a, b)| a == b) {
+    return Err(format!("managed tenant '{}' occurs more than once in manifest file", duplicate_tenant_id));
+  }
+  for tenant_manifest in &tenants_manifest.tenants {
+    if tenant_manifest.limits.cpu.is_some_and(|cpus| !(0.01..=16.0).contains(&cpus)) {
+      return Err(format!(
+        "number of cpus for managed tenant '{}' should be greater than or equal to 0.01 and lower than or equal to 16.0",
+        tenant_manifest.name
+      ));
+    }
+  }
+  Ok(tenants_manifest)
+}
+
+/// # Describes one change that is required to provision a managed tenant
+#[derive(Serialize)]
+struct ProvisionChange {
+  tenant: String,
+  action: &'static str,
+  resource: String,
+  current: Option<String>,
+  desired: String,
+  #[serde(skip_serializing)]
+  operation: ProvisionOperation,
+}
+
+enum ProvisionOperation {
+  Grant(ManagedStreamId, AccessRights),
+  PutLimit(PutTenantLimitByManagerByTenantByKindKind, LimitValue),
+  PutTenant(ManagedTenant),
+  Revoke(ManagedStreamId, AccessRights),
+}
+
+impl ProvisionChange {
+  async fn apply(&self, client: &DshApiClient) -> DshCliResult {
+    match &self.operation {
+      ProvisionOperation::Grant(managed_stream_id, access_rights) => {
+        client.grant_managed_stream_access_rights(managed_stream_id, &self.tenant, access_rights).await?;
+      }
+      ProvisionOperation::PutLimit(kind, limit_value) => client.put_tenant_limit(&self.tenant, *kind, limit_value).await?,
+      ProvisionOperation::PutTenant(managed_tenant) => client.put_tenant_configuration(&self.tenant, managed_tenant).await?,
+      ProvisionOperation::Revoke(managed_stream_id, access_rights) => {
+        client.revoke_managed_stream_access_rights(managed_stream_id, &self.tenant, access_rights).await?;
+      }
+    }
+    Ok(())
+  }
+}
+
+/// # Determines the changes required to provision the managed tenants
+///
+/// Compares the desired state from the manifest with the actual state of the managed tenants
+/// and returns the changes that are required to reconcile them.
+async fn provision_changes(tenants_manifest: &TenantsManifest, prune: bool, client: &DshApiClient) -> Result<Vec<ProvisionChange>, String> {
+  let manager = client.tenant_name();
+  let mut stream_ids: Vec<ManagedStreamId> = vec![];
+  for tenant_manifest in &tenants_manifest.tenants {
+    for stream_grant in &tenant_manifest.streams {
+      let stream_id = managed_stream_id(&stream_grant.stream, manager)?;
+      if !stream_ids.contains(&stream_id) {
+        stream_ids.push(stream_id);
+      }
+    }
+  }
+  if prune {
+    let (internal_stream_ids, public_stream_ids) = try_join!(client.get_stream_internals(), client.get_stream_publics())?;
+    for stream_id in internal_stream_ids.into_iter().chain(public_stream_ids) {
+      if !stream_ids.contains(&stream_id) {
+        stream_ids.push(stream_id);
+      }
+    }
+  }
+  let existing_tenant_ids = client.get_tenant_ids().await?;
+  let streams_access_rights: Vec<Vec<(String, AccessRights)>> = try_join_all(stream_ids.iter().map(|stream_id| client.get_tenants_with_access_rights(stream_id))).await?;
+  let mut changes = vec![];
+  for tenant_manifest in &tenants_manifest.tenants {
+    let tenant_id = tenant_manifest.name.clone();
+    let desired_limits = TenantLimits::from(&tenant_manifest.limits);
+    if existing_tenant_ids.contains(&tenant_id) {
+      let (mut managed_tenant, current_limits) = try_join!(client.get_tenant_configuration(&tenant_id), client.get_managed_tenant_limits(&tenant_id))?;
+      let current_services = services_to_string(&managed_tenant);
+      let mut update = false;
+      if let Some(tracing) = tenant_manifest.tracing {
+        update |= set_service_enabled(&mut managed_tenant, ManagedTenantServicesName::Tracing, tracing);
+      }
+      if let Some(vpn) = tenant_manifest.vpn {
+        update |= set_service_enabled(&mut managed_tenant, ManagedTenantServicesName::Vpn, vpn);
+      }
+      if update {
+        changes.push(ProvisionChange {
+          tenant: tenant_id.clone(),
+          action: "update",
+          resource: "services".to_string(),
+          current: Some(current_services),
+          desired: services_to_string(&managed_tenant),
+          operation: ProvisionOperation::PutTenant(managed_tenant),
+        });
+      }
+      for (limit_label, kind, limit_value) in limit_values(&desired_limits) {
+        let current_limit = current_limits.value(&limit_label, &tenant_id);
+        let desired_limit = desired_limits.value(&limit_label, &tenant_id);
+        if current_limit != desired_limit {
+          changes.push(ProvisionChange {
+            tenant: tenant_id.clone(),
+            action: "update",
+            resource: format!("{} limit", limit_label.as_str()),
+            current: if current_limit.is_empty() { None } else { Some(current_limit) },
+            desired: desired_limit,
+            operation: ProvisionOperation::PutLimit(kind, limit_value),
+          });
+        }
+      }
+    } else {
+      let managed_tenant = new_managed_tenant(manager, &tenant_id, tenant_manifest.tracing, tenant_manifest.vpn);
+      changes.push(ProvisionChange {
+        tenant: tenant_id.clone(),
+        action: "create",
+        resource: "services".to_string(),
+        current: None,
+        desired: services_to_string(&managed_tenant),
+        operation: ProvisionOperation::PutTenant(managed_tenant),
+      });
+      for (limit_label, kind, limit_value) in limit_values(&desired_limits) {
+        changes.push(ProvisionChange {
+          tenant: tenant_id.clone(),
+          action: "create",
+          resource: format!("{} limit", limit_label.as_str()),
+          current: None,
+          desired: desired_limits.value(&limit_label, &tenant_id),
+          operation: ProvisionOperation::PutLimit(kind, limit_value),
+        });
+      }
+    }
+    for (stream_id, stream_access_rights) in stream_ids.iter().zip(&streams_access_rights) {
+      let current_access_rights = stream_access_rights
+        .iter()
+        .find_map(|(granted_tenant_id, access_rights)| if granted_tenant_id == &tenant_id { Some(access_rights.clone()) } else { None });
+      let desired_access_rights = match tenant_manifest.streams.iter().find(|stream_grant| stream_grant.stream == stream_id.to_string()) {
+        Some(stream_grant) => Some(stream_grant.access.access_rights()),
+        None => {
+          if prune {
+            None
+          } else {
+            continue;
+          }
+        }
+      };
+      let (current_read, current_write) = access_rights_to_pair(current_access_rights.as_ref());
+      let (desired_read, desired_write) = access_rights_to_pair(desired_access_rights.as_ref());
+      let current = current_access_rights.as_ref().map(|access_rights| access_rights.to_string());
+      let desired = desired_access_rights
+        .as_ref()
+        .map(|access_rights| access_rights.to_string())
+        .unwrap_or("none".to_string());
+      if let Some(grant) = AccessRights::from(desired_read && !current_read, desired_write && !current_write) {
+        changes.push(ProvisionChange {
+          tenant: tenant_id.clone(),
+          action: "grant",
+          resource: format!("stream {}", stream_id),
+          current: current.clone(),
+          desired: desired.clone(),
+          operation: ProvisionOperation::Grant(stream_id.clone(), grant),
+        });
+      }
+      if let Some(revoke) = AccessRights::from(current_read && !desired_read, current_write && !desired_write) {
+        changes.push(ProvisionChange {
+          tenant: tenant_id.clone(),
+          action: "revoke",
+          resource: format!("stream {}", stream_id),
+          current,
+          desired,
+          operation: ProvisionOperation::Revoke(stream_id.clone(), revoke),
+        });
+      }
+    }
+  }
+  Ok(changes)
+}
+
+fn access_rights_to_pair(access_rights: Option<&AccessRights>) -> (bool, bool) {
+  match access_rights {
+    Some(access_rights) => (access_rights.has_read_access(), access_rights.has_write_access()),
+    None => (false, false),
+  }
+}
+
+fn services_to_string(managed_tenant: &ManagedTenant) -> String {
+  [TenantLabel::Monitoring, TenantLabel::Tracing, TenantLabel::Vpn]
+    .iter()
+    .filter_map(|label| {
+      let enabled = managed_tenant.value(label, "");
+      if enabled.is_empty() {
+        None
+      } else {
+        Some(format!("{} {}", label.as_str(), enabled))
+      }
+    })
+    .join(", ")
+}
+
 struct TenantRevoke {}
 
 #[async_trait]
@@ -337,7 +734,11 @@ impl CommandExecutor for TenantRevoke {
       rights, managed_stream_id, managed_tenant_id
     ));
     let kind = client.revoke_managed_stream_access_rights(&managed_stream_id, &managed_tenant_id, &rights).await?;
-    context.print_outcome(format!("access revoked from {} managed stream", kind));
+    context.print_audited_outcome(
+      client,
+      format!("tenant/{}", managed_tenant_id),
+      format!("{} access to {} managed stream '{}' revoked", rights, kind, managed_stream_id),
+    );
     Ok(())
   }
 
@@ -357,14 +758,15 @@ impl CommandExecutor for TenantShowAll {
     match try_join!(client.get_tenant_configuration(&tenant_id), client.get_managed_tenant_limits(&tenant_id)) {
       Ok((managed_tenant, tenant_limits)) => {
         context.print_execution_time(start_instant);
-        UnitFormatter::new(tenant_id, &TENANT_LABELS, Some("tenant id"), context).print(&(managed_tenant, tenant_limits), None)
+        UnitFormatter::new(tenant_id, &TENANT_LABELS, Some("tenant id"), context).print(&(managed_tenant, tenant_limits), None)?;
+        Ok(())
       }
       Err(error) => match error {
         DshApiError::NotFound(None) => {
           context.print_error(format!("tenant '{}' does not exist or you are not authorized to manage it", tenant_id));
           Ok(())
         }
-        error => Err(String::from(error)),
+        error => Err(DshCliError::from(error)),
       },
     }
   }
@@ -386,7 +788,8 @@ impl CommandExecutor for TenantShowStreams {
     context.print_execution_time(start_instant);
     let mut formatter = ListFormatter::new(&STREAM_ACCESS_LABELS, None, context);
     formatter.push_values(&grants);
-    formatter.print(None)
+    formatter.print(None)?;
+    Ok(())
   }
 
   fn requirements(&self, _: &ArgMatches) -> Requirements {
@@ -408,141 +811,28 @@ impl CommandExecutor for TenantUpdateLimit {
       enable_tracing_argument.is_some() || enable_vpn_argument.is_some(),
       !tenant_limits_from_arguments.is_empty(),
     ) {
-      (false, false) => Err("at least one limit or capability argument must be provided".to_string()),
+      (false, false) => Err(DshCliError::Validation("at least one limit or capability argument must be provided".to_string())),
       (false, true) => {
         // TODO This code must be replaced once PATCH /manage/{manager}/tenant/{tenant}/limit is fixed
         context.print_explanation(format!("update limit of managed tenant '{}'", tenant_id));
         if client.get_tenant_limits(&tenant_id).await.is_err() {
-          return Err(format!("tenant '{}' does not exist or you are not authorized to manage it", tenant_id));
-        }
-        if let Some(certificate_count) = tenant_limits_from_arguments.certificate_count {
-          if context.dry_run() {
-            context.print_warning("dry-run mode, certificate count limit not updated");
-          } else {
-            client
-              .put_tenant_limit(
-                &tenant_id,
-                PutTenantLimitByManagerByTenantByKindKind::Certificatecount,
-                &LimitValue::CertificateCount(LimitValueCertificateCount { name: LimitValueCertificateCountName::CertificateCount, value: certificate_count }),
-              )
-              .await?;
-          }
-        }
-        if let Some(consumer_rate) = tenant_limits_from_arguments.consumer_rate {
-          if context.dry_run() {
-            context.print_warning("dry-run mode, consumer rate limit not updated");
-          } else {
-            client
-              .put_tenant_limit(
-                &tenant_id,
-                PutTenantLimitByManagerByTenantByKindKind::Consumerrate,
-                &LimitValue::ConsumerRate(LimitValueConsumerRate { name: LimitValueConsumerRateName::ConsumerRate, value: consumer_rate }),
-              )
-              .await?;
-          }
-        }
-        if let Some(cpu) = tenant_limits_from_arguments.cpu {
-          if context.dry_run() {
-            context.print_warning("dry-run mode, cpu limit not updated");
-          } else {
-            client
-              .put_tenant_limit(
-                &tenant_id,
-                PutTenantLimitByManagerByTenantByKindKind::Cpu,
-                &LimitValue::Cpu(LimitValueCpu { name: LimitValueCpuName::Cpu, value: cpu }),
-              )
-              .await?;
-          }
-        }
-        if let Some(kafka_acl_group_count) = tenant_limits_from_arguments.kafka_acl_group_count {
-          if context.dry_run() {
-            context.print_warning("dry-run mode, kafka acl group count limit not updated");
-          } else {
-            client
-              .put_tenant_limit(
-                &tenant_id,
-                PutTenantLimitByManagerByTenantByKindKind::Kafkaaclgroupcount,
-                &LimitValue::KafkaAclGroupCount(LimitValueKafkaAclGroupCount { name: LimitValueKafkaAclGroupCountName::KafkaAclGroupCount, value: kafka_acl_group_count }),
-              )
-              .await?;
-          }
-        }
-        if let Some(mem) = tenant_limits_from_arguments.mem {
-          if context.dry_run() {
-            context.print_warning("dry-run mode, mem limit not updated");
-          } else {
-            client
-              .put_tenant_limit(
-                &tenant_id,
-                PutTenantLimitByManagerByTenantByKindKind::Mem,
-                &LimitValue::Mem(LimitValueMem { name: LimitValueMemName::Mem, value: mem }),
-              )
-              .await?;
-          }
-        }
-        if let Some(partition_count) = tenant_limits_from_arguments.partition_count {
-          if context.dry_run() {
-            context.print_warning("dry-run mode, partition count limit not updated");
-          } else {
-            client
-              .put_tenant_limit(
-                &tenant_id,
-                PutTenantLimitByManagerByTenantByKindKind::Partitioncount,
-                &LimitValue::PartitionCount(LimitValuePartitionCount { name: LimitValuePartitionCountName::PartitionCount, value: partition_count }),
-              )
-              .await?;
-          }
+          return Err(DshCliError::NotFound(format!(
+            "tenant '{}' does not exist or you are not authorized to manage it",
+            tenant_id
+          )));
         }
-        if let Some(producer_rate) = tenant_limits_from_arguments.producer_rate {
+        for (limit_label, kind, limit_value) in limit_values(&tenant_limits_from_arguments) {
           if context.dry_run() {
-            context.print_warning("dry-run mode, producer rate limit not updated");
+            context.print_warning(format!("dry-run mode, {} limit not updated", limit_label.as_str()));
           } else {
-            client
-              .put_tenant_limit(
-                &tenant_id,
-                PutTenantLimitByManagerByTenantByKindKind::Producerrate,
-                &LimitValue::ProducerRate(LimitValueProducerRate { name: LimitValueProducerRateName::ProducerRate, value: producer_rate }),
-              )
-              .await?;
-          }
-        }
-        if let Some(request_rate) = tenant_limits_from_arguments.request_rate {
-          if context.dry_run() {
-            context.print_warning("dry-run mode, request rate limit not updated");
-          } else {
-            client
-              .put_tenant_limit(
-                &tenant_id,
-                PutTenantLimitByManagerByTenantByKindKind::Requestrate,
-                &LimitValue::RequestRate(LimitValueRequestRate { name: LimitValueRequestRateName::RequestRate, value: request_rate }),
-              )
-              .await?;
-          }
-        }
-        if let Some(secret_count) = tenant_limits_from_arguments.secret_count {
-          if context.dry_run() {
-            context.print_warning("dry-run mode, secret count limit not updated");
-          } else {
-            client
-              .put_tenant_limit(
-                &tenant_id,
-                PutTenantLimitByManagerByTenantByKindKind::Secretcount,
-                &LimitValue::SecretCount(LimitValueSecretCount { name: LimitValueSecretCountName::SecretCount, value: secret_count }),
-              )
-              .await?;
-          }
-        }
-        if let Some(topic_count) = tenant_limits_from_arguments.topic_count {
-          if context.dry_run() {
-            context.print_warning("dry-run mode, topic count limit not updated");
-          } else {
-            client
-              .put_tenant_limit(
-                &tenant_id,
-                PutTenantLimitByManagerByTenantByKindKind::Topiccount,
-                &LimitValue::TopicCount(LimitValueTopicCount { name: LimitValueTopicCountName::TopicCount, value: topic_count }),
-              )
-              .await?;
+            client.put_tenant_limit(&tenant_id, kind, &limit_value).await?;
+            context.print_audited_change(
+              client,
+              format!("tenant/{}", tenant_id),
+              format!("{} limit of managed tenant '{}' updated", limit_label.as_str(), tenant_id),
+              None,
+              Some(&limit_value),
+            );
           }
         }
         Ok(())
@@ -580,45 +870,26 @@ impl CommandExecutor for TenantUpdateLimit {
         context.print_explanation(format!("update capabilities of managed tenant '{}'", tenant_id));
         match client.get_tenant_configuration(&tenant_id).await {
           Ok(mut managed_tenant) => {
+            let current_managed_tenant = managed_tenant.clone();
             let mut update = false;
             if let Some(eta) = enable_tracing_argument {
-              match managed_tenant.services.iter_mut().find(|s| s.name == ManagedTenantServicesName::Tracing) {
-                Some(tracing_service) => {
-                  if *eta != tracing_service.enabled {
-                    tracing_service.enabled = *eta;
-                    update = true;
-                  }
-                }
-                None => {
-                  managed_tenant
-                    .services
-                    .push(ManagedTenantServices { enabled: *eta, name: ManagedTenantServicesName::Tracing });
-                  update = true;
-                }
-              }
+              update |= set_service_enabled(&mut managed_tenant, ManagedTenantServicesName::Tracing, *eta);
             }
             if let Some(eva) = enable_vpn_argument {
-              match managed_tenant.services.iter_mut().find(|s| s.name == ManagedTenantServicesName::Vpn) {
-                Some(vpn_service) => {
-                  if *eva != vpn_service.enabled {
-                    vpn_service.enabled = *eva;
-                    update = true;
-                  }
-                }
-                None => {
-                  managed_tenant
-                    .services
-                    .push(ManagedTenantServices { enabled: *eva, name: ManagedTenantServicesName::Vpn });
-                  update = true;
-                }
-              }
+              update |= set_service_enabled(&mut managed_tenant, ManagedTenantServicesName::Vpn, *eva);
             }
             if update {
               if context.dry_run() {
                 context.print_warning("dry-run mode, capabilities not updated");
               } else {
                 client.put_tenant_configuration(&tenant_id, &managed_tenant).await?;
-                context.print_outcome(format!("capabilities for managed tenant '{}' updated", tenant_id));
+                context.print_audited_change(
+                  client,
+                  format!("tenant/{}", tenant_id),
+                  format!("capabilities for managed tenant '{}' updated", tenant_id),
+                  Some(&current_managed_tenant),
+                  Some(&managed_tenant),
+                );
               }
             } else {
               context.print_warning("provided arguments match current values, managed tenant not updated")
@@ -630,11 +901,13 @@ impl CommandExecutor for TenantUpdateLimit {
               context.print_error(format!("managed tenant '{}' does not exist or you are not authorized to manage it", tenant_id));
               Ok(())
             }
-            error => Err(String::from(error)),
+            error => Err(DshCliError::from(error)),
           },
         }
       }
-      (true, true) => Err("provide either limit arguments or capability arguments, but not both".to_string()),
+      (true, true) => Err(DshCliError::Validation(
+        "provide either limit arguments or capability arguments, but not both".to_string(),
+      )),
     }
   }
 
@@ -643,6 +916,176 @@ impl CommandExecutor for TenantUpdateLimit {
   }
 }
 
+struct TenantUsageReport {}
+
+#[async_trait]
+impl CommandExecutor for TenantUsageReport {
+  async fn execute_with_client(&self, target: Option<String>, _: Option<String>, _: &ArgMatches, client: &DshApiClient, context: &Context) -> DshCliResult {
+    let start_instant = context.now();
+    let (tenant_usage, usage_limits) = match target {
+      Some(tenant_id) => {
+        context.print_explanation(format!("compare limits with resource usage for tenant '{}'", tenant_id));
+        let tenant_limits = match client.get_managed_tenant_limits(&tenant_id).await {
+          Ok(tenant_limits) => tenant_limits,
+          Err(DshApiError::NotFound(None)) => {
+            return Err(DshCliError::NotFound(format!(
+              "tenant '{}' does not exist or you are not authorized to manage it",
+              tenant_id
+            )))
+          }
+          Err(error) => return Err(DshCliError::from(error)),
+        };
+        let tenant_client = create_client_for_tenant(&tenant_id, client.platform()).await?;
+        (TenantUsage::fetch(&tenant_client).await?, UsageLimits::from(&tenant_limits))
+      }
+      None => {
+        context.print_explanation(format!("show resource usage of tenant '{}'", client.tenant_name()));
+        (TenantUsage::fetch(client).await?, UsageLimits::default())
+      }
+    };
+    context.print_execution_time(start_instant);
+    let resource_usages = tenant_usage.resource_usages(&usage_limits, context);
+    let mut formatter = ListFormatter::new(&USAGE_LABELS, None, context);
+    formatter.push_values(&resource_usages);
+    formatter.print(None)?;
+    Ok(())
+  }
+
+  fn requirements(&self, _: &ArgMatches) -> Requirements {
+    Requirements::standard_with_api()
+  }
+}
+
+impl From<&TenantLimits> for UsageLimits {
+  fn from(tenant_limits: &TenantLimits) -> Self {
+    UsageLimits {
+      certificate_count: tenant_limits.certificate_count,
+      cpu: tenant_limits.cpu,
+      mem: tenant_limits.mem,
+      partition_count: tenant_limits.partition_count,
+      secret_count: tenant_limits.secret_count,
+      topic_count: tenant_limits.topic_count,
+    }
+  }
+}
+
+/// # Creates a new managed tenant configuration
+///
+/// The mandatory monitoring service will always be enabled.
+/// The tracing and vpn services will only be included when they are provided.
+fn new_managed_tenant(manager: &str, tenant_id: &str, enable_tracing: Option<bool>, enable_vpn: Option<bool>) -> ManagedTenant {
+  let mut services = vec![
+    // Monitoring service is mandatory.
+    ManagedTenantServices { enabled: true, name: ManagedTenantServicesName::Monitoring },
+  ];
+  if let Some(tracing_enabled) = enable_tracing {
+    services.push(ManagedTenantServices { enabled: tracing_enabled, name: ManagedTenantServicesName::Tracing });
+  }
+  if let Some(vpn_enabled) = enable_vpn {
+    services.push(ManagedTenantServices { enabled: vpn_enabled, name: ManagedTenantServicesName::Vpn });
+  }
+  ManagedTenant { manager: manager.to_string(), name: tenant_id.to_string(), services }
+}
+
+/// # Enables or disables a service of a managed tenant
+///
+/// Returns `true` when the managed tenant configuration was changed.
+fn set_service_enabled(managed_tenant: &mut ManagedTenant, name: ManagedTenantServicesName, enabled: bool) -> bool {
+  match managed_tenant.services.iter_mut().find(|service| service.name == name) {
+    Some(service) => {
+      if service.enabled != enabled {
+        service.enabled = enabled;
+        true
+      } else {
+        false
+      }
+    }
+    None => {
+      managed_tenant.services.push(ManagedTenantServices { enabled, name });
+      true
+    }
+  }
+}
+
+/// # Returns the limits that are set
+///
+/// Returns a list of tuples consisting of the label, the kind and the value
+/// of all limits that are set in `tenant_limits`.
+fn limit_values(tenant_limits: &TenantLimits) -> Vec<(TenantLabel, PutTenantLimitByManagerByTenantByKindKind, LimitValue)> {
+  let mut limit_values = vec![];
+  if let Some(certificate_count) = tenant_limits.certificate_count {
+    limit_values.push((
+      TenantLabel::CertificateCount,
+      PutTenantLimitByManagerByTenantByKindKind::Certificatecount,
+      LimitValue::CertificateCount(LimitValueCertificateCount { name: LimitValueCertificateCountName::CertificateCount, value: certificate_count }),
+    ));
+  }
+  if let Some(consumer_rate) = tenant_limits.consumer_rate {
+    limit_values.push((
+      TenantLabel::ConsumerRate,
+      PutTenantLimitByManagerByTenantByKindKind::Consumerrate,
+      LimitValue::ConsumerRate(LimitValueConsumerRate { name: LimitValueConsumerRateName::ConsumerRate, value: consumer_rate }),
+    ));
+  }
+  if let Some(cpu) = tenant_limits.cpu {
+    limit_values.push((
+      TenantLabel::Cpu,
+      PutTenantLimitByManagerByTenantByKindKind::Cpu,
+      LimitValue::Cpu(LimitValueCpu { name: LimitValueCpuName::Cpu, value: cpu }),
+    ));
+  }
+  if let Some(kafka_acl_group_count) = tenant_limits.kafka_acl_group_count {
+    limit_values.push((
+      TenantLabel::KafkaAclGroupCount,
+      PutTenantLimitByManagerByTenantByKindKind::Kafkaaclgroupcount,
+      LimitValue::KafkaAclGroupCount(LimitValueKafkaAclGroupCount { name: LimitValueKafkaAclGroupCountName::KafkaAclGroupCount, value: kafka_acl_group_count }),
+    ));
+  }
+  if let Some(mem) = tenant_limits.mem {
+    limit_values.push((
+      TenantLabel::Mem,
+      PutTenantLimitByManagerByTenantByKindKind::Mem,
+      LimitValue::Mem(LimitValueMem { name: LimitValueMemName::Mem, value: mem }),
+    ));
+  }
+  if let Some(partition_count) = tenant_limits.partition_count {
+    limit_values.push((
+      TenantLabel::PartitionCount,
+      PutTenantLimitByManagerByTenantByKindKind::Partitioncount,
+      LimitValue::PartitionCount(LimitValuePartitionCount { name: LimitValuePartitionCountName::PartitionCount, value: partition_count }),
+    ));
+  }
+  if let Some(producer_rate) = tenant_limits.producer_rate {
+    limit_values.push((
+      TenantLabel::ProducerRate,
+      PutTenantLimitByManagerByTenantByKindKind::Producerrate,
+      LimitValue::ProducerRate(LimitValueProducerRate { name: LimitValueProducerRateName::ProducerRate, value: producer_rate }),
+    ));
+  }
+  if let Some(request_rate) = tenant_limits.request_rate {
+    limit_values.push((
+      TenantLabel::RequestRate,
+      PutTenantLimitByManagerByTenantByKindKind::Requestrate,
+      LimitValue::RequestRate(LimitValueRequestRate { name: LimitValueRequestRateName::RequestRate, value: request_rate }),
+    ));
+  }
+  if let Some(secret_count) = tenant_limits.secret_count {
+    limit_values.push((
+      TenantLabel::SecretCount,
+      PutTenantLimitByManagerByTenantByKindKind::Secretcount,
+      LimitValue::SecretCount(LimitValueSecretCount { name: LimitValueSecretCountName::SecretCount, value: secret_count }),
+    ));
+  }
+  if let Some(topic_count) = tenant_limits.topic_count {
+    limit_values.push((
+      TenantLabel::TopicCount,
+      PutTenantLimitByManagerByTenantByKindKind::Topiccount,
+      LimitValue::TopicCount(LimitValueTopicCount { name: LimitValueTopicCountName::TopicCount, value: topic_count }),
+    ));
+  }
+  limit_values
+}
+
 fn get_managed_stream_id(matches: &ArgMatches, managing_tenant: &str) -> Result<(ManagedStreamId, AccessRights), String> {
   Ok(match matches.get_one::<String>(STREAM_READ_FLAG) {
     Some(stream) => (managed_stream_id(stream, managing_tenant)?, AccessRights::Read),
@@ -943,3 +1386,56 @@ fn service_enabled(managed_tenant: &ManagedTenant, name: ManagedTenantServicesNa
 }
 
 pub static _MANAGED_TENANT_LABELS: [TenantLabel; 5] = [TenantLabel::_Name, TenantLabel::Manager, TenantLabel::Monitoring, TenantLabel::Tracing, TenantLabel::Vpn];
+
+#[derive(Eq, Hash, PartialEq, Serialize)]
+pub(crate) enum ProvisionLabel {
+  Action,
+  Current,
+  Desired,
+  Resource,
+  Tenant,
+}
+
+impl Label for ProvisionLabel {
+  fn as_str(&self) -> &str {
+    match self {
+      Self::Action => "action",
+      Self::Current => "current value",
+      Self::Desired => "desired value",
+      Self::Resource => "resource",
+      Self::Tenant => "managed tenant",
+    }
+  }
+
+  fn as_str_for_list(&self) -> &str {
+    match self {
+      Self::Action => "action",
+      Self::Current => "current",
+      Self::Desired => "desired",
+      Self::Resource => "resource",
+      Self::Tenant => "tenant",
+    }
+  }
+
+  fn is_target_label(&self) -> bool {
+    matches!(self, Self::Tenant)
+  }
+}
+
+impl SubjectFormatter<ProvisionLabel> for ProvisionChange {
+  fn value(&self, label: &ProvisionLabel, _target_id: &str) -> String {
+    match label {
+      ProvisionLabel::Action => self.action.to_string(),
+      ProvisionLabel::Current => self.current.clone().unwrap_or_default(),
+      ProvisionLabel::Desired => self.desired.clone(),
+      ProvisionLabel::Resource => self.resource.clone(),
+      ProvisionLabel::Tenant => self.tenant.clone(),
+    }
+  }
+
+  fn target_id(&self) -> Option<String> {
+    Some(self.tenant.clone())
+  }
+}
+
+static PROVISION_CHANGE_LABELS: [ProvisionLabel; 5] = [ProvisionLabel::Tenant, ProvisionLabel::Action, ProvisionLabel::Resource, ProvisionLabel::Current, ProvisionLabel::Desired];
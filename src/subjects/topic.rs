@@ -2,6 +2,7 @@ use crate::arguments::topic_id_argument;
 use crate::capability::{Capability, CommandExecutor, CREATE_COMMAND, CREATE_COMMAND_ALIAS, DELETE_COMMAND, LIST_COMMAND, LIST_COMMAND_ALIAS, SHOW_COMMAND, SHOW_COMMAND_ALIAS};
 use crate::capability_builder::CapabilityBuilder;
 use crate::context::Context;
+use crate::error::DshCliError;
 use crate::flags::FlagType;
 use crate::formatters::formatter::{hashmap_to_table, PROPERTY_LABELS};
 use crate::formatters::formatter::{Label, SubjectFormatter};
@@ -275,7 +276,7 @@ impl CommandExecutor for TopicCreate {
     const REPLICATION_FACTOR: u32 = 3;
     let topic_id = target.unwrap_or_else(|| unreachable!());
     if client.get_topic_configuration(&topic_id).await.is_ok() {
-      return Err(format!("topic '{}' already exists", topic_id));
+      return Err(DshCliError::Conflict(format!("topic '{}' already exists", topic_id)));
     }
     let topic = create_topic(matches)?;
     context.print_explanation(format!(
@@ -286,7 +287,7 @@ impl CommandExecutor for TopicCreate {
       context.print_warning("dry-run mode, topic not created");
     } else {
       client.put_topic_configuration(&topic_id, &topic).await?;
-      context.print_outcome(format!("topic '{}' created", topic_id));
+      context.print_audited_outcome(client, format!("topic/{}", topic_id), format!("topic '{}' created", topic_id));
     }
     Ok(())
   }
@@ -309,6 +310,16 @@ pub(crate) fn create_topic(matches: &ArgMatches) -> Result<Topic, String> {
   const REPLICATION_FACTOR: u32 = 3;
   let replication_factor = REPLICATION_FACTOR as i64;
   let partitions = matches.get_one::<u32>(PARTITIONS_FLAG).cloned().unwrap_or(1) as i64;
+  let kafka_properties = kafka_properties_from_matches(matches);
+  Ok(Topic { kafka_properties, partitions, replication_factor })
+}
+
+/// # Get kafka properties from topic options
+///
+/// Returns a map with the kafka properties that were explicitly provided
+/// as topic options on the command line. Options that were not provided
+/// will not be present in the returned map.
+pub(crate) fn kafka_properties_from_matches(matches: &ArgMatches) -> HashMap<String, String> {
   let mut kafka_properties = HashMap::new();
   if let Some(cleanup_policy) = matches.get_one::<String>(CLEANUP_POLICY_FLAG) {
     kafka_properties.insert(CLEANUP_POLICY_PROPERTY.to_string(), cleanup_policy.to_string());
@@ -338,7 +349,7 @@ pub(crate) fn create_topic(matches: &ArgMatches) -> Result<Topic, String> {
   if let Some(segment_bytes) = matches.get_one::<u64>(SEGMENT_BYTES_FLAG) {
     kafka_properties.insert(SEGMENT_BYTES_PROPERTY.to_string(), segment_bytes.to_string());
   }
-  Ok(Topic { kafka_properties, partitions, replication_factor })
+  kafka_properties
 }
 
 struct TopicDelete {}
@@ -349,17 +360,17 @@ impl CommandExecutor for TopicDelete {
     let topic_id = target.unwrap_or_else(|| unreachable!());
     context.print_explanation(format!("delete topic '{}'", topic_id));
     if client.get_topic(&topic_id).await.is_err() {
-      return Err(format!("scratch topic '{}' does not exists", topic_id));
+      return Err(DshCliError::NotFound(format!("scratch topic '{}' does not exists", topic_id)));
     }
     if context.confirmed(format!("delete scratch topic '{}'?", topic_id))? {
       if context.dry_run() {
         context.print_warning("dry-run mode, topic not deleted");
       } else {
         client.delete_topic_configuration(&topic_id).await?;
-        context.print_outcome(format!("topic '{}' deleted", topic_id));
+        context.print_audited_outcome(client, format!("topic/{}", topic_id), format!("topic '{}' deleted", topic_id));
       }
     } else {
-      context.print_outcome(format!("cancelled, topic '{}' not deleted", topic_id));
+      return Err(DshCliError::Cancelled(format!("cancelled, topic '{}' not deleted", topic_id)));
     }
     Ok(())
   }
@@ -474,7 +485,8 @@ impl CommandExecutor for TopicShow {
     let start_instant = context.now();
     let topic = client.get_topic_configuration(&topic_id).await?;
     context.print_execution_time(start_instant);
-    UnitFormatter::new(topic_id, &TOPIC_STATUS_LABELS, None, context).print(&topic, None)
+    UnitFormatter::new(topic_id, &TOPIC_STATUS_LABELS, None, context).print(&topic, None)?;
+    Ok(())
   }
 
   fn requirements(&self, _: &ArgMatches) -> Requirements {
@@ -492,7 +504,8 @@ impl CommandExecutor for TopicShowAllocationStatus {
     let start_instant = context.now();
     let allocation_status = client.get_topic_status(&topic_id).await?;
     context.print_execution_time(start_instant);
-    UnitFormatter::new(topic_id, &DEFAULT_ALLOCATION_STATUS_LABELS, Some("topic id"), context).print(&allocation_status, None)
+    UnitFormatter::new(topic_id, &DEFAULT_ALLOCATION_STATUS_LABELS, Some("topic id"), context).print(&allocation_status, None)?;
+    Ok(())
   }
 
   fn requirements(&self, _: &ArgMatches) -> Requirements {
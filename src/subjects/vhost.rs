@@ -125,7 +125,8 @@ impl CommandExecutor for VhostList {
     vhost_list_values.sort_by(|a, b| (&a.vhost, &a.service_id).cmp(&(&b.vhost, &b.service_id)));
     let mut formatter = ListFormatter::new(&VHOST_LIST_LABELS, None, context);
     formatter.push_values(&vhost_list_values);
-    formatter.print(None)
+    formatter.print(None)?;
+    Ok(())
   }
 
   fn requirements(&self, _: &ArgMatches) -> Requirements {
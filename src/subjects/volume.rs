@@ -2,6 +2,7 @@ use crate::arguments::volume_id_argument;
 use crate::capability::{Capability, CommandExecutor, CREATE_COMMAND, CREATE_COMMAND_ALIAS, DELETE_COMMAND, LIST_COMMAND, LIST_COMMAND_ALIAS, SHOW_COMMAND, SHOW_COMMAND_ALIAS};
 use crate::capability_builder::CapabilityBuilder;
 use crate::context::Context;
+use crate::error::DshCliError;
 use crate::filter_flags::FilterFlagType;
 use crate::flags::FlagType;
 use crate::formatters::formatter::{Label, SubjectFormatter};
@@ -116,7 +117,7 @@ impl CommandExecutor for VolumeCreate {
     let volume_id = target.unwrap_or_else(|| unreachable!());
     context.print_explanation(format!("create new volume '{}'", volume_id));
     if client.get_volume(&volume_id).await.is_ok() {
-      return Err(format!("volume '{}' already exists", volume_id));
+      return Err(DshCliError::Conflict(format!("volume '{}' already exists", volume_id)));
     }
     let size_gi_b: i64 = match matches.get_one::<i64>(SIZE_FLAG) {
       Some(size) => *size,
@@ -130,7 +131,7 @@ impl CommandExecutor for VolumeCreate {
       context.print_warning("dry-run mode, volume not created");
     } else {
       client.put_volume_configuration(&volume_id, &volume).await?;
-      context.print_outcome("volume created");
+      context.print_audited_outcome(client, format!("volume/{}", volume_id), "volume created");
     }
     Ok(())
   }
@@ -148,17 +149,17 @@ impl CommandExecutor for VolumeDelete {
     let volume_id = target.unwrap_or_else(|| unreachable!());
     context.print_explanation(format!("delete volume '{}'", volume_id));
     if client.get_volume(&volume_id).await.is_err() {
-      return Err(format!("volume '{}' does not exists", volume_id));
+      return Err(DshCliError::NotFound(format!("volume '{}' does not exists", volume_id)));
     }
     if context.confirmed(format!("delete volume '{}'?", volume_id))? {
       if context.dry_run() {
         context.print_warning("dry-run mode, volume not deleted");
       } else {
         client.delete_volume_configuration(&volume_id).await?;
-        context.print_outcome("volume deleted");
+        context.print_audited_outcome(client, format!("volume/{}", volume_id), "volume deleted");
       }
     } else {
-      context.print_outcome("cancelled, volume not deleted");
+      return Err(DshCliError::Cancelled("cancelled, volume not deleted".to_string()));
     }
     Ok(())
   }
@@ -289,7 +290,8 @@ impl CommandExecutor for VolumeShowAll {
     let start_instant = context.now();
     let volume = client.get_volume(&volume_id).await?;
     context.print_execution_time(start_instant);
-    UnitFormatter::new(volume_id, &VOLUME_STATUS_LABELS, Some("volume id"), context).print(&volume, None)
+    UnitFormatter::new(volume_id, &VOLUME_STATUS_LABELS, Some("volume id"), context).print(&volume, None)?;
+    Ok(())
   }
 
   fn requirements(&self, _: &ArgMatches) -> Requirements {
@@ -307,7 +309,8 @@ impl CommandExecutor for VolumeShowAllocationStatus {
     let start_instant = context.now();
     let allocation_status = client.get_volume_status(&volume_id).await?;
     context.print_execution_time(start_instant);
-    UnitFormatter::new(volume_id, &DEFAULT_ALLOCATION_STATUS_LABELS, Some("volume id"), context).print(&allocation_status, None)
+    UnitFormatter::new(volume_id, &DEFAULT_ALLOCATION_STATUS_LABELS, Some("volume id"), context).print(&allocation_status, None)?;
+    Ok(())
   }
 
   fn requirements(&self, _: &ArgMatches) -> Requirements {
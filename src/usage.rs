@@ -0,0 +1,160 @@
+use crate::context::Context;
+use crate::formatters::formatter::{Label, SubjectFormatter};
+use dsh_api::dsh_api_client::DshApiClient;
+use futures::future::try_join_all;
+use futures::try_join;
+use serde::Serialize;
+
+/// Percentage of a limit from which the usage will be highlighted as a warning
+const WARNING_PERCENTAGE: f64 = 80.0;
+
+/// Percentage of a limit from which the usage will be highlighted as an error
+const ERROR_PERCENTAGE: f64 = 100.0;
+
+/// # Actual resource consumption of a tenant
+///
+/// The cpu and memory usage are the sums of `instances × cpus` and `instances × mem`
+/// of all services of the tenant.
+#[derive(Debug, Default)]
+pub(crate) struct TenantUsage {
+  certificate_count: usize,
+  cpu: f64,
+  mem: u64,
+  partition_count: i64,
+  secret_count: usize,
+  topic_count: usize,
+}
+
+/// # Limits to compare the resource consumption with
+///
+/// Limits that are not known will be `None`.
+#[derive(Debug, Default)]
+pub(crate) struct UsageLimits {
+  pub(crate) certificate_count: Option<i64>,
+  pub(crate) cpu: Option<f64>,
+  pub(crate) mem: Option<i64>,
+  pub(crate) partition_count: Option<i64>,
+  pub(crate) secret_count: Option<i64>,
+  pub(crate) topic_count: Option<i64>,
+}
+
+impl TenantUsage {
+  /// # Fetches the resource consumption of the tenant of `client`
+  pub(crate) async fn fetch(client: &DshApiClient) -> Result<Self, String> {
+    let (applications, certificate_ids, secret_ids, topic_ids) = try_join!(
+      client.get_application_configuration_map(),
+      client.get_certificate_ids(),
+      client.get_secret_ids(),
+      client.get_topic_ids()
+    )?;
+    let topics = try_join_all(topic_ids.iter().map(|topic_id| client.get_topic_configuration(topic_id))).await?;
+    Ok(Self {
+      certificate_count: certificate_ids.len(),
+      cpu: applications.values().map(|application| application.instances as f64 * application.cpus).sum(),
+      mem: applications.values().map(|application| application.instances * application.mem).sum(),
+      partition_count: topics.iter().map(|topic| topic.partitions).sum(),
+      secret_count: secret_ids.len(),
+      topic_count: topic_ids.len(),
+    })
+  }
+
+  /// # Compares the resource consumption with the limits
+  ///
+  /// Returns the usage for each resource type. When the output is presented as a table,
+  /// percentages close to or above their limit will be highlighted.
+  pub(crate) fn resource_usages(&self, limits: &UsageLimits, context: &Context) -> Vec<ResourceUsage> {
+    vec![
+      ResourceUsage::new(
+        "certificate count",
+        self.certificate_count as f64,
+        limits.certificate_count.map(|limit| limit as f64),
+        context,
+      ),
+      ResourceUsage::new("cpu", (self.cpu * 1000.0).round() / 1000.0, limits.cpu, context),
+      ResourceUsage::new("mem", self.mem as f64, limits.mem.map(|limit| limit as f64), context),
+      ResourceUsage::new(
+        "partition count",
+        self.partition_count as f64,
+        limits.partition_count.map(|limit| limit as f64),
+        context,
+      ),
+      ResourceUsage::new("secret count", self.secret_count as f64, limits.secret_count.map(|limit| limit as f64), context),
+      ResourceUsage::new("topic count", self.topic_count as f64, limits.topic_count.map(|limit| limit as f64), context),
+    ]
+  }
+}
+
+/// # Usage of one resource type
+#[derive(Debug, Serialize)]
+pub(crate) struct ResourceUsage {
+  resource: &'static str,
+  used: f64,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  limit: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  percentage: Option<f64>,
+  #[serde(skip_serializing)]
+  styled_percentage: String,
+}
+
+impl ResourceUsage {
+  fn new(resource: &'static str, used: f64, limit: Option<f64>, context: &Context) -> Self {
+    let percentage = limit.filter(|limit| *limit > 0.0).map(|limit| (used * 1000.0 / limit).round() / 10.0);
+    let styled_percentage = match percentage {
+      Some(percentage) if percentage >= ERROR_PERCENTAGE => context.apply_error_style_for_stdout(format!("{}%", percentage), None),
+      Some(percentage) if percentage >= WARNING_PERCENTAGE => context.apply_warning_style_for_stdout(format!("{}%", percentage), None),
+      Some(percentage) => format!("{}%", percentage),
+      None => "".to_string(),
+    };
+    Self { resource, used, limit, percentage, styled_percentage }
+  }
+}
+
+#[derive(Eq, Hash, PartialEq, Serialize)]
+pub(crate) enum UsageLabel {
+  Limit,
+  Percentage,
+  Resource,
+  Used,
+}
+
+impl Label for UsageLabel {
+  fn as_str(&self) -> &str {
+    match self {
+      Self::Limit => "limit",
+      Self::Percentage => "percentage of limit",
+      Self::Resource => "resource",
+      Self::Used => "used",
+    }
+  }
+
+  fn as_str_for_list(&self) -> &str {
+    match self {
+      Self::Limit => "limit",
+      Self::Percentage => "%",
+      Self::Resource => "resource",
+      Self::Used => "used",
+    }
+  }
+
+  fn is_target_label(&self) -> bool {
+    matches!(self, Self::Resource)
+  }
+}
+
+impl SubjectFormatter<UsageLabel> for ResourceUsage {
+  fn value(&self, label: &UsageLabel, _target_id: &str) -> String {
+    match label {
+      UsageLabel::Limit => self.limit.map(|limit| limit.to_string()).unwrap_or_default(),
+      UsageLabel::Percentage => self.styled_percentage.clone(),
+      UsageLabel::Resource => self.resource.to_string(),
+      UsageLabel::Used => self.used.to_string(),
+    }
+  }
+
+  fn target_id(&self) -> Option<String> {
+    Some(self.resource.to_string())
+  }
+}
+
+pub(crate) static USAGE_LABELS: [UsageLabel; 4] = [UsageLabel::Resource, UsageLabel::Used, UsageLabel::Limit, UsageLabel::Percentage];
@@ -0,0 +1,101 @@
+//! Tests of commands that change resources on the (mock) platform
+
+mod common;
+
+use common::{MockServer, EXIT_CODE_CONFLICT};
+use serde_json::Value;
+use std::fs;
+
+fn volume_ids(mock_server: &MockServer) -> Vec<String> {
+  let volumes: Value = serde_json::from_str(&mock_server.stdout(["--output-format", "json", "volume", "list", "--ids"])).unwrap();
+  volumes.as_array().unwrap().iter().map(|volume| volume.as_str().unwrap().to_string()).collect()
+}
+
+#[test]
+fn volume_create_and_delete() {
+  let mock_server = MockServer::start("volume_create_and_delete");
+  mock_server.dsh(["--force", "volume", "create", "new-volume", "--size", "2"]).assert().success();
+  assert_eq!(volume_ids(&mock_server), vec!["greeter-data", "new-volume"]);
+  mock_server.dsh(["--force", "volume", "delete", "new-volume"]).assert().success();
+  assert_eq!(volume_ids(&mock_server), vec!["greeter-data"]);
+}
+
+#[test]
+fn changes_invalidate_completion_cache() {
+  let mock_server = MockServer::start("changes_invalidate_completion_cache");
+  let completion_cache_file = mock_server.home().join("completion").join("mock").join("mock.json");
+  fs::create_dir_all(completion_cache_file.parent().unwrap()).unwrap();
+  let cached_ids = serde_json::json!({ "timestamp": u64::MAX, "ids": ["cached"] });
+  fs::write(
+    &completion_cache_file,
+    serde_json::json!({ "secret-id-argument": cached_ids, "volume-id-argument": cached_ids }).to_string(),
+  )
+  .unwrap();
+  mock_server.dsh(["--force", "volume", "create", "new-volume", "--size", "2"]).assert().success();
+  let completion_cache: Value = serde_json::from_str(&fs::read_to_string(&completion_cache_file).unwrap()).unwrap();
+  assert!(completion_cache.get("secret-id-argument").is_some());
+  assert!(completion_cache.get("volume-id-argument").is_none());
+}
+
+#[test]
+fn volume_create_existing_volume_is_conflict() {
+  let mock_server = MockServer::start("volume_create_existing_volume_is_conflict");
+  mock_server
+    .dsh(["--force", "volume", "create", "greeter-data", "--size", "1"])
+    .assert()
+    .code(EXIT_CODE_CONFLICT);
+}
+
+#[test]
+fn service_duplicate_to_existing_service_is_conflict() {
+  let mock_server = MockServer::start("service_duplicate_to_existing_service_is_conflict");
+  mock_server
+    .dsh(["service", "duplicate", "greeter", "--verbatim"])
+    .write_stdin("consumer\n")
+    .assert()
+    .code(EXIT_CODE_CONFLICT);
+}
+
+#[test]
+fn dry_run_does_not_change_resources() {
+  let mock_server = MockServer::start("dry_run_does_not_change_resources");
+  mock_server.dsh(["--dry-run", "volume", "create", "new-volume", "--size", "2"]).assert().success();
+  mock_server.dsh(["--dry-run", "--force", "volume", "delete", "greeter-data"]).assert().success();
+  assert_eq!(volume_ids(&mock_server), vec!["greeter-data"]);
+}
+
+#[test]
+fn topic_create() {
+  let mock_server = MockServer::start("topic_create");
+  mock_server
+    .dsh(["--force", "topic", "create", "scratch.new.mock", "--partitions", "1"])
+    .assert()
+    .success();
+  let topic: Value = serde_json::from_str(&mock_server.stdout(["--output-format", "json", "topic", "show", "scratch.new.mock"])).unwrap();
+  assert_eq!(topic["partitions"], 1);
+}
+
+#[test]
+fn service_restart() {
+  let mock_server = MockServer::start("service_restart");
+  mock_server.dsh(["--force", "service", "restart", "greeter"]).assert().success();
+  let services: Value = serde_json::from_str(&mock_server.stdout(["--output-format", "json", "service", "list", "--tasks"])).unwrap();
+  let greeter_tasks = services.as_array().unwrap().iter().find(|service| service[0] == "greeter").unwrap()[1]
+    .as_str()
+    .unwrap()
+    .to_string();
+  assert_eq!(
+    greeter_tasks,
+    "greeter.00000000-0000-0000-0000-000000000001, greeter.00000000-0000-0000-0000-000000000002"
+  );
+  // A restart ends with the same configuration, so no snapshot must be stored
+  assert!(!mock_server.home().join("snapshots").join("mock").join("mock").join("greeter.jsonl").exists());
+}
+
+#[test]
+fn changes_are_written_to_audit_log() {
+  let mock_server = MockServer::start("changes_are_written_to_audit_log");
+  mock_server.dsh(["--force", "volume", "delete", "greeter-data"]).assert().success();
+  let audit_log = fs::read_to_string(mock_server.home().join("audit.log")).unwrap();
+  assert!(audit_log.contains("greeter-data"));
+}
@@ -1,6 +1,7 @@
 #!/bin/bash
 
 export APP_UNDER_TEST=kafdrop
+export APP_NON_EXISTING=non-existing-app
 export BUCKET_UNDER_TEST=cpr
 export CERTIFICATE_UNDER_TEST=broker
 export ENV_VALUE_UNDER_TEST=info
@@ -34,14 +35,24 @@ export SAFE_COMMANDS=(
   #  "--generate-autocomplete-file zsh"
 
   "api delete secret-configuration $SECRET_UNDER_TEST --force --dry-run"
+  "api example put application-configuration"
+  "api example post secret"
   "api get secret $SECRET_UNDER_TEST"
+  "api get secret $SECRET_UNDER_TEST --raw"
+  "api get application-configuration $SERVICE_UNDER_TEST --raw"
   "api post secret --dry-run < /dev/null"
   "api put secret $SECRET_UNDER_TEST --dry-run < /dev/null"
+  "api put secret $SECRET_UNDER_TEST --body '\"value\"' --dry-run"
+  "api put application-configuration $SERVICE_UNDER_TEST --set env.LOG_LEVEL=debug --set cpus=0.2 --dry-run"
   "api show > /dev/null"
 
+  "app delete $APP_UNDER_TEST --force --dry-run"
+  "app deploy $MANIFEST_UNDER_TEST --name $APP_NON_EXISTING --version $MANIFEST_UNDER_TEST_VERSION --dry-run < /dev/null"
   "app list --ids"
   "app list"
+  "app outdated"
   "app show $APP_UNDER_TEST"
+  "app update $APP_UNDER_TEST --dry-run"
 
   "bucket list --ids"
   "bucket list"
@@ -56,6 +67,8 @@ export SAFE_COMMANDS=(
   "certificate show $CERTIFICATE_UNDER_TEST --usage"
   "certificate show $CERTIFICATE_UNDER_TEST"
 
+  "completion refresh"
+
   "env find $ENV_VALUE_UNDER_TEST --started"
   "env find $ENV_VALUE_UNDER_TEST --stopped"
   "env find $ENV_VALUE_UNDER_TEST"
@@ -63,6 +76,10 @@ export SAFE_COMMANDS=(
   "env find $ENV_VALUE_UNDER_TEST_REGEX --regex --stopped"
   "env find $ENV_VALUE_UNDER_TEST_REGEX --regex"
 
+  "history"
+  "history --since 7d"
+  "history --resource service/$SERVICE_UNDER_TEST"
+
   "image find $IMAGE_UNDER_TEST --started"
   "image find $IMAGE_UNDER_TEST --stopped"
   "image find $IMAGE_UNDER_TEST"
@@ -73,6 +90,7 @@ export SAFE_COMMANDS=(
   "image list --stopped"
   "image list"
 
+  "manifest diff $MANIFEST_UNDER_TEST $MANIFEST_UNDER_TEST_VERSION $MANIFEST_UNDER_TEST_VERSION"
   "manifest export $MANIFEST_UNDER_TEST $MANIFEST_UNDER_TEST_VERSION"
   "manifest list"
   "manifest list --ids"
@@ -86,7 +104,14 @@ export SAFE_COMMANDS=(
   "metric list"
 
   "platform export"
+  "platform validate"
   "platform list"
+  "platform list --columns name,realm --sort-by realm --desc"
+  "platform list --where production=false"
+  "platform list --template {{name}}:{{alias}}"
+  "platform list --output-format html"
+  "platform list --output-format markdown"
+  "platform list --output-format ndjson"
   "platform open app $APP_UNDER_TEST --platform $PLATFORM_UNDER_TEST --tenant $TENANT_UNDER_TEST --dry-run"
   "platform open app $APP_UNDER_TEST --dry-run"
   "platform open console --platform $PLATFORM_UNDER_TEST --tenant $TENANT_UNDER_TEST --dry-run"
@@ -111,6 +136,8 @@ export SAFE_COMMANDS=(
   "platform show --vhost $VHOST_UNDER_TEST --platform $PLATFORM_UNDER_TEST --tenant $TENANT_UNDER_TEST"
   "platform show --vhost $VHOST_UNDER_TEST"
   "platform show"
+  "platform usage"
+  "platform usage --output-format csv"
 
   "proxy list --ids"
   "proxy list"
@@ -136,10 +163,14 @@ export SAFE_COMMANDS=(
   "service list --stopped"
   "service list --tasks"
   "service list"
+  "service list --tasks --output-format ndjson"
   "service restart $SERVICE_UNDER_TEST --force --dry-run"
+  "service rollback $SERVICE_UNDER_TEST --force --dry-run"
+  "service rollback $SERVICE_UNDER_TEST --steps 2 --force --dry-run"
   "service show $SERVICE_UNDER_TEST --status"
   "service show $SERVICE_UNDER_TEST --tasks"
   "service show $SERVICE_UNDER_TEST"
+  "service show $SERVICE_UNDER_TEST --query env --output-format table"
   "service start $SERVICE_UNDER_TEST --force --dry-run"
   "service start $SERVICE_UNDER_TEST --force --instances 2 --dry-run"
   "service stop $SERVICE_UNDER_TEST --force --dry-run"
@@ -0,0 +1,121 @@
+//! # Integration test harness
+//!
+//! Every test starts its own mock server (see the `dsh-mock-server` crate) on a free port,
+//! with its own `dsh` home directory, and runs the `dsh` binary against it. This keeps the tests
+//! independent of each other and of the settings, targets and environment variables of the developer.
+//!
+//! The standard output of a command, or the standard error of a failing command,
+//! can be compared with a snapshot file in the `tests/snapshots` directory.
+//! Set the environment variable `DSH_CLI_UPDATE_SNAPSHOTS` to (re)write the snapshot files
+//! instead of comparing them.
+
+#![allow(dead_code)]
+
+use assert_cmd::Command;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub const EXIT_CODE_GENERAL: i32 = 1;
+pub const EXIT_CODE_NOT_FOUND: i32 = 3;
+pub const EXIT_CODE_UNAUTHORIZED: i32 = 4;
+pub const EXIT_CODE_CONFLICT: i32 = 5;
+
+const UPDATE_SNAPSHOTS: &str = "DSH_CLI_UPDATE_SNAPSHOTS";
+
+pub struct MockServer {
+  home: PathBuf,
+  environment_variables: Vec<(&'static str, String)>,
+  server: dsh_mock_server::MockServer,
+}
+
+impl MockServer {
+  /// Starts a mock server with the default fixtures
+  pub fn start(test_name: &str) -> Self {
+    let home = Path::new(env!("CARGO_TARGET_TMPDIR")).join("dsh-integration-tests").join(test_name);
+    let _ = fs::remove_dir_all(&home);
+    let server = dsh_mock_server::MockServer::start(&home, 0, None).expect("could not start mock server");
+    let environment_variables = server.environment_variables();
+    Self { home, environment_variables, server }
+  }
+
+  /// Returns a `dsh` command that targets the mock server, without any other settings
+  pub fn dsh<I, S>(&self, args: I) -> Command
+  where
+    I: IntoIterator<Item = S>,
+    S: AsRef<std::ffi::OsStr>,
+  {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_dsh"));
+    command
+      .args(args)
+      .env_clear()
+      .envs(self.environment_variables.iter().map(|(name, value)| (name, value)))
+      .env("NO_COLOR", "")
+      .timeout(COMMAND_TIMEOUT);
+    command
+  }
+
+  /// Runs a `dsh` command that must succeed and returns its standard output
+  pub fn stdout<I, S>(&self, args: I) -> String
+  where
+    I: IntoIterator<Item = S>,
+    S: AsRef<std::ffi::OsStr>,
+  {
+    let output = self.dsh(args).assert().success().get_output().stdout.clone();
+    String::from_utf8(output).expect("standard output is not valid utf-8")
+  }
+
+  /// Runs a `dsh` command that must fail with the provided exit code and returns its standard error
+  pub fn stderr<I, S>(&self, args: I, exit_code: i32) -> String
+  where
+    I: IntoIterator<Item = S>,
+    S: AsRef<std::ffi::OsStr>,
+  {
+    let output = self.dsh(args).assert().code(exit_code).get_output().stderr.clone();
+    String::from_utf8(output).expect("standard error is not valid utf-8")
+  }
+
+  /// Stops the mock server, while keeping its home directory
+  pub fn stop(&mut self) {
+    self.server.stop();
+  }
+
+  pub fn home(&self) -> &Path {
+    &self.home
+  }
+}
+
+/// Compares a value with the snapshot file `tests/snapshots/<name>`
+pub fn assert_snapshot(name: &str, actual: &str) {
+  let snapshot_file = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("snapshots").join(name);
+  if std::env::var(UPDATE_SNAPSHOTS).is_ok() {
+    fs::create_dir_all(snapshot_file.parent().unwrap()).expect("could not create snapshots directory");
+    fs::write(&snapshot_file, actual).expect("could not write snapshot file");
+    return;
+  }
+  match fs::read_to_string(&snapshot_file) {
+    Ok(expected) => assert_eq!(
+      expected,
+      actual,
+      "output differs from snapshot '{}' (set {} to update the snapshot)",
+      snapshot_file.display(),
+      UPDATE_SNAPSHOTS
+    ),
+    Err(_) => panic!(
+      "snapshot '{}' does not exist (set {} to create the snapshot)",
+      snapshot_file.display(),
+      UPDATE_SNAPSHOTS
+    ),
+  }
+}
+
+/// Compares a json value with the snapshot file `tests/snapshots/<name>`
+///
+/// The json value is normalized before it is compared, since the order of the
+/// fields of some objects (e.g. the environment variables of a service) is not stable.
+pub fn assert_json_snapshot(name: &str, actual: &str) {
+  let value = serde_json::from_str::<serde_json::Value>(actual).unwrap_or_else(|error| panic!("output is not valid json ({})\n{}", error, actual));
+  assert_snapshot(name, &format!("{}\n", serde_json::to_string_pretty(&value).unwrap()));
+}
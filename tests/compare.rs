@@ -0,0 +1,36 @@
+//! Tests of comparing the resources of two targets
+//!
+//! Other tenants than the target tenant need their own password from the keyring,
+//! which is not available when the tests run. Comparing the target tenant with itself
+//! must not report any differences.
+
+mod common;
+
+use common::MockServer;
+
+#[test]
+fn compare_tenants_without_differences() {
+  let mock_server = MockServer::start("compare_tenants_without_differences");
+  let stdout = mock_server.stdout(["--output-format", "json", "compare", "--from", "mock@mock", "--to", "mock"]);
+  assert!(stdout.is_empty());
+}
+
+#[test]
+fn compare_requires_password_of_other_tenant() {
+  let mock_server = MockServer::start("compare_requires_password_of_other_tenant");
+  let output = mock_server.dsh(["compare", "--to", "other"]).assert().failure().get_output().clone();
+  assert!(String::from_utf8_lossy(&output.stderr).contains("could not determine password for tenant 'other'"));
+}
+
+#[test]
+fn compare_requires_to_target() {
+  let mock_server = MockServer::start("compare_requires_to_target");
+  mock_server.dsh(["compare", "--from", "mock@mock"]).assert().failure();
+}
+
+#[test]
+fn compare_unknown_platform() {
+  let mock_server = MockServer::start("compare_unknown_platform");
+  let output = mock_server.dsh(["compare", "--to", "mock@non-existing"]).assert().failure().get_output().clone();
+  assert!(String::from_utf8_lossy(&output.stderr).contains("non-existing"));
+}
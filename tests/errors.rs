@@ -0,0 +1,108 @@
+//! Tests of the exit codes and the error output of erroneous commands
+
+mod common;
+
+use common::{assert_snapshot, MockServer, EXIT_CODE_CONFLICT, EXIT_CODE_GENERAL, EXIT_CODE_NOT_FOUND, EXIT_CODE_UNAUTHORIZED};
+use serde_json::Value;
+
+fn assert_error_snapshot(test_name: &str, args: &[&str], exit_code: i32) {
+  let mock_server = MockServer::start(test_name);
+  let stderr = mock_server.stderr(args, exit_code);
+  assert_snapshot(&format!("{}.stderr", test_name), &stderr);
+}
+
+#[test]
+fn show_non_existing_resource_is_not_found() {
+  let mock_server = MockServer::start("show_non_existing_resource_is_not_found");
+  for subject in ["bucket", "certificate", "service", "topic", "volume"] {
+    mock_server.dsh([subject, "show", "non-existing"]).assert().failure().code(EXIT_CODE_NOT_FOUND);
+  }
+}
+
+#[test]
+fn json_error_output() {
+  let mock_server = MockServer::start("json_error_output");
+  let output = mock_server
+    .dsh(["--output-format", "json", "volume", "show", "non-existing"])
+    .assert()
+    .code(EXIT_CODE_NOT_FOUND)
+    .get_output()
+    .clone();
+  assert!(output.stdout.is_empty());
+  let error: Value = serde_json::from_slice(&output.stderr).unwrap();
+  assert_eq!(error["code"], "not-found");
+  assert_eq!(error["exit-code"], EXIT_CODE_NOT_FOUND);
+}
+
+#[test]
+fn unknown_argument_does_not_panic() {
+  let mock_server = MockServer::start("unknown_argument_does_not_panic");
+  let output = mock_server
+    .dsh(["volume", "list", "--non-existing-argument"])
+    .assert()
+    .failure()
+    .get_output()
+    .clone();
+  assert!(!String::from_utf8_lossy(&output.stderr).contains("panicked"));
+}
+
+#[test]
+fn wrong_password_is_unauthorized() {
+  let mock_server = MockServer::start("wrong_password_is_unauthorized");
+  mock_server
+    .dsh(["volume", "list"])
+    .env("DSH_CLI_PASSWORD", "wrong")
+    .assert()
+    .code(EXIT_CODE_UNAUTHORIZED);
+}
+
+#[test]
+fn failing_query_fails_command() {
+  let mock_server = MockServer::start("failing_query_fails_command");
+  mock_server
+    .dsh(["--output-format", "template", "--query", "cpus", "api", "example", "put", "application-configuration"])
+    .assert()
+    .failure();
+}
+
+#[test]
+fn api_raw_request_non_existing_resource() {
+  assert_error_snapshot(
+    "api_raw_request_non_existing_resource",
+    &["api", "get", "volume-configuration", "non-existing", "--raw"],
+    EXIT_CODE_NOT_FOUND,
+  );
+}
+
+#[test]
+fn app_delete_non_existing_app() {
+  assert_error_snapshot("app_delete_non_existing_app", &["app", "delete", "non-existing"], EXIT_CODE_NOT_FOUND);
+}
+
+#[test]
+fn app_deploy_existing_app() {
+  assert_error_snapshot("app_deploy_existing_app", &["app", "deploy", "mock-echo", "--name", "echo"], EXIT_CODE_CONFLICT);
+}
+
+#[test]
+fn app_deploy_non_existing_manifest_version() {
+  assert_error_snapshot(
+    "app_deploy_non_existing_manifest_version",
+    &["app", "deploy", "mock-echo", "--name", "other", "--version", "9.9.9"],
+    EXIT_CODE_GENERAL,
+  );
+}
+
+#[test]
+fn app_update_non_existing_app() {
+  assert_error_snapshot("app_update_non_existing_app", &["app", "update", "non-existing"], EXIT_CODE_NOT_FOUND);
+}
+
+#[test]
+fn manifest_diff_non_existing_version() {
+  assert_error_snapshot(
+    "manifest_diff_non_existing_version",
+    &["manifest", "diff", "mock-echo", "1.0.0", "9.9.9"],
+    EXIT_CODE_GENERAL,
+  );
+}
@@ -0,0 +1,63 @@
+//! Tests of running a command for multiple stored targets
+//!
+//! The passwords of stored targets are read from the keyring, which is not available
+//! when the tests run. These tests therefore cover the selection of the targets
+//! and the reporting of the per target failures.
+
+mod common;
+
+use common::MockServer;
+use std::fs;
+
+fn store_targets(mock_server: &MockServer, tenants: &[&str]) {
+  let targets_directory = mock_server.home().join("targets");
+  fs::create_dir_all(&targets_directory).unwrap();
+  for tenant in tenants {
+    fs::write(
+      targets_directory.join(format!("mock.{}.toml", tenant)),
+      format!("platform = \"mock\"\ntenant = \"{}\"\n", tenant),
+    )
+    .unwrap();
+  }
+}
+
+#[test]
+fn failures_are_reported_per_target() {
+  let mock_server = MockServer::start("failures_are_reported_per_target");
+  store_targets(&mock_server, &["mock", "other"]);
+  let output = mock_server.dsh(["--all-targets", "volume", "list"]).assert().failure().get_output().clone();
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  assert!(stderr.contains("target mock@mock: "));
+  assert!(stderr.contains("target other@mock: "));
+  assert!(stderr.contains("command failed for 2 of 2 targets"));
+}
+
+#[test]
+fn targets_are_selected_by_pattern() {
+  let mock_server = MockServer::start("targets_are_selected_by_pattern");
+  store_targets(&mock_server, &["mock", "other"]);
+  let output = mock_server
+    .dsh(["--targets", "oth*@mock", "volume", "list"])
+    .assert()
+    .failure()
+    .get_output()
+    .clone();
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  assert!(!stderr.contains("target mock@mock: "));
+  assert!(stderr.contains("target other@mock: "));
+  assert!(stderr.contains("command failed for 1 of 1 targets"));
+  let output = mock_server
+    .dsh(["--targets", "non-existing", "volume", "list"])
+    .assert()
+    .failure()
+    .get_output()
+    .clone();
+  assert!(String::from_utf8_lossy(&output.stderr).contains("no stored targets match the --targets option"));
+}
+
+#[test]
+fn targets_conflict_with_tenant() {
+  let mock_server = MockServer::start("targets_conflict_with_tenant");
+  store_targets(&mock_server, &["mock"]);
+  mock_server.dsh(["--all-targets", "--tenant", "mock", "volume", "list"]).assert().failure();
+}
@@ -0,0 +1,30 @@
+//! Snapshot tests of the dependency graph of the mock tenant
+
+mod common;
+
+use common::{assert_json_snapshot, assert_snapshot, MockServer, EXIT_CODE_NOT_FOUND};
+
+#[test]
+fn graph_dot() {
+  let mock_server = MockServer::start("graph_dot");
+  assert_snapshot("graph_dot.dot", &mock_server.stdout(["graph"]));
+}
+
+#[test]
+fn graph_json() {
+  let mock_server = MockServer::start("graph_json");
+  assert_json_snapshot("graph_json.json", &mock_server.stdout(["graph", "--format", "json"]));
+}
+
+#[test]
+fn graph_mermaid_around_volume() {
+  let mock_server = MockServer::start("graph_mermaid_around_volume");
+  let stdout = mock_server.stdout(["graph", "--format", "mermaid", "--around", "volume:greeter-data", "--depth", "2"]);
+  assert_snapshot("graph_mermaid_around_volume.mmd", &stdout);
+}
+
+#[test]
+fn graph_around_unknown_resource() {
+  let mock_server = MockServer::start("graph_around_unknown_resource");
+  mock_server.dsh(["graph", "--around", "secret:non-existing"]).assert().code(EXIT_CODE_NOT_FOUND);
+}
@@ -0,0 +1,59 @@
+//! Tests of the api call records in the log file
+//!
+//! All http requests of a command are only intercepted and logged on Linux.
+
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use common::MockServer;
+use serde_json::Value;
+use std::fs;
+
+// Returns the api call records from a json log file
+fn api_calls(mock_server: &MockServer, args: &[&str]) -> Vec<Value> {
+  let log_file = mock_server.home().join("dsh.log");
+  let mut log_args = vec!["--log-file", log_file.to_str().unwrap(), "--log-format", "json"];
+  log_args.extend_from_slice(args);
+  mock_server.dsh(log_args).assert().success();
+  fs::read_to_string(&log_file)
+    .unwrap()
+    .lines()
+    .map(|line| serde_json::from_str::<Value>(line).unwrap_or_else(|error| panic!("log record is not valid json ({})\n{}", error, line)))
+    .filter(|record| record["target"] == "dsh::api_call")
+    .map(|record| record["api-call"].clone())
+    .collect()
+}
+
+#[test]
+fn log_file_contains_one_record_per_request() {
+  let mock_server = MockServer::start("log_file_contains_one_record_per_request");
+  let api_calls = api_calls(&mock_server, &["service", "list"]);
+  let requests = api_calls
+    .iter()
+    .map(|api_call| {
+      format!(
+        "{} {} {}",
+        api_call["method"].as_str().unwrap(),
+        api_call["path"].as_str().unwrap(),
+        api_call["status"]
+      )
+    })
+    .collect::<Vec<_>>();
+  assert_eq!(
+    requests,
+    vec!["POST /auth/realms/mock/protocol/openid-connect/token 200", "GET /resources/v0/allocation/mock/application/configuration 200"]
+  );
+  assert!(api_calls.iter().all(|api_call| api_call.get("response-body").is_none()));
+}
+
+#[test]
+fn log_file_with_trace_http_contains_redacted_bodies() {
+  let mock_server = MockServer::start("log_file_with_trace_http_contains_redacted_bodies");
+  let api_calls = api_calls(&mock_server, &["--trace-http", "volume", "show", "greeter-data"]);
+  assert_eq!(api_calls.len(), 2);
+  let token_response = api_calls[0]["response-body"].as_str().unwrap();
+  assert!(token_response.contains(r#""access_token":"***""#));
+  assert!(!token_response.contains("mock-access-token"));
+  assert!(api_calls[1]["response-body"].as_str().unwrap().contains("sizeGiB"));
+}
@@ -0,0 +1,43 @@
+//! Snapshot tests of the rendering of list and unit output in all output formats
+
+mod common;
+
+use common::{assert_snapshot, MockServer};
+
+// Toml output is not supported for lists, see KNOWN_ISSUES.md
+const LIST_OUTPUT_FORMATS: [&str; 10] = ["csv", "html", "json", "json-compact", "markdown", "ndjson", "plain", "table", "table-no-border", "yaml"];
+
+// Plain output is not supported for units, see KNOWN_ISSUES.md
+const UNIT_OUTPUT_FORMATS: [&str; 11] = ["csv", "html", "json", "json-compact", "markdown", "ndjson", "table", "table-no-border", "toml", "toml-compact", "yaml"];
+
+fn assert_output_format_snapshots(test_name: &str, output_formats: &[&str], args: &[&str]) {
+  let mock_server = MockServer::start(test_name);
+  for output_format in output_formats {
+    let stdout = mock_server.stdout([&["--output-format", output_format], args].concat());
+    assert_snapshot(&format!("{}.{}", test_name, output_format), &stdout);
+  }
+}
+
+#[test]
+fn list_output_formats() {
+  assert_output_format_snapshots("list_output_formats", &LIST_OUTPUT_FORMATS, &["topic", "list"]);
+}
+
+#[test]
+fn unit_output_formats() {
+  assert_output_format_snapshots("unit_output_formats", &UNIT_OUTPUT_FORMATS, &["volume", "show", "greeter-data"]);
+}
+
+#[test]
+fn template_output_format() {
+  let mock_server = MockServer::start("template_output_format");
+  let stdout = mock_server.stdout(["service", "list", "--template", "{{service id}}: {{image}}"]);
+  assert_snapshot("template_output_format.txt", &stdout);
+}
+
+#[test]
+fn query_selects_part_of_output() {
+  let mock_server = MockServer::start("query_selects_part_of_output");
+  let stdout = mock_server.stdout(["--output-format", "json", "--query", "[*].\"service id\"", "service", "list"]);
+  assert_snapshot("query_selects_part_of_output.json", &stdout);
+}
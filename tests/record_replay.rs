@@ -0,0 +1,60 @@
+//! Tests of recording api interactions and replaying them without the (mock) platform
+//!
+//! Recording and replaying requires the `record` feature.
+
+#![cfg(feature = "record")]
+
+mod common;
+
+use common::MockServer;
+use std::fs;
+
+#[test]
+fn replay_serves_recorded_responses() {
+  let mut mock_server = MockServer::start("replay_serves_recorded_responses");
+  let recording = mock_server.home().join("recording");
+  let recording = recording.to_str().unwrap();
+  let recorded_output = mock_server.stdout(["--record", recording, "volume", "show", "greeter-data"]);
+  mock_server.stop();
+  let replayed_output = mock_server.stdout(["--replay", recording, "volume", "show", "greeter-data"]);
+  assert_eq!(recorded_output, replayed_output);
+}
+
+#[test]
+fn replay_of_change() {
+  let mut mock_server = MockServer::start("replay_of_change");
+  let recording = mock_server.home().join("recording");
+  let recording = recording.to_str().unwrap();
+  mock_server
+    .dsh(["--record", recording, "--force", "volume", "delete", "greeter-data"])
+    .assert()
+    .success();
+  mock_server.stop();
+  mock_server
+    .dsh(["--replay", recording, "--force", "volume", "delete", "greeter-data"])
+    .assert()
+    .success();
+}
+
+#[test]
+fn recording_does_not_contain_credentials() {
+  let mock_server = MockServer::start("recording_does_not_contain_credentials");
+  let recording = mock_server.home().join("recording");
+  mock_server.dsh(["--record", recording.to_str().unwrap(), "volume", "list"]).assert().success();
+  let interaction_files = fs::read_dir(&recording).unwrap().map(|entry| entry.unwrap().path()).collect::<Vec<_>>();
+  assert!(interaction_files.len() > 2);
+  for interaction_file in interaction_files {
+    let interaction = fs::read_to_string(interaction_file).unwrap();
+    assert!(!interaction.contains("client_secret"));
+    assert!(!interaction.contains("mock-access-token"));
+  }
+}
+
+#[test]
+fn record_in_non_empty_directory_fails() {
+  let mock_server = MockServer::start("record_in_non_empty_directory_fails");
+  let recording = mock_server.home().join("recording");
+  fs::create_dir_all(&recording).unwrap();
+  fs::write(recording.join("file"), "").unwrap();
+  mock_server.dsh(["--record", recording.to_str().unwrap(), "volume", "list"]).assert().failure();
+}
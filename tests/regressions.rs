@@ -0,0 +1,35 @@
+//! Tests for previously known issues, see `KNOWN_ISSUES.md`
+
+mod common;
+
+use common::MockServer;
+use serde_json::Value;
+
+#[test]
+fn service_list_tasks_does_not_duplicate_service_id() {
+  let mock_server = MockServer::start("service_list_tasks_does_not_duplicate_service_id");
+  let services: Value = serde_json::from_str(&mock_server.stdout(["--output-format", "json", "service", "list", "--tasks"])).unwrap();
+  for service in services.as_array().unwrap() {
+    assert_eq!(service.as_array().unwrap().len(), 2);
+    assert!(service[0].is_string());
+    assert!(service[1].is_string(), "tasks of service {} must be a string", service[0]);
+  }
+}
+
+#[test]
+fn env_find_field_names_are_not_capitalized() {
+  let mock_server = MockServer::start("env_find_field_names_are_not_capitalized");
+  let matches: Value = serde_json::from_str(&mock_server.stdout(["--output-format", "json", "env", "find", "^scratch", "--regex"])).unwrap();
+  assert!(!matches.as_array().unwrap().is_empty());
+  for service_match in matches.as_array().unwrap() {
+    for field_name in service_match[1].as_object().unwrap().keys() {
+      assert_eq!(field_name, &field_name.to_lowercase());
+    }
+  }
+}
+
+#[test]
+fn env_find_query_is_not_parsed_as_output_query() {
+  let mock_server = MockServer::start("env_find_query_is_not_parsed_as_output_query");
+  mock_server.dsh(["env", "find", "^scratch$", "--regex"]).assert().success();
+}
@@ -0,0 +1,137 @@
+//! Snapshot tests of the list and show commands of all subjects, in json format,
+//! against the default fixtures of the mock server
+
+mod common;
+
+use common::{assert_json_snapshot, MockServer};
+
+fn assert_subject_snapshot(test_name: &str, args: &[&str]) {
+  let mock_server = MockServer::start(test_name);
+  let stdout = mock_server.stdout([&["--output-format", "json"], args].concat());
+  assert_json_snapshot(&format!("{}.json", test_name), &stdout);
+}
+
+#[test]
+fn app_list() {
+  assert_subject_snapshot("app_list", &["app", "list"]);
+}
+
+#[test]
+fn app_outdated() {
+  assert_subject_snapshot("app_outdated", &["app", "outdated"]);
+}
+
+#[test]
+fn app_show() {
+  assert_subject_snapshot("app_show", &["app", "show", "echo"]);
+}
+
+#[test]
+fn bucket_list() {
+  assert_subject_snapshot("bucket_list", &["bucket", "list"]);
+}
+
+#[test]
+fn bucket_show() {
+  assert_subject_snapshot("bucket_show", &["bucket", "show", "greeter-bucket"]);
+}
+
+#[test]
+fn certificate_list() {
+  assert_subject_snapshot("certificate_list", &["certificate", "list"]);
+}
+
+#[test]
+fn certificate_show() {
+  assert_subject_snapshot("certificate_show", &["certificate", "show", "greeter-certificate"]);
+}
+
+#[test]
+fn env_find() {
+  assert_subject_snapshot("env_find", &["env", "find", "^scratch", "--regex"]);
+}
+
+#[test]
+fn image_list() {
+  assert_subject_snapshot("image_list", &["image", "list"]);
+}
+
+#[test]
+fn manifest_list() {
+  assert_subject_snapshot("manifest_list", &["manifest", "list"]);
+}
+
+#[test]
+fn manifest_diff() {
+  assert_subject_snapshot("manifest_diff", &["manifest", "diff", "mock-echo", "1.0.0", "1.1.0"]);
+}
+
+#[test]
+fn manifest_show() {
+  assert_subject_snapshot("manifest_show", &["manifest", "show", "mock-echo"]);
+}
+
+#[test]
+fn manifest_show_version() {
+  assert_subject_snapshot("manifest_show_version", &["manifest", "show", "mock-echo", "1.0.0"]);
+}
+
+#[test]
+fn metric_list() {
+  assert_subject_snapshot("metric_list", &["metric", "list"]);
+}
+
+#[test]
+fn secret_list() {
+  assert_subject_snapshot("secret_list", &["secret", "list"]);
+}
+
+#[test]
+fn secret_list_usage() {
+  assert_subject_snapshot("secret_list_usage", &["secret", "list", "--usage"]);
+}
+
+#[test]
+fn service_list() {
+  assert_subject_snapshot("service_list", &["service", "list"]);
+}
+
+#[test]
+fn service_list_tasks() {
+  assert_subject_snapshot("service_list_tasks", &["service", "list", "--tasks"]);
+}
+
+#[test]
+fn service_show() {
+  assert_subject_snapshot("service_show", &["service", "show", "greeter"]);
+}
+
+#[test]
+fn topic_list() {
+  assert_subject_snapshot("topic_list", &["topic", "list"]);
+}
+
+#[test]
+fn topic_show() {
+  assert_subject_snapshot("topic_show", &["topic", "show", "scratch.greetings.mock"]);
+}
+
+#[test]
+fn vhost_list() {
+  assert_subject_snapshot("vhost_list", &["vhost", "list"]);
+}
+
+#[test]
+fn volume_list() {
+  assert_subject_snapshot("volume_list", &["volume", "list"]);
+}
+
+#[test]
+fn volume_list_usage() {
+  assert_subject_snapshot("volume_list_usage", &["volume", "list", "--usage"]);
+}
+
+#[test]
+fn volume_show() {
+  assert_subject_snapshot("volume_show", &["volume", "show", "greeter-data"]);
+}
@@ -31,7 +31,7 @@ MANAGE_COMMANDS=(
   "stream create --internal $STREAM_NON_EXISTING --retention-ms 31536000000 --dry-run"
   "stream create --internal $STREAM_NON_EXISTING --segment-bytes 52428800 --dry-run"
 
-  "stream create --public $STREAM_NON_EXISTING --can-be-retained --dry-run"
+  "stream create --public $STREAM_NON_EXISTING --can-be-retained true --dry-run"
   "stream create --public $STREAM_NON_EXISTING --cleanup-policy compact --dry-run"
   "stream create --public $STREAM_NON_EXISTING --compression-type gzip --dry-run"
   "stream create --public $STREAM_NON_EXISTING --delete-retention-ms 6000 --dry-run"
@@ -58,6 +58,13 @@ MANAGE_COMMANDS=(
 
   "stream show $INTERNAL_STREAM"
   "stream show $PUBLIC_STREAM"
+  "stream show $INTERNAL_STREAM --access"
+
+  "stream update $INTERNAL_STREAM --cleanup-policy compact --dry-run"
+  "stream update $INTERNAL_STREAM --partitions 3 --dry-run"
+  "stream update $INTERNAL_STREAM --retention-ms 31536000000 --dry-run"
+  "stream update $PUBLIC_STREAM --can-be-retained true --dry-run"
+  "stream update $PUBLIC_STREAM --topic-level-partitioner 2 --dry-run"
 
   "tenant create $TENANT_NON_EXISTING --dry-run"
   "tenant create $TENANT_NON_EXISTING --tracing true --dry-run"
@@ -74,6 +81,9 @@ MANAGE_COMMANDS=(
   "tenant list --stream"
   "tenant list"
 
+  "tenant provision --file tenants.yaml --dry-run"
+  "tenant provision --file tenants.yaml --prune --dry-run"
+
   "tenant revoke $TENANT --stream-read $INTERNAL_STREAM --dry-run"
   "tenant revoke $TENANT --stream-write $INTERNAL_STREAM --dry-run"
   "tenant revoke $TENANT --stream-rw $INTERNAL_STREAM --dry-run"
@@ -81,6 +91,9 @@ MANAGE_COMMANDS=(
   "tenant show $TENANT"
   "tenant show $TENANT --stream"
 
+  "tenant usage"
+  "tenant usage $TENANT"
+
   "tenant update $TENANT --certificate-count 5 --dry-run"
   "tenant update $TENANT --consumer-rate 1048576 --dry-run"
   "tenant update $TENANT --cpu 0.2 --dry-run"